@@ -1,4 +1,4 @@
-use sqparse::{parse, tokenize, Flavor};
+use sqparse::{parse_recover, tokenize_lossy, Flavor};
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 
@@ -20,6 +20,7 @@ fn main() {
     let mut total_size_bytes = 0;
     let mut total_lex_secs = 0.;
     let mut total_parse_secs = 0.;
+    let mut failed_files = 0;
 
     visit(&base_path, &mut |path| {
         let extension = path.extension().and_then(|val| val.to_str());
@@ -38,24 +39,25 @@ fn main() {
         };
 
         let lex_start = Instant::now();
-        let tokens = match tokenize(&file_text, Flavor::SquirrelRespawn) {
-            Ok(tokens) => tokens,
-            Err(err) => {
-                eprintln!("{}", err.display(&file_text, path.to_str()));
-                std::process::exit(1);
-            }
-        };
+        let (tokens, lex_errors) = tokenize_lossy(&file_text, Flavor::SquirrelRespawn);
         let lex_secs = lex_start.elapsed().as_secs_f64();
         println!("  tokenize: {lex_secs}s");
 
         let parse_start = Instant::now();
-        if let Err(err) = parse(&tokens, Flavor::SquirrelRespawn) {
-            eprintln!("{}", err.display(&file_text, &tokens, path.to_str()));
-            std::process::exit(1);
-        }
+        let (_, parse_errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
         let parse_secs = parse_start.elapsed().as_secs_f64();
         println!("  parse: {parse_secs}s");
 
+        for err in &lex_errors {
+            eprintln!("{}", err.display(&file_text, path.to_str()));
+        }
+        for err in &parse_errors {
+            eprintln!("{}", err.display(&file_text, &tokens, path.to_str()));
+        }
+        if !lex_errors.is_empty() || !parse_errors.is_empty() {
+            failed_files += 1;
+        }
+
         total_size_bytes += file_text.len();
         total_lex_secs += lex_secs;
         total_parse_secs += parse_secs;
@@ -73,6 +75,11 @@ fn main() {
         total_parse_secs,
         total_mb / total_parse_secs
     );
+
+    if failed_files > 0 {
+        println!("{failed_files} file(s) had errors");
+        std::process::exit(1);
+    }
 }
 
 fn visit<F: FnMut(&Path)>(path: &Path, cb: &mut F) {
@@ -0,0 +1,621 @@
+use crate::analysis::{DeclarationKind, Resolution};
+use crate::ast::{
+    walk_call_argument, walk_call_expression, walk_function_definition_statement,
+    walk_function_expression, walk_lambda_expression, CallArgument, CallExpression,
+    DelayThreadStatement, Expression, FunctionDefinitionStatement, FunctionExpression, Identifier,
+    LambdaExpression, MethodIdentifier, Program, ThreadStatement, Visit, WaitThreadSoloStatement,
+    WaitThreadStatement,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Identifies a [`CallGraphFunction`] in a [`CallGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FunctionId(usize);
+
+/// The functions in one or more programs, and the calls between them.
+///
+/// Each named [`FunctionDefinitionStatement`] is a function in the graph, including functions
+/// with namespaced names like `CPlayer::Respawn`, which are named with `::`. Calls to names that
+/// are not defined in the programs, like native functions, add a function without a definition.
+///
+/// A [`Call`] is added for:
+///  - Each [`CallExpression`] that calls a plain name like `Foo()`, or a namespaced function like
+///    `CPlayer.Respawn()`. Calls to local variables and parameters are not included.
+///  - The call in each `thread`, `waitthread`, `waitthreadsolo` and `delaythread` statement.
+///  - Each function passed as an argument by name, like
+///    `AddCallback_OnClientConnected( OnConnected )`.
+///
+/// Calls in function literals and lambdas belong to the function that contains them. Functions
+/// and calls in every preprocessor branch are included.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{CallGraph, CallKind};
+///
+/// let source = r#"
+/// void function Init()
+/// {
+///     AddCallback_OnClientConnected( OnConnected )
+/// }
+///
+/// void function OnConnected( entity player )
+/// {
+///     thread Respawn( player )
+/// }
+///
+/// void function Respawn( entity player ) {}
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+/// let graph = CallGraph::new(&program);
+///
+/// let respawn = graph.find("Respawn").unwrap();
+/// let callers: Vec<_> = graph
+///     .callers(respawn)
+///     .map(|call| (graph.function(call.caller.unwrap()).name.as_str(), call.kind))
+///     .collect();
+/// assert_eq!(callers, [("OnConnected", CallKind::Thread)]);
+///
+/// let affected: Vec<_> = graph
+///     .transitive_callers(respawn)
+///     .into_iter()
+///     .map(|id| graph.function(id).name.as_str())
+///     .collect();
+/// assert_eq!(affected, ["OnConnected", "Init"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph<'s> {
+    functions: Vec<CallGraphFunction<'s>>,
+    calls: Vec<Call<'s>>,
+    names: HashMap<String, FunctionId>,
+}
+
+/// A function in a [`CallGraph`].
+#[derive(Debug, Clone)]
+pub struct CallGraphFunction<'s> {
+    /// The name of the function, with namespaces separated by `::`.
+    pub name: String,
+
+    /// The first definition of the function and the index of the program it is in, or `None` if
+    /// the function is called but not defined in the programs.
+    pub definition: Option<(usize, &'s FunctionDefinitionStatement<'s>)>,
+}
+
+/// Type of [`Call`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// A call expression, like `Foo()`.
+    Call,
+
+    /// A `thread` statement, like `thread Foo()`.
+    Thread,
+
+    /// A `waitthread` statement, like `waitthread Foo()`.
+    WaitThread,
+
+    /// A `waitthreadsolo` statement, like `waitthreadsolo Foo()`.
+    WaitThreadSolo,
+
+    /// A `delaythread` statement, like `delaythread( 1.0 ) Foo()`.
+    DelayThread,
+
+    /// A function passed as an argument, like `AddCallback_OnClientConnected( Foo )`.
+    Reference,
+}
+
+/// A call from one function in a [`CallGraph`] to another.
+#[derive(Debug, Clone)]
+pub struct Call<'s> {
+    /// The function the call is in, or `None` if the call is outside a function.
+    pub caller: Option<FunctionId>,
+
+    /// The called function.
+    pub callee: FunctionId,
+
+    /// The type of call.
+    pub kind: CallKind,
+
+    /// The name of the called function at the call site.
+    pub name: &'s Identifier<'s>,
+
+    /// The index of the program the call is in.
+    pub program: usize,
+
+    /// Set if the call is in a function literal or lambda in the caller, so it may not run when
+    /// the caller does.
+    pub in_literal: bool,
+}
+
+impl<'s> CallGraph<'s> {
+    /// Builds the call graph of a program.
+    pub fn new(program: &'s Program<'s>) -> Self {
+        CallGraph::from_programs([program])
+    }
+
+    /// Builds the call graph of a set of programs, like the scripts of a VM.
+    ///
+    /// Programs are identified by their index in `programs`.
+    pub fn from_programs(programs: impl IntoIterator<Item = &'s Program<'s>>) -> Self {
+        let programs: Vec<_> = programs.into_iter().collect();
+        let mut graph = CallGraph::default();
+
+        // Functions can be called before they are defined, so find them all first.
+        for (index, &program) in programs.iter().enumerate() {
+            let mut finder = FunctionFinder {
+                graph: &mut graph,
+                program: index,
+            };
+            finder.visit_program(program);
+        }
+
+        for (index, &program) in programs.iter().enumerate() {
+            let resolution = Resolution::new(program);
+            let mut builder = CallBuilder {
+                graph: &mut graph,
+                resolution: &resolution,
+                program: index,
+                caller: None,
+                kind: None,
+                literal_depth: 0,
+            };
+            builder.visit_program(program);
+        }
+        graph
+    }
+
+    /// Returns a function.
+    pub fn function(&self, id: FunctionId) -> &CallGraphFunction<'s> {
+        &self.functions[id.0]
+    }
+
+    /// Returns an iterator over every function, with defined functions before any others.
+    pub fn functions(&self) -> impl Iterator<Item = (FunctionId, &CallGraphFunction<'s>)> {
+        (self.functions.iter().enumerate()).map(|(i, f)| (FunctionId(i), f))
+    }
+
+    /// Returns the function with a name, like `Foo` or `CPlayer::Respawn`.
+    pub fn find(&self, name: &str) -> Option<FunctionId> {
+        self.names.get(name).copied()
+    }
+
+    /// Returns every call, in the order they appear in the programs.
+    pub fn calls(&self) -> &[Call<'s>] {
+        &self.calls
+    }
+
+    /// Returns an iterator over the calls made by a function.
+    pub fn callees(&self, id: FunctionId) -> impl Iterator<Item = &Call<'s>> {
+        (self.calls.iter()).filter(move |call| call.caller == Some(id))
+    }
+
+    /// Returns an iterator over the calls to a function.
+    pub fn callers(&self, id: FunctionId) -> impl Iterator<Item = &Call<'s>> {
+        (self.calls.iter()).filter(move |call| call.callee == id)
+    }
+
+    /// Returns every function that calls a function directly or indirectly, nearest callers first.
+    ///
+    /// These are the functions that could be affected by a change to the function.
+    pub fn transitive_callers(&self, id: FunctionId) -> Vec<FunctionId> {
+        let mut visited = HashSet::from([id]);
+        let mut found = Vec::new();
+        let mut next = 0;
+        let mut current = id;
+        loop {
+            for caller in self.callers(current).filter_map(|call| call.caller) {
+                if visited.insert(caller) {
+                    found.push(caller);
+                }
+            }
+            let Some(&caller) = found.get(next) else {
+                return found;
+            };
+            current = caller;
+            next += 1;
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes the graph in the
+    /// [DOT](https://graphviz.org/doc/info/lang.html) format.
+    ///
+    /// Functions that are not defined in the programs are drawn with dashed outlines, and calls
+    /// other than plain calls are labelled with their kind. Calls outside functions are not
+    /// included, and multiple calls of the same kind between two functions are drawn once.
+    ///
+    /// # Example
+    /// ```
+    /// use sqparse::{Flavor, parse, tokenize};
+    /// use sqparse::analysis::CallGraph;
+    ///
+    /// let source = "void function A() { thread B() }\nvoid function B() { print( 1 ) }";
+    /// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+    /// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+    /// let graph = CallGraph::new(&program);
+    ///
+    /// assert_eq!(
+    ///     graph.dot().to_string(),
+    ///     r#"digraph calls {
+    ///     "A";
+    ///     "B";
+    ///     "print" [style=dashed];
+    ///     "A" -> "B" [label="thread"];
+    ///     "B" -> "print";
+    /// }
+    /// "#
+    /// );
+    /// ```
+    pub fn dot(&self) -> impl std::fmt::Display + '_ {
+        DotDisplay(self)
+    }
+
+    fn add_function(&mut self, name: String) -> FunctionId {
+        if let Some(&id) = self.names.get(&name) {
+            return id;
+        }
+        let id = FunctionId(self.functions.len());
+        self.names.insert(name.clone(), id);
+        self.functions.push(CallGraphFunction {
+            name,
+            definition: None,
+        });
+        id
+    }
+}
+
+impl CallKind {
+    /// Returns the keyword or description of the call kind, like `thread`.
+    pub fn label(self) -> &'static str {
+        match self {
+            CallKind::Call => "call",
+            CallKind::Thread => "thread",
+            CallKind::WaitThread => "waitthread",
+            CallKind::WaitThreadSolo => "waitthreadsolo",
+            CallKind::DelayThread => "delaythread",
+            CallKind::Reference => "reference",
+        }
+    }
+}
+
+// Returns the full name of a function definition, like `CPlayer::Respawn`.
+fn definition_name(statement: &FunctionDefinitionStatement) -> String {
+    let mut name = String::new();
+    for (item, _) in &statement.name.items {
+        name.push_str(item.value);
+        name.push_str("::");
+    }
+    name.push_str(statement.name.last_item.value);
+    name
+}
+
+// Returns the name of a namespaced function in a property expression like `CPlayer.Respawn`.
+fn property_name(expression: &Expression) -> Option<String> {
+    match expression {
+        Expression::Var(var) => Some(var.name.value.to_string()),
+        Expression::Property(property) => {
+            let MethodIdentifier::Identifier(name) = &property.property else {
+                return None;
+            };
+            Some(format!(
+                "{}::{}",
+                property_name(&property.base)?,
+                name.value
+            ))
+        }
+        _ => None,
+    }
+}
+
+struct FunctionFinder<'a, 's> {
+    graph: &'a mut CallGraph<'s>,
+    program: usize,
+}
+
+impl<'s> Visit<'s> for FunctionFinder<'_, 's> {
+    fn visit_function_definition_statement(&mut self, node: &'s FunctionDefinitionStatement<'s>) {
+        let id = self.graph.add_function(definition_name(node));
+        let function = &mut self.graph.functions[id.0];
+        if function.definition.is_none() {
+            function.definition = Some((self.program, node));
+        }
+        walk_function_definition_statement(self, node);
+    }
+}
+
+struct CallBuilder<'a, 's> {
+    graph: &'a mut CallGraph<'s>,
+    resolution: &'a Resolution<'s>,
+    program: usize,
+    caller: Option<FunctionId>,
+
+    // The kind of the next call, set by statements like `thread`.
+    kind: Option<CallKind>,
+
+    // The number of function literals and lambdas around the current node.
+    literal_depth: usize,
+}
+
+impl<'s> CallBuilder<'_, 's> {
+    fn add_call(&mut self, name: &'s Identifier<'s>, callee: String, kind: CallKind) {
+        let callee = self.graph.add_function(callee);
+        self.graph.calls.push(Call {
+            caller: self.caller,
+            callee,
+            kind,
+            name,
+            program: self.program,
+            in_literal: self.literal_depth > 0,
+        });
+    }
+
+    // Returns true if a name refers to a function, or is not declared in the program.
+    fn is_function_name(&self, name: &Identifier) -> bool {
+        let Some(reference) = self.resolution.reference(name) else {
+            return false;
+        };
+        reference.declaration.is_none_or(|id| {
+            matches!(
+                self.resolution.declaration(id).kind,
+                DeclarationKind::Function(_) | DeclarationKind::GlobalFunction
+            )
+        })
+    }
+
+    fn visit_thread_value(&mut self, kind: CallKind, value: &'s Expression<'s>) {
+        self.kind = Some(kind);
+        self.visit_expression(value);
+        self.kind = None;
+    }
+}
+
+impl<'s> Visit<'s> for CallBuilder<'_, 's> {
+    fn visit_function_definition_statement(&mut self, node: &'s FunctionDefinitionStatement<'s>) {
+        let outer = self
+            .caller
+            .replace(self.graph.add_function(definition_name(node)));
+        walk_function_definition_statement(self, node);
+        self.caller = outer;
+    }
+
+    fn visit_function_expression(&mut self, node: &'s FunctionExpression<'s>) {
+        self.literal_depth += 1;
+        walk_function_expression(self, node);
+        self.literal_depth -= 1;
+    }
+
+    fn visit_lambda_expression(&mut self, node: &'s LambdaExpression<'s>) {
+        self.literal_depth += 1;
+        walk_lambda_expression(self, node);
+        self.literal_depth -= 1;
+    }
+
+    fn visit_thread_statement(&mut self, node: &'s ThreadStatement<'s>) {
+        self.visit_thread_value(CallKind::Thread, &node.value);
+    }
+
+    fn visit_delay_thread_statement(&mut self, node: &'s DelayThreadStatement<'s>) {
+        self.visit_expression(&node.duration);
+        self.visit_thread_value(CallKind::DelayThread, &node.value);
+    }
+
+    fn visit_wait_thread_statement(&mut self, node: &'s WaitThreadStatement<'s>) {
+        self.visit_thread_value(CallKind::WaitThread, &node.value);
+    }
+
+    fn visit_wait_thread_solo_statement(&mut self, node: &'s WaitThreadSoloStatement<'s>) {
+        self.visit_thread_value(CallKind::WaitThreadSolo, &node.value);
+    }
+
+    fn visit_call_expression(&mut self, node: &'s CallExpression<'s>) {
+        let kind = self.kind.take().unwrap_or(CallKind::Call);
+        match &*node.function {
+            Expression::Var(var) if self.is_function_name(&var.name) => {
+                self.add_call(&var.name, var.name.value.to_string(), kind);
+            }
+            Expression::Property(property) => {
+                let name =
+                    property_name(&node.function).filter(|name| self.graph.find(name).is_some());
+                if let (Some(name), MethodIdentifier::Identifier(identifier)) =
+                    (name, &property.property)
+                {
+                    self.add_call(identifier, name, kind);
+                }
+            }
+            _ => {}
+        }
+        walk_call_expression(self, node);
+    }
+
+    fn visit_call_argument(&mut self, node: &'s CallArgument<'s>) {
+        if let Expression::Var(var) = &*node.value {
+            let is_defined = (self.graph.find(var.name.value))
+                .is_some_and(|id| self.graph.function(id).definition.is_some());
+            if is_defined && self.is_function_name(&var.name) {
+                self.add_call(&var.name, var.name.value.to_string(), CallKind::Reference);
+            }
+        }
+        walk_call_argument(self, node);
+    }
+}
+
+struct DotDisplay<'a, 's>(&'a CallGraph<'s>);
+
+impl std::fmt::Display for DotDisplay<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let graph = self.0;
+        writeln!(f, "digraph calls {{")?;
+        for (_, function) in graph.functions() {
+            match function.definition {
+                Some(_) => writeln!(f, "    \"{}\";", function.name)?,
+                None => writeln!(f, "    \"{}\" [style=dashed];", function.name)?,
+            }
+        }
+
+        let mut drawn = HashSet::new();
+        for call in graph.calls() {
+            let Some(caller) = call.caller else {
+                continue;
+            };
+            if !drawn.insert((caller, call.callee, call.kind)) {
+                continue;
+            }
+            let caller = &graph.function(caller).name;
+            let callee = &graph.function(call.callee).name;
+            match call.kind {
+                CallKind::Call => writeln!(f, "    \"{caller}\" -> \"{callee}\";")?,
+                CallKind::Reference => writeln!(
+                    f,
+                    "    \"{caller}\" -> \"{callee}\" [label=\"reference\", style=dotted];"
+                )?,
+                kind => writeln!(
+                    f,
+                    "    \"{caller}\" -> \"{callee}\" [label=\"{}\"];",
+                    kind.label()
+                )?,
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CallGraph, CallKind};
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns each call as the caller name, callee name and kind.
+    fn calls(source: &str) -> Vec<(String, String, CallKind)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let graph = CallGraph::new(&program);
+        (graph.calls().iter())
+            .map(|call| {
+                let caller = call.caller.map(|id| graph.function(id).name.as_str());
+                (
+                    caller.unwrap_or("").to_string(),
+                    graph.function(call.callee).name.clone(),
+                    call.kind,
+                )
+            })
+            .collect()
+    }
+
+    fn call(caller: &str, callee: &str, kind: CallKind) -> (String, String, CallKind) {
+        (caller.to_string(), callee.to_string(), kind)
+    }
+
+    #[test]
+    fn direct_calls() {
+        let source = "
+void function A( f )
+{
+    B( C() )
+    f()
+    local g = @() D()
+    g()
+}
+void function B( x ) {}
+void function C() {}
+Init()
+";
+        assert_eq!(
+            calls(source),
+            [
+                call("A", "B", CallKind::Call),
+                call("A", "C", CallKind::Call),
+                call("A", "D", CallKind::Call),
+                call("", "Init", CallKind::Call),
+            ]
+        );
+    }
+
+    #[test]
+    fn thread_statements() {
+        let source = "
+void function A()
+{
+    thread B( C() )
+    waitthread B()
+    waitthreadsolo B()
+    delaythread( Delay() ) B()
+    thread Method.Call()
+}
+void function B() {}
+";
+        assert_eq!(
+            calls(source),
+            [
+                call("A", "B", CallKind::Thread),
+                call("A", "C", CallKind::Call),
+                call("A", "B", CallKind::WaitThread),
+                call("A", "B", CallKind::WaitThreadSolo),
+                call("A", "Delay", CallKind::Call),
+                call("A", "B", CallKind::DelayThread),
+            ]
+        );
+    }
+
+    #[test]
+    fn function_references() {
+        let source = "
+void function Init( entity player )
+{
+    AddCallback_OnClientConnected( OnConnected )
+    AddCallback_OnClientConnected( player )
+    AddCallback_OnClientConnected( Unknown )
+}
+void function OnConnected( entity player ) {}
+";
+        assert_eq!(
+            calls(source),
+            [
+                call("Init", "AddCallback_OnClientConnected", CallKind::Call),
+                call("Init", "OnConnected", CallKind::Reference),
+                call("Init", "AddCallback_OnClientConnected", CallKind::Call),
+                call("Init", "AddCallback_OnClientConnected", CallKind::Call),
+            ]
+        );
+    }
+
+    #[test]
+    fn namespaced_functions() {
+        let source = "
+void function CPlayer::Respawn() { CPlayer.Reset() }
+void function CPlayer::Reset() {}
+void function Main() { CPlayer.Respawn(); player.Respawn() }
+";
+        assert_eq!(
+            calls(source),
+            [
+                call("CPlayer::Respawn", "CPlayer::Reset", CallKind::Call),
+                call("Main", "CPlayer::Respawn", CallKind::Call),
+            ]
+        );
+    }
+
+    #[test]
+    fn multiple_programs() {
+        let sources = [
+            "global function Util\nvoid function Util() { Helper() }\nvoid function Helper() {}",
+            "void function Main() { Util(); thread Main() }",
+        ];
+        let tokens: Vec<_> = (sources.iter())
+            .map(|source| tokenize(source, Flavor::SquirrelRespawn).unwrap())
+            .collect();
+        let programs: Vec<_> = (tokens.iter())
+            .map(|tokens| parse(tokens, Flavor::SquirrelRespawn).unwrap())
+            .collect();
+        let graph = CallGraph::from_programs(&programs);
+
+        let helper = graph.find("Helper").unwrap();
+        let callers: Vec<_> = (graph.transitive_callers(helper).into_iter())
+            .map(|id| graph.function(id).name.as_str())
+            .collect();
+        assert_eq!(callers, ["Util", "Main"]);
+
+        let main = graph.find("Main").unwrap();
+        assert_eq!(graph.function(main).definition.unwrap().0, 1);
+        let programs: Vec<_> = graph.callees(main).map(|call| call.program).collect();
+        assert_eq!(programs, [1, 1]);
+    }
+}
@@ -0,0 +1,819 @@
+use crate::analysis::ConstEvaluator;
+use crate::ast::{
+    ConditionalStatement, DoWhileStatement, Expression, ForDefinition, ForStatement,
+    ForeachStatement, FunctionDefinition, IfStatement, IfStatementType, Spanned, Statement,
+    StatementType, SwitchCase, SwitchCaseCondition, SwitchStatement, TryCatchStatement,
+    WhileStatement,
+};
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Identifies a [`BasicBlock`] in a [`ControlFlowGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub usize);
+
+/// A control-flow graph of a function body.
+///
+/// The body is lowered into [`BasicBlock`]s, which run their items in order and then move to one
+/// of their successors. Every graph has an [`ENTRY`] block where the function starts, and an
+/// empty [`EXIT`] block that returns and throws that leave the function go to.
+///
+/// Code that can never run, like a statement after a `return`, is still lowered into blocks but
+/// those blocks are not [reachable](ControlFlowGraph::reachable) from the entry. Function
+/// definitions nested in the body are a single item, and are not lowered into this graph.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{ControlFlowGraph, EdgeKind};
+/// use sqparse::ast::StatementType;
+///
+/// let source = "void function F( int a ) { if ( a ) return\n print( a ) }";
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// let StatementType::FunctionDefinition(function) = &program.statements[0].ty else { unreachable!() };
+/// let cfg = ControlFlowGraph::new(&function.definition);
+///
+/// let entry = cfg.block(ControlFlowGraph::ENTRY);
+/// let kinds: Vec<_> = entry.edges.iter().map(|edge| edge.kind).collect();
+/// assert_eq!(kinds, [EdgeKind::True, EdgeKind::False]);
+/// assert!(cfg.reachable().contains(&ControlFlowGraph::EXIT));
+/// ```
+///
+/// [`ENTRY`]: ControlFlowGraph::ENTRY
+/// [`EXIT`]: ControlFlowGraph::EXIT
+#[derive(Debug, Clone)]
+pub struct ControlFlowGraph<'s> {
+    blocks: Vec<BasicBlock<'s>>,
+}
+
+/// A sequence of items in a [`ControlFlowGraph`] that always run together.
+#[derive(Debug, Clone, Default)]
+pub struct BasicBlock<'s> {
+    /// The items that run in this block, in order.
+    pub items: Vec<BlockItem<'s>>,
+
+    /// The blocks that can run after this block.
+    pub edges: Vec<Edge>,
+}
+
+/// Something that runs in a [`BasicBlock`].
+///
+/// Each item refers back to the syntax tree, so [`BlockItem::span`] can find it in the source.
+#[derive(Debug, Clone, Copy)]
+pub enum BlockItem<'s> {
+    /// A statement that does not change control flow, like an expression or a variable
+    /// definition, or a `break`, `continue`, `return` or `throw` that ends the block.
+    Statement(&'s StatementType<'s>),
+
+    /// A condition that chooses the next block, of an `if`, `while`, `do while` or `for`
+    /// statement, or the value a `switch` statement matches against.
+    Condition(&'s Expression<'s>),
+
+    /// The initializer of a `for` statement.
+    Initializer(&'s ForDefinition<'s>),
+
+    /// An expression evaluated for a statement, like the increment of a `for` statement or the
+    /// array of a `foreach` statement.
+    Expression(&'s Expression<'s>),
+
+    /// A `foreach` statement moving to the next value, or leaving the loop.
+    Next(&'s ForeachStatement<'s>),
+
+    /// A `case` or `default` label in a `switch` statement.
+    Case(&'s SwitchCase<'s>),
+
+    /// The `catch` of a `try` statement, which binds the caught value.
+    Catch(&'s TryCatchStatement<'s>),
+}
+
+/// An edge between two [`BasicBlock`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    /// The block that runs next.
+    pub target: BlockId,
+
+    /// Why control moves along this edge.
+    pub kind: EdgeKind,
+}
+
+/// Kind of an [`Edge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Control moves to the next statement.
+    Normal,
+
+    /// A condition is true, or a `foreach` statement has another value.
+    True,
+
+    /// A condition is false, or a `foreach` statement has no more values.
+    False,
+
+    /// A `switch` value matches a `case` or `default` label.
+    Case,
+
+    /// A `switch` value does not match any label, and there is no `default` label.
+    NoMatch,
+
+    /// A `case` body falls through to the next `case` body.
+    Fallthrough,
+
+    /// A loop body goes back to the loop condition.
+    Loop,
+
+    /// A `break` statement.
+    Break,
+
+    /// A `continue` statement.
+    Continue,
+
+    /// A `return` statement.
+    Return,
+
+    /// A `throw` statement.
+    Throw,
+
+    /// An exception thrown while running the body of a `try` statement.
+    Exception,
+}
+
+impl<'s> ControlFlowGraph<'s> {
+    /// The block that the function starts in.
+    pub const ENTRY: BlockId = BlockId(0);
+
+    /// The block that the function leaves from.
+    pub const EXIT: BlockId = BlockId(1);
+
+    /// Builds the control-flow graph of a function body.
+    pub fn new(definition: &'s FunctionDefinition<'s>) -> Self {
+        ControlFlowGraph::from_body(&definition.body)
+    }
+
+    /// Builds the control-flow graph of a statement, as if it were a function body.
+    pub fn from_body(body: &'s StatementType<'s>) -> Self {
+        let mut builder = Builder {
+            blocks: vec![BasicBlock::default(), BasicBlock::default()],
+            current: Some(ControlFlowGraph::ENTRY),
+            jumps: Vec::new(),
+            handlers: Vec::new(),
+        };
+        builder.statement(body);
+        builder.jump(ControlFlowGraph::EXIT, EdgeKind::Normal);
+        ControlFlowGraph {
+            blocks: builder.blocks,
+        }
+    }
+
+    /// Returns a block in the graph.
+    ///
+    /// # Panics
+    /// Panics if the block is not in this graph.
+    pub fn block(&self, id: BlockId) -> &BasicBlock<'s> {
+        &self.blocks[id.0]
+    }
+
+    /// Returns all blocks in the graph.
+    ///
+    /// The [`ENTRY`](ControlFlowGraph::ENTRY) and [`EXIT`](ControlFlowGraph::EXIT) blocks come
+    /// first, followed by the other blocks roughly in source order.
+    pub fn blocks(&self) -> impl Iterator<Item = (BlockId, &BasicBlock<'s>)> {
+        (self.blocks.iter().enumerate()).map(|(index, block)| (BlockId(index), block))
+    }
+
+    /// Returns the blocks that can run directly after a block.
+    pub fn successors(&self, id: BlockId) -> impl Iterator<Item = BlockId> + '_ {
+        self.block(id).edges.iter().map(|edge| edge.target)
+    }
+
+    /// Returns the blocks that can run directly before a block, with the edge to the block.
+    pub fn predecessors(&self, id: BlockId) -> impl Iterator<Item = (BlockId, Edge)> + '_ {
+        self.blocks().flat_map(move |(from, block)| {
+            (block.edges.iter())
+                .filter(move |edge| edge.target == id)
+                .map(move |edge| (from, *edge))
+        })
+    }
+
+    /// Returns the blocks that can run when the function is called, in breadth-first order from
+    /// the entry.
+    pub fn reachable(&self) -> Vec<BlockId> {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut queue = VecDeque::from([ControlFlowGraph::ENTRY]);
+        let mut reachable = Vec::new();
+        visited[ControlFlowGraph::ENTRY.0] = true;
+        while let Some(id) = queue.pop_front() {
+            reachable.push(id);
+            for successor in self.successors(id) {
+                if !visited[successor.0] {
+                    visited[successor.0] = true;
+                    queue.push_back(successor);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Returns the block that runs a statement, if the statement is an item in the graph.
+    ///
+    /// Statements that only contain other statements, like blocks and loops, are not items.
+    pub fn block_of(&self, statement: &StatementType) -> Option<BlockId> {
+        self.blocks()
+            .find(|(_, block)| {
+                block.items.iter().any(|item| match item {
+                    BlockItem::Statement(item) => std::ptr::eq(*item, statement),
+                    _ => false,
+                })
+            })
+            .map(|(id, _)| id)
+    }
+}
+
+impl<'s> BasicBlock<'s> {
+    /// Returns the character range of the block's items in the source code.
+    ///
+    /// Returns [`None`] if the block has no items.
+    pub fn span(&self) -> Option<Range<usize>> {
+        let mut spans = self.items.iter().filter_map(BlockItem::span);
+        let first = spans.next()?;
+        Some(spans.fold(first, |span, item| {
+            span.start.min(item.start)..span.end.max(item.end)
+        }))
+    }
+}
+
+impl<'s> BlockItem<'s> {
+    /// Returns the character range of the item in the source code.
+    ///
+    /// Items for parts of a statement only cover that part: a [`BlockItem::Next`] covers the
+    /// `foreach` header, a [`BlockItem::Case`] covers the label and a [`BlockItem::Catch`] covers
+    /// `catch( name )`.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            BlockItem::Statement(statement) => statement.span(),
+            BlockItem::Condition(expression) | BlockItem::Expression(expression) => {
+                expression.span()
+            }
+            BlockItem::Initializer(definition) => definition.span(),
+            BlockItem::Next(foreach) => Some(foreach.foreach.range.start..foreach.close.range.end),
+            BlockItem::Case(case) => {
+                let start = case
+                    .condition
+                    .span()
+                    .map_or(case.colon.range.start, |s| s.start);
+                Some(start..case.colon.range.end)
+            }
+            BlockItem::Catch(try_catch) => {
+                Some(try_catch.catch.range.start..try_catch.close.range.end)
+            }
+        }
+    }
+}
+
+// Where `break` and `continue` statements go.
+struct JumpTargets {
+    break_: BlockId,
+    continue_: Option<BlockId>,
+}
+
+struct Builder<'s> {
+    blocks: Vec<BasicBlock<'s>>,
+
+    // The block that new items are added to, or `None` if the previous statement never completes.
+    current: Option<BlockId>,
+    jumps: Vec<JumpTargets>,
+
+    // The catch blocks of the `try` statements being lowered, and the blocks in their bodies.
+    handlers: Vec<(BlockId, Vec<BlockId>)>,
+}
+
+impl<'s> Builder<'s> {
+    fn new_block(&mut self) -> BlockId {
+        let id = BlockId(self.blocks.len());
+        self.blocks.push(BasicBlock::default());
+        if let Some((_, blocks)) = self.handlers.last_mut() {
+            blocks.push(id);
+        }
+        id
+    }
+
+    fn edge(&mut self, from: BlockId, target: BlockId, kind: EdgeKind) {
+        self.blocks[from.0].edges.push(Edge { target, kind });
+    }
+
+    // Adds an edge from the current block, if there is one.
+    fn jump(&mut self, target: BlockId, kind: EdgeKind) {
+        if let Some(current) = self.current {
+            self.edge(current, target, kind);
+        }
+    }
+
+    // Returns the current block, starting an unreachable block if the previous statement never
+    // completes.
+    fn current(&mut self) -> BlockId {
+        match self.current {
+            Some(current) => current,
+            None => {
+                let block = self.new_block();
+                self.current = Some(block);
+                block
+            }
+        }
+    }
+
+    fn push(&mut self, item: BlockItem<'s>) {
+        let current = self.current();
+        self.blocks[current.0].items.push(item);
+    }
+
+    // Moves to a new block, with an edge from the current block.
+    fn start(&mut self, kind: EdgeKind) -> BlockId {
+        let block = self.new_block();
+        self.jump(block, kind);
+        self.current = Some(block);
+        block
+    }
+
+    fn statements(&mut self, statements: &'s [Statement<'s>]) {
+        for statement in statements {
+            self.statement(&statement.ty);
+        }
+    }
+
+    fn statement(&mut self, statement: &'s StatementType<'s>) {
+        match statement {
+            StatementType::Empty(_) => {}
+            StatementType::Block(block) => self.statements(&block.statements),
+            StatementType::If(if_) => self.if_statement(if_),
+            StatementType::While(while_) => self.while_statement(while_),
+            StatementType::DoWhile(do_while) => self.do_while_statement(do_while),
+            StatementType::Switch(switch) => self.switch_statement(switch),
+            StatementType::For(for_) => self.for_statement(for_),
+            StatementType::Foreach(foreach) => self.foreach_statement(foreach),
+            StatementType::TryCatch(try_catch) => self.try_catch_statement(try_catch),
+            StatementType::Conditional(conditional) => self.conditional_statement(conditional),
+            StatementType::Break(_) => {
+                self.push(BlockItem::Statement(statement));
+                if let Some(targets) = self.jumps.last() {
+                    let target = targets.break_;
+                    self.jump(target, EdgeKind::Break);
+                }
+                self.current = None;
+            }
+            StatementType::Continue(_) => {
+                self.push(BlockItem::Statement(statement));
+                let target = self
+                    .jumps
+                    .iter()
+                    .rev()
+                    .find_map(|targets| targets.continue_);
+                if let Some(target) = target {
+                    self.jump(target, EdgeKind::Continue);
+                }
+                self.current = None;
+            }
+            StatementType::Return(_) => {
+                self.push(BlockItem::Statement(statement));
+                self.jump(ControlFlowGraph::EXIT, EdgeKind::Return);
+                self.current = None;
+            }
+            StatementType::Throw(_) => {
+                self.push(BlockItem::Statement(statement));
+                let target = self.handlers.last().map_or(ControlFlowGraph::EXIT, |h| h.0);
+                self.jump(target, EdgeKind::Throw);
+                self.current = None;
+            }
+            _ => self.push(BlockItem::Statement(statement)),
+        }
+    }
+
+    fn if_statement(&mut self, if_: &'s IfStatement<'s>) {
+        self.push(BlockItem::Condition(&if_.condition));
+        let condition = self.current();
+        let after = match &if_.ty {
+            IfStatementType::NoElse { body } => {
+                self.start(EdgeKind::True);
+                self.statement(body);
+                let after = self.start(EdgeKind::Normal);
+                self.edge(condition, after, EdgeKind::False);
+                after
+            }
+            IfStatementType::Else {
+                body, else_body, ..
+            } => {
+                self.start(EdgeKind::True);
+                self.statement(&body.ty);
+                let body_end = self.current;
+
+                self.current = Some(condition);
+                self.start(EdgeKind::False);
+                self.statement(else_body);
+                let after = self.start(EdgeKind::Normal);
+                if let Some(body_end) = body_end {
+                    self.edge(body_end, after, EdgeKind::Normal);
+                }
+                after
+            }
+        };
+        self.current = Some(after);
+    }
+
+    fn while_statement(&mut self, while_: &'s WhileStatement<'s>) {
+        let condition = self.start(EdgeKind::Normal);
+        self.push(BlockItem::Condition(&while_.condition));
+        let after = self.new_block();
+        let body_kind = self.loop_condition(condition, after, &while_.condition);
+
+        self.start(body_kind);
+        self.loop_body(&while_.body, after, condition);
+        self.jump(condition, EdgeKind::Loop);
+        self.current = Some(after);
+    }
+
+    fn do_while_statement(&mut self, do_while: &'s DoWhileStatement<'s>) {
+        let body = self.start(EdgeKind::Normal);
+        let condition = self.new_block();
+        let after = self.new_block();
+        self.loop_body(&do_while.body.ty, after, condition);
+        self.jump(condition, EdgeKind::Normal);
+
+        self.current = Some(condition);
+        self.push(BlockItem::Condition(&do_while.condition));
+        if is_always_true(&do_while.condition) {
+            self.edge(condition, body, EdgeKind::Normal);
+        } else {
+            self.edge(condition, body, EdgeKind::True);
+            self.edge(condition, after, EdgeKind::False);
+        }
+        self.current = Some(after);
+    }
+
+    fn for_statement(&mut self, for_: &'s ForStatement<'s>) {
+        if let Some(initializer) = &for_.initializer {
+            self.push(BlockItem::Initializer(initializer));
+        }
+        let condition = self.start(EdgeKind::Normal);
+        let after = self.new_block();
+        let body_kind = match &for_.condition {
+            Some(expression) => {
+                self.push(BlockItem::Condition(expression));
+                self.loop_condition(condition, after, expression)
+            }
+            None => EdgeKind::Normal,
+        };
+        let increment = self.new_block();
+        if let Some(expression) = &for_.increment {
+            self.blocks[increment.0]
+                .items
+                .push(BlockItem::Expression(expression));
+        }
+        self.edge(increment, condition, EdgeKind::Loop);
+
+        self.start(body_kind);
+        self.loop_body(&for_.body, after, increment);
+        self.jump(increment, EdgeKind::Normal);
+        self.current = Some(after);
+    }
+
+    fn foreach_statement(&mut self, foreach: &'s ForeachStatement<'s>) {
+        self.push(BlockItem::Expression(&foreach.array));
+        let next = self.start(EdgeKind::Normal);
+        self.push(BlockItem::Next(foreach));
+        let after = self.new_block();
+        self.edge(next, after, EdgeKind::False);
+
+        self.start(EdgeKind::True);
+        self.loop_body(&foreach.body, after, next);
+        self.jump(next, EdgeKind::Loop);
+        self.current = Some(after);
+    }
+
+    // Adds the exit edge of a loop condition, and returns the kind of edge into the loop body.
+    fn loop_condition(
+        &mut self,
+        condition: BlockId,
+        after: BlockId,
+        expression: &'s Expression<'s>,
+    ) -> EdgeKind {
+        if is_always_true(expression) {
+            EdgeKind::Normal
+        } else {
+            self.edge(condition, after, EdgeKind::False);
+            EdgeKind::True
+        }
+    }
+
+    fn loop_body(&mut self, body: &'s StatementType<'s>, break_: BlockId, continue_: BlockId) {
+        self.jumps.push(JumpTargets {
+            break_,
+            continue_: Some(continue_),
+        });
+        self.statement(body);
+        self.jumps.pop();
+    }
+
+    fn switch_statement(&mut self, switch: &'s SwitchStatement<'s>) {
+        self.push(BlockItem::Condition(&switch.condition));
+        let condition = self.current();
+        let cases: Vec<_> = (switch.cases.iter())
+            .map(|case| {
+                let block = self.new_block();
+                self.blocks[block.0].items.push(BlockItem::Case(case));
+                self.edge(condition, block, EdgeKind::Case);
+                block
+            })
+            .collect();
+        let after = self.new_block();
+        let has_default = (switch.cases.iter())
+            .any(|case| matches!(case.condition, SwitchCaseCondition::Default { .. }));
+        if !has_default {
+            self.edge(condition, after, EdgeKind::NoMatch);
+        }
+
+        self.jumps.push(JumpTargets {
+            break_: after,
+            continue_: None,
+        });
+        self.current = None;
+        for (case, block) in switch.cases.iter().zip(cases) {
+            self.jump(block, EdgeKind::Fallthrough);
+            self.current = Some(block);
+            self.statements(&case.body);
+        }
+        self.jumps.pop();
+        self.jump(after, EdgeKind::Normal);
+        self.current = Some(after);
+    }
+
+    fn try_catch_statement(&mut self, try_catch: &'s TryCatchStatement<'s>) {
+        let catch = BlockId(self.blocks.len());
+        self.blocks.push(BasicBlock::default());
+        self.blocks[catch.0].items.push(BlockItem::Catch(try_catch));
+
+        self.handlers.push((catch, Vec::new()));
+        self.start(EdgeKind::Normal);
+        self.statement(&try_catch.body.ty);
+        let body_end = self.current;
+        let (_, body) = self.handlers.pop().unwrap();
+        for block in body {
+            self.edge(block, catch, EdgeKind::Exception);
+        }
+        if let Some((_, blocks)) = self.handlers.last_mut() {
+            blocks.push(catch);
+        }
+
+        self.current = Some(catch);
+        self.statement(&try_catch.catch_body);
+        let after = self.start(EdgeKind::Normal);
+        if let Some(body_end) = body_end {
+            self.edge(body_end, after, EdgeKind::Normal);
+        }
+        self.current = Some(after);
+    }
+
+    // Preprocessor conditions are not known while lowering, so each branch can run.
+    fn conditional_statement(&mut self, conditional: &'s ConditionalStatement<'s>) {
+        let start = self.current();
+        let branches = std::iter::once(&conditional.if_.statements)
+            .chain(conditional.else_ifs.iter().map(|branch| &branch.statements))
+            .chain(&conditional.else_);
+        let mut ends = Vec::new();
+        for statements in branches {
+            self.current = Some(start);
+            self.start(EdgeKind::Normal);
+            self.statements(statements);
+            ends.extend(self.current);
+        }
+        let after = self.new_block();
+        if conditional.else_.is_none() {
+            self.edge(start, after, EdgeKind::Normal);
+        }
+        for end in ends {
+            self.edge(end, after, EdgeKind::Normal);
+        }
+        self.current = Some(after);
+    }
+}
+
+// Returns true if a loop condition is a constant that is always true, like `while ( true )`. These
+// loops never exit through their condition, the same as a missing `for` condition.
+fn is_always_true<'s>(expression: &'s Expression<'s>) -> bool {
+    (ConstEvaluator::default().evaluate(expression)).is_ok_and(|value| value.is_truthy())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BlockItem, ControlFlowGraph, EdgeKind};
+    use crate::ast::StatementType;
+    use crate::{parse, tokenize, Flavor};
+
+    // Renders the graph of the first function in the source, one reachable block per line.
+    fn render(source: &str) -> Vec<String> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let StatementType::FunctionDefinition(function) = &program.statements[0].ty else {
+            panic!("expected a function")
+        };
+        let cfg = ControlFlowGraph::new(&function.definition);
+        let mut reachable = cfg.reachable();
+        reachable.sort();
+        reachable
+            .into_iter()
+            .map(|id| {
+                let block = cfg.block(id);
+                let items: Vec<_> = (block.items.iter())
+                    .map(|item| &source[item.span().unwrap()])
+                    .collect();
+                let edges: Vec<_> = (block.edges.iter())
+                    .map(|edge| format!("{:?} {}", edge.kind, edge.target.0))
+                    .collect();
+                format!("{}: [{}] -> {}", id.0, items.join("; "), edges.join(", "))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn straight_line() {
+        assert_eq!(
+            render("void function F() { local a = 1\n print( a ) }"),
+            ["0: [local a = 1; print( a )] -> Normal 1", "1: [] -> "]
+        );
+    }
+
+    #[test]
+    fn if_else() {
+        assert_eq!(
+            render("void function F( bool a ) { if ( a ) A() else B()\n C() }"),
+            [
+                "0: [a] -> True 2, False 3",
+                "1: [] -> ",
+                "2: [A()] -> Normal 4",
+                "3: [B()] -> Normal 4",
+                "4: [C()] -> Normal 1",
+            ]
+        );
+        assert_eq!(
+            render("void function F( bool a ) { if ( a ) return\n C() }"),
+            [
+                "0: [a] -> True 2, False 3",
+                "1: [] -> ",
+                "2: [return] -> Return 1",
+                "3: [C()] -> Normal 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn loops() {
+        assert_eq!(
+            render("void function F( int a ) { while ( a ) { if ( a ) break\n a-- } }"),
+            [
+                "0: [] -> Normal 2",
+                "1: [] -> ",
+                "2: [a] -> False 3, True 4",
+                "3: [] -> Normal 1",
+                "4: [a] -> True 5, False 6",
+                "5: [break] -> Break 3",
+                "6: [a--] -> Loop 2",
+            ]
+        );
+        assert_eq!(
+            render("void function F() { do { continue } while ( A() ) }"),
+            [
+                "0: [] -> Normal 2",
+                "1: [] -> ",
+                "2: [continue] -> Continue 3",
+                "3: [A()] -> True 2, False 4",
+                "4: [] -> Normal 1",
+            ]
+        );
+        assert_eq!(
+            render("void function F() { for ( int i = 0; i < 3; i++ ) A( i ) }"),
+            [
+                "0: [int i = 0] -> Normal 2",
+                "1: [] -> ",
+                "2: [i < 3] -> False 3, True 5",
+                "3: [] -> Normal 1",
+                "4: [i++] -> Loop 2",
+                "5: [A( i )] -> Normal 4",
+            ]
+        );
+        assert_eq!(
+            render("void function F( array a ) { foreach ( v in a ) A( v ) }"),
+            [
+                "0: [a] -> Normal 2",
+                "1: [] -> ",
+                "2: [foreach ( v in a )] -> False 3, True 4",
+                "3: [] -> Normal 1",
+                "4: [A( v )] -> Loop 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn infinite_loop() {
+        assert_eq!(
+            render("void function F() { for ( ;; ) A()\n B() }"),
+            [
+                "0: [] -> Normal 2",
+                "2: [] -> Normal 5",
+                "4: [] -> Loop 2",
+                "5: [A()] -> Normal 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn constant_true_loop() {
+        assert_eq!(
+            render("void function F() { while ( true ) A()\n B() }"),
+            [
+                "0: [] -> Normal 2",
+                "2: [true] -> Normal 4",
+                "4: [A()] -> Loop 2",
+            ]
+        );
+        assert_eq!(
+            render("void function F() { while ( 0 ) A()\n B() }"),
+            [
+                "0: [] -> Normal 2",
+                "1: [] -> ",
+                "2: [0] -> False 3, True 4",
+                "3: [B()] -> Normal 1",
+                "4: [A()] -> Loop 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn switch_fallthrough() {
+        assert_eq!(
+            render(
+                "void function F( int a ) { switch ( a ) { case 1: A()\n case 2: B()\n break\n default: C() } }"
+            ),
+            [
+                "0: [a] -> Case 2, Case 3, Case 4",
+                "1: [] -> ",
+                "2: [case 1:; A()] -> Fallthrough 3",
+                "3: [case 2:; B(); break] -> Break 5",
+                "4: [default:; C()] -> Normal 5",
+                "5: [] -> Normal 1",
+            ]
+        );
+        assert_eq!(
+            render("void function F( int a ) { switch ( a ) { case 1: return } }"),
+            [
+                "0: [a] -> Case 2, NoMatch 3",
+                "1: [] -> ",
+                "2: [case 1:; return] -> Return 1",
+                "3: [] -> Normal 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn try_catch() {
+        assert_eq!(
+            render("void function F() { try { A()\n throw 1 } catch ( e ) B( e )\n C() }"),
+            [
+                "0: [] -> Normal 3",
+                "1: [] -> ",
+                "2: [catch ( e ); B( e )] -> Normal 4",
+                "3: [A(); throw 1] -> Throw 2, Exception 2",
+                "4: [C()] -> Normal 1",
+            ]
+        );
+        assert_eq!(
+            render("void function F() { throw 1 }"),
+            ["0: [throw 1] -> Throw 1", "1: [] -> "]
+        );
+    }
+
+    #[test]
+    fn unreachable_statements() {
+        let source = "void function F() { return\n A() }";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let StatementType::FunctionDefinition(function) = &program.statements[0].ty else {
+            panic!("expected a function")
+        };
+        let cfg = ControlFlowGraph::new(&function.definition);
+        let StatementType::Block(block) = &*function.definition.body else {
+            panic!("expected a block")
+        };
+
+        let call = cfg.block_of(&block.statements[1].ty).unwrap();
+        assert!(!cfg.reachable().contains(&call));
+        assert_eq!(cfg.predecessors(call).count(), 0);
+        assert!(matches!(
+            cfg.block(call).items[..],
+            [BlockItem::Statement(StatementType::Expression(_))]
+        ));
+        assert_eq!(
+            cfg.predecessors(ControlFlowGraph::EXIT)
+                .map(|(_, edge)| edge.kind)
+                .collect::<Vec<_>>(),
+            [EdgeKind::Return, EdgeKind::Normal]
+        );
+    }
+}
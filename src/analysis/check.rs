@@ -0,0 +1,1006 @@
+use crate::analysis::{DeclarationId, DeclarationKind, Resolution, Ty, TypeRegistry};
+use crate::annotation::{Diagnostic, Label, Level};
+use crate::ast::{
+    walk_binary_expression, walk_call_expression, walk_const_definition_statement,
+    walk_function_definition_statement, walk_function_expression, walk_lambda_expression,
+    walk_property_expression, walk_return_statement, walk_slot, walk_struct_definition,
+    walk_var_definition_statement, BinaryExpression, BinaryOperator, CallArgument, CallExpression,
+    ConstDefinitionStatement, Expression, FunctionDefinitionStatement, FunctionExpression,
+    FunctionParam, FunctionParams, LambdaExpression, LiteralExpression, MethodIdentifier,
+    PrefixOperator, Program, PropertyExpression, ReturnStatement, Slot, Spanned, StructDefinition,
+    StructProperty, Type, VarDefinitionStatement, VarInitializer, Visit,
+};
+use crate::token::{LiteralToken, StringToken};
+use std::cell::Cell;
+use std::ops::Range;
+
+/// Type of [`TypeError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeErrorType<'s> {
+    /// A value has a different type to the location it is stored in or passed to.
+    ///
+    /// # Example
+    /// ```text
+    /// int count = "none"
+    ///             ^^^^^^ error
+    /// ```
+    Mismatch { expected: Ty<'s>, found: Ty<'s> },
+
+    /// A function was called with the wrong number of arguments.
+    ///
+    /// # Example
+    /// ```text
+    /// void function Kill( entity player ) {}
+    /// Kill()
+    /// ^^^^^^ error
+    /// ```
+    ArgumentCount {
+        min: usize,
+        max: Option<usize>,
+        found: usize,
+    },
+
+    /// A `void` function returns a value.
+    ///
+    /// # Example
+    /// ```text
+    /// void function Reset() { return 0 }
+    ///                                ^ error
+    /// ```
+    ReturnValueInVoidFunction,
+
+    /// A function with a return type returns without a value.
+    ///
+    /// # Example
+    /// ```text
+    /// int function Count() { return }
+    ///                        ^^^^^^ error
+    /// ```
+    MissingReturnValue { expected: Ty<'s> },
+
+    /// A struct property that does not exist is used.
+    ///
+    /// # Example
+    /// ```text
+    /// struct { int count } file
+    /// file.total = 0
+    ///      ^^^^^ error
+    /// ```
+    UnknownField { ty: Ty<'s>, field: &'s str },
+
+    /// A type name that is not declared.
+    ///
+    /// # Example
+    /// ```text
+    /// Player player
+    /// ^^^^^^ error
+    /// ```
+    UnknownType { name: &'s str },
+
+    /// A `typedef` that refers to itself, directly or through other typedefs.
+    ///
+    /// # Example
+    /// ```text
+    /// typedef Tree array<Tree>
+    ///         ^^^^ error
+    /// ```
+    CyclicTypeDefinition { name: &'s str },
+
+    /// A generic type with the wrong number of type arguments.
+    ///
+    /// # Example
+    /// ```text
+    /// table<string> names
+    /// ^^^^^^^^^^^^^ error
+    /// ```
+    TypeArgumentCount { expected: usize, found: usize },
+}
+
+/// An error found by [`check_types`] or [`TypeRegistry::errors`].
+#[derive(Debug, Clone)]
+pub struct TypeError<'s> {
+    /// The type of error.
+    pub ty: TypeErrorType<'s>,
+
+    /// The character range of the code with the error.
+    pub range: Range<usize>,
+}
+
+/// Checks the types of expressions in a program.
+///
+/// Types of expressions are inferred from literals, operators, and the declared types of variables,
+/// parameters, constants, struct properties and functions. Errors are reported where a value's type
+/// is known and does not match the type it is stored in, passed as or returned as. Values with a
+/// `var` or `local` type, and values that come from outside the program like native functions, are
+/// dynamically typed so are never errors.
+///
+/// `resolution` must be the [`Resolution`] of `program`, and `types` must be a [`TypeRegistry`]
+/// containing `program`. Problems with the types themselves, like unknown type names, are reported
+/// by [`TypeRegistry::errors`] instead.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{check_types, Resolution, Ty, TypeErrorType, TypeRegistry};
+///
+/// let source = r#"
+/// int function Double( int value )
+/// {
+///     return value * 2
+/// }
+///
+/// void function Main()
+/// {
+///     string result = Double( 4 )
+/// }
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+/// let resolution = Resolution::new(&program);
+/// let types = TypeRegistry::new(&program);
+///
+/// let errors = check_types(&program, &resolution, &types);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(
+///     errors[0].ty,
+///     TypeErrorType::Mismatch { expected: Ty::String, found: Ty::Int }
+/// );
+/// assert_eq!(&source[errors[0].range.clone()], "Double( 4 )");
+/// ```
+pub fn check_types<'s>(
+    program: &'s Program<'s>,
+    resolution: &Resolution<'s>,
+    types: &TypeRegistry<'s>,
+) -> Vec<TypeError<'s>> {
+    let mut checker = TypeChecker {
+        resolution,
+        types,
+        errors: Vec::new(),
+        return_types: Vec::new(),
+        depth: Cell::new(0),
+    };
+    checker.visit_program(program);
+    checker.errors
+}
+
+impl<'s> TypeError<'s> {
+    /// Creates a new `TypeError`.
+    pub fn new(ty: TypeErrorType<'s>, range: Range<usize>) -> Self {
+        TypeError { ty, range }
+    }
+
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`].
+    ///
+    /// [`code`]: TypeErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        Diagnostic::new(Level::Error, self.ty.to_string(), label).with_code(self.ty.code())
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
+    /// context using [`Diagnostic::display`].
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source,
+            file_name,
+        }
+    }
+}
+
+impl<'s> TypeErrorType<'s> {
+    /// Returns a stable code for the error type, from `E0201` to `E0299`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            TypeErrorType::Mismatch { .. } => "E0201",
+            TypeErrorType::ArgumentCount { .. } => "E0202",
+            TypeErrorType::ReturnValueInVoidFunction => "E0203",
+            TypeErrorType::MissingReturnValue { .. } => "E0204",
+            TypeErrorType::UnknownField { .. } => "E0205",
+            TypeErrorType::UnknownType { .. } => "E0206",
+            TypeErrorType::CyclicTypeDefinition { .. } => "E0207",
+            TypeErrorType::TypeArgumentCount { .. } => "E0208",
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(&self) -> impl std::fmt::Display + '_ {
+        TypeErrorInlineDisplay(self)
+    }
+}
+
+impl std::fmt::Display for TypeErrorType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeErrorType::Mismatch { expected, found } => {
+                write!(
+                    f,
+                    "mismatched types: expected `{expected}`, found `{found}`"
+                )
+            }
+            TypeErrorType::ArgumentCount { min, max, found } => {
+                let plural = |count: usize| if count == 1 { "" } else { "s" };
+                match max {
+                    Some(max) if max == min => write!(
+                        f,
+                        "function takes {min} argument{} but {found} were supplied",
+                        plural(*min)
+                    ),
+                    Some(max) => write!(
+                        f,
+                        "function takes {min} to {max} arguments but {found} were supplied"
+                    ),
+                    None => write!(
+                        f,
+                        "function takes at least {min} argument{} but {found} were supplied",
+                        plural(*min)
+                    ),
+                }
+            }
+            TypeErrorType::ReturnValueInVoidFunction => {
+                write!(f, "cannot return a value from a `void` function")
+            }
+            TypeErrorType::MissingReturnValue { expected } => {
+                write!(f, "missing return value of type `{expected}`")
+            }
+            TypeErrorType::UnknownField { ty, field } => {
+                write!(f, "no field `{field}` on type `{ty}`")
+            }
+            TypeErrorType::UnknownType { name } => write!(f, "unknown type `{name}`"),
+            TypeErrorType::CyclicTypeDefinition { name } => {
+                write!(f, "type definition `{name}` refers to itself")
+            }
+            TypeErrorType::TypeArgumentCount { expected, found } => {
+                let supplied = if *found == 1 { "was" } else { "were" };
+                match expected {
+                    0 => write!(f, "type takes no type arguments"),
+                    1 => write!(f, "type takes 1 type argument"),
+                    _ => write!(f, "type takes {expected} type arguments"),
+                }?;
+                write!(f, " but {found} {supplied} supplied")
+            }
+        }
+    }
+}
+
+struct TypeErrorInlineDisplay<'a, 's>(&'a TypeErrorType<'s>);
+
+impl std::fmt::Display for TypeErrorInlineDisplay<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            TypeErrorType::Mismatch { expected, .. } => write!(f, "expected `{expected}`"),
+            TypeErrorType::ArgumentCount { found, .. } => {
+                write!(f, "called with {found} arguments")
+            }
+            TypeErrorType::ReturnValueInVoidFunction => write!(f, "returns a value"),
+            TypeErrorType::MissingReturnValue { .. } => write!(f, "returns nothing"),
+            TypeErrorType::UnknownField { .. } => write!(f, "unknown field"),
+            TypeErrorType::UnknownType { .. } => write!(f, "not found"),
+            TypeErrorType::CyclicTypeDefinition { .. } => write!(f, "cyclic type definition"),
+            TypeErrorType::TypeArgumentCount { found, .. } => {
+                write!(f, "{found} type arguments")
+            }
+        }
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
+
+// Constants can refer to each other, so resolving them is limited to this depth to avoid looping
+// forever on cycles.
+const MAX_DEPTH: usize = 64;
+
+struct TypeChecker<'r, 's> {
+    resolution: &'r Resolution<'s>,
+    types: &'r TypeRegistry<'s>,
+    errors: Vec<TypeError<'s>>,
+
+    // Return types of the functions being checked, or `None` if a function has no return type.
+    return_types: Vec<Option<Ty<'s>>>,
+
+    depth: Cell<usize>,
+}
+
+impl<'s> TypeChecker<'_, 's> {
+    fn error(&mut self, ty: TypeErrorType<'s>, node: &impl Spanned<'s>) {
+        if let Some(range) = node.span() {
+            self.errors.push(TypeError::new(ty, range));
+        }
+    }
+
+    // Reports an error if a value can't be stored in a location of the expected type.
+    fn check_assign(&mut self, expected: &Ty<'s>, value: &'s Expression<'s>) {
+        let found = self.expression_type(value);
+        if !expected.accepts(&found) {
+            let expected = expected.clone();
+            self.error(TypeErrorType::Mismatch { expected, found }, value);
+        }
+    }
+
+    fn check_initializer(
+        &mut self,
+        type_: &'s Type<'s>,
+        initializer: Option<&'s VarInitializer<'s>>,
+    ) {
+        if let Some(initializer) = initializer {
+            let expected = self.resolve_type(type_);
+            self.check_assign(&expected, &initializer.value);
+        }
+    }
+
+    // Runs `f` with an extra level of depth, returning `Ty::Unknown` if the depth is too high.
+    fn nested(&self, f: impl FnOnce() -> Ty<'s>) -> Ty<'s> {
+        let depth = self.depth.get();
+        if depth >= MAX_DEPTH {
+            return Ty::Unknown;
+        }
+        self.depth.set(depth + 1);
+        let ty = f();
+        self.depth.set(depth);
+        ty
+    }
+
+    fn resolve_type(&self, type_: &'s Type<'s>) -> Ty<'s> {
+        self.types.resolve(type_)
+    }
+
+    // Resolves an optional type, where a missing type is dynamic.
+    fn optional_type(&self, type_: Option<&'s Type<'s>>) -> Ty<'s> {
+        type_.map_or(Ty::Var, |type_| self.resolve_type(type_))
+    }
+
+    // Returns the type of values of a declaration.
+    fn declaration_type(&self, id: DeclarationId) -> Ty<'s> {
+        self.nested(|| match self.resolution.declaration(id).kind {
+            DeclarationKind::Var { type_, .. } => self.resolve_type(type_),
+            DeclarationKind::Param(param) => self.optional_type(param.type_.as_ref()),
+            DeclarationKind::Capture { captured } => {
+                captured.map_or(Ty::Unknown, |id| self.declaration_type(id))
+            }
+            DeclarationKind::ForeachIndex(index) => self.optional_type(index.type_.as_ref()),
+            DeclarationKind::ForeachValue(statement) => match &statement.value_type {
+                Some(type_) => self.resolve_type(type_),
+                None => match self.expression_type(&statement.array) {
+                    Ty::Array(Some(value)) => *value,
+                    Ty::Table(Some(params)) => params.1,
+                    _ => Ty::Var,
+                },
+            },
+            DeclarationKind::Function(statement) => self.function_type(statement),
+            DeclarationKind::Const(statement) => self.const_type(statement),
+            DeclarationKind::EnumEntry(_) => Ty::Int,
+            DeclarationKind::StructProperty(property) => self.resolve_type(&property.type_),
+            DeclarationKind::UntypedGlobalVar { .. }
+            | DeclarationKind::CatchName(_)
+            | DeclarationKind::Class(_) => Ty::Var,
+            DeclarationKind::GlobalFunction
+            | DeclarationKind::Enum(_)
+            | DeclarationKind::Struct(_)
+            | DeclarationKind::TypeDefinition(_) => Ty::Unknown,
+        })
+    }
+
+    fn function_type(&self, statement: &'s FunctionDefinitionStatement<'s>) -> Ty<'s> {
+        Ty::FunctionRef {
+            return_type: Box::new(self.optional_type(statement.return_type.as_ref())),
+            params: (statement.definition.params.iter())
+                .map(|param| self.optional_type(param.type_.as_ref()))
+                .collect(),
+        }
+    }
+
+    fn const_type(&self, statement: &'s ConstDefinitionStatement<'s>) -> Ty<'s> {
+        match &statement.const_type {
+            Some(type_) => self.resolve_type(type_),
+            None => self.expression_type(&statement.initializer.value),
+        }
+    }
+
+    fn literal_type(&self, literal: &LiteralExpression) -> Ty<'s> {
+        match literal.literal {
+            LiteralToken::Int(..) | LiteralToken::Char(_) => Ty::Int,
+            LiteralToken::Float(_) => Ty::Float,
+            LiteralToken::String(StringToken::Asset(_)) => Ty::Asset,
+            LiteralToken::String(_) => Ty::String,
+        }
+    }
+
+    // Infers the type of an expression.
+    fn expression_type(&self, expression: &'s Expression<'s>) -> Ty<'s> {
+        match expression {
+            Expression::Parens(parens) => self.expression_type(&parens.value),
+            Expression::Literal(literal) => self.literal_type(literal),
+            Expression::Var(var) => match var.name.value {
+                "true" | "false" => Ty::Bool,
+                "null" => Ty::Null,
+                _ => match self.resolution.resolve(&var.name) {
+                    Some(id) => self.declaration_type(id),
+                    None => Ty::Unknown,
+                },
+            },
+            Expression::RootVar(var) => match self.resolution.resolve(&var.name) {
+                Some(id) => self.declaration_type(id),
+                None => Ty::Unknown,
+            },
+            Expression::Index(index) => match self.expression_type(&index.base) {
+                Ty::Array(Some(value)) => *value,
+                Ty::Table(Some(params)) => params.1,
+                Ty::String => Ty::Int,
+                Ty::Vector => Ty::Float,
+                _ => Ty::Unknown,
+            },
+            Expression::Property(property) => self.property_type(property),
+            Expression::Ternary(ternary) => {
+                let true_type = self.expression_type(&ternary.true_value);
+                let false_type = self.expression_type(&ternary.false_value);
+                match (&true_type, &false_type) {
+                    (Ty::Null, other) | (other, Ty::Null) if !other.is_dynamic() => {
+                        Ty::Nullable(Box::new(other.clone()))
+                    }
+                    _ if true_type == false_type => true_type,
+                    _ => Ty::Unknown,
+                }
+            }
+            Expression::Binary(binary) => self.binary_type(binary),
+            Expression::Prefix(prefix) => match prefix.operator {
+                PrefixOperator::LogicalNot(_) => Ty::Bool,
+                PrefixOperator::Typeof(_) => Ty::String,
+                PrefixOperator::Delete(_) => Ty::Unknown,
+                PrefixOperator::Negate(_)
+                | PrefixOperator::BitwiseNot(_)
+                | PrefixOperator::Clone(_)
+                | PrefixOperator::Increment(_)
+                | PrefixOperator::Decrement(_) => self.expression_type(&prefix.value),
+            },
+            Expression::Postfix(postfix) => self.expression_type(&postfix.value),
+            Expression::Comma(comma) => self.expression_type(&comma.values.last_item),
+            Expression::Table(_) => Ty::Table(None),
+            Expression::Array(_) => Ty::Array(None),
+            Expression::Function(function) => Ty::FunctionRef {
+                return_type: Box::new(self.optional_type(function.return_type.as_ref())),
+                params: (function.definition.params.iter())
+                    .map(|param| self.optional_type(param.type_.as_ref()))
+                    .collect(),
+            },
+            Expression::Lambda(lambda) => Ty::FunctionRef {
+                return_type: Box::new(Ty::Var),
+                params: lambda.params.iter().map(|_| Ty::Var).collect(),
+            },
+            Expression::Call(call) => match self.expression_type(&call.function) {
+                Ty::FunctionRef { return_type, .. } => *return_type,
+                _ => Ty::Unknown,
+            },
+            Expression::Vector(_) => Ty::Vector,
+            Expression::Expect(expect) => self.resolve_type(&expect.ty),
+            Expression::Class(_) | Expression::Delegate(_) | Expression::Error(_) => Ty::Unknown,
+        }
+    }
+
+    fn property_type(&self, property: &'s PropertyExpression<'s>) -> Ty<'s> {
+        let MethodIdentifier::Identifier(name) = &property.property else {
+            return Ty::Unknown;
+        };
+
+        // Enum entries and file struct properties are resolved by name.
+        if let Some(id) = self.resolution.resolve(name) {
+            return self.declaration_type(id);
+        }
+
+        match self.expression_type(&property.base) {
+            Ty::Struct { definition, .. } => match find_property(definition, name.value) {
+                Some(property) => self.resolve_type(&property.type_),
+                None => Ty::Unknown,
+            },
+            Ty::Vector if matches!(name.value, "x" | "y" | "z") => Ty::Float,
+            _ => Ty::Unknown,
+        }
+    }
+
+    fn binary_type(&self, binary: &'s BinaryExpression<'s>) -> Ty<'s> {
+        let left = || self.expression_type(&binary.left);
+        let right = || self.expression_type(&binary.right);
+        match binary.operator {
+            BinaryOperator::Assign(_)
+            | BinaryOperator::AssignNewSlot(..)
+            | BinaryOperator::AssignAdd(_)
+            | BinaryOperator::AssignSubtract(_)
+            | BinaryOperator::AssignMultiply(_)
+            | BinaryOperator::AssignDivide(_)
+            | BinaryOperator::AssignModulo(_) => left(),
+
+            BinaryOperator::Add(_) => match (left(), right()) {
+                (Ty::String, _) | (_, Ty::String) => Ty::String,
+                (left, right) => arithmetic_type(left, right),
+            },
+            BinaryOperator::Subtract(_)
+            | BinaryOperator::Multiply(_)
+            | BinaryOperator::Divide(_)
+            | BinaryOperator::Modulo(_) => arithmetic_type(left(), right()),
+
+            BinaryOperator::Equal(_)
+            | BinaryOperator::NotEqual(_)
+            | BinaryOperator::Less(_)
+            | BinaryOperator::LessEqual(_)
+            | BinaryOperator::Greater(_)
+            | BinaryOperator::GreaterEqual(_)
+            | BinaryOperator::LogicalAnd(_)
+            | BinaryOperator::LogicalOr(_)
+            | BinaryOperator::In(_)
+            | BinaryOperator::Instanceof(_) => Ty::Bool,
+
+            BinaryOperator::ThreeWay(_)
+            | BinaryOperator::BitwiseAnd(_)
+            | BinaryOperator::BitwiseOr(_)
+            | BinaryOperator::BitwiseXor(_)
+            | BinaryOperator::ShiftLeft(..)
+            | BinaryOperator::ShiftRight(..)
+            | BinaryOperator::UnsignedShiftRight(..) => Ty::Int,
+        }
+    }
+
+    fn check_call(&mut self, call: &'s CallExpression<'s>) {
+        // Calls to functions in the program can be checked against the parameters, including any
+        // default values.
+        let function = match &*call.function {
+            Expression::Var(var) => self.resolution.resolve(&var.name),
+            _ => None,
+        };
+        if let Some(DeclarationKind::Function(statement)) =
+            function.map(|id| self.resolution.declaration(id).kind)
+        {
+            let params: Vec<_> = statement.definition.params.iter().collect();
+            let is_variable = !matches!(
+                statement.definition.params,
+                FunctionParams::NonVariable { .. }
+            );
+            self.check_arguments(call, &params, is_variable);
+            return;
+        }
+
+        if let Ty::FunctionRef { params, .. } = self.expression_type(&call.function) {
+            self.check_argument_count(call, params.len(), Some(params.len()));
+            for (param, argument) in params.iter().zip(&call.arguments) {
+                self.check_assign(param, &argument.value);
+            }
+        }
+    }
+
+    fn check_arguments(
+        &mut self,
+        call: &'s CallExpression<'s>,
+        params: &[&'s FunctionParam<'s>],
+        is_variable: bool,
+    ) {
+        let min = params
+            .iter()
+            .take_while(|param| param.initializer.is_none())
+            .count();
+        let max = (!is_variable).then_some(params.len());
+        self.check_argument_count(call, min, max);
+
+        let arguments: &'s [CallArgument<'s>] = &call.arguments;
+        for (param, argument) in params.iter().zip(arguments) {
+            if let Some(type_) = &param.type_ {
+                let expected = self.resolve_type(type_);
+                self.check_assign(&expected, &argument.value);
+            }
+        }
+    }
+
+    fn check_argument_count(
+        &mut self,
+        call: &'s CallExpression<'s>,
+        min: usize,
+        max: Option<usize>,
+    ) {
+        let found = call.arguments.len();
+        if found < min || max.is_some_and(|max| found > max) {
+            let error = TypeErrorType::ArgumentCount { min, max, found };
+            self.error(error, call);
+        }
+    }
+
+    fn check_params(&mut self, params: &'s FunctionParams<'s>) {
+        for param in params.iter() {
+            if let Some(type_) = &param.type_ {
+                self.check_initializer(type_, param.initializer.as_ref());
+            }
+        }
+    }
+
+    fn check_struct(&mut self, definition: &'s StructDefinition<'s>) {
+        for property in &definition.properties {
+            self.check_initializer(&property.type_, property.initializer.as_ref());
+        }
+    }
+}
+
+fn find_property<'s>(
+    definition: &'s StructDefinition<'s>,
+    name: &str,
+) -> Option<&'s StructProperty<'s>> {
+    (definition.properties.iter()).find(|property| property.name.value == name)
+}
+
+fn arithmetic_type<'s>(left: Ty<'s>, right: Ty<'s>) -> Ty<'s> {
+    match (left, right) {
+        (Ty::Int, Ty::Int) => Ty::Int,
+        (Ty::Float, Ty::Int | Ty::Float) | (Ty::Int, Ty::Float) => Ty::Float,
+        (Ty::Vector, Ty::Vector | Ty::Int | Ty::Float) | (Ty::Int | Ty::Float, Ty::Vector) => {
+            Ty::Vector
+        }
+        _ => Ty::Unknown,
+    }
+}
+
+impl<'s> Visit<'s> for TypeChecker<'_, 's> {
+    fn visit_var_definition_statement(&mut self, node: &'s VarDefinitionStatement<'s>) {
+        for definition in node.definitions.iter() {
+            self.check_initializer(&node.type_, definition.initializer.as_ref());
+        }
+        walk_var_definition_statement(self, node);
+    }
+
+    fn visit_const_definition_statement(&mut self, node: &'s ConstDefinitionStatement<'s>) {
+        if let Some(type_) = &node.const_type {
+            self.check_initializer(type_, Some(&node.initializer));
+        }
+        walk_const_definition_statement(self, node);
+    }
+
+    fn visit_struct_definition(&mut self, node: &'s StructDefinition<'s>) {
+        self.check_struct(node);
+        walk_struct_definition(self, node);
+    }
+
+    fn visit_function_definition_statement(&mut self, node: &'s FunctionDefinitionStatement<'s>) {
+        let return_type = node
+            .return_type
+            .as_ref()
+            .map(|type_| self.resolve_type(type_));
+        self.check_params(&node.definition.params);
+        self.return_types.push(return_type);
+        walk_function_definition_statement(self, node);
+        self.return_types.pop();
+    }
+
+    fn visit_function_expression(&mut self, node: &'s FunctionExpression<'s>) {
+        let return_type = node
+            .return_type
+            .as_ref()
+            .map(|type_| self.resolve_type(type_));
+        self.check_params(&node.definition.params);
+        self.return_types.push(return_type);
+        walk_function_expression(self, node);
+        self.return_types.pop();
+    }
+
+    fn visit_slot(&mut self, node: &'s Slot<'s>) {
+        let return_type = match node {
+            Slot::Function {
+                return_type,
+                definition,
+                ..
+            } => {
+                self.check_params(&definition.params);
+                return_type.as_ref().map(|type_| self.resolve_type(type_))
+            }
+            Slot::Constructor { definition, .. } => {
+                self.check_params(&definition.params);
+                None
+            }
+            Slot::Property { .. } | Slot::ComputedProperty { .. } => {
+                walk_slot(self, node);
+                return;
+            }
+        };
+        self.return_types.push(return_type);
+        walk_slot(self, node);
+        self.return_types.pop();
+    }
+
+    fn visit_lambda_expression(&mut self, node: &'s LambdaExpression<'s>) {
+        self.return_types.push(None);
+        walk_lambda_expression(self, node);
+        self.return_types.pop();
+    }
+
+    fn visit_return_statement(&mut self, node: &'s ReturnStatement<'s>) {
+        if let Some(Some(expected)) = self.return_types.last() {
+            let expected = expected.clone();
+            match (&expected, &node.value) {
+                (Ty::Void, Some(value)) => {
+                    self.error(TypeErrorType::ReturnValueInVoidFunction, value)
+                }
+                (Ty::Void, None) => {}
+                (expected, None) if !expected.is_dynamic() => {
+                    let expected = expected.clone();
+                    self.error(
+                        TypeErrorType::MissingReturnValue { expected },
+                        &node.return_,
+                    );
+                }
+                (expected, Some(value)) => self.check_assign(expected, value),
+                (_, None) => {}
+            }
+        }
+        walk_return_statement(self, node);
+    }
+
+    fn visit_binary_expression(&mut self, node: &'s BinaryExpression<'s>) {
+        if let BinaryOperator::Assign(_) = node.operator {
+            let expected = self.expression_type(&node.left);
+            self.check_assign(&expected, &node.right);
+        }
+        walk_binary_expression(self, node);
+    }
+
+    fn visit_call_expression(&mut self, node: &'s CallExpression<'s>) {
+        self.check_call(node);
+        walk_call_expression(self, node);
+    }
+
+    fn visit_property_expression(&mut self, node: &'s PropertyExpression<'s>) {
+        if let MethodIdentifier::Identifier(name) = &node.property {
+            if let Ty::Struct {
+                definition,
+                name: struct_name,
+            } = self.expression_type(&node.base)
+            {
+                if find_property(definition, name.value).is_none() {
+                    let ty = Ty::Struct {
+                        name: struct_name,
+                        definition,
+                    };
+                    let error = TypeErrorType::UnknownField {
+                        ty,
+                        field: name.value,
+                    };
+                    self.error(error, &name.token);
+                }
+            }
+        }
+        walk_property_expression(self, node);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_types, TypeErrorType};
+    use crate::analysis::{Resolution, Ty, TypeRegistry};
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns each error message with the source code it points at.
+    fn check(source: &str) -> Vec<(String, &str)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let resolution = Resolution::new(&program);
+        let types = TypeRegistry::new(&program);
+        check_types(&program, &resolution, &types)
+            .into_iter()
+            .map(|error| (error.ty.to_string(), &source[error.range]))
+            .collect()
+    }
+
+    #[test]
+    fn valid_program() {
+        let errors = check(
+            r#"
+            struct Data { int count, array<string> names }
+            typedef Names array<string>
+            const float SCALE = 2
+            enum Team { A, B }
+
+            int function Count( Data data, int offset = 0, ... )
+            {
+                return data.count + offset
+            }
+
+            void function Main()
+            {
+                Data data
+                data.count = Count( data ) * 2
+                Names names = data.names
+                string first = names[0] + "!"
+                float scaled = data.count * SCALE
+                int team = Team.B
+                entity player = null
+                vector origin = < 1, 2, 3 >
+                float x = origin.x
+                local dynamic = "anything"
+                int fromDynamic = dynamic
+                int native = GetNativeValue()
+                bool same = first == "a" && team != 0
+                Count( data, 1, "extra", "args" )
+                foreach ( string name in names )
+                {
+                    first = name
+                }
+            }
+            "#,
+        );
+        assert_eq!(errors, Vec::<(String, &str)>::new());
+    }
+
+    #[test]
+    fn mismatched_locals() {
+        let errors = check(
+            r#"
+            void function Main()
+            {
+                int count = "none"
+                string name = 1.5
+                float ok = 1
+                count = name
+                int ornull maybe = null
+                maybe = "what"
+            }
+            "#,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "mismatched types: expected `int`, found `string`".to_string(),
+                    "\"none\""
+                ),
+                (
+                    "mismatched types: expected `string`, found `float`".to_string(),
+                    "1.5"
+                ),
+                (
+                    "mismatched types: expected `int`, found `string`".to_string(),
+                    "name"
+                ),
+                (
+                    "mismatched types: expected `int ornull`, found `string`".to_string(),
+                    "\"what\""
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn function_arguments() {
+        let errors = check(
+            r#"
+            void function Kill( entity player, string reason = "" ) {}
+
+            void function Main( void functionref( int ) callback )
+            {
+                Kill()
+                Kill( null, "a", "b" )
+                Kill( 1 )
+                callback( "one" )
+                callback()
+            }
+            "#,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "function takes 1 to 2 arguments but 0 were supplied".to_string(),
+                    "Kill()"
+                ),
+                (
+                    "function takes 1 to 2 arguments but 3 were supplied".to_string(),
+                    "Kill( null, \"a\", \"b\" )"
+                ),
+                (
+                    "mismatched types: expected `entity`, found `int`".to_string(),
+                    "1"
+                ),
+                (
+                    "mismatched types: expected `int`, found `string`".to_string(),
+                    "\"one\""
+                ),
+                (
+                    "function takes 1 argument but 0 were supplied".to_string(),
+                    "callback()"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn return_values() {
+        let errors = check(
+            r#"
+            void function Reset()
+            {
+                return 0
+            }
+
+            int function Count()
+            {
+                if ( true )
+                    return
+                return "many"
+            }
+
+            function Untyped()
+            {
+                return 1
+            }
+
+            void function Outer()
+            {
+                string functionref() inner = string function() { return 2 }
+                var lambda = @() 1
+            }
+            "#,
+        );
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "cannot return a value from a `void` function".to_string(),
+                    "0"
+                ),
+                ("missing return value of type `int`".to_string(), "return"),
+                (
+                    "mismatched types: expected `int`, found `string`".to_string(),
+                    "\"many\""
+                ),
+                (
+                    "mismatched types: expected `string`, found `int`".to_string(),
+                    "2"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let tokens = tokenize(
+            r#"
+            struct Data { int count }
+            struct { Data data } file
+
+            void function Main()
+            {
+                file.data.total = 1
+                file.data.count = 1
+            }
+            "#,
+            Flavor::SquirrelRespawn,
+        )
+        .unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let resolution = Resolution::new(&program);
+        let types = TypeRegistry::new(&program);
+        let errors = check_types(&program, &resolution, &types);
+
+        assert_eq!(errors.len(), 1);
+        let TypeErrorType::UnknownField { ty, field } = &errors[0].ty else {
+            panic!("expected an unknown field error");
+        };
+        assert_eq!(ty.to_string(), "Data");
+        assert_eq!(*field, "total");
+        assert!(matches!(ty, Ty::Struct { .. }));
+    }
+}
@@ -0,0 +1,900 @@
+use crate::annotation::{Diagnostic, Label, Level};
+use crate::ast::{
+    BinaryExpression, BinaryOperator, ConstDefinitionStatement, EnumDefinitionStatement, EnumEntry,
+    Expression, GlobalDefinition, Identifier, MethodIdentifier, PrefixExpression, PrefixOperator,
+    Program, Spanned, Statement, StatementType,
+};
+use crate::token::{LiteralToken, StringToken};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// The value of a constant expression.
+///
+/// Implements [`std::fmt::Display`] to write the value as a Squirrel literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Asset(String),
+}
+
+/// Type of [`ConstError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstErrorType<'s> {
+    /// An integer is divided by zero, with `/` or `%`.
+    ///
+    /// # Example
+    /// ```text
+    /// const int HALF = 1 / 0
+    ///                  ^^^^^ error
+    /// ```
+    DivisionByZero,
+
+    /// An expression that is never constant, like a function call.
+    ///
+    /// # Example
+    /// ```text
+    /// const int COUNT = GetCount()
+    ///                   ^^^^^^^^^^ error
+    /// ```
+    NotConstant,
+
+    /// A name that is not a constant or enum entry.
+    ///
+    /// # Example
+    /// ```text
+    /// const int COUNT = count
+    ///                   ^^^^^ error
+    /// ```
+    NonConstantName { name: &'s str },
+
+    /// A constant that refers to itself.
+    ///
+    /// # Example
+    /// ```text
+    /// const int A = B
+    /// const int B = A + 1
+    ///               ^ error
+    /// ```
+    CyclicReference { name: &'s str },
+
+    /// An operator is used with values it does not support.
+    ///
+    /// # Example
+    /// ```text
+    /// const int VALUE = true + 1
+    ///                   ^^^^^^^^ error
+    /// ```
+    InvalidOperands {
+        operator: &'static str,
+        left: &'static str,
+        right: Option<&'static str>,
+    },
+}
+
+/// An error emitted while evaluating a constant expression.
+///
+/// Each error has a type with more information, and a range indicating where in the source string
+/// the error occurred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstError<'s> {
+    /// The type of error.
+    pub ty: ConstErrorType<'s>,
+
+    /// The character range of the expression with the error.
+    pub range: Range<usize>,
+}
+
+/// Evaluates constant expressions, using the constants and enums declared in one or more programs.
+///
+/// Constant expressions are built from literals, `true`, `false` and `null`, parentheses, prefix
+/// and binary operators, ternaries, and references to constants and enum entries like
+/// `eTeam.IMC`. Evaluation follows Squirrel's semantics:
+///  - Integer arithmetic wraps, and division truncates towards zero. Dividing an integer by zero is
+///    an error, but dividing a float by zero is not.
+///  - An operator with an integer and a float operand converts the integer to a float.
+///  - `+` with a string operand concatenates the other operand to it.
+///  - `&&` and `||` return one of their operands, not a `bool`.
+///  - Character literals like `'a'` are integers.
+///
+/// Enum entries without a value are numbered from zero, counting only the entries without a value.
+/// Entries with a value do not affect the numbering.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{ConstEvaluator, ConstValue};
+///
+/// let source = r#"
+/// const int MAX_PLAYERS = 16
+/// const float HALF_PLAYERS = MAX_PLAYERS / 2.0
+/// enum eTeam { UNASSIGNED, IMC = MAX_PLAYERS + 1, MILITIA }
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+/// let consts = ConstEvaluator::new(&program);
+///
+/// assert_eq!(consts.const_value("HALF_PLAYERS"), Some(Ok(ConstValue::Float(8.0))));
+/// assert_eq!(
+///     consts.enum_values("eTeam").unwrap().unwrap(),
+///     [
+///         ("UNASSIGNED", ConstValue::Int(0)),
+///         ("IMC", ConstValue::Int(17)),
+///         ("MILITIA", ConstValue::Int(1)),
+///     ]
+/// );
+/// assert!(consts.errors().is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConstEvaluator<'s> {
+    consts: HashMap<&'s str, &'s ConstDefinitionStatement<'s>>,
+    enums: HashMap<&'s str, &'s EnumDefinitionStatement<'s>>,
+    order: Vec<Declaration<'s>>,
+
+    // Evaluated constants, by name.
+    values: RefCell<HashMap<&'s str, Result<ConstValue, ConstError<'s>>>>,
+
+    // Names of constants and enum entries currently being evaluated, to stop at cycles.
+    evaluating: RefCell<Vec<&'s Identifier<'s>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Declaration<'s> {
+    Const(&'s ConstDefinitionStatement<'s>),
+    Enum(&'s EnumDefinitionStatement<'s>),
+}
+
+impl<'s> ConstEvaluator<'s> {
+    /// Creates an evaluator for the constants and enums declared in a program.
+    pub fn new(program: &'s Program<'s>) -> Self {
+        let mut evaluator = ConstEvaluator::default();
+        evaluator.add_program(program);
+        evaluator
+    }
+
+    /// Adds the constants and enums declared in another program, like another file in the same VM.
+    ///
+    /// Declarations in every branch of preprocessor conditionals are added. If a name is declared
+    /// more than once, the first declaration is used.
+    pub fn add_program(&mut self, program: &'s Program<'s>) {
+        for statement in &program.statements {
+            self.add_statement(statement);
+        }
+    }
+
+    /// Evaluates a constant expression.
+    pub fn evaluate(&self, expression: &'s Expression<'s>) -> Result<ConstValue, ConstError<'s>> {
+        let error = |ty| Err(ConstError::new(ty, expression_range(expression)));
+        match expression {
+            Expression::Parens(parens) => self.evaluate(&parens.value),
+            Expression::Literal(literal) => Ok(match literal.literal {
+                LiteralToken::Int(value, _) => ConstValue::Int(value),
+                LiteralToken::Char(value) => {
+                    let value = unescape(value).chars().next().map_or(0, |c| c as i64);
+                    ConstValue::Int(value)
+                }
+                LiteralToken::Float(value) => ConstValue::Float(value),
+                LiteralToken::String(StringToken::Literal(value)) => {
+                    ConstValue::String(unescape(value))
+                }
+                LiteralToken::String(StringToken::Verbatim(value)) => {
+                    ConstValue::String(value.replace("\"\"", "\""))
+                }
+                LiteralToken::String(StringToken::Asset(value)) => {
+                    ConstValue::Asset(unescape(value))
+                }
+            }),
+            Expression::Var(var) => self.evaluate_name(&var.name),
+            Expression::RootVar(var) => self.evaluate_name(&var.name),
+            Expression::Property(property) => {
+                let (Expression::Var(base), MethodIdentifier::Identifier(entry)) =
+                    (&*property.base, &property.property)
+                else {
+                    return error(ConstErrorType::NotConstant);
+                };
+                match self.enum_value(base.name.value, entry.value) {
+                    Some(value) => value,
+                    None => error(ConstErrorType::NonConstantName { name: entry.value }),
+                }
+            }
+            Expression::Ternary(ternary) => {
+                if self.evaluate(&ternary.condition)?.is_truthy() {
+                    self.evaluate(&ternary.true_value)
+                } else {
+                    self.evaluate(&ternary.false_value)
+                }
+            }
+            Expression::Binary(binary) => self.evaluate_binary(binary, expression),
+            Expression::Prefix(prefix) => self.evaluate_prefix(prefix, expression),
+            _ => error(ConstErrorType::NotConstant),
+        }
+    }
+
+    /// Returns the value of the constant with a name.
+    pub fn const_value(&self, name: &str) -> Option<Result<ConstValue, ConstError<'s>>> {
+        let statement = *self.consts.get(name)?;
+        Some(self.evaluate_const(statement, &statement.name))
+    }
+
+    /// Returns the value of an entry in the enum with a name.
+    pub fn enum_value(
+        &self,
+        enum_name: &str,
+        entry_name: &str,
+    ) -> Option<Result<ConstValue, ConstError<'s>>> {
+        let statement = *self.enums.get(enum_name)?;
+        let index = (statement.entries.iter()).position(|entry| entry.name.value == entry_name)?;
+        Some(self.evaluate_entry(statement, index, &statement.entries[index].name))
+    }
+
+    /// Returns the name and value of each entry in the enum with a name.
+    ///
+    /// Returns the first error if the value of an entry could not be evaluated.
+    pub fn enum_values(
+        &self,
+        enum_name: &str,
+    ) -> Option<Result<Vec<(&'s str, ConstValue)>, ConstError<'s>>> {
+        let statement = *self.enums.get(enum_name)?;
+        let values = (statement.entries.iter().enumerate())
+            .map(|(index, entry)| {
+                let value = self.evaluate_entry(statement, index, &entry.name)?;
+                Ok((entry.name.value, value))
+            })
+            .collect();
+        Some(values)
+    }
+
+    /// Returns errors in the values of every constant and enum entry, in the order they are
+    /// declared.
+    ///
+    /// Errors in a constant are only reported once, even if other constants refer to it.
+    pub fn errors(&self) -> Vec<ConstError<'s>> {
+        let mut errors: Vec<ConstError> = Vec::new();
+        let mut push = |result: Result<ConstValue, ConstError<'s>>| {
+            if let Err(error) = result {
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
+            }
+        };
+        for declaration in &self.order {
+            match *declaration {
+                Declaration::Const(statement) => {
+                    push(self.evaluate_const(statement, &statement.name))
+                }
+                Declaration::Enum(statement) => {
+                    for (index, entry) in statement.entries.iter().enumerate() {
+                        push(self.evaluate_entry(statement, index, &entry.name));
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    fn add_statement(&mut self, statement: &'s Statement<'s>) {
+        match &statement.ty {
+            StatementType::Const(statement) => self.add_const(statement),
+            StatementType::EnumDefinition(statement) => self.add_enum(statement),
+            StatementType::Global(global) => match &global.definition {
+                GlobalDefinition::Const(statement) => self.add_const(statement),
+                GlobalDefinition::Enum(statement) => self.add_enum(statement),
+                _ => {}
+            },
+            StatementType::Conditional(conditional) => {
+                let branches = std::iter::once(&conditional.if_.statements)
+                    .chain(conditional.else_ifs.iter().map(|branch| &branch.statements))
+                    .chain(&conditional.else_);
+                for statement in branches.flatten() {
+                    self.add_statement(statement);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn add_const(&mut self, statement: &'s ConstDefinitionStatement<'s>) {
+        if !self.consts.contains_key(statement.name.value) {
+            self.consts.insert(statement.name.value, statement);
+            self.order.push(Declaration::Const(statement));
+        }
+    }
+
+    fn add_enum(&mut self, statement: &'s EnumDefinitionStatement<'s>) {
+        if !self.enums.contains_key(statement.name.value) {
+            self.enums.insert(statement.name.value, statement);
+            self.order.push(Declaration::Enum(statement));
+        }
+    }
+
+    fn evaluate_name(&self, name: &'s Identifier<'s>) -> Result<ConstValue, ConstError<'s>> {
+        if let Some(statement) = self.consts.get(name.value) {
+            return self.evaluate_const(statement, name);
+        }
+        match name.value {
+            "true" => Ok(ConstValue::Bool(true)),
+            "false" => Ok(ConstValue::Bool(false)),
+            "null" => Ok(ConstValue::Null),
+            _ => Err(ConstError::new(
+                ConstErrorType::NonConstantName { name: name.value },
+                name.token.range.clone(),
+            )),
+        }
+    }
+
+    // Evaluates a constant, where `reference` is the name used to refer to it.
+    fn evaluate_const(
+        &self,
+        statement: &'s ConstDefinitionStatement<'s>,
+        reference: &'s Identifier<'s>,
+    ) -> Result<ConstValue, ConstError<'s>> {
+        if let Some(value) = self.values.borrow().get(statement.name.value) {
+            return value.clone();
+        }
+        let value = self.evaluate_declaration(&statement.name, reference, || {
+            self.evaluate(&statement.initializer.value)
+        })?;
+        (self.values.borrow_mut()).insert(statement.name.value, value.clone());
+        value
+    }
+
+    // Evaluates an enum entry, where `reference` is the name used to refer to it.
+    fn evaluate_entry(
+        &self,
+        statement: &'s EnumDefinitionStatement<'s>,
+        index: usize,
+        reference: &'s Identifier<'s>,
+    ) -> Result<ConstValue, ConstError<'s>> {
+        let entry: &EnumEntry = &statement.entries[index];
+        let Some(initializer) = &entry.initializer else {
+            let implicit = (statement.entries[..index].iter())
+                .filter(|entry| entry.initializer.is_none())
+                .count();
+            return Ok(ConstValue::Int(implicit as i64));
+        };
+        self.evaluate_declaration(&entry.name, reference, || self.evaluate(&initializer.value))?
+    }
+
+    // Evaluates a declared value, returning an error at `reference` if it is already being
+    // evaluated.
+    fn evaluate_declaration(
+        &self,
+        name: &'s Identifier<'s>,
+        reference: &'s Identifier<'s>,
+        evaluate: impl FnOnce() -> Result<ConstValue, ConstError<'s>>,
+    ) -> Result<Result<ConstValue, ConstError<'s>>, ConstError<'s>> {
+        let is_cycle = (self.evaluating.borrow().iter()).any(|other| std::ptr::eq(*other, name));
+        if is_cycle {
+            return Err(ConstError::new(
+                ConstErrorType::CyclicReference {
+                    name: reference.value,
+                },
+                reference.token.range.clone(),
+            ));
+        }
+        self.evaluating.borrow_mut().push(name);
+        let value = evaluate();
+        self.evaluating.borrow_mut().pop();
+        Ok(value)
+    }
+
+    fn evaluate_prefix(
+        &self,
+        prefix: &'s PrefixExpression<'s>,
+        expression: &'s Expression<'s>,
+    ) -> Result<ConstValue, ConstError<'s>> {
+        let range = expression_range(expression);
+        let (operator, value) = match prefix.operator {
+            PrefixOperator::Negate(_) => ("-", self.evaluate(&prefix.value)?),
+            PrefixOperator::LogicalNot(_) => ("!", self.evaluate(&prefix.value)?),
+            PrefixOperator::BitwiseNot(_) => ("~", self.evaluate(&prefix.value)?),
+            PrefixOperator::Typeof(_) => ("typeof", self.evaluate(&prefix.value)?),
+            _ => return Err(ConstError::new(ConstErrorType::NotConstant, range)),
+        };
+        match (operator, &value) {
+            ("-", ConstValue::Int(value)) => Ok(ConstValue::Int(value.wrapping_neg())),
+            ("-", ConstValue::Float(value)) => Ok(ConstValue::Float(-value)),
+            ("!", value) => Ok(ConstValue::Bool(!value.is_truthy())),
+            ("~", ConstValue::Int(value)) => Ok(ConstValue::Int(!value)),
+            ("typeof", value) => Ok(ConstValue::String(value.type_name().to_string())),
+            _ => Err(ConstError::new(
+                ConstErrorType::InvalidOperands {
+                    operator,
+                    left: value.type_name(),
+                    right: None,
+                },
+                range,
+            )),
+        }
+    }
+
+    fn evaluate_binary(
+        &self,
+        binary: &'s BinaryExpression<'s>,
+        expression: &'s Expression<'s>,
+    ) -> Result<ConstValue, ConstError<'s>> {
+        let range = expression_range(expression);
+        let operator = match binary.operator {
+            BinaryOperator::Add(_) => "+",
+            BinaryOperator::Subtract(_) => "-",
+            BinaryOperator::Multiply(_) => "*",
+            BinaryOperator::Divide(_) => "/",
+            BinaryOperator::Modulo(_) => "%",
+            BinaryOperator::Equal(_) => "==",
+            BinaryOperator::NotEqual(_) => "!=",
+            BinaryOperator::Less(_) => "<",
+            BinaryOperator::LessEqual(_) => "<=",
+            BinaryOperator::Greater(_) => ">",
+            BinaryOperator::GreaterEqual(_) => ">=",
+            BinaryOperator::ThreeWay(_) => "<=>",
+            BinaryOperator::LogicalAnd(_) => "&&",
+            BinaryOperator::LogicalOr(_) => "||",
+            BinaryOperator::BitwiseAnd(_) => "&",
+            BinaryOperator::BitwiseOr(_) => "|",
+            BinaryOperator::BitwiseXor(_) => "^",
+            BinaryOperator::ShiftLeft(..) => "<<",
+            BinaryOperator::ShiftRight(..) => ">>",
+            BinaryOperator::UnsignedShiftRight(..) => ">>>",
+            _ => return Err(ConstError::new(ConstErrorType::NotConstant, range)),
+        };
+
+        let left = self.evaluate(&binary.left)?;
+        match operator {
+            "&&" if !left.is_truthy() => return Ok(left),
+            "||" if left.is_truthy() => return Ok(left),
+            "&&" | "||" => return self.evaluate(&binary.right),
+            _ => {}
+        }
+        let right = self.evaluate(&binary.right)?;
+
+        let invalid = || {
+            ConstError::new(
+                ConstErrorType::InvalidOperands {
+                    operator,
+                    left: left.type_name(),
+                    right: Some(right.type_name()),
+                },
+                range.clone(),
+            )
+        };
+        let division_by_zero = || ConstError::new(ConstErrorType::DivisionByZero, range.clone());
+
+        use ConstValue::{Bool, Float, Int, String};
+        let value = match (operator, &left, &right) {
+            ("+", String(left), right) => String(format!("{left}{}", right.to_display_string())),
+            ("+", left, String(right)) => String(format!("{}{right}", left.to_display_string())),
+
+            ("+", Int(left), Int(right)) => Int(left.wrapping_add(*right)),
+            ("-", Int(left), Int(right)) => Int(left.wrapping_sub(*right)),
+            ("*", Int(left), Int(right)) => Int(left.wrapping_mul(*right)),
+            ("/" | "%", Int(_), Int(0)) => return Err(division_by_zero()),
+            ("/", Int(left), Int(right)) => Int(left.wrapping_div(*right)),
+            ("%", Int(left), Int(right)) => Int(left.wrapping_rem(*right)),
+            ("&", Int(left), Int(right)) => Int(left & right),
+            ("|", Int(left), Int(right)) => Int(left | right),
+            ("^", Int(left), Int(right)) => Int(left ^ right),
+            ("<<", Int(left), Int(right)) => Int(left.wrapping_shl(*right as u32)),
+            (">>", Int(left), Int(right)) => Int(left.wrapping_shr(*right as u32)),
+            (">>>", Int(left), Int(right)) => {
+                Int((*left as u64).wrapping_shr(*right as u32) as i64)
+            }
+
+            ("==", left, right) => Bool(left.compare(right) == Some(Ordering::Equal)),
+            ("!=", left, right) => Bool(left.compare(right) != Some(Ordering::Equal)),
+            ("<" | "<=" | ">" | ">=" | "<=>", left, right) => {
+                let ordering = (left.ordering(right)).ok_or_else(invalid)?;
+                match operator {
+                    "<" => Bool(ordering.is_lt()),
+                    "<=" => Bool(ordering.is_le()),
+                    ">" => Bool(ordering.is_gt()),
+                    ">=" => Bool(ordering.is_ge()),
+                    _ => Int(ordering as i64),
+                }
+            }
+
+            (_, left, right) => {
+                let (Some(left), Some(right)) = (left.as_float(), right.as_float()) else {
+                    return Err(invalid());
+                };
+                match operator {
+                    "+" => Float(left + right),
+                    "-" => Float(left - right),
+                    "*" => Float(left * right),
+                    "/" => Float(left / right),
+                    "%" => Float(left % right),
+                    _ => return Err(invalid()),
+                }
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl ConstValue {
+    /// Returns the name of the value's type, as returned by `typeof`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ConstValue::Null => "null",
+            ConstValue::Bool(_) => "bool",
+            ConstValue::Int(_) => "integer",
+            ConstValue::Float(_) => "float",
+            ConstValue::String(_) => "string",
+            ConstValue::Asset(_) => "asset",
+        }
+    }
+
+    /// Returns true if the value is true in a condition. `null`, `false`, `0` and `0.0` are false,
+    /// and every other value is true.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            ConstValue::Null => false,
+            ConstValue::Bool(value) => *value,
+            ConstValue::Int(value) => *value != 0,
+            ConstValue::Float(value) => *value != 0.0,
+            ConstValue::String(_) | ConstValue::Asset(_) => true,
+        }
+    }
+
+    /// Returns the value of an integer or float as a float.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            ConstValue::Int(value) => Some(*value as f64),
+            ConstValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // Returns the ordering of two values for `<` and similar operators, which only compare numbers
+    // and strings.
+    fn ordering(&self, other: &ConstValue) -> Option<Ordering> {
+        match (self, other) {
+            (ConstValue::Int(left), ConstValue::Int(right)) => Some(left.cmp(right)),
+            (ConstValue::String(left), ConstValue::String(right)) => Some(left.cmp(right)),
+            _ => self.as_float()?.partial_cmp(&other.as_float()?),
+        }
+    }
+
+    // Returns the ordering of two values for `==`, where values of different types are not equal.
+    fn compare(&self, other: &ConstValue) -> Option<Ordering> {
+        match (self, other) {
+            (ConstValue::Null, ConstValue::Null) => Some(Ordering::Equal),
+            (ConstValue::Bool(left), ConstValue::Bool(right)) => Some(left.cmp(right)),
+            (ConstValue::Asset(left), ConstValue::Asset(right)) => Some(left.cmp(right)),
+            _ => self.ordering(other),
+        }
+    }
+
+    // Returns the value as it is converted to a string when concatenated.
+    fn to_display_string(&self) -> String {
+        match self {
+            ConstValue::String(value) | ConstValue::Asset(value) => value.clone(),
+            ConstValue::Float(value) => value.to_string(),
+            value => value.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstValue::Null => write!(f, "null"),
+            ConstValue::Bool(value) => write!(f, "{value}"),
+            ConstValue::Int(value) => write!(f, "{value}"),
+            ConstValue::Float(value) => write!(f, "{value:?}"),
+            ConstValue::String(value) => write!(f, "{value:?}"),
+            ConstValue::Asset(value) => write!(f, "${value:?}"),
+        }
+    }
+}
+
+impl<'s> ConstError<'s> {
+    /// Creates a new `ConstError`.
+    pub fn new(ty: ConstErrorType<'s>, range: Range<usize>) -> Self {
+        ConstError { ty, range }
+    }
+
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`].
+    ///
+    /// [`code`]: ConstErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        Diagnostic::new(Level::Error, self.ty.to_string(), label).with_code(self.ty.code())
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
+    /// context using [`Diagnostic::display`].
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source,
+            file_name,
+        }
+    }
+}
+
+impl<'s> ConstErrorType<'s> {
+    /// Returns a stable code for the error type, from `E0301` to `E0399`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConstErrorType::DivisionByZero => "E0301",
+            ConstErrorType::NotConstant => "E0302",
+            ConstErrorType::NonConstantName { .. } => "E0303",
+            ConstErrorType::CyclicReference { .. } => "E0304",
+            ConstErrorType::InvalidOperands { .. } => "E0305",
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(&self) -> impl std::fmt::Display + '_ {
+        ConstErrorInlineDisplay(self)
+    }
+}
+
+impl std::fmt::Display for ConstErrorType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstErrorType::DivisionByZero => write!(f, "division by zero"),
+            ConstErrorType::NotConstant => write!(f, "expression is not constant"),
+            ConstErrorType::NonConstantName { name } => {
+                write!(f, "`{name}` is not a constant")
+            }
+            ConstErrorType::CyclicReference { name } => {
+                write!(f, "the value of `{name}` depends on itself")
+            }
+            ConstErrorType::InvalidOperands {
+                operator,
+                left,
+                right: Some(right),
+            } => write!(f, "cannot apply `{operator}` to `{left}` and `{right}`"),
+            ConstErrorType::InvalidOperands {
+                operator,
+                left,
+                right: None,
+            } => write!(f, "cannot apply `{operator}` to `{left}`"),
+        }
+    }
+}
+
+struct ConstErrorInlineDisplay<'a, 's>(&'a ConstErrorType<'s>);
+
+impl std::fmt::Display for ConstErrorInlineDisplay<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            ConstErrorType::DivisionByZero => write!(f, "divides by zero"),
+            ConstErrorType::NotConstant => write!(f, "not constant"),
+            ConstErrorType::NonConstantName { .. } => write!(f, "not a constant"),
+            ConstErrorType::CyclicReference { .. } => write!(f, "cyclic reference"),
+            ConstErrorType::InvalidOperands { .. } => write!(f, "invalid operands"),
+        }
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
+
+fn expression_range(expression: &Expression) -> Range<usize> {
+    expression.span().unwrap_or(0..0)
+}
+
+// Processes the escape sequences in the contents of a string or character literal.
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        let Some(escape) = chars.next() else {
+            result.push('\\');
+            break;
+        };
+        match escape {
+            't' => result.push('\t'),
+            'a' => result.push('\x07'),
+            'b' => result.push('\x08'),
+            'n' => result.push('\n'),
+            'r' => result.push('\r'),
+            'v' => result.push('\x0b'),
+            'f' => result.push('\x0c'),
+            '0' => result.push('\0'),
+            'x' | 'u' | 'U' => {
+                let max_digits = match escape {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 8,
+                };
+                let digits: String = (chars.clone())
+                    .take(max_digits)
+                    .take_while(char::is_ascii_hexdigit)
+                    .collect();
+                for _ in 0..digits.len() {
+                    chars.next();
+                }
+                let code = u32::from_str_radix(&digits, 16).ok();
+                result.extend(code.and_then(char::from_u32));
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConstErrorType, ConstEvaluator, ConstValue};
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns the value of the last constant in the source.
+    fn value(source: &str) -> Result<ConstValue, String> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let consts = ConstEvaluator::new(&program);
+        let last = source.lines().last().unwrap();
+        let name = last
+            .split('=')
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .last()
+            .unwrap();
+        consts
+            .const_value(name)
+            .unwrap()
+            .map_err(|error| error.ty.to_string())
+    }
+
+    #[test]
+    fn integer_and_float_semantics() {
+        assert_eq!(value("const X = 7 / 2"), Ok(ConstValue::Int(3)));
+        assert_eq!(value("const X = -7 / 2"), Ok(ConstValue::Int(-3)));
+        assert_eq!(value("const X = -7 % 3"), Ok(ConstValue::Int(-1)));
+        assert_eq!(value("const X = 7 / 2.0"), Ok(ConstValue::Float(3.5)));
+        assert_eq!(value("const X = 1.5 * 2"), Ok(ConstValue::Float(3.0)));
+        assert_eq!(
+            value("const X = 1.0 / 0"),
+            Ok(ConstValue::Float(f64::INFINITY))
+        );
+        assert_eq!(
+            value("const X = 0x7FFFFFFFFFFFFFFF + 1"),
+            Ok(ConstValue::Int(i64::MIN))
+        );
+        assert_eq!(value("const X = 1 << 4 | 3 & ~1"), Ok(ConstValue::Int(18)));
+        assert_eq!(value("const X = -16 >> 2"), Ok(ConstValue::Int(-4)));
+        assert_eq!(value("const X = 'a' + 1"), Ok(ConstValue::Int(98)));
+        assert_eq!(value("const X = '\\n'"), Ok(ConstValue::Int(10)));
+    }
+
+    #[test]
+    fn comparisons_and_logic() {
+        assert_eq!(value("const X = 1 == 1.0"), Ok(ConstValue::Bool(true)));
+        assert_eq!(value("const X = 1 == \"1\""), Ok(ConstValue::Bool(false)));
+        assert_eq!(value("const X = 2 <=> 1"), Ok(ConstValue::Int(1)));
+        assert_eq!(value("const X = \"a\" < \"b\""), Ok(ConstValue::Bool(true)));
+        assert_eq!(value("const X = 0 || 5"), Ok(ConstValue::Int(5)));
+        assert_eq!(value("const X = 0 && 1 / 0"), Ok(ConstValue::Int(0)));
+        assert_eq!(value("const X = !null"), Ok(ConstValue::Bool(true)));
+        assert_eq!(value("const X = 1 > 2 ? \"a\" : 3"), Ok(ConstValue::Int(3)));
+        assert_eq!(
+            value("const X = typeof 1.0"),
+            Ok(ConstValue::String("float".to_string()))
+        );
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            value("const X = \"count: \" + 3 + \"\\t\""),
+            Ok(ConstValue::String("count: 3\t".to_string()))
+        );
+        assert_eq!(
+            value("const X = @\"say \"\"hi\"\"\""),
+            Ok(ConstValue::String("say \"hi\"".to_string()))
+        );
+        assert_eq!(
+            value("const asset X = $\"models/a.mdl\""),
+            Ok(ConstValue::Asset("models/a.mdl".to_string()))
+        );
+        assert_eq!(ConstValue::Asset("a".to_string()).to_string(), "$\"a\"");
+        assert_eq!(ConstValue::Float(2.0).to_string(), "2.0");
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(
+            value("enum E { A, B }\nconst C = E.B * 10\nconst D = ::C + 1"),
+            Ok(ConstValue::Int(11))
+        );
+        assert_eq!(
+            value("#if SERVER\nconst A = 1\n#else\nconst A = 2\n#endif\nconst B = A"),
+            Ok(ConstValue::Int(1))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            value("const X = 1 % 0"),
+            Err("division by zero".to_string())
+        );
+        assert_eq!(
+            value("const X = Foo()"),
+            Err("expression is not constant".to_string())
+        );
+        assert_eq!(
+            value("const X = count + 1"),
+            Err("`count` is not a constant".to_string())
+        );
+        assert_eq!(
+            value("const X = true + 1"),
+            Err("cannot apply `+` to `bool` and `integer`".to_string())
+        );
+        assert_eq!(
+            value("const X = -\"a\""),
+            Err("cannot apply `-` to `string`".to_string())
+        );
+        assert_eq!(
+            value("const A = B\nconst X = A + 1\nconst B = X"),
+            Err("the value of `B` depends on itself".to_string())
+        );
+
+        let source = "const A = B\nconst B = A\nenum E { X = 1 / 0, Y = E.Z }";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let consts = ConstEvaluator::new(&program);
+        let errors: Vec<_> = (consts.errors().into_iter())
+            .map(|error| (error.ty, &source[error.range]))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (ConstErrorType::CyclicReference { name: "A" }, "A"),
+                (ConstErrorType::DivisionByZero, "1 / 0"),
+                (ConstErrorType::NonConstantName { name: "Z" }, "E.Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_values() {
+        let source = "enum E { A, B = 10, C, D = B + C, F = 'x' }";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let consts = ConstEvaluator::new(&program);
+        assert_eq!(
+            consts.enum_values("E").unwrap().unwrap_err().ty,
+            ConstErrorType::NonConstantName { name: "B" }
+        );
+        assert_eq!(consts.enum_value("E", "C"), Some(Ok(ConstValue::Int(1))));
+        assert_eq!(consts.enum_value("E", "F"), Some(Ok(ConstValue::Int(120))));
+        assert_eq!(consts.enum_value("E", "G"), None);
+        assert_eq!(consts.enum_values("Missing"), None);
+    }
+}
@@ -0,0 +1,798 @@
+use crate::analysis::{
+    BlockItem, ControlFlowGraph, DeclarationId, DeclarationKind, EdgeKind, Resolution,
+};
+use crate::annotation::{Diagnostic, Label, Level};
+use crate::ast::{
+    walk_binary_expression, walk_function_definition, walk_lambda_expression,
+    walk_postfix_expression, walk_prefix_expression, BinaryExpression, BinaryOperator, Expression,
+    ForDefinition, FunctionCaptures, FunctionDefinition, Identifier, LambdaExpression,
+    PostfixExpression, PostfixOperator, PrefixExpression, PrefixOperator, Program, Spanned,
+    SwitchCaseCondition, Type, VarDefinition, VarExpression, Visit,
+};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Type of [`DataflowError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataflowErrorType<'s> {
+    /// A `local` or `var` variable is read before it is initialized or assigned on some path.
+    ///
+    /// # Example
+    /// ```text
+    /// local count
+    /// if ( IsAlive( player ) ) count = 1
+    /// print( count )
+    ///        ^^^^^ error
+    /// ```
+    UseBeforeAssignment { name: &'s str },
+
+    /// A local variable is never read.
+    ///
+    /// # Example
+    /// ```text
+    /// int count = 0
+    ///     ^^^^^ warning
+    /// ```
+    UnusedVariable { name: &'s str },
+
+    /// A function parameter is never read.
+    ///
+    /// # Example
+    /// ```text
+    /// void function OnDamaged( entity victim, var damageInfo ) {}
+    ///                                 ^^^^^^ warning
+    /// ```
+    UnusedParameter { name: &'s str },
+
+    /// A value is assigned to a variable, but is always overwritten or goes out of scope before it
+    /// is read.
+    ///
+    /// # Example
+    /// ```text
+    /// int count = 0
+    ///     ^^^^^^^^^ warning
+    /// count = GetCount()
+    /// print( count )
+    /// ```
+    DeadStore { name: &'s str },
+}
+
+/// An error found by [`check_dataflow`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataflowError<'s> {
+    /// The type of error.
+    pub ty: DataflowErrorType<'s>,
+
+    /// The character range of the code with the error.
+    pub range: Range<usize>,
+}
+
+/// Finds local variables that are read before they are assigned, variables and parameters that
+/// are never read, and assignments that are never read.
+///
+/// Only `local` and `var` variables can be read before they are assigned. Variables with any other
+/// type, like `int count` or `array<entity> players`, start with a default value.
+///
+/// Each function body is lowered into a [`ControlFlowGraph`], and names are bound to their
+/// declarations with a [`Resolution`]. Capturing a variable in a function's captures, or using it
+/// in a lambda or nested function, reads the variable where the function or lambda is created.
+/// Compound assignments like `+=` and increments like `++` read the variable too.
+///
+/// Variables and parameters with names that start with `_` are not reported as unused, so they can
+/// be used for parameters that callbacks must accept.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{check_dataflow, DataflowErrorType, Resolution};
+///
+/// let source = r#"
+/// int function Count( array<int> values, bool verbose )
+/// {
+///     int count = 0
+///     count = values.len()
+///     int unused = 1
+///     return count
+/// }
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+/// let resolution = Resolution::new(&program);
+///
+/// let errors = check_dataflow(&program, &resolution);
+/// let types: Vec<_> = errors.iter().map(|error| error.ty).collect();
+/// assert_eq!(
+///     types,
+///     [
+///         DataflowErrorType::UnusedParameter { name: "verbose" },
+///         DataflowErrorType::DeadStore { name: "count" },
+///         DataflowErrorType::UnusedVariable { name: "unused" },
+///     ]
+/// );
+/// assert_eq!(&source[errors[1].range.clone()], "count = 0");
+/// ```
+pub fn check_dataflow<'s>(
+    program: &'s Program<'s>,
+    resolution: &Resolution<'s>,
+) -> Vec<DataflowError<'s>> {
+    let mut definitions = HashMap::new();
+    let mut params = HashMap::new();
+    for (id, declaration) in resolution.declarations() {
+        match declaration.kind {
+            DeclarationKind::Var { definition, .. }
+                if declaration.scope != Resolution::FILE_SCOPE =>
+            {
+                definitions.insert(definition as *const VarDefinition as usize, id);
+            }
+            DeclarationKind::Param(param) => {
+                params.insert(param as *const _ as usize, id);
+            }
+            _ => {}
+        }
+    }
+
+    let mut checker = DataflowChecker {
+        resolution,
+        definitions: &definitions,
+        params: &params,
+        errors: Vec::new(),
+    };
+    checker.visit_program(program);
+    checker.errors
+}
+
+impl<'s> DataflowError<'s> {
+    /// Creates a new `DataflowError`.
+    pub fn new(ty: DataflowErrorType<'s>, range: Range<usize>) -> Self {
+        DataflowError { ty, range }
+    }
+
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`].
+    ///
+    /// [`code`]: DataflowErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let level = if self.ty.is_warning() {
+            Level::Warning
+        } else {
+            Level::Error
+        };
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        Diagnostic::new(level, self.ty.to_string(), label).with_code(self.ty.code())
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
+    /// context using [`Diagnostic::display`].
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source,
+            file_name,
+        }
+    }
+}
+
+impl DataflowErrorType<'_> {
+    /// Returns a stable code for the error type, from `E0501` to `E0599`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(self) -> &'static str {
+        match self {
+            DataflowErrorType::UseBeforeAssignment { .. } => "E0501",
+            DataflowErrorType::UnusedVariable { .. } => "E0502",
+            DataflowErrorType::UnusedParameter { .. } => "E0503",
+            DataflowErrorType::DeadStore { .. } => "E0504",
+        }
+    }
+
+    /// Returns `true` if the error is a warning about code that is valid, but likely a mistake.
+    pub fn is_warning(&self) -> bool {
+        !matches!(self, DataflowErrorType::UseBeforeAssignment { .. })
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(&self) -> impl std::fmt::Display + '_ {
+        DataflowErrorInlineDisplay(self)
+    }
+}
+
+impl std::fmt::Display for DataflowErrorType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataflowErrorType::UseBeforeAssignment { name } => {
+                write!(f, "`{name}` is used before it is assigned")
+            }
+            DataflowErrorType::UnusedVariable { name } => write!(f, "unused variable `{name}`"),
+            DataflowErrorType::UnusedParameter { name } => write!(f, "unused parameter `{name}`"),
+            DataflowErrorType::DeadStore { name } => {
+                write!(f, "value assigned to `{name}` is never read")
+            }
+        }
+    }
+}
+
+struct DataflowErrorInlineDisplay<'a, 's>(&'a DataflowErrorType<'s>);
+
+impl std::fmt::Display for DataflowErrorInlineDisplay<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            DataflowErrorType::UseBeforeAssignment { .. } => write!(f, "may not be assigned here"),
+            DataflowErrorType::UnusedVariable { .. }
+            | DataflowErrorType::UnusedParameter { .. } => {
+                write!(f, "never read")
+            }
+            DataflowErrorType::DeadStore { .. } => write!(f, "overwritten before it is read"),
+        }
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
+
+// Declarations of locals and parameters, keyed by the address of the node that declares them.
+type DeclarationMap = HashMap<usize, DeclarationId>;
+
+struct DataflowChecker<'r, 's> {
+    resolution: &'r Resolution<'s>,
+    definitions: &'r DeclarationMap,
+    params: &'r DeclarationMap,
+    errors: Vec<DataflowError<'s>>,
+}
+
+// Something that happens to a variable while a block runs.
+#[derive(Debug, Clone)]
+enum Event<'s> {
+    Read(DeclarationId, &'s Identifier<'s>),
+    Write(DeclarationId, Range<usize>),
+
+    // A `local` or `var` variable is defined without an initializer.
+    Declare(DeclarationId),
+}
+
+impl Event<'_> {
+    fn declaration(&self) -> DeclarationId {
+        match self {
+            Event::Read(id, _) | Event::Write(id, _) | Event::Declare(id) => *id,
+        }
+    }
+}
+
+impl<'s> DataflowChecker<'_, 's> {
+    fn check_function(&mut self, definition: &'s FunctionDefinition<'s>) {
+        let cfg = ControlFlowGraph::new(definition);
+        let mut collector = EventCollector {
+            resolution: self.resolution,
+            definitions: self.definitions,
+            events: Vec::new(),
+            defined: Vec::new(),
+            closures: 0,
+        };
+        let events: Vec<_> = (cfg.blocks())
+            .map(|(_, block)| {
+                for item in &block.items {
+                    collector.item(item);
+                }
+                std::mem::take(&mut collector.events)
+            })
+            .collect();
+
+        // Only variables defined in this function and its parameters are checked, not variables
+        // from enclosing functions that a nested function uses.
+        let mut tracked: Vec<DeclarationId> = (definition.params.iter())
+            .filter_map(|param| self.params.get(&(param as *const _ as usize)).copied())
+            .collect();
+        let param_count = tracked.len();
+        tracked.extend(collector.defined);
+        if tracked.is_empty() {
+            return;
+        }
+        let indices: HashMap<_, _> = (tracked.iter().enumerate())
+            .map(|(index, id)| (*id, index))
+            .collect();
+        let events: Vec<Vec<(usize, Event)>> = (events.into_iter())
+            .map(|events| {
+                (events.into_iter())
+                    .filter_map(|event| Some((*indices.get(&event.declaration())?, event)))
+                    .collect()
+            })
+            .collect();
+
+        let mut reachable = vec![false; events.len()];
+        for id in cfg.reachable() {
+            reachable[id.0] = true;
+        }
+
+        let mut errors = Vec::new();
+        let mut is_read = vec![false; tracked.len()];
+        for (index, event) in events.iter().flatten() {
+            if let Event::Read(..) = event {
+                is_read[*index] = true;
+            }
+        }
+        for (index, id) in tracked.iter().enumerate() {
+            let name = self.resolution.declaration(*id).name;
+            if is_read[index] || name.value.starts_with('_') {
+                continue;
+            }
+            let ty = if index < param_count {
+                DataflowErrorType::UnusedParameter { name: name.value }
+            } else {
+                DataflowErrorType::UnusedVariable { name: name.value }
+            };
+            errors.push(DataflowError::new(ty, name.token.range.clone()));
+        }
+
+        // Finds reads of variables that are not assigned on every path, going forwards from the
+        // entry. A variable can only be read after it is defined, so variables are only unassigned
+        // after a definition without an initializer or default value.
+        let mut assigned_in = vec![vec![true; tracked.len()]; events.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (id, block) in cfg.blocks() {
+                if !reachable[id.0] {
+                    continue;
+                }
+
+                // An exception can be thrown anywhere in the block, so the catch only has the
+                // variables that are assigned at every point.
+                let mut assigned = assigned_in[id.0].clone();
+                let mut always_assigned = assigned.clone();
+                for (index, event) in &events[id.0] {
+                    match event {
+                        Event::Read(..) => {}
+                        Event::Write(..) => assigned[*index] = true,
+                        Event::Declare(_) => {
+                            assigned[*index] = false;
+                            always_assigned[*index] = false;
+                        }
+                    }
+                }
+                for edge in &block.edges {
+                    let from = match edge.kind {
+                        EdgeKind::Exception => &always_assigned,
+                        _ => &assigned,
+                    };
+                    let into = &mut assigned_in[edge.target.0];
+                    for (into, from) in into.iter_mut().zip(from) {
+                        if *into && !from {
+                            *into = false;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        let mut reported = vec![false; tracked.len()];
+        for (id, _) in cfg.blocks() {
+            if !reachable[id.0] {
+                continue;
+            }
+            let mut assigned = assigned_in[id.0].clone();
+            for (index, event) in &events[id.0] {
+                match event {
+                    Event::Read(_, name) if !assigned[*index] && !reported[*index] => {
+                        reported[*index] = true;
+                        let ty = DataflowErrorType::UseBeforeAssignment { name: name.value };
+                        errors.push(DataflowError::new(ty, name.token.range.clone()));
+                    }
+                    Event::Read(..) => {}
+                    Event::Write(..) => assigned[*index] = true,
+                    Event::Declare(_) => assigned[*index] = false,
+                }
+            }
+        }
+
+        // Finds assignments that are never read, going backwards from the exit.
+        // An exception can be thrown anywhere in a block, so variables the catch reads are live at
+        // every point.
+        let live_out = |live_in: &[Vec<bool>], id, kinds: &[EdgeKind]| {
+            let mut live = vec![false; tracked.len()];
+            for edge in &cfg.block(id).edges {
+                if kinds.is_empty() || kinds.contains(&edge.kind) {
+                    for (live, successor) in live.iter_mut().zip(&live_in[edge.target.0]) {
+                        *live |= *successor;
+                    }
+                }
+            }
+            live
+        };
+        let mut live_in = vec![vec![false; tracked.len()]; events.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (id, _) in cfg.blocks() {
+                let mut live = live_out(&live_in, id, &[]);
+                let caught = live_out(&live_in, id, &[EdgeKind::Exception]);
+                for (index, event) in events[id.0].iter().rev() {
+                    live[*index] = matches!(event, Event::Read(..)) || caught[*index];
+                }
+                if live != live_in[id.0] {
+                    live_in[id.0] = live;
+                    changed = true;
+                }
+            }
+        }
+        for (id, _) in cfg.blocks() {
+            if !reachable[id.0] {
+                continue;
+            }
+            let mut live = live_out(&live_in, id, &[]);
+            let caught = live_out(&live_in, id, &[EdgeKind::Exception]);
+            for (index, event) in events[id.0].iter().rev() {
+                if let Event::Write(declaration, range) = event {
+                    if !live[*index] && is_read[*index] {
+                        let name = self.resolution.declaration(*declaration).name.value;
+                        let ty = DataflowErrorType::DeadStore { name };
+                        errors.push(DataflowError::new(ty, range.clone()));
+                    }
+                }
+                live[*index] = matches!(event, Event::Read(..)) || caught[*index];
+            }
+        }
+
+        errors.sort_by_key(|error| error.range.start);
+        self.errors.extend(errors);
+    }
+}
+
+impl<'s> Visit<'s> for DataflowChecker<'_, 's> {
+    fn visit_function_definition(&mut self, node: &'s FunctionDefinition<'s>) {
+        self.check_function(node);
+        walk_function_definition(self, node);
+    }
+}
+
+// Collects the events of the items in a block, in the order they run.
+struct EventCollector<'r, 's> {
+    resolution: &'r Resolution<'s>,
+    definitions: &'r DeclarationMap,
+    events: Vec<Event<'s>>,
+
+    // Variables defined in the function, in the order they are defined.
+    defined: Vec<DeclarationId>,
+
+    // How many functions and lambdas the collector is in. Any use of a variable in them is a read
+    // when they are created.
+    closures: usize,
+}
+
+impl<'s> EventCollector<'_, 's> {
+    fn item(&mut self, item: &BlockItem<'s>) {
+        match *item {
+            BlockItem::Statement(statement) => self.visit_statement_type(statement),
+            BlockItem::Condition(expression) | BlockItem::Expression(expression) => {
+                self.visit_expression(expression)
+            }
+            BlockItem::Initializer(ForDefinition::Expression(expression)) => {
+                self.visit_expression(expression)
+            }
+            BlockItem::Initializer(ForDefinition::Definition(definition)) => {
+                self.visit_var_definition_statement(definition)
+            }
+            BlockItem::Case(case) => {
+                if let SwitchCaseCondition::Case { value, .. } = &case.condition {
+                    self.visit_expression(value);
+                }
+            }
+            BlockItem::Next(_) | BlockItem::Catch(_) => {}
+        }
+    }
+
+    fn read(&mut self, name: &'s Identifier<'s>) {
+        if let Some(id) = self.resolution.resolve(name) {
+            self.events.push(Event::Read(id, name));
+        }
+    }
+
+    // Returns the variable an expression assigns to, if it is a plain variable outside a closure.
+    fn target(
+        &self,
+        expression: &'s Expression<'s>,
+    ) -> Option<(DeclarationId, &'s Identifier<'s>)> {
+        match expression {
+            Expression::Var(var) if self.closures == 0 => {
+                Some((self.resolution.resolve(&var.name)?, &var.name))
+            }
+            _ => None,
+        }
+    }
+
+    // Returns true if a variable is a `local` or `var`, which has no value until it is assigned.
+    fn is_untyped(&self, id: DeclarationId) -> bool {
+        match self.resolution.declaration(id).kind {
+            DeclarationKind::Var { type_, .. } => match type_ {
+                Type::Local(_) | Type::Var(_) => true,
+                Type::Plain(plain) => plain.name.value == "var",
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn read_write(&mut self, target: &'s Expression<'s>, range: Option<Range<usize>>) -> bool {
+        let (Some((id, name)), Some(range)) = (self.target(target), range) else {
+            return false;
+        };
+        self.events.push(Event::Read(id, name));
+        self.events.push(Event::Write(id, range));
+        true
+    }
+}
+
+impl<'s> Visit<'s> for EventCollector<'_, 's> {
+    fn visit_var_expression(&mut self, node: &'s VarExpression<'s>) {
+        self.read(&node.name);
+    }
+
+    fn visit_binary_expression(&mut self, node: &'s BinaryExpression<'s>) {
+        match node.operator {
+            BinaryOperator::Assign(_) | BinaryOperator::AssignNewSlot(_, _) => {
+                if let (Some((id, _)), Some(range)) = (self.target(&node.left), node.span()) {
+                    self.visit_expression(&node.right);
+                    self.events.push(Event::Write(id, range));
+                    return;
+                }
+            }
+            BinaryOperator::AssignAdd(_)
+            | BinaryOperator::AssignSubtract(_)
+            | BinaryOperator::AssignMultiply(_)
+            | BinaryOperator::AssignDivide(_)
+            | BinaryOperator::AssignModulo(_)
+                if self.target(&node.left).is_some() =>
+            {
+                self.visit_expression(&node.right);
+                self.read_write(&node.left, node.span());
+                return;
+            }
+            _ => {}
+        }
+        walk_binary_expression(self, node);
+    }
+
+    fn visit_prefix_expression(&mut self, node: &'s PrefixExpression<'s>) {
+        let is_step = matches!(
+            node.operator,
+            PrefixOperator::Increment(_) | PrefixOperator::Decrement(_)
+        );
+        if !(is_step && self.read_write(&node.value, node.span())) {
+            walk_prefix_expression(self, node);
+        }
+    }
+
+    fn visit_postfix_expression(&mut self, node: &'s PostfixExpression<'s>) {
+        let is_step = matches!(
+            node.operator,
+            PostfixOperator::Increment(_) | PostfixOperator::Decrement(_)
+        );
+        if !(is_step && self.read_write(&node.value, node.span())) {
+            walk_postfix_expression(self, node);
+        }
+    }
+
+    fn visit_var_definition(&mut self, node: &'s VarDefinition<'s>) {
+        if let Some(initializer) = &node.initializer {
+            self.visit_var_initializer(initializer);
+        }
+        if self.closures > 0 {
+            return;
+        }
+        let key = node as *const VarDefinition as usize;
+        if let Some(&id) = self.definitions.get(&key) {
+            self.defined.push(id);
+            match (&node.initializer, node.span()) {
+                (Some(_), Some(range)) => self.events.push(Event::Write(id, range)),
+                (Some(_), None) => self.events.push(Event::Declare(id)),
+                (None, _) if self.is_untyped(id) => self.events.push(Event::Declare(id)),
+                (None, _) => {}
+            }
+        }
+    }
+
+    fn visit_function_captures(&mut self, node: &'s FunctionCaptures<'s>) {
+        for name in node.names.iter().flat_map(|names| names.iter()) {
+            self.read(name);
+        }
+    }
+
+    fn visit_function_definition(&mut self, node: &'s FunctionDefinition<'s>) {
+        self.closures += 1;
+        walk_function_definition(self, node);
+        self.closures -= 1;
+    }
+
+    fn visit_lambda_expression(&mut self, node: &'s LambdaExpression<'s>) {
+        self.closures += 1;
+        walk_lambda_expression(self, node);
+        self.closures -= 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_dataflow, DataflowErrorType};
+    use crate::analysis::Resolution;
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns each error message with the source code it points at.
+    fn errors(source: &str) -> Vec<(String, &str)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let resolution = Resolution::new(&program);
+        check_dataflow(&program, &resolution)
+            .into_iter()
+            .map(|error| (error.ty.to_string(), &source[error.range]))
+            .collect()
+    }
+
+    #[test]
+    fn use_before_assignment() {
+        assert_eq!(
+            errors(
+                r#"
+                void function Main( bool flag ) {
+                    local a
+                    var b
+                    if ( flag ) a = 1
+                    else a = 2
+                    if ( flag ) b = 1
+                    print( a + b + b )
+                }
+                "#
+            ),
+            [("`b` is used before it is assigned".to_string(), "b")]
+        );
+    }
+
+    #[test]
+    fn typed_variables_have_default_values() {
+        assert_eq!(
+            errors(
+                r#"
+                struct S { int a }
+                void function Main( entity p, string k, int v ) {
+                    array<entity> players
+                    players.append( p )
+                    table t
+                    t[ k ] <- v
+                    int count
+                    count++
+                    S s
+                    s.a = 1
+                    print( count )
+                }
+                "#
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn loops() {
+        assert_eq!(
+            errors(
+                r#"
+                void function Main( array<int> values ) {
+                    int total = 0
+                    int last
+                    foreach ( value in values ) {
+                        total += value
+                        last = value
+                    }
+                    print( total )
+                    while ( true ) {
+                        local step
+                        print( step )
+                        step = 1
+                    }
+                }
+                "#
+            ),
+            [
+                ("unused variable `last`".to_string(), "last"),
+                ("`step` is used before it is assigned".to_string(), "step"),
+                (
+                    "value assigned to `step` is never read".to_string(),
+                    "step = 1"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unused() {
+        assert_eq!(
+            errors(
+                r#"
+                int total
+                void function Callback( entity _player, var damageInfo ) {
+                    total = 1
+                    int a = 1, _b = 2
+                    int c
+                    c = 3
+                    c++
+                }
+                "#
+            ),
+            [
+                ("unused parameter `damageInfo`".to_string(), "damageInfo"),
+                ("unused variable `a`".to_string(), "a"),
+                ("value assigned to `c` is never read".to_string(), "c++"),
+            ]
+        );
+    }
+
+    #[test]
+    fn dead_stores() {
+        assert_eq!(
+            errors(
+                r#"
+                int function Main( int a ) {
+                    a = 2
+                    int b = a
+                    b = 3
+                    try {
+                        b = 4
+                        Throws()
+                        b = 5
+                    } catch ( error ) {
+                        return b
+                    }
+                    return b
+                }
+                "#
+            ),
+            [("value assigned to `b` is never read".to_string(), "b = a")]
+        );
+    }
+
+    #[test]
+    fn closures() {
+        assert_eq!(
+            errors(
+                r#"
+                void function Main() {
+                    int captured = 1
+                    int inLambda = 2
+                    local later
+                    void functionref() f = void function() : ( captured ) {
+                        int inner
+                    }
+                    var g = @() inLambda + later
+                    later = 3
+                    f()
+                    g()
+                }
+                "#
+            ),
+            [
+                ("`later` is used before it is assigned".to_string(), "later"),
+                (
+                    "value assigned to `later` is never read".to_string(),
+                    "later = 3"
+                ),
+                ("unused variable `inner`".to_string(), "inner"),
+            ]
+        );
+        assert!(DataflowErrorType::DeadStore { name: "a" }.is_warning());
+    }
+}
@@ -0,0 +1,600 @@
+use crate::analysis::{BlockItem, ControlFlowGraph, EdgeKind};
+use crate::annotation::{Diagnostic, Label, Level};
+use crate::ast::{
+    walk_constructor_definition_statement, walk_function_definition_statement,
+    walk_function_expression, walk_slot, ConstructorDefinitionStatement, FunctionDefinition,
+    FunctionDefinitionStatement, FunctionExpression, IfStatementType, Program, Slot, Spanned,
+    Statement, StatementType, Type, Visit,
+};
+use std::ops::Range;
+
+/// Type of [`FlowError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowErrorType {
+    /// A function with a return type can reach the end of its body without returning a value.
+    ///
+    /// The name is [`None`] for function expressions.
+    ///
+    /// # Example
+    /// ```text
+    /// int function Sign( int value ) {
+    ///     if ( value < 0 ) return -1
+    ///     if ( value > 0 ) return 1
+    /// }
+    /// ^ error
+    /// ```
+    MissingReturn { name: Option<String> },
+
+    /// Code that can never run, because every path to it returns, throws, breaks or continues
+    /// first.
+    ///
+    /// This is a warning, see [`FlowErrorType::is_warning`].
+    ///
+    /// # Example
+    /// ```text
+    /// return
+    /// print( "done" )
+    /// ^^^^^^^^^^^^^^^ warning
+    /// ```
+    UnreachableCode,
+}
+
+/// An error found by [`check_flow`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowError {
+    /// The type of error.
+    pub ty: FlowErrorType,
+
+    /// The character range of the code with the error.
+    pub range: Range<usize>,
+
+    /// The character range of related code: the return type of a function missing a return, or
+    /// the statement that stops unreachable code from running.
+    pub related: Option<Range<usize>>,
+}
+
+/// Finds functions that can end without returning a value, and code that can never run.
+///
+/// Every function in the program is checked, including methods, constructors and function
+/// expressions. Functions with no return type or a `void` or `var` return type can end without
+/// returning a value, like the Squirrel compiler allows.
+///
+/// Paths are found with a [`ControlFlowGraph`], so conditions are never evaluated: code after a
+/// `while ( true )` loop without a `break` is still reachable.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{check_flow, FlowErrorType};
+///
+/// let source = r#"
+/// int function Sign( int value )
+/// {
+///     if ( value < 0 )
+///     {
+///         return -1
+///         value = 0
+///     }
+///     else if ( value > 0 )
+///         return 1
+/// }
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// let errors = check_flow(&program);
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors[0].ty, FlowErrorType::MissingReturn { name: Some("Sign".to_string()) });
+/// assert_eq!(errors[1].ty, FlowErrorType::UnreachableCode);
+/// assert_eq!(&source[errors[1].range.clone()], "value = 0");
+/// ```
+pub fn check_flow(program: &Program) -> Vec<FlowError> {
+    let mut checker = FlowChecker { errors: Vec::new() };
+    checker.visit_program(program);
+    checker.errors
+}
+
+impl FlowError {
+    /// Creates a new `FlowError`.
+    pub fn new(ty: FlowErrorType, range: Range<usize>) -> Self {
+        FlowError {
+            ty,
+            range,
+            related: None,
+        }
+    }
+
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`] and the related code
+    /// as a secondary label.
+    ///
+    /// [`code`]: FlowErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let level = if self.ty.is_warning() {
+            Level::Warning
+        } else {
+            Level::Error
+        };
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        let mut diagnostic =
+            Diagnostic::new(level, self.ty.to_string(), label).with_code(self.ty.code());
+        if let Some(related) = &self.related {
+            let text = match self.ty {
+                FlowErrorType::MissingReturn { .. } => "returns this type",
+                FlowErrorType::UnreachableCode => "any code after this never runs",
+            };
+            diagnostic = diagnostic.with_label(Label::new(related.clone(), text));
+        }
+        diagnostic
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
+    /// context using [`Diagnostic::display`].
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source,
+            file_name,
+        }
+    }
+}
+
+impl FlowErrorType {
+    /// Returns a stable code for the error type, from `E0401` to `E0499`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            FlowErrorType::MissingReturn { .. } => "E0401",
+            FlowErrorType::UnreachableCode => "E0402",
+        }
+    }
+
+    /// Returns `true` if the error is a warning about code that is valid, but likely a mistake.
+    pub fn is_warning(&self) -> bool {
+        matches!(self, FlowErrorType::UnreachableCode)
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(&self) -> impl std::fmt::Display + '_ {
+        FlowErrorInlineDisplay(self)
+    }
+}
+
+impl std::fmt::Display for FlowErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlowErrorType::MissingReturn { name: Some(name) } => {
+                write!(f, "`{name}` does not return a value on every path")
+            }
+            FlowErrorType::MissingReturn { name: None } => {
+                write!(f, "function does not return a value on every path")
+            }
+            FlowErrorType::UnreachableCode => write!(f, "unreachable code"),
+        }
+    }
+}
+
+struct FlowErrorInlineDisplay<'a>(&'a FlowErrorType);
+
+impl std::fmt::Display for FlowErrorInlineDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            FlowErrorType::MissingReturn { .. } => {
+                write!(f, "can reach the end without returning a value")
+            }
+            FlowErrorType::UnreachableCode => write!(f, "never runs"),
+        }
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
+
+struct FlowChecker {
+    errors: Vec<FlowError>,
+}
+
+impl FlowChecker {
+    fn check_function(
+        &mut self,
+        name: Option<String>,
+        return_type: Option<&Type>,
+        definition: &FunctionDefinition,
+    ) {
+        let cfg = ControlFlowGraph::new(definition);
+        let mut reachable = vec![false; cfg.blocks().count()];
+        for id in cfg.reachable() {
+            reachable[id.0] = true;
+        }
+
+        let returns_value = match return_type {
+            None | Some(Type::Var(_)) => false,
+            Some(Type::Plain(plain)) => !matches!(plain.name.value, "void" | "var"),
+            Some(_) => true,
+        };
+        let falls_through = cfg
+            .predecessors(ControlFlowGraph::EXIT)
+            .any(|(from, edge)| reachable[from.0] && edge.kind == EdgeKind::Normal);
+        if returns_value && falls_through {
+            if let Some(end) = definition.body.last_token() {
+                self.errors.push(FlowError {
+                    ty: FlowErrorType::MissingReturn { name },
+                    range: end.range.clone(),
+                    related: return_type.and_then(Spanned::span),
+                });
+            }
+        }
+
+        let mut spans = DeadCode {
+            live: Vec::new(),
+            all: Vec::new(),
+            errors: &mut self.errors,
+        };
+        for (id, block) in cfg.blocks() {
+            let item_spans = block.items.iter().filter_map(BlockItem::span);
+            if reachable[id.0] {
+                spans.live.extend(item_spans.clone());
+            }
+            spans.all.extend(item_spans);
+        }
+        spans.statement(&definition.body);
+    }
+}
+
+impl<'s> Visit<'s> for FlowChecker {
+    fn visit_function_definition_statement(&mut self, node: &'s FunctionDefinitionStatement<'s>) {
+        let name = (node.name.iter())
+            .map(|name| name.value)
+            .collect::<Vec<_>>()
+            .join("::");
+        self.check_function(Some(name), node.return_type.as_ref(), &node.definition);
+        walk_function_definition_statement(self, node);
+    }
+
+    fn visit_constructor_definition_statement(
+        &mut self,
+        node: &'s ConstructorDefinitionStatement<'s>,
+    ) {
+        self.check_function(None, None, &node.definition);
+        walk_constructor_definition_statement(self, node);
+    }
+
+    fn visit_function_expression(&mut self, node: &'s FunctionExpression<'s>) {
+        self.check_function(None, node.return_type.as_ref(), &node.definition);
+        walk_function_expression(self, node);
+    }
+
+    fn visit_slot(&mut self, node: &'s Slot<'s>) {
+        match node {
+            Slot::Function {
+                return_type,
+                name,
+                definition,
+                ..
+            } => self.check_function(
+                Some(name.value.to_string()),
+                return_type.as_ref(),
+                definition,
+            ),
+            Slot::Constructor { definition, .. } => self.check_function(None, None, definition),
+            Slot::Property { .. } | Slot::ComputedProperty { .. } => {}
+        }
+        walk_slot(self, node);
+    }
+}
+
+// Finds the first unreachable statement in each list of statements that is reachable.
+struct DeadCode<'a> {
+    // The spans of the items in reachable blocks, and in all blocks.
+    live: Vec<Range<usize>>,
+    all: Vec<Range<usize>>,
+    errors: &'a mut Vec<FlowError>,
+}
+
+impl DeadCode<'_> {
+    // Returns `Some(true)` if any part of a statement can run, `Some(false)` if none of it can, or
+    // `None` if it has nothing to run, like an empty block.
+    fn is_live(&self, span: &Range<usize>) -> Option<bool> {
+        let contains = |item: &Range<usize>| span.start <= item.start && item.end <= span.end;
+        if self.live.iter().any(contains) {
+            Some(true)
+        } else if self.all.iter().any(contains) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        let mut previous = None;
+        for (index, statement) in statements.iter().enumerate() {
+            let Some(span) = statement.ty.span() else {
+                continue;
+            };
+            match self.is_live(&span) {
+                Some(true) => self.statement(&statement.ty),
+                Some(false) => {
+                    let end = (statements[index..].iter().rev())
+                        .find_map(|statement| statement.ty.span())
+                        .map_or(span.end, |last| last.end);
+                    self.errors.push(FlowError {
+                        ty: FlowErrorType::UnreachableCode,
+                        range: span.start..end,
+                        related: previous,
+                    });
+                    return;
+                }
+                None => {}
+            }
+            previous = Some(span);
+        }
+    }
+
+    fn statement(&mut self, statement: &StatementType) {
+        match statement {
+            StatementType::Block(block) => self.statements(&block.statements),
+            StatementType::If(if_) => match &if_.ty {
+                IfStatementType::NoElse { body } => self.body(body),
+                IfStatementType::Else {
+                    body, else_body, ..
+                } => {
+                    self.body(&body.ty);
+                    self.body(else_body);
+                }
+            },
+            StatementType::While(while_) => self.body(&while_.body),
+            StatementType::DoWhile(do_while) => self.body(&do_while.body.ty),
+            StatementType::For(for_) => self.body(&for_.body),
+            StatementType::Foreach(foreach) => self.body(&foreach.body),
+            StatementType::Switch(switch) => {
+                for case in &switch.cases {
+                    self.statements(&case.body);
+                }
+            }
+            StatementType::TryCatch(try_catch) => {
+                self.body(&try_catch.body.ty);
+                self.body(&try_catch.catch_body);
+            }
+            StatementType::Conditional(conditional) => {
+                let branches = std::iter::once(&conditional.if_.statements)
+                    .chain(conditional.else_ifs.iter().map(|branch| &branch.statements))
+                    .chain(&conditional.else_);
+                for statements in branches {
+                    self.statements(statements);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Checks the body of a statement, which is reported on its own if none of it can run.
+    fn body(&mut self, body: &StatementType) {
+        let Some(span) = body.span() else {
+            return;
+        };
+        match self.is_live(&span) {
+            Some(true) => self.statement(body),
+            Some(false) => self
+                .errors
+                .push(FlowError::new(FlowErrorType::UnreachableCode, span)),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_flow, FlowErrorType};
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns each error message with the source code it points at.
+    fn errors(source: &str) -> Vec<(String, &str)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        check_flow(&program)
+            .into_iter()
+            .map(|error| (error.ty.to_string(), &source[error.range]))
+            .collect()
+    }
+
+    #[test]
+    fn missing_return() {
+        assert_eq!(
+            errors(
+                r#"
+                int function Sign( int value ) {
+                    if ( value < 0 ) return -1
+                    else if ( value > 0 ) return 1
+                }
+                "#
+            ),
+            [(
+                "`Sign` does not return a value on every path".to_string(),
+                "}"
+            )]
+        );
+        assert_eq!(
+            errors(
+                r#"
+                int function Sign( int value ) {
+                    if ( value < 0 ) return -1
+                    else if ( value > 0 ) return 1
+                    else throw "zero"
+                }
+                "#
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn untyped_and_void_functions() {
+        assert_eq!(
+            errors(
+                r#"
+                void function A() {}
+                var function B() {}
+                function C() {}
+                "#
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn loops_and_switches() {
+        assert_eq!(
+            errors(
+                r#"
+                int function Forever() {
+                    for ( ;; ) {}
+                }
+                int function WhileTrue( int a ) {
+                    while ( true ) {
+                        if ( a ) return 1
+                    }
+                }
+                int function Switch( int a ) {
+                    switch ( a ) {
+                        case 1: return 1
+                        default: return 0
+                    }
+                }
+                int function Break( int a ) {
+                    while ( a ) {
+                        if ( a ) break
+                        return 0
+                    }
+                }
+                "#
+            ),
+            [(
+                "`Break` does not return a value on every path".to_string(),
+                "}"
+            )]
+        );
+    }
+
+    #[test]
+    fn nested_functions() {
+        assert_eq!(
+            errors(
+                r#"
+                void function Outer() {
+                    int functionref() inner = int function() {}
+                    return
+                }
+                class Thing {
+                    function Count() { return 1; print( 1 ) }
+                    int function Total() {}
+                }
+                "#
+            ),
+            [
+                (
+                    "function does not return a value on every path".to_string(),
+                    "}"
+                ),
+                ("unreachable code".to_string(), "print( 1 )"),
+                (
+                    "`Total` does not return a value on every path".to_string(),
+                    "}"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_code() {
+        let source = r#"
+            void function Main( array<int> values ) {
+                foreach ( value in values ) {
+                    continue
+                    print( value )
+                    print( value + 1 )
+                }
+                if ( values.len() ) return
+                else throw "empty"
+                print( "unreachable" )
+            }
+        "#;
+        assert_eq!(
+            errors(source),
+            [
+                (
+                    "unreachable code".to_string(),
+                    "print( value )\n                    print( value + 1 )"
+                ),
+                ("unreachable code".to_string(), "print( \"unreachable\" )"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_branches() {
+        let source = r#"
+            void function Main() {
+                try { throw 1 } catch ( error ) { return }
+                while ( true ) { print( 1 ) }
+            }
+            void function Dead() {
+                return
+                if ( true ) print( 1 )
+            }
+        "#;
+        let found = errors(source);
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0],
+            (
+                "unreachable code".to_string(),
+                "while ( true ) { print( 1 ) }"
+            )
+        );
+        assert_eq!(
+            found[1],
+            ("unreachable code".to_string(), "if ( true ) print( 1 )")
+        );
+        assert!(FlowErrorType::UnreachableCode.is_warning());
+    }
+
+    #[test]
+    fn display() {
+        let source = "int function F() {\n    return 1\n    print( 1 )\n}";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let errors = check_flow(&program);
+
+        yansi::Paint::disable();
+        assert_eq!(
+            errors[0].display(source, Some("flow.nut")).to_string(),
+            "warning[E0402]: unreachable code
+ --> flow.nut:3:5
+  |
+2 |     return 1
+  |     -------- any code after this never runs
+3 |     print( 1 )
+  |     ^^^^^^^^^^ never runs"
+        );
+    }
+}
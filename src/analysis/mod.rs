@@ -0,0 +1,34 @@
+//! Semantic analysis of syntax trees.
+//!
+//! [`Resolution`] finds the scopes in a [`Program`] and binds each use of a name to its
+//! declaration. [`TypeRegistry`] resolves type names to the types they declare, and
+//! [`check_types`] uses both to find type errors in typed Squirrel. [`CallGraph`] finds the calls
+//! between functions, and [`check_threading`] uses it to find functions that wait but are called
+//! outside a thread. [`ConstEvaluator`] computes the values of constants and enum entries.
+//! [`ControlFlowGraph`] lowers a function body into basic blocks, and [`check_flow`] uses it to
+//! find functions that can end without returning a value and code that can never run.
+//! [`check_dataflow`] finds variables that are read before they are assigned or never read.
+//!
+//! [`Program`]: crate::ast::Program
+
+mod calls;
+mod cfg;
+mod check;
+mod consts;
+mod dataflow;
+mod flow;
+mod registry;
+mod scope;
+mod threads;
+mod ty;
+
+pub use self::calls::*;
+pub use self::cfg::*;
+pub use self::check::*;
+pub use self::consts::*;
+pub use self::dataflow::*;
+pub use self::flow::*;
+pub use self::registry::*;
+pub use self::scope::*;
+pub use self::threads::*;
+pub use self::ty::*;
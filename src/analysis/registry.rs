@@ -0,0 +1,462 @@
+use crate::analysis::{Ty, TypeError, TypeErrorType};
+use crate::ast::{
+    walk_generic_type, walk_plain_type, walk_type, walk_type_definition_statement,
+    ClassDefinitionStatement, EnumDefinitionStatement, Expression, GenericType, GlobalDefinition,
+    Identifier, PlainType, Program, Spanned, Statement, StatementType, StructDefinitionStatement,
+    StructProperty, Type, TypeDefinitionStatement, Visit,
+};
+use std::collections::{HashMap, HashSet};
+
+/// A named type declared in a program.
+#[derive(Debug, Clone, Copy)]
+pub enum NamedType<'s> {
+    /// A `struct` definition.
+    Struct(&'s StructDefinitionStatement<'s>),
+
+    /// A `typedef` alias for another type.
+    TypeDefinition(&'s TypeDefinitionStatement<'s>),
+
+    /// An `enum`, with values of type `int`.
+    Enum(&'s EnumDefinitionStatement<'s>),
+
+    /// A `class`, with dynamically typed instances.
+    Class(&'s ClassDefinitionStatement<'s>),
+}
+
+/// Named types declared in one or more programs.
+///
+/// The registry maps type names to the struct, typedef, enum and class definitions that declare
+/// them, including ones declared with `global`. Type syntax can then be resolved to a [`Ty`], with
+/// typedefs expanded to the types they alias.
+///
+/// Types are resolved leniently: names that aren't declared, typedefs that refer to themselves and
+/// generics with the wrong number of type arguments all resolve to [`Ty::Unknown`]. These problems
+/// are reported by [`errors`](TypeRegistry::errors).
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{Ty, TypeRegistry};
+/// use sqparse::ast::StatementType;
+///
+/// let source = r#"
+/// struct Player { string name, int score }
+/// global typedef Players array<Player>
+/// typedef Lookup table<string, Players>
+/// Lookup teams
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+/// let types = TypeRegistry::new(&program);
+///
+/// let properties = types.struct_properties("Player").unwrap();
+/// assert_eq!(properties[1].name.value, "score");
+///
+/// let StatementType::VarDefinition(teams) = &program.statements[3].ty else { unreachable!() };
+/// assert_eq!(types.resolve(&teams.type_).to_string(), "table<string, array<Player>>");
+/// assert!(types.errors().is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TypeRegistry<'s> {
+    programs: Vec<&'s Program<'s>>,
+    types: HashMap<&'s str, NamedType<'s>>,
+}
+
+impl<'s> TypeRegistry<'s> {
+    /// Creates a registry of the types declared in a program.
+    pub fn new(program: &'s Program<'s>) -> Self {
+        let mut registry = TypeRegistry::default();
+        registry.add_program(program);
+        registry
+    }
+
+    /// Adds the types declared in another program, like another file in the same VM.
+    ///
+    /// Types declared in every branch of preprocessor conditionals are added. If a name is declared
+    /// more than once, the first declaration is used.
+    pub fn add_program(&mut self, program: &'s Program<'s>) {
+        self.programs.push(program);
+        for statement in &program.statements {
+            self.add_statement(statement);
+        }
+    }
+
+    /// Returns the type declared with a name.
+    pub fn get(&self, name: &str) -> Option<NamedType<'s>> {
+        self.types.get(name).copied()
+    }
+
+    /// Returns the `typedef` declared with a name.
+    pub fn typedef(&self, name: &str) -> Option<&'s TypeDefinitionStatement<'s>> {
+        match self.get(name)? {
+            NamedType::TypeDefinition(statement) => Some(statement),
+            _ => None,
+        }
+    }
+
+    /// Returns the properties of the struct with a name.
+    ///
+    /// Typedefs that alias a named struct are expanded.
+    pub fn struct_properties(&self, name: &str) -> Option<&'s [StructProperty<'s>]> {
+        match self.resolve_name(name) {
+            Ty::Struct { definition, .. } => Some(&definition.properties),
+            _ => None,
+        }
+    }
+
+    /// Resolves type syntax to the type it refers to, expanding typedefs.
+    pub fn resolve(&self, type_: &'s Type<'s>) -> Ty<'s> {
+        self.resolve_expanding(type_, &mut Vec::new())
+    }
+
+    /// Resolves a type name, like `int`, `Player` or a typedef, to the type it refers to.
+    pub fn resolve_name(&self, name: &str) -> Ty<'s> {
+        self.resolve_name_expanding(name, &mut Vec::new())
+    }
+
+    /// Returns errors for unknown type names, cyclic typedefs and generic types with the wrong
+    /// number of type arguments, in all programs in the registry.
+    pub fn errors(&self) -> Vec<TypeError<'s>> {
+        let mut checker = RegistryChecker {
+            registry: self,
+            errors: Vec::new(),
+        };
+        for program in &self.programs {
+            checker.visit_program(program);
+        }
+        checker.errors
+    }
+
+    fn add_statement(&mut self, statement: &'s Statement<'s>) {
+        let named = match &statement.ty {
+            StatementType::StructDefinition(statement) => {
+                Some((&statement.name, NamedType::Struct(statement)))
+            }
+            StatementType::TypeDefinition(statement) => {
+                Some((&statement.name, NamedType::TypeDefinition(statement)))
+            }
+            StatementType::EnumDefinition(statement) => {
+                Some((&statement.name, NamedType::Enum(statement)))
+            }
+            StatementType::ClassDefinition(statement) => class_type(statement),
+            StatementType::Global(global) => match &global.definition {
+                GlobalDefinition::Struct(statement) => {
+                    Some((&statement.name, NamedType::Struct(statement)))
+                }
+                GlobalDefinition::Type(statement) => {
+                    Some((&statement.name, NamedType::TypeDefinition(statement)))
+                }
+                GlobalDefinition::Enum(statement) => {
+                    Some((&statement.name, NamedType::Enum(statement)))
+                }
+                GlobalDefinition::Class(statement) => class_type(statement),
+                _ => None,
+            },
+            StatementType::Conditional(conditional) => {
+                let branches = std::iter::once(&conditional.if_.statements)
+                    .chain(conditional.else_ifs.iter().map(|branch| &branch.statements))
+                    .chain(&conditional.else_);
+                for statement in branches.flatten() {
+                    self.add_statement(statement);
+                }
+                None
+            }
+            _ => None,
+        };
+        if let Some((name, named)) = named {
+            self.types.entry(name.value).or_insert(named);
+        }
+    }
+
+    // `expanding` holds the names of typedefs currently being expanded, to stop at cycles.
+    fn resolve_expanding(&self, type_: &'s Type<'s>, expanding: &mut Vec<&'s str>) -> Ty<'s> {
+        match type_ {
+            Type::Local(_) | Type::Var(_) => Ty::Var,
+            Type::Plain(plain) => self.resolve_name_expanding(plain.name.value, expanding),
+            Type::Array(array) => Ty::Array(Some(Box::new(
+                self.resolve_expanding(&array.base, expanding),
+            ))),
+            Type::Generic(generic) => {
+                let params: Vec<_> = generic.params.iter().collect();
+                if params.len() != generic_arity(generic) {
+                    return Ty::Unknown;
+                }
+                match (
+                    self.resolve_expanding(&generic.base, expanding),
+                    &params[..],
+                ) {
+                    (Ty::Array(None), [value]) => {
+                        Ty::Array(Some(Box::new(self.resolve_expanding(value, expanding))))
+                    }
+                    (Ty::Table(None), [key, value]) => Ty::Table(Some(Box::new((
+                        self.resolve_expanding(key, expanding),
+                        self.resolve_expanding(value, expanding),
+                    )))),
+                    _ => Ty::Unknown,
+                }
+            }
+            Type::FunctionRef(function_ref) => Ty::FunctionRef {
+                return_type: Box::new(match &function_ref.return_type {
+                    Some(return_type) => self.resolve_expanding(return_type, expanding),
+                    None => Ty::Var,
+                }),
+                params: (function_ref.params.iter())
+                    .flat_map(|params| params.iter())
+                    .map(|param| self.resolve_expanding(&param.type_, expanding))
+                    .collect(),
+            },
+            Type::Struct(struct_type) => Ty::Struct {
+                name: None,
+                definition: &struct_type.definition,
+            },
+            Type::Reference(reference) => self.resolve_expanding(&reference.base, expanding),
+            Type::Nullable(nullable) => {
+                Ty::Nullable(Box::new(self.resolve_expanding(&nullable.base, expanding)))
+            }
+        }
+    }
+
+    fn resolve_name_expanding(&self, name: &str, expanding: &mut Vec<&'s str>) -> Ty<'s> {
+        if let Some(ty) = Ty::builtin(name) {
+            return ty;
+        }
+        match self.get(name) {
+            Some(NamedType::Struct(statement)) => Ty::Struct {
+                name: Some(statement.name.value),
+                definition: &statement.definition,
+            },
+            Some(NamedType::TypeDefinition(statement)) => {
+                if expanding.contains(&statement.name.value) {
+                    return Ty::Unknown;
+                }
+                expanding.push(statement.name.value);
+                let ty = self.resolve_expanding(&statement.type_, expanding);
+                expanding.pop();
+                ty
+            }
+            Some(NamedType::Enum(_)) => Ty::Int,
+            Some(NamedType::Class(_)) => Ty::Var,
+            None => Ty::Unknown,
+        }
+    }
+
+    // Returns true if expanding a typedef eventually refers back to the typedef.
+    fn is_cyclic(&self, statement: &'s TypeDefinitionStatement<'s>) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending = type_names(&statement.type_);
+        while let Some(name) = pending.pop() {
+            if name.value == statement.name.value {
+                return true;
+            }
+            if !visited.insert(name.value) {
+                continue;
+            }
+            if let Some(typedef) = self.typedef(name.value) {
+                pending.extend(type_names(&typedef.type_));
+            }
+        }
+        false
+    }
+}
+
+fn class_type<'s>(
+    statement: &'s ClassDefinitionStatement<'s>,
+) -> Option<(&'s Identifier<'s>, NamedType<'s>)> {
+    match &*statement.name {
+        Expression::Var(var) => Some((&var.name, NamedType::Class(statement))),
+        _ => None,
+    }
+}
+
+// Returns the number of type arguments a generic type needs.
+fn generic_arity(generic: &GenericType) -> usize {
+    match &*generic.base {
+        Type::Plain(plain) if plain.name.value == "array" => 1,
+        Type::Plain(plain) if plain.name.value == "table" => 2,
+        _ => 0,
+    }
+}
+
+// Returns the type names used in a type.
+fn type_names<'s>(type_: &'s Type<'s>) -> Vec<&'s Identifier<'s>> {
+    struct TypeNames<'s>(Vec<&'s Identifier<'s>>);
+
+    impl<'s> Visit<'s> for TypeNames<'s> {
+        fn visit_plain_type(&mut self, node: &'s PlainType<'s>) {
+            self.0.push(&node.name);
+        }
+    }
+
+    let mut names = TypeNames(Vec::new());
+    walk_type(&mut names, type_);
+    names.0
+}
+
+struct RegistryChecker<'r, 's> {
+    registry: &'r TypeRegistry<'s>,
+    errors: Vec<TypeError<'s>>,
+}
+
+impl<'s> Visit<'s> for RegistryChecker<'_, 's> {
+    fn visit_type_definition_statement(&mut self, node: &'s TypeDefinitionStatement<'s>) {
+        if self.registry.is_cyclic(node) {
+            let error = TypeErrorType::CyclicTypeDefinition {
+                name: node.name.value,
+            };
+            self.errors
+                .push(TypeError::new(error, node.name.token.range.clone()));
+        }
+        walk_type_definition_statement(self, node);
+    }
+
+    fn visit_plain_type(&mut self, node: &'s PlainType<'s>) {
+        let name = node.name.value;
+        if Ty::builtin(name).is_none() && self.registry.get(name).is_none() {
+            let error = TypeErrorType::UnknownType { name };
+            self.errors
+                .push(TypeError::new(error, node.name.token.range.clone()));
+        }
+        walk_plain_type(self, node);
+    }
+
+    fn visit_generic_type(&mut self, node: &'s GenericType<'s>) {
+        let expected = generic_arity(node);
+        let found = node.params.iter().count();
+        if found != expected {
+            if let Some(range) = node.span() {
+                let error = TypeErrorType::TypeArgumentCount { expected, found };
+                self.errors.push(TypeError::new(error, range));
+            }
+        }
+        walk_generic_type(self, node);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TypeRegistry;
+    use crate::analysis::Ty;
+    use crate::ast::StatementType;
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns each error message with the source code it points at.
+    fn errors(source: &str) -> Vec<(String, &str)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let types = TypeRegistry::new(&program);
+        types
+            .errors()
+            .into_iter()
+            .map(|error| (error.ty.to_string(), &source[error.range]))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_names() {
+        let source = r#"
+        struct Data { int count }
+        typedef Alias Data
+        typedef Callback void functionref( Alias, int ornull )
+        enum Team { A }
+        class Thing {}
+        #if SERVER
+        global typedef Server array<Team>
+        #endif
+        "#;
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let types = TypeRegistry::new(&program);
+
+        assert_eq!(types.resolve_name("int"), Ty::Int);
+        assert_eq!(types.resolve_name("var"), Ty::Var);
+        assert_eq!(types.resolve_name("Alias").to_string(), "Data");
+        assert_eq!(types.resolve_name("Alias"), types.resolve_name("Data"));
+        assert_eq!(
+            types.resolve_name("Callback").to_string(),
+            "void functionref(Data, int ornull)"
+        );
+        assert_eq!(types.resolve_name("Server").to_string(), "array<int>");
+        assert_eq!(types.resolve_name("Thing"), Ty::Var);
+        assert_eq!(types.resolve_name("Missing"), Ty::Unknown);
+        assert_eq!(types.struct_properties("Alias").unwrap().len(), 1);
+        assert!(types.struct_properties("Team").is_none());
+        assert!(types.typedef("Callback").is_some());
+        assert!(types.typedef("Data").is_none());
+
+        let StatementType::TypeDefinition(alias) = &program.statements[1].ty else {
+            unreachable!()
+        };
+        assert_eq!(types.resolve(&alias.type_).to_string(), "Data");
+    }
+
+    #[test]
+    fn multiple_programs() {
+        let first_tokens =
+            tokenize("global struct Shared { int a }", Flavor::SquirrelRespawn).unwrap();
+        let first = parse(&first_tokens, Flavor::SquirrelRespawn).unwrap();
+        let second_tokens = tokenize("Shared value", Flavor::SquirrelRespawn).unwrap();
+        let second = parse(&second_tokens, Flavor::SquirrelRespawn).unwrap();
+
+        let mut types = TypeRegistry::new(&second);
+        assert_eq!(types.errors().len(), 1);
+        types.add_program(&first);
+        assert!(types.errors().is_empty());
+        assert_eq!(types.resolve_name("Shared").to_string(), "Shared");
+    }
+
+    #[test]
+    fn unknown_types() {
+        assert_eq!(
+            errors("typedef Thing Missing\nvoid function F( Other o, array<Thing> t ) {}"),
+            vec![
+                ("unknown type `Missing`".to_string(), "Missing"),
+                ("unknown type `Other`".to_string(), "Other"),
+            ]
+        );
+    }
+
+    #[test]
+    fn var_type() {
+        assert_eq!(
+            errors("global var a\nvar function F( var b ) { var c = b }\ntypedef T array<var>"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn cyclic_typedefs() {
+        assert_eq!(
+            errors("typedef A array<B>\ntypedef B A ornull\ntypedef C A\ntypedef D D"),
+            vec![
+                ("type definition `A` refers to itself".to_string(), "A"),
+                ("type definition `B` refers to itself".to_string(), "B"),
+                ("type definition `D` refers to itself".to_string(), "D"),
+            ]
+        );
+
+        let tokens = tokenize("typedef A B\ntypedef B A", Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        assert_eq!(TypeRegistry::new(&program).resolve_name("A"), Ty::Unknown);
+    }
+
+    #[test]
+    fn generic_arity() {
+        assert_eq!(
+            errors("array<int, int> a\ntable<int> b\nint<string> c\narray d\ntable<string, int> e"),
+            vec![
+                (
+                    "type takes 1 type argument but 2 were supplied".to_string(),
+                    "array<int, int>"
+                ),
+                (
+                    "type takes 2 type arguments but 1 was supplied".to_string(),
+                    "table<int>"
+                ),
+                (
+                    "type takes no type arguments but 1 was supplied".to_string(),
+                    "int<string>"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1063 @@
+use crate::ast::{
+    walk_class_definition_statement, walk_property_expression, walk_statement_type, BlockStatement,
+    ClassDefinitionStatement, ConstDefinitionStatement, EnumDefinitionStatement, EnumEntry,
+    Expression, ForStatement, ForeachIndex, ForeachStatement, FunctionDefinition,
+    FunctionDefinitionStatement, FunctionParam, GlobalDefinition, GlobalStatement, Identifier,
+    LambdaExpression, MethodIdentifier, PlainType, Program, PropertyExpression, RootVarExpression,
+    Statement, StatementType, StructDefinitionStatement, StructProperty, SwitchStatement,
+    TryCatchStatement, Type, TypeDefinitionStatement, VarDefinition, VarDefinitionStatement,
+    VarExpression, VarInitializer, Visit,
+};
+use std::collections::HashMap;
+
+/// Identifies a [`Scope`] in a [`Resolution`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(usize);
+
+/// Identifies a [`Declaration`] in a [`Resolution`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeclarationId(usize);
+
+/// Names and scopes in a [`Program`], with each name use bound to the declaration it refers to.
+///
+/// The file has a single [`ScopeKind::File`] scope containing functions, globals, constants, enums,
+/// structs, typedefs, classes and file-level variables like the struct `file` variable. These are
+/// visible from anywhere in the file, including before they are declared. Functions, lambdas,
+/// blocks, `for` and `foreach` loops and `catch` clauses each create a nested scope, and names
+/// declared in them are visible from their declaration to the end of the scope.
+///
+/// Names that are not declared in the file, like native functions or globals from other files, are
+/// left unresolved.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{DeclarationKind, Resolution};
+///
+/// let source = r#"
+/// int function Sum( array<int> values )
+/// {
+///     int total = 0
+///     foreach ( value in values )
+///         total += value
+///     return total
+/// }
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+/// let resolution = Resolution::new(&program);
+///
+/// // Find the declaration of the `total` used in `return total`.
+/// let offset = source.rfind("total").unwrap();
+/// let total = resolution.declaration_at(offset).unwrap();
+/// assert!(matches!(resolution.declaration(total).kind, DeclarationKind::Var { .. }));
+/// assert_eq!(resolution.references_to(total).count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Resolution<'s> {
+    scopes: Vec<Scope>,
+    declarations: Vec<Declaration<'s>>,
+    references: Vec<Reference<'s>>,
+
+    // Indices of references, keyed by the address of the identifier.
+    reference_indices: HashMap<usize, usize>,
+}
+
+/// A region of a program where names can be declared.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    /// The type of scope.
+    pub kind: ScopeKind,
+
+    /// The enclosing scope, or `None` for the file scope.
+    pub parent: Option<ScopeId>,
+
+    /// Declarations in this scope, in the order they appear.
+    pub declarations: Vec<DeclarationId>,
+}
+
+/// Type of [`Scope`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// The top level of the file.
+    File,
+
+    /// Parameters and captures of a function, method or function literal.
+    Function,
+
+    /// Parameters of a lambda.
+    Lambda,
+
+    /// A block statement, or the cases of a switch statement.
+    Block,
+
+    /// Variables defined in the initializer of a `for` loop.
+    For,
+
+    /// Index and value variables of a `foreach` loop.
+    Foreach,
+
+    /// The name in a `catch` clause.
+    Catch,
+}
+
+/// A declared name.
+#[derive(Debug, Clone)]
+pub struct Declaration<'s> {
+    /// The declared name.
+    pub name: &'s Identifier<'s>,
+
+    /// The type of declaration, with the node that declares the name.
+    pub kind: DeclarationKind<'s>,
+
+    /// The scope the name is declared in.
+    pub scope: ScopeId,
+
+    /// The declaration this is a member of, for enum entries and struct properties.
+    pub parent: Option<DeclarationId>,
+
+    /// Set if the name was declared with `global`, so can be visible in other files.
+    pub is_global: bool,
+}
+
+/// Type of [`Declaration`].
+#[derive(Debug, Clone, Copy)]
+pub enum DeclarationKind<'s> {
+    /// A variable in a [`VarDefinitionStatement`], including locals, file-level variables like the
+    /// struct `file` variable and typed globals.
+    Var {
+        type_: &'s Type<'s>,
+        definition: &'s VarDefinition<'s>,
+    },
+
+    /// An untyped global variable, like `global MaxThreads = 10`.
+    UntypedGlobalVar { initializer: &'s VarInitializer<'s> },
+
+    /// A parameter of a function or lambda.
+    Param(&'s FunctionParam<'s>),
+
+    /// A captured variable in a function's [`FunctionCaptures`], referring to the declaration in
+    /// the enclosing scope if it was found.
+    ///
+    /// [`FunctionCaptures`]: crate::ast::FunctionCaptures
+    Capture { captured: Option<DeclarationId> },
+
+    /// The index variable of a `foreach` loop.
+    ForeachIndex(&'s ForeachIndex<'s>),
+
+    /// The value variable of a `foreach` loop.
+    ForeachValue(&'s ForeachStatement<'s>),
+
+    /// The name in a `catch` clause.
+    CatchName(&'s TryCatchStatement<'s>),
+
+    /// A named function definition.
+    Function(&'s FunctionDefinitionStatement<'s>),
+
+    /// A `global function` declaration, which is defined separately.
+    GlobalFunction,
+
+    /// A constant.
+    Const(&'s ConstDefinitionStatement<'s>),
+
+    /// An enum.
+    Enum(&'s EnumDefinitionStatement<'s>),
+
+    /// An entry in an enum. The enum is the declaration's parent.
+    EnumEntry(&'s EnumEntry<'s>),
+
+    /// A named struct.
+    Struct(&'s StructDefinitionStatement<'s>),
+
+    /// A property of a named struct or an anonymous struct variable. The struct or variable is the
+    /// declaration's parent.
+    StructProperty(&'s StructProperty<'s>),
+
+    /// A type alias.
+    TypeDefinition(&'s TypeDefinitionStatement<'s>),
+
+    /// A class with a plain name, like `class Person {}`.
+    Class(&'s ClassDefinitionStatement<'s>),
+}
+
+/// A use of a name.
+#[derive(Debug, Clone)]
+pub struct Reference<'s> {
+    /// The name that is used.
+    pub name: &'s Identifier<'s>,
+
+    /// The type of reference.
+    pub kind: ReferenceKind,
+
+    /// The scope the name is used in.
+    pub scope: ScopeId,
+
+    /// The declaration the name refers to, or `None` if it is not declared in the file.
+    pub declaration: Option<DeclarationId>,
+}
+
+/// Type of [`Reference`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// A value in a [`VarExpression`].
+    Value,
+
+    /// A value in a [`RootVarExpression`], which is only looked up in the file scope.
+    Root,
+
+    /// A type name in a [`PlainType`].
+    Type,
+
+    /// A property of an enum or struct variable in a [`PropertyExpression`].
+    Member,
+
+    /// A name in a function's [`FunctionCaptures`], looked up in the enclosing scope.
+    ///
+    /// [`FunctionCaptures`]: crate::ast::FunctionCaptures
+    Capture,
+}
+
+impl DeclarationKind<'_> {
+    /// Returns true if the declaration names a type that can be used in a [`PlainType`].
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self,
+            DeclarationKind::Enum(_)
+                | DeclarationKind::Struct(_)
+                | DeclarationKind::TypeDefinition(_)
+                | DeclarationKind::Class(_)
+        )
+    }
+
+    /// Returns true if the declaration is a member of another declaration, so is not visible in
+    /// its scope by name.
+    pub fn is_member(&self) -> bool {
+        matches!(
+            self,
+            DeclarationKind::EnumEntry(_) | DeclarationKind::StructProperty(_)
+        )
+    }
+}
+
+impl<'s> Resolution<'s> {
+    /// Builds the scopes in a program and resolves every name use.
+    pub fn new(program: &'s Program<'s>) -> Self {
+        let mut resolver = Resolver {
+            resolution: Resolution {
+                scopes: Vec::new(),
+                declarations: Vec::new(),
+                references: Vec::new(),
+                reference_indices: HashMap::new(),
+            },
+            names: Vec::new(),
+            struct_owners: HashMap::new(),
+            scope: Resolution::FILE_SCOPE,
+        };
+        resolver.push_scope(ScopeKind::File, None);
+
+        // Names at the top level are visible throughout the file, so declare them first.
+        for statement in &program.statements {
+            resolver.declare_file_statement(statement);
+        }
+        resolver.visit_program(program);
+        resolver.resolution
+    }
+
+    /// The scope of the whole file.
+    pub const FILE_SCOPE: ScopeId = ScopeId(0);
+
+    /// Returns a scope.
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    /// Returns a declaration.
+    pub fn declaration(&self, id: DeclarationId) -> &Declaration<'s> {
+        &self.declarations[id.0]
+    }
+
+    /// Returns an iterator over every scope, from outermost to innermost.
+    pub fn scopes(&self) -> impl Iterator<Item = (ScopeId, &Scope)> {
+        self.scopes.iter().enumerate().map(|(i, s)| (ScopeId(i), s))
+    }
+
+    /// Returns an iterator over every declaration, in the order they were found.
+    ///
+    /// Top-level declarations are found before any others.
+    pub fn declarations(&self) -> impl Iterator<Item = (DeclarationId, &Declaration<'s>)> {
+        (self.declarations.iter().enumerate()).map(|(i, d)| (DeclarationId(i), d))
+    }
+
+    /// Returns an iterator over every name use, in source order.
+    pub fn references(&self) -> impl Iterator<Item = &Reference<'s>> {
+        self.references.iter()
+    }
+
+    /// Returns the reference for an identifier in the program, if the identifier is a name use.
+    pub fn reference(&self, name: &Identifier) -> Option<&Reference<'s>> {
+        let index = *self.reference_indices.get(&identifier_key(name))?;
+        Some(&self.references[index])
+    }
+
+    /// Returns the declaration an identifier in the program refers to, if it is a resolved name
+    /// use.
+    pub fn resolve(&self, name: &Identifier) -> Option<DeclarationId> {
+        self.reference(name)?.declaration
+    }
+
+    /// Returns an iterator over the uses of a declaration.
+    pub fn references_to(&self, id: DeclarationId) -> impl Iterator<Item = &Reference<'s>> {
+        (self.references.iter()).filter(move |reference| reference.declaration == Some(id))
+    }
+
+    /// Returns an iterator over the name uses that could not be resolved.
+    ///
+    /// Built-in type names like `int` are not included.
+    pub fn unresolved(&self) -> impl Iterator<Item = &Reference<'s>> {
+        (self.references.iter()).filter(|reference| reference.declaration.is_none())
+    }
+
+    /// Returns an iterator over the members of an enum, a struct, or a variable with an anonymous
+    /// struct type.
+    pub fn members(&self, id: DeclarationId) -> impl Iterator<Item = DeclarationId> + '_ {
+        (self.declarations())
+            .filter(move |(_, declaration)| declaration.parent == Some(id))
+            .map(|(id, _)| id)
+    }
+
+    /// Returns the name use at a character offset in the source code.
+    pub fn reference_at(&self, offset: usize) -> Option<&Reference<'s>> {
+        (self.references.iter()).find(|reference| contains(reference.name, offset))
+    }
+
+    /// Returns the declaration of the name at a character offset in the source code.
+    ///
+    /// The offset can either be in the declared name, or in a use of the name.
+    pub fn declaration_at(&self, offset: usize) -> Option<DeclarationId> {
+        let declared = (self.declarations())
+            .find(|(_, declaration)| contains(declaration.name, offset))
+            .map(|(id, _)| id);
+        declared.or_else(|| self.reference_at(offset)?.declaration)
+    }
+
+    /// Looks up a name that is visible in a scope, at the end of the scope.
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<DeclarationId> {
+        let mut scope = Some(scope);
+        while let Some(id) = scope {
+            let found = (self.scope(id).declarations.iter().rev())
+                .find(|&&declaration| self.declaration(declaration).name.value == name);
+            if let Some(&declaration) = found {
+                return Some(declaration);
+            }
+            scope = self.scope(id).parent;
+        }
+        None
+    }
+}
+
+// Type names that are built into the language, so are never declared.
+pub(crate) const BUILTIN_TYPES: &[&str] = &[
+    "void", "bool", "int", "float", "string", "vector", "asset", "entity", "table", "array", "var",
+];
+
+fn identifier_key(name: &Identifier) -> usize {
+    name as *const Identifier as usize
+}
+
+fn contains(name: &Identifier, offset: usize) -> bool {
+    name.token.range.contains(&offset)
+}
+
+struct Resolver<'s> {
+    resolution: Resolution<'s>,
+
+    // Names that are currently visible in each scope. Several declarations can have the same name,
+    // for example a `global function` and its definition.
+    names: Vec<HashMap<&'s str, Vec<DeclarationId>>>,
+
+    // Declarations with a struct type, mapped to the declaration that owns the struct's properties.
+    struct_owners: HashMap<DeclarationId, DeclarationId>,
+
+    scope: ScopeId,
+}
+
+impl<'s> Resolver<'s> {
+    fn push_scope(&mut self, kind: ScopeKind, parent: Option<ScopeId>) -> ScopeId {
+        let id = ScopeId(self.resolution.scopes.len());
+        self.resolution.scopes.push(Scope {
+            kind,
+            parent,
+            declarations: Vec::new(),
+        });
+        self.names.push(HashMap::new());
+        id
+    }
+
+    // Runs `f` in a new scope nested in the current scope.
+    fn scoped(&mut self, kind: ScopeKind, f: impl FnOnce(&mut Self)) {
+        let parent = self.scope;
+        self.scope = self.push_scope(kind, Some(parent));
+        f(self);
+        self.scope = parent;
+    }
+
+    fn is_file_scope(&self) -> bool {
+        self.scope == Resolution::FILE_SCOPE
+    }
+
+    fn declare(
+        &mut self,
+        name: &'s Identifier<'s>,
+        kind: DeclarationKind<'s>,
+        is_global: bool,
+    ) -> DeclarationId {
+        self.declare_in(self.scope, name, kind, None, is_global)
+    }
+
+    fn declare_in(
+        &mut self,
+        scope: ScopeId,
+        name: &'s Identifier<'s>,
+        kind: DeclarationKind<'s>,
+        parent: Option<DeclarationId>,
+        is_global: bool,
+    ) -> DeclarationId {
+        let id = DeclarationId(self.resolution.declarations.len());
+        self.resolution.declarations.push(Declaration {
+            name,
+            kind,
+            scope,
+            parent,
+            is_global,
+        });
+        if !kind.is_member() {
+            self.resolution.scopes[scope.0].declarations.push(id);
+            (self.names[scope.0].entry(name.value).or_default()).push(id);
+        }
+        id
+    }
+
+    fn declare_members(&mut self, owner: DeclarationId, properties: &'s [StructProperty<'s>]) {
+        let scope = self.resolution.declaration(owner).scope;
+        for property in properties {
+            let kind = DeclarationKind::StructProperty(property);
+            self.declare_in(scope, &property.name, kind, Some(owner), false);
+        }
+        self.struct_owners.insert(owner, owner);
+    }
+
+    fn declare_var_definitions(
+        &mut self,
+        statement: &'s VarDefinitionStatement<'s>,
+        is_global: bool,
+    ) {
+        for definition in statement.definitions.iter() {
+            self.declare_var(&statement.type_, definition, is_global);
+        }
+    }
+
+    fn declare_var(
+        &mut self,
+        type_: &'s Type<'s>,
+        definition: &'s VarDefinition<'s>,
+        is_global: bool,
+    ) -> DeclarationId {
+        let kind = DeclarationKind::Var { type_, definition };
+        let id = self.declare(&definition.name, kind, is_global);
+        self.set_struct_type(id, Some(type_));
+        id
+    }
+
+    // Records which struct's properties a variable of this type has, if it has a struct type.
+    fn set_struct_type(&mut self, id: DeclarationId, type_: Option<&'s Type<'s>>) {
+        match type_ {
+            Some(Type::Struct(struct_type)) => {
+                self.declare_members(id, &struct_type.definition.properties)
+            }
+            Some(Type::Plain(plain)) => {
+                let owner = self.lookup(plain.name.value, |kind| kind.is_type());
+                let is_struct = owner.is_some_and(|owner| {
+                    matches!(
+                        self.resolution.declaration(owner).kind,
+                        DeclarationKind::Struct(_)
+                    )
+                });
+                if let (Some(owner), true) = (owner, is_struct) {
+                    self.struct_owners.insert(id, owner);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn declare_statement(&mut self, statement: &'s StatementType<'s>) {
+        match statement {
+            StatementType::VarDefinition(statement) => {
+                self.declare_var_definitions(statement, false)
+            }
+            StatementType::FunctionDefinition(statement) => {
+                self.declare_function(statement);
+            }
+            StatementType::Const(statement) => self.declare_const(statement, false),
+            StatementType::EnumDefinition(statement) => self.declare_enum(statement, false),
+            StatementType::StructDefinition(statement) => self.declare_struct(statement, false),
+            StatementType::TypeDefinition(statement) => {
+                let kind = DeclarationKind::TypeDefinition(statement);
+                self.declare(&statement.name, kind, false);
+            }
+            StatementType::ClassDefinition(statement) => self.declare_class(statement, false),
+            StatementType::Global(statement) => self.declare_global(statement),
+            _ => {}
+        }
+    }
+
+    // Declares a top-level statement, including statements in preprocessor conditionals.
+    fn declare_file_statement(&mut self, statement: &'s Statement<'s>) {
+        let StatementType::Conditional(conditional) = &statement.ty else {
+            self.declare_statement(&statement.ty);
+            return;
+        };
+        let branches = std::iter::once(&conditional.if_.statements)
+            .chain(conditional.else_ifs.iter().map(|branch| &branch.statements))
+            .chain(&conditional.else_);
+        for statement in branches.flatten() {
+            self.declare_file_statement(statement);
+        }
+    }
+
+    fn declare_function(&mut self, statement: &'s FunctionDefinitionStatement<'s>) {
+        // Functions with namespaced names like `Class::Method` are members of another value.
+        if statement.name.items.is_empty() {
+            let kind = DeclarationKind::Function(statement);
+            self.declare(&statement.name.last_item, kind, false);
+        }
+    }
+
+    fn declare_const(&mut self, statement: &'s ConstDefinitionStatement<'s>, is_global: bool) {
+        self.declare(
+            &statement.name,
+            DeclarationKind::Const(statement),
+            is_global,
+        );
+    }
+
+    fn declare_enum(&mut self, statement: &'s EnumDefinitionStatement<'s>, is_global: bool) {
+        let id = self.declare(&statement.name, DeclarationKind::Enum(statement), is_global);
+        for entry in &statement.entries {
+            let kind = DeclarationKind::EnumEntry(entry);
+            self.declare_in(self.scope, &entry.name, kind, Some(id), false);
+        }
+    }
+
+    fn declare_struct(&mut self, statement: &'s StructDefinitionStatement<'s>, is_global: bool) {
+        let id = self.declare(
+            &statement.name,
+            DeclarationKind::Struct(statement),
+            is_global,
+        );
+        self.declare_members(id, &statement.definition.properties);
+    }
+
+    fn declare_class(&mut self, statement: &'s ClassDefinitionStatement<'s>, is_global: bool) {
+        if let Expression::Var(var) = &*statement.name {
+            self.declare(&var.name, DeclarationKind::Class(statement), is_global);
+        }
+    }
+
+    fn declare_global(&mut self, statement: &'s GlobalStatement<'s>) {
+        match &statement.definition {
+            GlobalDefinition::Function { name, .. } => {
+                self.declare(name, DeclarationKind::GlobalFunction, true);
+            }
+            GlobalDefinition::UntypedVar { name, initializer } => {
+                let kind = DeclarationKind::UntypedGlobalVar { initializer };
+                self.declare(name, kind, true);
+            }
+            GlobalDefinition::TypedVar(definition) => {
+                self.declare_var_definitions(definition, true)
+            }
+            GlobalDefinition::Const(definition) => self.declare_const(definition, true),
+            GlobalDefinition::Enum(definition) => self.declare_enum(definition, true),
+            GlobalDefinition::Class(definition) => self.declare_class(definition, true),
+            GlobalDefinition::Struct(definition) => self.declare_struct(definition, true),
+            GlobalDefinition::Type(definition) => {
+                let kind = DeclarationKind::TypeDefinition(definition);
+                self.declare(&definition.name, kind, true);
+            }
+        }
+    }
+
+    // Looks up a name from the current scope, returning the most recent declaration that matches.
+    // Function definitions are preferred over `global function` declarations of the same name.
+    fn lookup(
+        &self,
+        name: &str,
+        is_match: impl Fn(&DeclarationKind<'s>) -> bool,
+    ) -> Option<DeclarationId> {
+        let mut scope = Some(self.scope);
+        while let Some(id) = scope {
+            if let Some(candidates) = self.names[id.0].get(name) {
+                let kind = |id: &&DeclarationId| self.resolution.declaration(**id).kind;
+                let mut matches = candidates.iter().rev().filter(|id| is_match(&kind(id)));
+                let first = matches.clone().next();
+                let definition =
+                    matches.find(|id| !matches!(kind(id), DeclarationKind::GlobalFunction));
+                if let Some(&id) = definition.or(first) {
+                    return Some(id);
+                }
+            }
+            scope = self.resolution.scope(id).parent;
+        }
+        None
+    }
+
+    fn reference(
+        &mut self,
+        name: &'s Identifier<'s>,
+        kind: ReferenceKind,
+        declaration: Option<DeclarationId>,
+    ) {
+        let index = self.resolution.references.len();
+        (self.resolution.reference_indices).insert(identifier_key(name), index);
+        self.resolution.references.push(Reference {
+            name,
+            kind,
+            scope: self.scope,
+            declaration,
+        });
+    }
+
+    // Returns the declaration that owns the members accessible through a declaration.
+    fn member_owner(&self, id: DeclarationId) -> Option<DeclarationId> {
+        match self.resolution.declaration(id).kind {
+            DeclarationKind::Enum(_) => Some(id),
+            DeclarationKind::Capture { captured } => self.member_owner(captured?),
+            _ => self.struct_owners.get(&id).copied(),
+        }
+    }
+
+    fn visit_params(&mut self, definition: &'s FunctionDefinition<'s>) {
+        for param in definition.params.iter() {
+            if let Some(type_) = &param.type_ {
+                self.visit_type(type_);
+            }
+            if let Some(initializer) = &param.initializer {
+                self.visit_var_initializer(initializer);
+            }
+            let id = self.declare(&param.name, DeclarationKind::Param(param), false);
+            self.set_struct_type(id, param.type_.as_ref());
+        }
+    }
+}
+
+impl<'s> Visit<'s> for Resolver<'s> {
+    fn visit_statement_type(&mut self, node: &'s StatementType<'s>) {
+        // Top-level declarations were already made before visiting.
+        if !self.is_file_scope() {
+            match node {
+                // Variables are declared after their initializers are visited.
+                StatementType::VarDefinition(_) => {}
+                _ => self.declare_statement(node),
+            }
+        }
+        walk_statement_type(self, node);
+    }
+
+    fn visit_var_definition_statement(&mut self, node: &'s VarDefinitionStatement<'s>) {
+        self.visit_type(&node.type_);
+        for definition in node.definitions.iter() {
+            if let Some(initializer) = &definition.initializer {
+                self.visit_var_initializer(initializer);
+            }
+            if !self.is_file_scope() {
+                self.declare_var(&node.type_, definition, false);
+            }
+        }
+    }
+
+    fn visit_block_statement(&mut self, node: &'s BlockStatement<'s>) {
+        self.scoped(ScopeKind::Block, |this| {
+            for statement in &node.statements {
+                this.visit_statement(statement);
+            }
+        });
+    }
+
+    fn visit_switch_statement(&mut self, node: &'s SwitchStatement<'s>) {
+        self.visit_expression(&node.condition);
+        self.scoped(ScopeKind::Block, |this| {
+            for case in &node.cases {
+                this.visit_switch_case(case);
+            }
+        });
+    }
+
+    fn visit_for_statement(&mut self, node: &'s ForStatement<'s>) {
+        self.scoped(ScopeKind::For, |this| {
+            if let Some(initializer) = &node.initializer {
+                this.visit_for_definition(initializer);
+            }
+            if let Some(condition) = &node.condition {
+                this.visit_expression(condition);
+            }
+            if let Some(increment) = &node.increment {
+                this.visit_expression(increment);
+            }
+            this.visit_statement_type(&node.body);
+        });
+    }
+
+    fn visit_foreach_statement(&mut self, node: &'s ForeachStatement<'s>) {
+        self.visit_expression(&node.array);
+        self.scoped(ScopeKind::Foreach, |this| {
+            if let Some(index) = &node.index {
+                if let Some(type_) = &index.type_ {
+                    this.visit_type(type_);
+                }
+                this.declare(&index.name, DeclarationKind::ForeachIndex(index), false);
+            }
+            if let Some(type_) = &node.value_type {
+                this.visit_type(type_);
+            }
+            let kind = DeclarationKind::ForeachValue(node);
+            let id = this.declare(&node.value_name, kind, false);
+            this.set_struct_type(id, node.value_type.as_ref());
+            this.visit_statement_type(&node.body);
+        });
+    }
+
+    fn visit_try_catch_statement(&mut self, node: &'s TryCatchStatement<'s>) {
+        self.visit_statement(&node.body);
+        self.scoped(ScopeKind::Catch, |this| {
+            this.declare(&node.catch_name, DeclarationKind::CatchName(node), false);
+            this.visit_statement_type(&node.catch_body);
+        });
+    }
+
+    fn visit_class_definition_statement(&mut self, node: &'s ClassDefinitionStatement<'s>) {
+        // A plain class name is a declaration, but a name like `a.b` uses `a`.
+        match &*node.name {
+            Expression::Var(_) => self.visit_class_definition(&node.definition),
+            _ => walk_class_definition_statement(self, node),
+        }
+    }
+
+    fn visit_function_definition(&mut self, node: &'s FunctionDefinition<'s>) {
+        if let Some(environment) = &node.environment {
+            self.visit_function_environment(environment);
+        }
+
+        // Captured names are looked up before entering the function.
+        let captures: Vec<_> = (node.captures.iter())
+            .flat_map(|captures| captures.names.iter().flat_map(|names| names.iter()))
+            .map(|name| {
+                let captured = self.lookup(name.value, |kind| !kind.is_type());
+                self.reference(name, ReferenceKind::Capture, captured);
+                (name, captured)
+            })
+            .collect();
+
+        self.scoped(ScopeKind::Function, |this| {
+            this.visit_params(node);
+            for (name, captured) in captures {
+                this.declare(name, DeclarationKind::Capture { captured }, false);
+            }
+            this.visit_statement_type(&node.body);
+        });
+    }
+
+    fn visit_lambda_expression(&mut self, node: &'s LambdaExpression<'s>) {
+        self.scoped(ScopeKind::Lambda, |this| {
+            for param in node.params.iter() {
+                if let Some(initializer) = &param.initializer {
+                    this.visit_var_initializer(initializer);
+                }
+                this.declare(&param.name, DeclarationKind::Param(param), false);
+            }
+            this.visit_expression(&node.value);
+        });
+    }
+
+    fn visit_var_expression(&mut self, node: &'s VarExpression<'s>) {
+        // `this` and `vargv` are implicitly defined in functions, and the lexer treats literal
+        // keywords as identifiers.
+        if matches!(
+            node.name.value,
+            "this" | "vargv" | "true" | "false" | "null"
+        ) {
+            return;
+        }
+        let declaration = self.lookup(node.name.value, |kind| !kind.is_member());
+        self.reference(&node.name, ReferenceKind::Value, declaration);
+    }
+
+    fn visit_root_var_expression(&mut self, node: &'s RootVarExpression<'s>) {
+        let declaration =
+            (self.names[0].get(node.name.value)).and_then(|candidates| candidates.last().copied());
+        self.reference(&node.name, ReferenceKind::Root, declaration);
+    }
+
+    fn visit_plain_type(&mut self, node: &'s PlainType<'s>) {
+        if BUILTIN_TYPES.contains(&node.name.value) {
+            return;
+        }
+        let declaration = self.lookup(node.name.value, |kind| kind.is_type());
+        self.reference(&node.name, ReferenceKind::Type, declaration);
+    }
+
+    fn visit_property_expression(&mut self, node: &'s PropertyExpression<'s>) {
+        walk_property_expression(self, node);
+
+        // Properties of enums and struct variables can be resolved from the base's declaration.
+        let (Expression::Var(base), MethodIdentifier::Identifier(property)) =
+            (&*node.base, &node.property)
+        else {
+            return;
+        };
+        let Some(owner) = self
+            .lookup(base.name.value, |kind| !kind.is_member())
+            .and_then(|base| self.member_owner(base))
+        else {
+            return;
+        };
+        let member = (self.resolution.members(owner))
+            .find(|&member| self.resolution.declaration(member).name.value == property.value);
+        self.reference(property, ReferenceKind::Member, member);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DeclarationKind, ReferenceKind, Resolution, ScopeKind};
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns the kind of declaration for each use of a name, with its line and declared name.
+    fn resolve(source: &str) -> Vec<(String, Option<(usize, &'static str)>)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let resolution = Resolution::new(&program);
+        resolution
+            .references()
+            .map(|reference| {
+                let declaration = reference.declaration.map(|id| {
+                    let declaration = resolution.declaration(id);
+                    let line = source[..declaration.name.token.range.start].lines().count();
+                    let kind = match declaration.kind {
+                        DeclarationKind::Var { .. } => "var",
+                        DeclarationKind::UntypedGlobalVar { .. } => "global var",
+                        DeclarationKind::Param(_) => "param",
+                        DeclarationKind::Capture { .. } => "capture",
+                        DeclarationKind::ForeachIndex(_) => "foreach index",
+                        DeclarationKind::ForeachValue(_) => "foreach value",
+                        DeclarationKind::CatchName(_) => "catch",
+                        DeclarationKind::Function(_) => "function",
+                        DeclarationKind::GlobalFunction => "global function",
+                        DeclarationKind::Const(_) => "const",
+                        DeclarationKind::Enum(_) => "enum",
+                        DeclarationKind::EnumEntry(_) => "enum entry",
+                        DeclarationKind::Struct(_) => "struct",
+                        DeclarationKind::StructProperty(_) => "struct property",
+                        DeclarationKind::TypeDefinition(_) => "typedef",
+                        DeclarationKind::Class(_) => "class",
+                    };
+                    (line, kind)
+                });
+                (reference.name.value.to_string(), declaration)
+            })
+            .collect()
+    }
+
+    fn name(
+        name: &str,
+        declaration: Option<(usize, &'static str)>,
+    ) -> (String, Option<(usize, &'static str)>) {
+        (name.to_string(), declaration)
+    }
+
+    #[test]
+    fn locals_are_visible_after_declaration() {
+        let source = "
+void function Foo()
+{
+    a = 1
+    int a = a
+    {
+        string a = \"b\"
+        print( a )
+    }
+    print( a )
+}
+";
+        assert_eq!(
+            resolve(source),
+            [
+                name("a", None),
+                name("a", None),
+                name("print", None),
+                name("a", Some((7, "var"))),
+                name("print", None),
+                name("a", Some((5, "var"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_declarations_are_hoisted() {
+        let source = "
+global function Foo
+
+void function Bar()
+{
+    Foo()
+    Baz()
+}
+
+#if SERVER
+void function Foo() {}
+#endif
+
+const int MAX = 10
+global struct Data { int value }
+typedef Alias Data
+void function Baz( Alias a ) { return MAX }
+";
+        assert_eq!(
+            resolve(source),
+            [
+                name("Foo", Some((11, "function"))),
+                name("Baz", Some((17, "function"))),
+                name("Data", Some((15, "struct"))),
+                name("Alias", Some((16, "typedef"))),
+                name("MAX", Some((14, "const"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn function_scopes() {
+        let source = "
+void function Foo( int a, b = 2 )
+{
+    local c = 3
+    local f = function( d ) : ( a, c ) { return a + c + d }
+    local g = @( e ) a + e
+    foreach ( i, v in [] )
+        print( i + v )
+    try {} catch ( err ) { print( err ) }
+    for ( local j = 0; j < b; j++ ) {}
+}
+";
+        let references = resolve(source);
+        assert_eq!(
+            &references[..6],
+            [
+                name("a", Some((2, "param"))),
+                name("c", Some((4, "var"))),
+                name("a", Some((5, "capture"))),
+                name("c", Some((5, "capture"))),
+                name("d", Some((5, "param"))),
+                name("a", Some((2, "param"))),
+            ]
+        );
+        assert_eq!(
+            &references[6..],
+            [
+                name("e", Some((6, "param"))),
+                name("print", None),
+                name("i", Some((7, "foreach index"))),
+                name("v", Some((7, "foreach value"))),
+                name("print", None),
+                name("err", Some((9, "catch"))),
+                name("j", Some((10, "var"))),
+                name("b", Some((2, "param"))),
+                name("j", Some((10, "var"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn members() {
+        let source = "
+enum Mode { A, B = 2 }
+struct Point { int x }
+struct {
+    int count
+} file
+
+void function Foo( Point p )
+{
+    file.count = Mode.B
+    p.x = p.y
+    Mode.C
+}
+";
+        assert_eq!(
+            resolve(source),
+            [
+                name("Point", Some((3, "struct"))),
+                name("file", Some((6, "var"))),
+                name("count", Some((5, "struct property"))),
+                name("Mode", Some((2, "enum"))),
+                name("B", Some((2, "enum entry"))),
+                name("p", Some((8, "param"))),
+                name("x", Some((3, "struct property"))),
+                name("p", Some((8, "param"))),
+                name("y", None),
+                name("Mode", Some((2, "enum"))),
+                name("C", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn scopes_and_queries() {
+        let source = "void function Foo( int a ) { if ( a ) { local b = a } }";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let resolution = Resolution::new(&program);
+
+        let kinds: Vec<_> = resolution.scopes().map(|(_, scope)| scope.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ScopeKind::File,
+                ScopeKind::Function,
+                ScopeKind::Block,
+                ScopeKind::Block
+            ]
+        );
+
+        let a = resolution
+            .declaration_at(source.find("a )").unwrap())
+            .unwrap();
+        assert_eq!(
+            resolution.declaration_at(source.rfind('a').unwrap()),
+            Some(a)
+        );
+        assert_eq!(resolution.references_to(a).count(), 2);
+        assert!(resolution
+            .references_to(a)
+            .all(|reference| reference.kind == ReferenceKind::Value));
+
+        let b = resolution.declaration(
+            resolution
+                .declaration_at(source.find('b').unwrap())
+                .unwrap(),
+        );
+        assert_eq!(
+            resolution.lookup(b.scope, "b"),
+            resolution.declaration_at(source.find('b').unwrap())
+        );
+        assert_eq!(resolution.lookup(Resolution::FILE_SCOPE, "b"), None);
+        assert_eq!(resolution.unresolved().count(), 0);
+    }
+
+    #[test]
+    fn var_type_is_builtin() {
+        let source = "global var a\nvar function Foo( var b ) { var c = b }";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let resolution = Resolution::new(&program);
+        assert_eq!(resolution.unresolved().count(), 0);
+    }
+}
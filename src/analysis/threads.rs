@@ -0,0 +1,574 @@
+use crate::analysis::{CallGraph, CallKind, FunctionId};
+use crate::annotation::{line_column, Diagnostic, Label, Level};
+use crate::ast::{FunctionExpression, LambdaExpression, Spanned, Visit, WaitStatement};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+
+/// Native functions that wait, so can only be called in a thread.
+pub const WAITING_NATIVES: &[&str] = &[
+    "WaitFrame",
+    "WaitForever",
+    "WaitSignal",
+    "WaitSignalOnDeadEnt",
+    "WaitSignalTimeout",
+];
+
+/// Type of [`ThreadError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThreadErrorType {
+    /// A function that may wait is called directly outside a thread, either from a callback or
+    /// outside any function.
+    ///
+    /// # Example
+    /// ```text
+    /// void function OnPlayerKilled( entity victim )
+    /// {
+    ///     RespawnAfterDelay( victim )
+    ///     ^^^^^^^^^^^^^^^^^ error
+    /// }
+    /// ```
+    BlockingCall {
+        name: String,
+        callback: Option<String>,
+    },
+
+    /// A callback waits, but callbacks are not run in a thread.
+    ///
+    /// # Example
+    /// ```text
+    /// void function OnPlayerKilled( entity victim )
+    /// {
+    ///     wait 1.0
+    ///     ^^^^^^^^ error
+    /// }
+    /// ```
+    WaitInCallback { name: String },
+}
+
+/// Type of [`ChainStep`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainStepType {
+    /// A function calls the next function in the chain.
+    Call { caller: String, callee: String },
+
+    /// A function waits, with a `wait`, `waitthread` or `waitthreadsolo` statement or a call to
+    /// one of the [`WAITING_NATIVES`].
+    Wait { function: String },
+}
+
+/// A step in the chain of calls that leads to a [`ThreadError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainStep {
+    /// The type of step.
+    pub ty: ChainStepType,
+
+    /// The index of the program the step is in.
+    pub program: usize,
+
+    /// The character range of the step.
+    pub range: Range<usize>,
+}
+
+/// An error emitted when a function that may wait is used outside a thread.
+///
+/// Each error has a type with more information, the program and character range where the error
+/// occurred, and the chain of calls from there to the code that waits.
+#[derive(Debug, Clone)]
+pub struct ThreadError {
+    /// The type of error.
+    pub ty: ThreadErrorType,
+
+    /// The index of the program the error occurred in.
+    pub program: usize,
+
+    /// The character range of where the error occurred.
+    pub range: Range<usize>,
+
+    /// The calls from the called function to the code that waits, ending in a
+    /// [`ChainStepType::Wait`].
+    pub chain: Vec<ChainStep>,
+}
+
+// Why a function may wait.
+#[derive(Debug, Clone)]
+enum Blocking {
+    // The function waits itself, at a range in a program.
+    Waits(usize, Range<usize>),
+
+    // The function calls another function that may wait.
+    Calls(usize),
+}
+
+/// Finds functions that may wait, and reports where they are used outside a thread.
+///
+/// A function may wait if it contains a `wait`, `waitthread` or `waitthreadsolo` statement, calls
+/// one of the [`WAITING_NATIVES`], or directly calls another function that may wait. Code in
+/// function literals and lambdas is not included, since it usually runs in its own thread.
+///
+/// Functions that may wait must be run with `thread`, `waitthread` or `delaythread`. Errors are
+/// reported for direct calls to them from callbacks, which are functions passed as arguments like
+/// `AddCallback_OnPlayerKilled( OnPlayerKilled )`, and from code outside any function.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::analysis::{check_threading, CallGraph, ThreadErrorType};
+///
+/// let source = r#"
+/// void function Init()
+/// {
+///     AddCallback_OnPlayerKilled( OnPlayerKilled )
+/// }
+///
+/// void function OnPlayerKilled( entity victim )
+/// {
+///     RespawnAfterDelay( victim )
+///     thread RespawnAfterDelay( victim )
+/// }
+///
+/// void function RespawnAfterDelay( entity player )
+/// {
+///     wait 5.0
+/// }
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+/// let graph = CallGraph::new(&program);
+///
+/// let errors = check_threading(&graph);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(
+///     errors[0].ty,
+///     ThreadErrorType::BlockingCall {
+///         name: "RespawnAfterDelay".to_string(),
+///         callback: Some("OnPlayerKilled".to_string()),
+///     }
+/// );
+/// assert_eq!(errors[0].chain.len(), 1);
+/// ```
+pub fn check_threading(graph: &CallGraph) -> Vec<ThreadError> {
+    let blocking = blocking_functions(graph);
+    let name = |id: FunctionId| graph.function(id).name.clone();
+
+    // Returns the chain of steps from a function that may wait to the code that waits.
+    let chain = |mut id: FunctionId| {
+        let mut chain = Vec::new();
+        loop {
+            match &blocking[&id] {
+                Blocking::Waits(program, range) => {
+                    chain.push(ChainStep {
+                        ty: ChainStepType::Wait { function: name(id) },
+                        program: *program,
+                        range: range.clone(),
+                    });
+                    return chain;
+                }
+                Blocking::Calls(call) => {
+                    let call = &graph.calls()[*call];
+                    chain.push(ChainStep {
+                        ty: ChainStepType::Call {
+                            caller: name(id),
+                            callee: name(call.callee),
+                        },
+                        program: call.program,
+                        range: call.name.token.range.clone(),
+                    });
+                    id = call.callee;
+                }
+            }
+        }
+    };
+
+    let mut errors = Vec::new();
+
+    // Calls outside functions.
+    for call in graph.calls() {
+        if call.caller.is_none() && is_direct(call.kind) && blocking.contains_key(&call.callee) {
+            errors.push(ThreadError {
+                ty: ThreadErrorType::BlockingCall {
+                    name: name(call.callee),
+                    callback: None,
+                },
+                program: call.program,
+                range: call.name.token.range.clone(),
+                chain: chain(call.callee),
+            });
+        }
+    }
+
+    // Callbacks that may wait, reported at the first step in the callback.
+    let mut callbacks: Vec<_> = (graph.calls().iter())
+        .filter(|call| call.kind == CallKind::Reference)
+        .map(|call| call.callee)
+        .collect();
+    callbacks.sort();
+    callbacks.dedup();
+    for callback in callbacks {
+        let Some(reason) = blocking.get(&callback) else {
+            continue;
+        };
+        let mut chain = chain(callback);
+        let first = chain.remove(0);
+        let ty = match reason {
+            Blocking::Waits(..) => ThreadErrorType::WaitInCallback {
+                name: name(callback),
+            },
+            Blocking::Calls(call) => ThreadErrorType::BlockingCall {
+                name: name(graph.calls()[*call].callee),
+                callback: Some(name(callback)),
+            },
+        };
+        errors.push(ThreadError {
+            ty,
+            program: first.program,
+            range: first.range,
+            chain,
+        });
+    }
+    errors
+}
+
+impl ThreadError {
+    /// Converts the error to a [`Diagnostic`] in the error's program, with the error type's
+    /// [`code`].
+    ///
+    /// Steps of the chain in the same program are secondary labels. Steps in other programs can't
+    /// be shown with the error's source, so they are notes with the step's file and location.
+    ///
+    /// `files` contains the file name and source code of each program in the [`CallGraph`].
+    ///
+    /// [`code`]: ThreadErrorType::code
+    pub fn to_diagnostic(&self, files: &[(Option<&str>, &str)]) -> Diagnostic {
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        let mut diagnostic =
+            Diagnostic::new(Level::Error, self.ty.to_string(), label).with_code(self.ty.code());
+        for step in &self.chain {
+            if step.program == self.program {
+                diagnostic =
+                    diagnostic.with_label(Label::new(step.range.clone(), step.ty.to_string()));
+                continue;
+            }
+            let (file_name, source) = files[step.program];
+            let (line, column) = line_column(source, step.range.start);
+            let location = match file_name {
+                Some(file_name) => format!("{file_name}:{line}:{column}"),
+                None => format!("{line}:{column}"),
+            };
+            diagnostic = diagnostic.with_note(format!("{} at {location}", step.ty));
+        }
+        diagnostic
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error and the
+    /// chain of calls that lead to it using [`Diagnostic::display`].
+    ///
+    /// `files` contains the file name and source code of each program in the [`CallGraph`].
+    pub fn display<'a>(
+        &'a self,
+        files: &'a [(Option<&'a str>, &'a str)],
+    ) -> impl std::fmt::Display + 'a {
+        let (file_name, source) = files[self.program];
+        Display {
+            diagnostic: self.to_diagnostic(files),
+            source,
+            file_name,
+        }
+    }
+}
+
+impl ThreadErrorType {
+    /// Returns a stable code for the error type, from `E0601` to `E0699`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ThreadErrorType::BlockingCall { .. } => "E0601",
+            ThreadErrorType::WaitInCallback { .. } => "E0602",
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(&self) -> impl std::fmt::Display + '_ {
+        ThreadErrorInlineDisplay(self)
+    }
+}
+
+impl std::fmt::Display for ThreadErrorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThreadErrorType::BlockingCall {
+                name,
+                callback: Some(callback),
+            } => write!(
+                f,
+                "`{name}` may wait, but is called from callback `{callback}` outside a thread"
+            ),
+            ThreadErrorType::BlockingCall {
+                name,
+                callback: None,
+            } => write!(f, "`{name}` may wait, but is called outside a thread"),
+            ThreadErrorType::WaitInCallback { name } => {
+                write!(f, "callback `{name}` waits, but is not run in a thread")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ChainStepType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainStepType::Call { caller, callee } => write!(f, "`{caller}` calls `{callee}`"),
+            ChainStepType::Wait { function } => write!(f, "`{function}` waits here"),
+        }
+    }
+}
+
+struct ThreadErrorInlineDisplay<'a>(&'a ThreadErrorType);
+
+impl std::fmt::Display for ThreadErrorInlineDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            ThreadErrorType::BlockingCall { .. } => write!(f, "help: call this with `thread`"),
+            ThreadErrorType::WaitInCallback { .. } => write!(f, "waits outside a thread"),
+        }
+    }
+}
+
+// Returns true if a call runs the callee in the caller's thread.
+fn is_direct(kind: CallKind) -> bool {
+    kind == CallKind::Call
+}
+
+// Finds the functions that may wait, with the nearest reason for each.
+fn blocking_functions(graph: &CallGraph) -> HashMap<FunctionId, Blocking> {
+    let mut waits: HashMap<FunctionId, Vec<(usize, Range<usize>)>> = HashMap::new();
+    for (id, function) in graph.functions() {
+        let Some((program, definition)) = function.definition else {
+            continue;
+        };
+        let mut finder = WaitFinder { waits: Vec::new() };
+        finder.visit_function_definition_statement(definition);
+        for range in finder.waits {
+            waits.entry(id).or_default().push((program, range));
+        }
+    }
+    for call in graph.calls() {
+        let Some(caller) = call.caller.filter(|_| !call.in_literal) else {
+            continue;
+        };
+        let callee = graph.function(call.callee);
+        let waits_for_native = call.kind == CallKind::Call
+            && callee.definition.is_none()
+            && WAITING_NATIVES.contains(&callee.name.as_str());
+        let waits_for_thread = matches!(call.kind, CallKind::WaitThread | CallKind::WaitThreadSolo);
+        if waits_for_native || waits_for_thread {
+            let range = call.name.token.range.clone();
+            waits.entry(caller).or_default().push((call.program, range));
+        }
+    }
+
+    // Search outwards from the functions that wait, so each function's reason is the shortest.
+    let mut blocking = HashMap::new();
+    let mut queue = VecDeque::new();
+    let mut waits: Vec<_> = waits.into_iter().collect();
+    waits.sort_by_key(|(id, _)| *id);
+    for (id, mut waits) in waits {
+        waits.sort_by_key(|(program, range)| (*program, range.start));
+        let (program, range) = waits.swap_remove(0);
+        blocking.insert(id, Blocking::Waits(program, range));
+        queue.push_back(id);
+    }
+    while let Some(id) = queue.pop_front() {
+        for (index, call) in graph.calls().iter().enumerate() {
+            if call.callee != id || call.in_literal || !is_direct(call.kind) {
+                continue;
+            }
+            let Some(caller) = call.caller else {
+                continue;
+            };
+            if let Entry::Vacant(entry) = blocking.entry(caller) {
+                entry.insert(Blocking::Calls(index));
+                queue.push_back(caller);
+            }
+        }
+    }
+    blocking
+}
+
+// Finds `wait` statements in a function, outside function literals and lambdas.
+struct WaitFinder {
+    waits: Vec<Range<usize>>,
+}
+
+impl<'s> Visit<'s> for WaitFinder {
+    fn visit_wait_statement(&mut self, node: &'s WaitStatement<'s>) {
+        self.waits.extend(node.span());
+    }
+
+    fn visit_function_expression(&mut self, _node: &'s FunctionExpression<'s>) {}
+
+    fn visit_lambda_expression(&mut self, _node: &'s LambdaExpression<'s>) {}
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_threading, ChainStepType, ThreadErrorType};
+    use crate::analysis::CallGraph;
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns the type of each error, with the chain of steps.
+    fn errors(source: &str) -> Vec<(ThreadErrorType, Vec<ChainStepType>)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let graph = CallGraph::new(&program);
+        (check_threading(&graph).into_iter())
+            .map(|error| {
+                let chain = error.chain.into_iter().map(|step| step.ty).collect();
+                (error.ty, chain)
+            })
+            .collect()
+    }
+
+    fn blocking_call(name: &str, callback: Option<&str>) -> ThreadErrorType {
+        ThreadErrorType::BlockingCall {
+            name: name.to_string(),
+            callback: callback.map(str::to_string),
+        }
+    }
+
+    fn call(caller: &str, callee: &str) -> ChainStepType {
+        ChainStepType::Call {
+            caller: caller.to_string(),
+            callee: callee.to_string(),
+        }
+    }
+
+    fn wait(function: &str) -> ChainStepType {
+        ChainStepType::Wait {
+            function: function.to_string(),
+        }
+    }
+
+    #[test]
+    fn call_chains() {
+        let source = "
+void function OnSpawned( entity player )
+{
+    A( player )
+}
+void function A( entity player ) { B( player ) }
+void function B( entity player ) { WaitSignal( player, \"OnDeath\" ) }
+void function C() { waitthread B( null ) }
+
+AddCallback_OnPlayerRespawned( OnSpawned )
+C()
+";
+        assert_eq!(
+            errors(source),
+            [
+                (blocking_call("C", None), vec![wait("C")]),
+                (
+                    blocking_call("A", Some("OnSpawned")),
+                    vec![call("A", "B"), wait("B")]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn threaded_calls() {
+        let source = "
+void function OnSpawned( entity player )
+{
+    thread A()
+    delaythread( 1.0 ) A()
+    thread void function() : ( player )
+    {
+        wait 1.0
+        A()
+    }()
+}
+void function A() { wait 0 }
+void function Init()
+{
+    AddCallback_OnPlayerRespawned( OnSpawned )
+    A()
+}
+";
+        assert_eq!(errors(source), []);
+    }
+
+    #[test]
+    fn waiting_callbacks() {
+        let source = "
+void function OnSpawned( entity player ) { WaitFrame() }
+void function OnKilled( entity player ) { wait 1 }
+void function Init()
+{
+    AddCallback_OnPlayerRespawned( OnSpawned )
+    AddCallback_OnPlayerKilled( OnKilled )
+}
+";
+        assert_eq!(
+            errors(source),
+            [
+                (
+                    ThreadErrorType::WaitInCallback {
+                        name: "OnSpawned".to_string()
+                    },
+                    vec![]
+                ),
+                (
+                    ThreadErrorType::WaitInCallback {
+                        name: "OnKilled".to_string()
+                    },
+                    vec![]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn display() {
+        let source = "void function A() { B() }
+void function B() { wait 1 }
+AddCallback_Test( A )";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let graph = CallGraph::new(&program);
+        let errors = check_threading(&graph);
+
+        yansi::Paint::disable();
+        let files = [(Some("test.nut"), source)];
+        assert_eq!(
+            errors[0].display(&files).to_string(),
+            "error[E0601]: `B` may wait, but is called from callback `A` outside a thread
+ --> test.nut:1:21
+  |
+1 | void function A() { B() }
+  |                     ^ help: call this with `thread`
+2 | void function B() { wait 1 }
+  |                     ------ `B` waits here"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::ast::StructDefinition;
+use std::fmt::{Display, Formatter};
+
+/// A type in Respawn's typed Squirrel dialect, as understood by the type checker.
+///
+/// Types are resolved from the [`Type`] syntax in a program, or inferred from expressions.
+///
+/// [`Type`]: crate::ast::Type
+#[derive(Debug, Clone)]
+pub enum Ty<'s> {
+    /// A type that could not be determined. This is compatible with every type, so errors are not
+    /// reported twice.
+    Unknown,
+
+    /// A dynamically typed value, like a `var` or `local` variable, or an untyped function.
+    Var,
+
+    /// No value, only used as a return type.
+    Void,
+
+    /// The type of `null`.
+    Null,
+
+    Bool,
+    Int,
+    Float,
+    String,
+    Vector,
+    Asset,
+    Entity,
+
+    /// A table, with the key and value types if they are known.
+    Table(Option<Box<(Ty<'s>, Ty<'s>)>>),
+
+    /// An array, with the element type if it is known.
+    Array(Option<Box<Ty<'s>>>),
+
+    /// A struct. Two struct types are the same if they have the same definition.
+    Struct {
+        name: Option<&'s str>,
+        definition: &'s StructDefinition<'s>,
+    },
+
+    /// A reference to a function.
+    FunctionRef {
+        return_type: Box<Ty<'s>>,
+        params: Vec<Ty<'s>>,
+    },
+
+    /// A value of the inner type, or `null`.
+    Nullable(Box<Ty<'s>>),
+}
+
+impl<'s> Ty<'s> {
+    /// Returns the built-in type with a name, like `int` or `entity`.
+    ///
+    /// The `array` and `table` types are returned without type arguments.
+    pub fn builtin(name: &str) -> Option<Self> {
+        Some(match name {
+            "var" => Ty::Var,
+            "void" => Ty::Void,
+            "bool" => Ty::Bool,
+            "int" => Ty::Int,
+            "float" => Ty::Float,
+            "string" => Ty::String,
+            "vector" => Ty::Vector,
+            "asset" => Ty::Asset,
+            "entity" => Ty::Entity,
+            "table" => Ty::Table(None),
+            "array" => Ty::Array(None),
+            _ => return None,
+        })
+    }
+
+    /// Returns true if nothing is known about values of this type, so any value can be used.
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, Ty::Unknown | Ty::Var)
+    }
+
+    /// Returns true if the type is `int` or `float`.
+    pub fn is_number(&self) -> bool {
+        matches!(self, Ty::Int | Ty::Float)
+    }
+
+    /// Returns true if a value of type `from` can be stored in a location of this type.
+    ///
+    /// `int` values can be stored in `float` locations, and `null` can be stored in reference
+    /// types like `entity`, `table` and `array`. Element types of arrays and tables must match
+    /// exactly, unless either is unknown.
+    pub fn accepts(&self, from: &Ty<'s>) -> bool {
+        match (self, from) {
+            (to, from) if to.is_dynamic() || from.is_dynamic() => true,
+            (Ty::Float, Ty::Int) => true,
+            (Ty::Nullable(_), Ty::Null) => true,
+            (Ty::Nullable(to), Ty::Nullable(from)) => to.accepts(from),
+            (Ty::Nullable(to), from) => to.accepts(from),
+            (
+                Ty::Entity
+                | Ty::Table(_)
+                | Ty::Array(_)
+                | Ty::Struct { .. }
+                | Ty::FunctionRef { .. },
+                Ty::Null,
+            ) => true,
+            (to, from) => to.is_same(from),
+        }
+    }
+
+    // Returns true if the types are the same, treating unknown parts as matching anything.
+    fn is_same(&self, other: &Ty<'s>) -> bool {
+        match (self, other) {
+            (a, b) if a.is_dynamic() || b.is_dynamic() => true,
+            (Ty::Table(a), Ty::Table(b)) => match (a, b) {
+                (Some(a), Some(b)) => a.0.is_same(&b.0) && a.1.is_same(&b.1),
+                _ => true,
+            },
+            (Ty::Array(a), Ty::Array(b)) => match (a, b) {
+                (Some(a), Some(b)) => a.is_same(b),
+                _ => true,
+            },
+            (Ty::Struct { definition: a, .. }, Ty::Struct { definition: b, .. }) => {
+                std::ptr::eq(*a, *b)
+            }
+            (
+                Ty::FunctionRef {
+                    return_type: a_return,
+                    params: a_params,
+                },
+                Ty::FunctionRef {
+                    return_type: b_return,
+                    params: b_params,
+                },
+            ) => {
+                a_return.is_same(b_return)
+                    && a_params.len() == b_params.len()
+                    && a_params.iter().zip(b_params).all(|(a, b)| a.is_same(b))
+            }
+            (Ty::Nullable(a), Ty::Nullable(b)) => a.is_same(b),
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+        }
+    }
+}
+
+impl PartialEq for Ty<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Ty::Unknown, Ty::Unknown) | (Ty::Var, Ty::Var) => true,
+            (a, b) if a.is_dynamic() || b.is_dynamic() => false,
+            (a, b) => a.is_same(b),
+        }
+    }
+}
+
+impl Display for Ty<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ty::Unknown => write!(f, "{{unknown}}"),
+            Ty::Var => write!(f, "var"),
+            Ty::Void => write!(f, "void"),
+            Ty::Null => write!(f, "null"),
+            Ty::Bool => write!(f, "bool"),
+            Ty::Int => write!(f, "int"),
+            Ty::Float => write!(f, "float"),
+            Ty::String => write!(f, "string"),
+            Ty::Vector => write!(f, "vector"),
+            Ty::Asset => write!(f, "asset"),
+            Ty::Entity => write!(f, "entity"),
+            Ty::Table(None) => write!(f, "table"),
+            Ty::Table(Some(params)) => write!(f, "table<{}, {}>", params.0, params.1),
+            Ty::Array(None) => write!(f, "array"),
+            Ty::Array(Some(param)) => write!(f, "array<{param}>"),
+            Ty::Struct {
+                name: Some(name), ..
+            } => write!(f, "{name}"),
+            Ty::Struct { name: None, .. } => write!(f, "struct"),
+            Ty::FunctionRef {
+                return_type,
+                params,
+            } => {
+                write!(f, "{return_type} functionref(")?;
+                for (index, param) in params.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{param}")?;
+                }
+                write!(f, ")")
+            }
+            Ty::Nullable(base) => write!(f, "{base} ornull"),
+        }
+    }
+}
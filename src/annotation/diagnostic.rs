@@ -0,0 +1,471 @@
+use crate::annotation::{annotations_gutter, display_annotations, Annotation, Mode};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use yansi::Paint;
+
+/// A message about some source code, like an error or a warning.
+///
+/// A diagnostic has a [`Level`], an optional stable code, a message and a primary [`Label`] showing
+/// where the problem is. It can also have secondary labels that point to related code, notes and
+/// help text printed after the code, and [`Suggestion`]s that fix the problem.
+///
+/// Errors in sqparse can be converted to diagnostics, like with [`LexerError::to_diagnostic`] and
+/// [`ParseError::to_diagnostic`], so they can all be printed with [`Diagnostic::display`].
+///
+/// # Example
+/// ```
+/// use sqparse::annotation::{Diagnostic, Label, Level, Suggestion};
+///
+/// yansi::Paint::disable();
+///
+/// let source = "local a = 1\nlocal a = 2";
+/// let primary = Label::new(18..19, "defined again here");
+/// let diagnostic = Diagnostic::new(Level::Error, "`a` is defined twice", primary)
+///     .with_code("E9000")
+///     .with_label(Label::new(6..7, "first defined here"))
+///     .with_help("rename one of the variables")
+///     .with_suggestion(Suggestion::new("rename it", 18..19, "b"));
+/// assert_eq!(
+///     diagnostic.display(source, Some("file.nut")).to_string(),
+///     "error[E9000]: `a` is defined twice
+///  --> file.nut:2:7
+///   |
+/// 1 | local a = 1
+///   |       - first defined here
+/// 2 | local a = 2
+///   |       ^ defined again here
+///   |
+///   = help: rename one of the variables
+///   = help: rename it: `b`"
+/// );
+/// ```
+///
+/// [`LexerError::to_diagnostic`]: crate::LexerError::to_diagnostic
+/// [`ParseError::to_diagnostic`]: crate::ParseError::to_diagnostic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the diagnostic is.
+    pub level: Level,
+
+    /// A stable code identifying the kind of diagnostic, like `E0101` or `unused-local`.
+    pub code: Option<&'static str>,
+
+    /// A message describing the problem.
+    pub message: String,
+
+    /// The code with the problem, and a short message to show inline with it.
+    pub primary: Label,
+
+    /// Other code related to the problem.
+    pub secondary: Vec<Label>,
+
+    /// Notes printed after the code.
+    pub notes: Vec<String>,
+
+    /// Help text printed after the notes.
+    pub help: Vec<String>,
+
+    /// Edits that fix the problem.
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Extra information, that is not a problem on its own.
+    Note,
+
+    /// Something that is likely a mistake, but is still valid code.
+    Warning,
+
+    /// Something that is invalid code.
+    Error,
+}
+
+/// A range of source code in a [`Diagnostic`], with a short message shown inline with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// The character range of the code.
+    pub range: Range<usize>,
+
+    /// The message to show inline with the code. This can be empty.
+    pub text: String,
+}
+
+/// An edit to the source code that fixes the problem in a [`Diagnostic`].
+///
+/// Suggestions are machine-applicable: applying them with [`apply_suggestions`] results in the
+/// code the suggestion describes, without any other changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// A message describing the fix.
+    pub message: String,
+
+    /// The character range of the code to replace. This is empty to insert code.
+    pub range: Range<usize>,
+
+    /// The code to replace the range with. This is empty to remove code.
+    pub replacement: String,
+}
+
+impl Diagnostic {
+    /// Creates a new `Diagnostic` with no code, secondary labels, notes, help or suggestions.
+    pub fn new(level: Level, message: impl Into<String>, primary: Label) -> Self {
+        Diagnostic {
+            level,
+            code: None,
+            message: message.into(),
+            primary,
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Sets the code of the diagnostic.
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Adds a secondary label.
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.secondary.push(label);
+        self
+    }
+
+    /// Adds a note.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Adds help text.
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    /// Adds a suggestion.
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    /// Returns the annotations for the labels, with the primary label first.
+    ///
+    /// A secondary label that contains the primary label keeps the primary label's lines visible.
+    pub fn annotations(&self) -> Vec<Annotation> {
+        let primary = &self.primary.range;
+        let primary_annotation = Annotation {
+            mode: self.level.mode(),
+            text: self.primary.text.clone(),
+            note: "".to_string(),
+            highlight: primary.clone(),
+            visible: primary.clone(),
+        };
+        let secondary_annotations = self.secondary.iter().map(|label| {
+            let contains_primary =
+                label.range.start <= primary.start && primary.end <= label.range.end;
+            Annotation {
+                mode: Mode::Info,
+                text: label.text.clone(),
+                note: "".to_string(),
+                highlight: label.range.clone(),
+                visible: if contains_primary {
+                    primary.clone()
+                } else {
+                    label.range.clone()
+                },
+            }
+        });
+        std::iter::once(primary_annotation)
+            .chain(secondary_annotations)
+            .collect()
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the diagnostic using
+    /// [`display_annotations`].
+    ///
+    /// The labels are printed first, followed by the notes, help and suggestions.
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        DiagnosticDisplay {
+            diagnostic: self,
+            source,
+            file_name,
+        }
+    }
+}
+
+impl Level {
+    /// Returns the name of the level printed before a diagnostic's message: `note`, `warning` or
+    /// `error`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Note => "note",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+
+    /// Returns the annotation [`Mode`] used for the primary label of a diagnostic with this level.
+    pub fn mode(self) -> Mode {
+        match self {
+            Level::Note => Mode::Info,
+            Level::Warning => Mode::Warning,
+            Level::Error => Mode::Error,
+        }
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Label {
+    /// Creates a new `Label`.
+    pub fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        Label {
+            range,
+            text: text.into(),
+        }
+    }
+}
+
+impl Suggestion {
+    /// Creates a new `Suggestion`.
+    pub fn new(
+        message: impl Into<String>,
+        range: Range<usize>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        Suggestion {
+            message: message.into(),
+            range,
+            replacement: replacement.into(),
+        }
+    }
+}
+
+/// Applies suggestions to a source string, returning the new source.
+///
+/// Suggestions are applied in order of their range. A suggestion that overlaps one that was already
+/// applied is skipped, so that applying the suggestions of several diagnostics never produces a
+/// mix of two edits.
+///
+/// # Example
+/// ```
+/// use sqparse::annotation::{apply_suggestions, Suggestion};
+///
+/// let source = "print( a  b )";
+/// let suggestions = [
+///     Suggestion::new("add a comma", 8..8, ","),
+///     Suggestion::new("remove the extra space", 8..9, ""),
+///     Suggestion::new("use a `printl`", 0..5, "printl"),
+/// ];
+/// assert_eq!(apply_suggestions(source, &suggestions), "printl( a, b )");
+/// ```
+pub fn apply_suggestions<'a>(
+    source: &str,
+    suggestions: impl IntoIterator<Item = &'a Suggestion>,
+) -> String {
+    let mut suggestions: Vec<_> = suggestions.into_iter().collect();
+    suggestions.sort_by_key(|suggestion| (suggestion.range.start, suggestion.range.end));
+
+    let mut result = String::with_capacity(source.len());
+    let mut applied_end = 0;
+    let mut last_insert = None;
+    for suggestion in suggestions {
+        let range = &suggestion.range;
+        // Two insertions at the same place would have to be ordered, so only the first is kept.
+        let overlaps =
+            range.start < applied_end || (range.is_empty() && last_insert == Some(range.start));
+        if overlaps {
+            continue;
+        }
+        result.push_str(&source[applied_end..range.start]);
+        result.push_str(&suggestion.replacement);
+        applied_end = range.end;
+        last_insert = range.is_empty().then_some(range.start);
+    }
+    result.push_str(&source[applied_end..]);
+    result
+}
+
+struct DiagnosticDisplay<'a> {
+    diagnostic: &'a Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl Display for DiagnosticDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let diagnostic = self.diagnostic;
+        let mode = diagnostic.level.mode();
+        match diagnostic.code {
+            Some(code) => write!(
+                f,
+                "{}",
+                mode.display(format!("{}[{code}]", diagnostic.level))
+            )?,
+            None => write!(f, "{}", mode.display(diagnostic.level))?,
+        }
+        writeln!(
+            f,
+            "{}{}",
+            Paint::white(": ").bold(),
+            Paint::white(&diagnostic.message).bold(),
+        )?;
+
+        let annotations = diagnostic.annotations();
+        write!(
+            f,
+            "{}",
+            display_annotations(self.file_name, self.source, &annotations)
+        )?;
+
+        let suggestions = (diagnostic.suggestions.iter()).map(|suggestion| {
+            if suggestion.replacement.is_empty() {
+                suggestion.message.clone()
+            } else {
+                format!("{}: `{}`", suggestion.message, suggestion.replacement)
+            }
+        });
+        let footer: Vec<_> = (diagnostic.notes.iter())
+            .map(|note| ("note", note.clone()))
+            .chain(diagnostic.help.iter().map(|help| ("help", help.clone())))
+            .chain(suggestions.map(|suggestion| ("help", suggestion)))
+            .collect();
+        if footer.is_empty() {
+            return Ok(());
+        }
+
+        // The gutter must line up with the one printed by `display_annotations`.
+        let gutter = annotations_gutter(self.source, &annotations);
+
+        write!(f, "\n{}", gutter.empty())?;
+        for (kind, text) in footer {
+            write!(
+                f,
+                "\n{} {}: {text}",
+                gutter.separator(),
+                Paint::white(kind).bold()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply_suggestions, Diagnostic, Label, Level, Suggestion};
+    use crate::analysis::{check_dataflow, check_types, Resolution, TypeRegistry};
+    use crate::{parse, tokenize, tokenize_lossy, Flavor};
+
+    #[test]
+    fn display_notes() {
+        yansi::Paint::disable();
+        let source = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj";
+        let diagnostic = Diagnostic::new(Level::Note, "last line", Label::new(18..19, "here"))
+            .with_note("lines are counted from 1")
+            .with_suggestion(Suggestion::new("remove it", 17..19, ""));
+        assert_eq!(
+            diagnostic.display(source, None).to_string(),
+            "note: last line
+  --> 10:1
+   |
+10 | j
+   | - here
+   |
+   = note: lines are counted from 1
+   = help: remove it"
+        );
+    }
+
+    #[test]
+    fn lexer_error() {
+        yansi::Paint::disable();
+        let source = "a = \"hello";
+        let (_, errors) = tokenize_lossy(source, Flavor::SquirrelRespawn);
+        let diagnostic = errors[0].to_diagnostic();
+        assert_eq!(diagnostic.code, Some("E0001"));
+        assert_eq!(
+            errors[0].display(source, Some("a.nut")).to_string(),
+            "error[E0001]: input ended in the middle of a string
+ --> a.nut:1:11
+  |
+1 | a = \"hello
+  |           ^ help: add a `\"`"
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        yansi::Paint::disable();
+        let source = "struct MyStruct {\n    int a = ,\n}";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let error = parse(&tokens, Flavor::SquirrelRespawn).unwrap_err();
+        let diagnostic = error.to_diagnostic(&tokens);
+        assert_eq!(diagnostic.level, Level::Error);
+        assert_eq!(diagnostic.code, Some(error.ty.code()));
+        assert_eq!(&source[diagnostic.primary.range.clone()], ",");
+        assert_eq!(diagnostic.secondary.len(), 1);
+        assert_eq!(
+            error.display(source, &tokens, None).to_string(),
+            "error[E0107]: expected a value, found a `,`
+ --> 2:13
+  |
+2 |     int a = ,
+  |     ------- ^ expected a value
+  |     |
+  |     for this property"
+        );
+    }
+
+    #[test]
+    fn analysis_errors() {
+        yansi::Paint::disable();
+        let source = "void function F()\n{\n    int a = \"one\"\n}";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let resolution = Resolution::new(&program);
+        let types = TypeRegistry::new(&program);
+
+        let type_errors = check_types(&program, &resolution, &types);
+        assert_eq!(type_errors[0].to_diagnostic().code, Some("E0201"));
+        assert_eq!(
+            type_errors[0].display(source, Some("a.nut")).to_string(),
+            "error[E0201]: mismatched types: expected `int`, found `string`
+ --> a.nut:3:13
+  |
+3 |     int a = \"one\"
+  |             ^^^^^ expected `int`"
+        );
+
+        let dataflow_errors = check_dataflow(&program, &resolution);
+        let diagnostic = dataflow_errors[0].to_diagnostic();
+        assert_eq!(diagnostic.level, Level::Warning);
+        assert_eq!(diagnostic.code, Some("E0502"));
+    }
+
+    #[test]
+    fn overlapping_suggestions() {
+        let source = "abcdef";
+        let suggestions = [
+            Suggestion::new("", 1..4, "X"),
+            Suggestion::new("", 2..3, "Y"),
+            Suggestion::new("", 4..4, "1"),
+            Suggestion::new("", 4..4, "2"),
+            Suggestion::new("", 4..5, "Z"),
+            Suggestion::new("", 6..6, "!"),
+        ];
+        assert_eq!(apply_suggestions(source, &suggestions), "aX1Zf!");
+    }
+}
@@ -18,6 +18,10 @@ impl Gutter {
         GutterDisplay(FileGutterDisplay(self))
     }
 
+    pub fn secondary_file(self) -> impl Display {
+        GutterDisplay(SecondaryFileGutterDisplay(self))
+    }
+
     pub fn empty(self) -> impl Display {
         GutterDisplay(EmptyGutterDisplay(self))
     }
@@ -51,6 +55,14 @@ impl Display for FileGutterDisplay {
     }
 }
 
+struct SecondaryFileGutterDisplay(Gutter);
+
+impl Display for SecondaryFileGutterDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{pad}:::", pad = repeat(self.0.number_width, ' '))
+    }
+}
+
 struct EmptyGutterDisplay(Gutter);
 
 impl Display for EmptyGutterDisplay {
@@ -1,7 +1,9 @@
 //! A utility for pretty-printing source code annotations, warnings and errors.
 //!
 //! [`display_annotations`] returns an object that implements [`Display`], that will pretty-print
-//! some source code based on a list of [`Annotation`]s.
+//! some source code based on a list of [`Annotation`]s. A [`Diagnostic`] describes an error or
+//! warning with labels, notes and suggestions, and is printed with annotations or in a
+//! machine-readable [`OutputFormat`] with [`display_diagnostics`].
 //!
 //! # Example
 //! ```
@@ -26,19 +28,22 @@
 //!   |           -- this is me!");
 //! ```
 
-mod formats;
+mod diagnostic;
 mod gutter;
-mod line_printer;
 mod mode;
+mod output;
 mod repeat;
+mod snippet;
 
-use crate::annotation::formats::{MultiLineFormatDisplay, SingleLineFormatDisplay};
 use crate::annotation::gutter::Gutter;
+use crate::annotation::snippet::{group_spans, SnippetDisplay, Span};
 use std::fmt::{Display, Formatter};
-use std::ops::{Range, RangeInclusive};
+use std::ops::Range;
 use yansi::Paint;
 
+pub use self::diagnostic::{apply_suggestions, Diagnostic, Label, Level, Suggestion};
 pub use self::mode::Mode;
+pub use self::output::{display_diagnostics, FileDiagnostics, OutputFormat};
 
 /// A source code annotation.
 ///
@@ -57,7 +62,7 @@ pub struct Annotation {
     /// ```
     pub text: String,
 
-    /// Text to print as a note underneath the annotation.
+    /// Text to print as a note underneath the snippet of source code with the annotation.
     ///
     /// # Example
     /// ```text
@@ -118,6 +123,11 @@ pub struct Annotation {
 ///    | ^^^^^
 /// ```
 ///
+/// Annotations on the same or nearby lines are printed together in one snippet of source code.
+/// Labels of annotations on the same line are stacked below the line, and overlapping multi-line
+/// annotations are drawn with separate bars in the gutter. Annotations further away are printed
+/// in separate snippets, each starting with its location.
+///
 /// For details on how each annotation is formatted, see [`Annotation`].
 ///
 /// # Example
@@ -142,119 +152,134 @@ pub struct Annotation {
 /// 1 | highlight me!
 ///   |           -- this is me!");
 /// ```
+///
+/// Several annotations on one line:
+/// ```
+/// use sqparse::annotation::{Annotation, display_annotations, Mode};
+///
+/// yansi::Paint::disable();
+///
+/// let source = "local sum = first + second";
+/// let annotation = |mode, text: &str, highlight: std::ops::Range<usize>| Annotation {
+///     mode,
+///     text: text.to_string(),
+///     note: "".to_string(),
+///     highlight: highlight.clone(),
+///     visible: highlight,
+/// };
+/// let annotations = [
+///     annotation(Mode::Error, "not defined", 12..17),
+///     annotation(Mode::Info, "the sum", 6..9),
+///     annotation(Mode::Info, "also not defined", 20..26),
+/// ];
+/// let annotations = format!("{}", display_annotations(None, source, &annotations));
+/// assert_eq!(annotations, " --> 1:13
+///   |
+/// 1 | local sum = first + second
+///   |       ---   ^^^^^   ------ also not defined
+///   |       |     |
+///   |       |     not defined
+///   |       the sum");
+/// ```
 pub fn display_annotations<'s>(
     file_name: Option<&'s str>,
     source: &'s str,
     annotations: &'s [Annotation],
 ) -> impl Display + 's {
-    let format_data: Vec<_> = annotations
-        .iter()
-        .map(|annotation| {
-            FormatData::new(
-                source,
-                annotation.highlight.clone(),
-                annotation.visible.clone(),
-            )
-        })
+    let spans: Vec<_> = (annotations.iter())
+        .map(|annotation| Span::new(source, annotation))
         .collect();
-    let max_line_number = format_data
-        .iter()
-        .map(|format| *format.line_numbers().end())
-        .max()
-        .unwrap_or(0);
-    let gutter = Gutter::new(max_line_number);
+    let groups = group_spans(&spans);
 
     AnnotationsDisplay {
         file_name,
         annotations,
-        gutter,
-        format_data,
+        lines: source.split('\n').collect(),
+        gutter: spans_gutter(&spans),
+        spans,
+        groups,
     }
 }
 
+// Returns the gutter used to print a list of annotations, wide enough for every line number.
+pub(crate) fn annotations_gutter(source: &str, annotations: &[Annotation]) -> Gutter {
+    let spans: Vec<_> = (annotations.iter())
+        .map(|annotation| Span::new(source, annotation))
+        .collect();
+    spans_gutter(&spans)
+}
+
+fn spans_gutter(spans: &[Span]) -> Gutter {
+    Gutter::new(spans.iter().map(|span| span.last_line).max().unwrap_or(0))
+}
+
 struct AnnotationsDisplay<'s> {
     file_name: Option<&'s str>,
     annotations: &'s [Annotation],
+    lines: Vec<&'s str>,
     gutter: Gutter,
-    format_data: Vec<FormatData<'s>>,
+    spans: Vec<Span>,
+    groups: Vec<Vec<usize>>,
 }
 
-impl Display for AnnotationsDisplay<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} ", self.gutter.file())?;
+impl AnnotationsDisplay<'_> {
+    fn write_location(&self, f: &mut Formatter<'_>, span: Option<&Span>) -> std::fmt::Result {
         if let Some(file_name) = self.file_name {
             write!(f, "{file_name}:")?;
         }
-        if let Some(first_format_data) = self.format_data.first() {
-            write!(
-                f,
-                "{}:{}",
-                first_format_data.line_numbers().start(),
-                first_format_data.first_line_highlight() + 1
-            )?;
+        if let Some(span) = span {
+            write!(f, "{}:{}", span.first_line, span.first_column + 1)?;
         }
+        Ok(())
+    }
+}
 
-        for (annotation_index, (annotation, format_data)) in self
-            .annotations
-            .iter()
-            .zip(self.format_data.iter())
-            .enumerate()
-        {
-            writeln!(f, "\n{}", self.gutter.empty())?;
-
-            match format_data {
-                FormatData::SingleLine {
-                    line,
-                    line_number,
-                    line_highlight,
-                } => {
-                    write!(
-                        f,
-                        "{}",
-                        SingleLineFormatDisplay {
-                            mode: annotation.mode,
-                            gutter: self.gutter,
-                            line,
-                            line_number: *line_number,
-                            line_highlight: line_highlight.clone()
-                        },
-                    )?;
-                }
-                FormatData::MultiLine {
-                    lines,
-                    line_numbers,
-                    must_be_visible_line_numbers,
-                    first_line_highlight,
-                    last_line_highlight,
-                } => {
-                    write!(
-                        f,
-                        "{}",
-                        MultiLineFormatDisplay {
-                            mode: annotation.mode,
-                            gutter: self.gutter,
-                            lines,
-                            line_numbers: line_numbers.clone(),
-                            must_be_visible_line_numbers: must_be_visible_line_numbers.clone(),
-                            first_line_highlight: *first_line_highlight,
-                            last_line_highlight: *last_line_highlight,
-                        },
-                    )?;
-                }
+impl Display for AnnotationsDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ", self.gutter.file())?;
+        self.write_location(f, self.spans.first())?;
+
+        for (group_index, group) in self.groups.iter().enumerate() {
+            // Snippets after the first start with their own location.
+            if group_index > 0 {
+                let first_span = (group.iter())
+                    .map(|&index| &self.spans[index])
+                    .min_by_key(|span| (span.first_line, span.first_column));
+                write!(
+                    f,
+                    "\n{}\n{} ",
+                    self.gutter.empty(),
+                    self.gutter.secondary_file()
+                )?;
+                self.write_location(f, first_span)?;
             }
 
-            write!(f, " {}", annotation.mode.display(&annotation.text))?;
+            write!(
+                f,
+                "\n{}",
+                SnippetDisplay {
+                    gutter: self.gutter,
+                    lines: &self.lines,
+                    annotations: self.annotations,
+                    spans: &self.spans,
+                    group,
+                }
+            )?;
 
-            if !annotation.note.is_empty() {
-                writeln!(f, "\n{}", self.gutter.empty())?;
+            let mut notes = (group.iter())
+                .map(|&index| &self.annotations[index].note)
+                .filter(|note| !note.is_empty())
+                .peekable();
+            if notes.peek().is_some() {
+                write!(f, "\n{}", self.gutter.empty())?;
+            }
+            for note in notes {
                 write!(
                     f,
-                    "{} {}",
+                    "\n{} {}",
                     self.gutter.separator(),
-                    Paint::white(&annotation.note).bold()
+                    Paint::white(note).bold()
                 )?;
-            } else if annotation_index + 1 < self.annotations.len() {
-                write!(f, "\n{}", self.gutter.separator())?;
             }
         }
 
@@ -262,93 +287,22 @@ impl Display for AnnotationsDisplay<'_> {
     }
 }
 
-enum FormatData<'s> {
-    SingleLine {
-        line: &'s str,
-        line_number: usize,
-        line_highlight: Range<usize>,
-    },
-    MultiLine {
-        lines: &'s str,
-        line_numbers: RangeInclusive<usize>,
-        must_be_visible_line_numbers: RangeInclusive<usize>,
-        first_line_highlight: usize,
-        last_line_highlight: usize,
-    },
-}
-
-impl<'s> FormatData<'s> {
-    pub fn new(text: &'s str, highlight: Range<usize>, visible: Range<usize>) -> Self {
-        let has_newline = text[highlight.clone()].contains('\n');
-
-        if has_newline {
-            Self::new_multi_line(text, highlight, visible)
-        } else {
-            Self::new_single_line(text, highlight)
-        }
-    }
-
-    pub fn line_numbers(&self) -> RangeInclusive<usize> {
-        match self {
-            FormatData::SingleLine { line_number, .. } => (*line_number)..=(*line_number),
-            FormatData::MultiLine { line_numbers, .. } => line_numbers.clone(),
-        }
-    }
-
-    pub fn first_line_highlight(&self) -> usize {
-        match self {
-            FormatData::SingleLine { line_highlight, .. } => line_highlight.start,
-            FormatData::MultiLine {
-                first_line_highlight,
-                ..
-            } => *first_line_highlight,
-        }
-    }
-
-    fn new_single_line(text: &'s str, highlight: Range<usize>) -> Self {
-        let (line_number, line_start_index) = get_line_containing(highlight.start, text);
-        let text_from_start = &text[line_start_index..];
-
-        let end_index = text_from_start.find('\n').unwrap_or(text_from_start.len());
-        let line = &text_from_start[..end_index];
-
-        let line_highlight =
-            (highlight.start - line_start_index)..(highlight.end - line_start_index);
-
-        FormatData::SingleLine {
-            line,
-            line_number,
-            line_highlight,
-        }
-    }
-
-    fn new_multi_line(text: &'s str, highlight: Range<usize>, visible: Range<usize>) -> Self {
-        let (first_line_number, first_start_index) = get_line_containing(highlight.start, text);
-        let (last_line_number, last_start_index) =
-            get_line_containing(highlight.end.saturating_sub(1), text);
-
-        let (first_must_be_visible_line_number, _) = get_line_containing(visible.start, text);
-        let (last_must_be_visible_line_number, _) =
-            get_line_containing(visible.end.saturating_sub(1), text);
-
-        let last_end_index = text[last_start_index..]
-            .find('\n')
-            .map(|idx| last_start_index + idx)
-            .unwrap_or(text.len());
-
-        let lines = &text[first_start_index..last_end_index];
-        let first_line_highlight = highlight.start - first_start_index;
-        let last_line_highlight = highlight.end.saturating_sub(1) - last_start_index;
-
-        FormatData::MultiLine {
-            line_numbers: first_line_number..=last_line_number,
-            must_be_visible_line_numbers: first_must_be_visible_line_number
-                ..=last_must_be_visible_line_number,
-            lines,
-            first_line_highlight,
-            last_line_highlight,
-        }
-    }
+/// Returns the 1-based line and column of a character offset in a source string.
+///
+/// Lines are separated by `\n`, and columns are counted in bytes from the start of the line. This
+/// is the same position that [`display_annotations`] prints for the first annotation.
+///
+/// # Example
+/// ```
+/// use sqparse::annotation::line_column;
+///
+/// let source = "first line\nsecond line";
+/// assert_eq!(line_column(source, 0), (1, 1));
+/// assert_eq!(line_column(source, 18), (2, 8));
+/// ```
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let (line, line_start_index) = get_line_containing(offset, source);
+    (line, offset - line_start_index + 1)
 }
 
 fn get_line_containing(index: usize, val: &str) -> (usize, usize) {
@@ -4,7 +4,7 @@ use yansi::Paint;
 /// Controls the theme/styling of an [`Annotation`].
 ///
 /// [`Annotation`]: crate::annotation::Annotation
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     /// An information annotation. Prints highlights with hyphens and cyan text when color is
     /// enabled.
@@ -0,0 +1,685 @@
+use crate::annotation::{line_column, Diagnostic, Level, Suggestion};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// A format to print [`Diagnostic`]s in, with [`display_diagnostics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// Pretty-printed source code snippets, using [`Diagnostic::display`].
+    Human,
+
+    /// A JSON array with an object for each diagnostic.
+    ///
+    /// Each object has the `file`, `severity`, `code` (or `null`), `message` and the `range` and
+    /// `label` of the primary label. Other labels, like the context of a parse error, are in the
+    /// `secondary` array with their own `range` and `label`. The `notes` and `help` arrays have
+    /// the notes and help text, and `suggestions` has the `message`, `range` and `replacement` of
+    /// each suggestion.
+    ///
+    /// A range has a `start` and `end` position, each with a 1-based `line` and `column` and a
+    /// byte `offset` into the file. The end position is exclusive.
+    Json,
+
+    /// A [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) log with
+    /// a single run.
+    ///
+    /// Codes are used as rule IDs, secondary labels are related locations and suggestions are
+    /// fixes. Columns are counted in Unicode code points, and file names are percent-encoded to be
+    /// used as URIs.
+    Sarif,
+
+    /// Checkstyle XML, with an `error` element for each diagnostic and the code as its `source`.
+    ///
+    /// Characters that cannot be written in XML, like most control characters, are replaced with
+    /// `U+FFFD`.
+    Checkstyle,
+
+    /// [GitHub Actions workflow commands](https://docs.github.com/en/actions/using-workflow-commands-for-github-actions),
+    /// like `::error file=a.nut,line=1,col=5::message`, so diagnostics are shown on pull
+    /// requests.
+    Github,
+}
+
+/// The [`Diagnostic`]s reported in a file.
+#[derive(Debug, Clone, Copy)]
+pub struct FileDiagnostics<'a> {
+    /// The name of the file, printed as it is.
+    pub file_name: &'a str,
+
+    /// The source code of the file.
+    pub source: &'a str,
+
+    /// The diagnostics in the file.
+    pub diagnostics: &'a [Diagnostic],
+}
+
+impl OutputFormat {
+    /// Returns the format with a name: `human`, `json`, `sarif`, `checkstyle` or `github`.
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "human" => Some(OutputFormat::Human),
+            "json" => Some(OutputFormat::Json),
+            "sarif" => Some(OutputFormat::Sarif),
+            "checkstyle" => Some(OutputFormat::Checkstyle),
+            "github" => Some(OutputFormat::Github),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the format.
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Human => "human",
+            OutputFormat::Json => "json",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Checkstyle => "checkstyle",
+            OutputFormat::Github => "github",
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Returns an implementation of [`std::fmt::Display`] that prints the diagnostics in some files in
+/// an [`OutputFormat`].
+///
+/// Lines and columns are found with [`line_column`], so they match the locations printed by
+/// [`display_annotations`].
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::annotation::{display_diagnostics, FileDiagnostics, OutputFormat};
+///
+/// let source = "local sum = 1 + ?";
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let error = parse(&tokens, Flavor::SquirrelRespawn).unwrap_err();
+///
+/// let diagnostics = [error.to_diagnostic(&tokens)];
+/// let files = [FileDiagnostics { file_name: "sum.nut", source, diagnostics: &diagnostics }];
+/// assert_eq!(
+///     display_diagnostics(OutputFormat::Github, &files).to_string(),
+///     "::error file=sum.nut,line=1,col=17,endLine=1,endColumn=17,title=E0107::expected a value, found a `?`"
+/// );
+/// ```
+///
+/// [`display_annotations`]: crate::annotation::display_annotations
+pub fn display_diagnostics<'a>(
+    format: OutputFormat,
+    files: &'a [FileDiagnostics<'a>],
+) -> impl Display + 'a {
+    DiagnosticsDisplay { format, files }
+}
+
+struct DiagnosticsDisplay<'a> {
+    format: OutputFormat,
+    files: &'a [FileDiagnostics<'a>],
+}
+
+impl DiagnosticsDisplay<'_> {
+    fn diagnostics(&self) -> impl Iterator<Item = (&FileDiagnostics<'_>, &Diagnostic)> {
+        (self.files.iter()).flat_map(|file| {
+            file.diagnostics
+                .iter()
+                .map(move |diagnostic| (file, diagnostic))
+        })
+    }
+
+    fn fmt_human(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, (file, diagnostic)) in self.diagnostics().enumerate() {
+            if index > 0 {
+                write!(f, "\n\n")?;
+            }
+            write!(
+                f,
+                "{}",
+                diagnostic.display(file.source, Some(file.file_name))
+            )?;
+        }
+        Ok(())
+    }
+
+    fn fmt_json(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (index, (file, diagnostic)) in self.diagnostics().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            let range = |range| JsonRange(file.source, range);
+            write!(
+                f,
+                "{{\"file\":{},\"severity\":{},\"code\":",
+                JsonString(file.file_name),
+                JsonString(diagnostic.level.name())
+            )?;
+            match diagnostic.code {
+                Some(code) => write!(f, "{}", JsonString(code))?,
+                None => write!(f, "null")?,
+            }
+            write!(
+                f,
+                ",\"message\":{},\"range\":{},\"label\":{}",
+                JsonString(&diagnostic.message),
+                range(&diagnostic.primary.range),
+                JsonString(&diagnostic.primary.text)
+            )?;
+
+            write!(f, ",\"secondary\":[")?;
+            for (index, label) in diagnostic.secondary.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ",")?;
+                }
+                write!(
+                    f,
+                    "{{\"range\":{},\"label\":{}}}",
+                    range(&label.range),
+                    JsonString(&label.text)
+                )?;
+            }
+            write!(
+                f,
+                "],\"notes\":{},\"help\":{},\"suggestions\":[",
+                JsonStrings(&diagnostic.notes),
+                JsonStrings(&diagnostic.help)
+            )?;
+            for (index, suggestion) in diagnostic.suggestions.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ",")?;
+                }
+                write!(
+                    f,
+                    "{{\"message\":{},\"range\":{},\"replacement\":{}}}",
+                    JsonString(&suggestion.message),
+                    range(&suggestion.range),
+                    JsonString(&suggestion.replacement)
+                )?;
+            }
+            write!(f, "]}}")?;
+        }
+        write!(f, "]")
+    }
+
+    fn fmt_sarif(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut codes: Vec<&str> = Vec::new();
+        for (_, diagnostic) in self.diagnostics() {
+            if let Some(code) = diagnostic.code {
+                if !codes.contains(&code) {
+                    codes.push(code);
+                }
+            }
+        }
+
+        write!(
+            f,
+            "{{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"version\":\"2.1.0\",\
+             \"runs\":[{{\"tool\":{{\"driver\":{{\"name\":\"sqparse\",\"version\":{},\
+             \"informationUri\":{},\"rules\":[",
+            JsonString(env!("CARGO_PKG_VERSION")),
+            JsonString(env!("CARGO_PKG_REPOSITORY"))
+        )?;
+        for (index, code) in codes.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{{\"id\":{}}}", JsonString(code))?;
+        }
+        write!(
+            f,
+            "]}}}},\"columnKind\":\"unicodeCodePoints\",\"results\":["
+        )?;
+
+        for (index, (file, diagnostic)) in self.diagnostics().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            let location = |range| SarifLocation(file, range);
+            write!(f, "{{")?;
+            if let Some(code) = diagnostic.code {
+                let rule_index = codes.iter().position(|&other| other == code).unwrap_or(0);
+                write!(
+                    f,
+                    "\"ruleId\":{},\"ruleIndex\":{rule_index},",
+                    JsonString(code)
+                )?;
+            }
+            let level = match diagnostic.level {
+                Level::Error => "error",
+                Level::Warning => "warning",
+                Level::Note => "note",
+            };
+            write!(
+                f,
+                "\"level\":\"{level}\",\"message\":{{\"text\":{}}},\"locations\":[{}]",
+                JsonString(&diagnostic.message),
+                location(&diagnostic.primary.range)
+            )?;
+
+            if !diagnostic.secondary.is_empty() {
+                write!(f, ",\"relatedLocations\":[")?;
+                for (index, label) in diagnostic.secondary.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(
+                        f,
+                        "{{\"id\":{index},\"message\":{{\"text\":{}}},{}}}",
+                        JsonString(&label.text),
+                        SarifPhysicalLocation(file, &label.range)
+                    )?;
+                }
+                write!(f, "]")?;
+            }
+
+            if !diagnostic.suggestions.is_empty() {
+                write!(f, ",\"fixes\":[")?;
+                for (index, suggestion) in diagnostic.suggestions.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", SarifFix(file, suggestion))?;
+                }
+                write!(f, "]")?;
+            }
+            write!(f, "}}")?;
+        }
+        write!(f, "]}}]}}")
+    }
+
+    fn fmt_checkstyle(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">"
+        )?;
+        for file in self.files {
+            write!(f, "\n<file name=\"{}\">", XmlString(file.file_name))?;
+            for diagnostic in file.diagnostics {
+                let (line, column) = line_column(file.source, diagnostic.primary.range.start);
+                let severity = match diagnostic.level {
+                    Level::Error => "error",
+                    Level::Warning => "warning",
+                    Level::Note => "info",
+                };
+                write!(
+                    f,
+                    "\n<error line=\"{line}\" column=\"{column}\" severity=\"{severity}\" \
+                     message=\"{}\"",
+                    XmlString(&diagnostic.message)
+                )?;
+                if let Some(code) = diagnostic.code {
+                    write!(f, " source=\"{}\"", XmlString(code))?;
+                }
+                write!(f, "/>")?;
+            }
+            write!(f, "\n</file>")?;
+        }
+        write!(f, "\n</checkstyle>")
+    }
+
+    fn fmt_github(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, (file, diagnostic)) in self.diagnostics().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            // GitHub includes the end column in the annotation.
+            let range = &diagnostic.primary.range;
+            let (line, column) = line_column(file.source, range.start);
+            let (end_line, end_column) =
+                line_column(file.source, range.start.max(range.end.saturating_sub(1)));
+            let command = match diagnostic.level {
+                Level::Error => "error",
+                Level::Warning => "warning",
+                Level::Note => "notice",
+            };
+            write!(
+                f,
+                "::{command} file={},line={line},col={column},endLine={end_line},\
+                 endColumn={end_column}",
+                GithubString(file.file_name, true)
+            )?;
+            if let Some(code) = diagnostic.code {
+                write!(f, ",title={}", GithubString(code, true))?;
+            }
+
+            // Notes and help are added as extra lines of the message.
+            let mut message = diagnostic.message.clone();
+            for note in &diagnostic.notes {
+                message.push_str(&format!("\nnote: {note}"));
+            }
+            for help in &diagnostic.help {
+                message.push_str(&format!("\nhelp: {help}"));
+            }
+            write!(f, "::{}", GithubString(&message, false))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DiagnosticsDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.format {
+            OutputFormat::Human => self.fmt_human(f),
+            OutputFormat::Json => self.fmt_json(f),
+            OutputFormat::Sarif => self.fmt_sarif(f),
+            OutputFormat::Checkstyle => self.fmt_checkstyle(f),
+            OutputFormat::Github => self.fmt_github(f),
+        }
+    }
+}
+
+// A string written as a JSON string literal.
+struct JsonString<'a>(&'a str);
+
+impl Display for JsonString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"")?;
+        for char in self.0.chars() {
+            match char {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                _ if char.is_control() => write!(f, "\\u{:04x}", char as u32)?,
+                _ => write!(f, "{char}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+// A list of strings written as a JSON array.
+struct JsonStrings<'a>(&'a [String]);
+
+impl Display for JsonStrings<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[")?;
+        for (index, string) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", JsonString(string))?;
+        }
+        write!(f, "]")
+    }
+}
+
+// A range written as a JSON object with start and end positions.
+struct JsonRange<'a>(&'a str, &'a Range<usize>);
+
+impl Display for JsonRange<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let JsonRange(source, range) = *self;
+        let position = |offset| {
+            let (line, column) = line_column(source, offset);
+            format!("{{\"line\":{line},\"column\":{column},\"offset\":{offset}}}")
+        };
+        write!(
+            f,
+            "{{\"start\":{},\"end\":{}}}",
+            position(range.start),
+            position(range.end)
+        )
+    }
+}
+
+// A SARIF `location` object for a range in a file.
+struct SarifLocation<'a>(&'a FileDiagnostics<'a>, &'a Range<usize>);
+
+impl Display for SarifLocation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{{}}}", SarifPhysicalLocation(self.0, self.1))
+    }
+}
+
+// The `physicalLocation` property of a SARIF `location` object.
+struct SarifPhysicalLocation<'a>(&'a FileDiagnostics<'a>, &'a Range<usize>);
+
+impl Display for SarifPhysicalLocation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let SarifPhysicalLocation(file, range) = *self;
+        write!(
+            f,
+            "\"physicalLocation\":{{\"artifactLocation\":{{\"uri\":{}}},\"region\":{}}}",
+            SarifUri(file.file_name),
+            SarifRegion(file.source, range)
+        )
+    }
+}
+
+// A file name written as a JSON string with a percent-encoded URI reference. Only unreserved
+// characters and `/` are left as they are.
+struct SarifUri<'a>(&'a str);
+
+impl Display for SarifUri<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"")?;
+        for byte in self.0.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                    write!(f, "{}", byte as char)?
+                }
+                _ => write!(f, "%{byte:02X}")?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+// A SARIF `region` object for a range. The end column is exclusive, and columns are counted in
+// code points since the run's `columnKind` is `unicodeCodePoints`.
+struct SarifRegion<'a>(&'a str, &'a Range<usize>);
+
+impl Display for SarifRegion<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let SarifRegion(source, range) = *self;
+        let (start_line, start_column) = code_point_line_column(source, range.start);
+        let (end_line, end_column) = code_point_line_column(source, range.end);
+        write!(
+            f,
+            "{{\"startLine\":{start_line},\"startColumn\":{start_column},\"endLine\":{end_line},\
+             \"endColumn\":{end_column},\"byteOffset\":{},\"byteLength\":{}}}",
+            range.start,
+            range.len()
+        )
+    }
+}
+
+// Returns the 1-based line and column of an offset, with the column counted in code points instead
+// of bytes.
+fn code_point_line_column(source: &str, offset: usize) -> (usize, usize) {
+    let (line, column) = line_column(source, offset);
+    let line_start = offset - (column - 1);
+    (line, source[line_start..offset].chars().count() + 1)
+}
+
+// A SARIF `fix` object for a suggestion.
+struct SarifFix<'a>(&'a FileDiagnostics<'a>, &'a Suggestion);
+
+impl Display for SarifFix<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let SarifFix(file, suggestion) = *self;
+        write!(
+            f,
+            "{{\"description\":{{\"text\":{}}},\"artifactChanges\":[{{\"artifactLocation\":\
+             {{\"uri\":{}}},\"replacements\":[{{\"deletedRegion\":{},\"insertedContent\":\
+             {{\"text\":{}}}}}]}}]}}",
+            JsonString(&suggestion.message),
+            SarifUri(file.file_name),
+            SarifRegion(file.source, &suggestion.range),
+            JsonString(&suggestion.replacement)
+        )
+    }
+}
+
+// A string escaped to be used in an XML attribute. Characters that are not allowed in XML 1.0 are
+// replaced with `U+FFFD`.
+struct XmlString<'a>(&'a str);
+
+impl Display for XmlString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for char in self.0.chars() {
+            match char {
+                '&' => write!(f, "&amp;")?,
+                '<' => write!(f, "&lt;")?,
+                '>' => write!(f, "&gt;")?,
+                '"' => write!(f, "&quot;")?,
+                '\'' => write!(f, "&apos;")?,
+                '\n' => write!(f, "&#10;")?,
+                '\r' => write!(f, "&#13;")?,
+                '\t' => write!(f, "&#9;")?,
+                '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => write!(f, "\u{fffd}")?,
+                _ => write!(f, "{char}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// A string escaped to be used in a GitHub workflow command, either as a property value or as the
+// message.
+struct GithubString<'a>(&'a str, bool);
+
+impl Display for GithubString<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let GithubString(text, is_property) = *self;
+        for char in text.chars() {
+            match char {
+                '%' => write!(f, "%25")?,
+                '\r' => write!(f, "%0D")?,
+                '\n' => write!(f, "%0A")?,
+                ':' if is_property => write!(f, "%3A")?,
+                ',' if is_property => write!(f, "%2C")?,
+                _ => write!(f, "{char}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{display_diagnostics, FileDiagnostics, OutputFormat};
+    use crate::annotation::{Diagnostic, Label, Level, Suggestion};
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic::new(Level::Error, "bad \"value\"", Label::new(14..15, "here"))
+                .with_code("E0107")
+                .with_label(Label::new(4..15, "in this, <expression>"))
+                .with_note("50% sure")
+                .with_suggestion(Suggestion::new("use one", 14..15, "1")),
+            Diagnostic::new(Level::Warning, "unused", Label::new(0..3, "")),
+        ]
+    }
+
+    fn display(format: OutputFormat) -> String {
+        let diagnostics = diagnostics();
+        let files = [FileDiagnostics {
+            file_name: "dir/a,b.nut",
+            source: "foo\nsum = 1 + ?\n",
+            diagnostics: &diagnostics,
+        }];
+        let display = display_diagnostics(format, &files).to_string();
+        display
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            display(OutputFormat::Json),
+            concat!(
+                r#"[{"file":"dir/a,b.nut","severity":"error","code":"E0107","message":"bad \"value\"","#,
+                r#""range":{"start":{"line":2,"column":11,"offset":14},"end":{"line":2,"column":12,"offset":15}},"#,
+                r#""label":"here","secondary":[{"range":{"start":{"line":2,"column":1,"offset":4},"#,
+                r#""end":{"line":2,"column":12,"offset":15}},"label":"in this, <expression>"}],"#,
+                r#""notes":["50% sure"],"help":[],"suggestions":[{"message":"use one","#,
+                r#""range":{"start":{"line":2,"column":11,"offset":14},"end":{"line":2,"column":12,"offset":15}},"#,
+                r#""replacement":"1"}]},"#,
+                r#"{"file":"dir/a,b.nut","severity":"warning","code":null,"message":"unused","#,
+                r#""range":{"start":{"line":1,"column":1,"offset":0},"end":{"line":1,"column":4,"offset":3}},"#,
+                r#""label":"","secondary":[],"notes":[],"help":[],"suggestions":[]}]"#,
+            )
+        );
+    }
+
+    #[test]
+    fn sarif() {
+        let sarif = display(OutputFormat::Sarif);
+        assert!(sarif.starts_with(r#"{"$schema":"https://json.schemastore.org/sarif-2.1.0.json","version":"2.1.0","runs":[{"tool":{"driver":{"name":"sqparse","#));
+        assert!(sarif.contains(r#""rules":[{"id":"E0107"}]}},"columnKind":"unicodeCodePoints","results":[{"ruleId":"E0107","ruleIndex":0,"level":"error","message":{"text":"bad \"value\""},"#));
+        assert!(sarif.contains(r#""locations":[{"physicalLocation":{"artifactLocation":{"uri":"dir/a%2Cb.nut"},"region":{"startLine":2,"startColumn":11,"endLine":2,"endColumn":12,"byteOffset":14,"byteLength":1}}}]"#));
+        assert!(sarif.contains(r#""relatedLocations":[{"id":0,"message":{"text":"in this, <expression>"},"physicalLocation":"#));
+        assert!(sarif.contains(r#""fixes":[{"description":{"text":"use one"},"artifactChanges":[{"artifactLocation":{"uri":"dir/a%2Cb.nut"},"replacements":[{"deletedRegion":{"startLine":2,"startColumn":11,"endLine":2,"endColumn":12,"byteOffset":14,"byteLength":1},"insertedContent":{"text":"1"}}]}]}]"#));
+        assert!(sarif.ends_with(r#"{"level":"warning","message":{"text":"unused"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"dir/a%2Cb.nut"},"region":{"startLine":1,"startColumn":1,"endLine":1,"endColumn":4,"byteOffset":0,"byteLength":3}}}]}]}]}"#));
+    }
+
+    #[test]
+    fn sarif_code_points_and_uris() {
+        let diagnostics = [Diagnostic::new(Level::Error, "bad", Label::new(9..10, ""))];
+        let files = [FileDiagnostics {
+            file_name: "my dir/é#1.nut",
+            source: "a = \"é\" + ?",
+            diagnostics: &diagnostics,
+        }];
+        let sarif = display_diagnostics(OutputFormat::Sarif, &files).to_string();
+        assert!(sarif.contains(r#""artifactLocation":{"uri":"my%20dir/%C3%A9%231.nut"},"region":{"startLine":1,"startColumn":9,"endLine":1,"endColumn":10,"byteOffset":9,"byteLength":1}"#));
+    }
+
+    #[test]
+    fn checkstyle() {
+        assert_eq!(
+            display(OutputFormat::Checkstyle),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+<file name="dir/a,b.nut">
+<error line="2" column="11" severity="error" message="bad &quot;value&quot;" source="E0107"/>
+<error line="1" column="1" severity="warning" message="unused"/>
+</file>
+</checkstyle>"#
+        );
+    }
+
+    #[test]
+    fn checkstyle_invalid_characters() {
+        let diagnostics = [Diagnostic::new(
+            Level::Error,
+            "bad \u{1b}[1mvalue\u{0}\tnow",
+            Label::new(0..1, ""),
+        )];
+        let files = [FileDiagnostics {
+            file_name: "a\u{ffff}.nut",
+            source: "a",
+            diagnostics: &diagnostics,
+        }];
+        let checkstyle = display_diagnostics(OutputFormat::Checkstyle, &files).to_string();
+        assert!(checkstyle.contains("<file name=\"a\u{fffd}.nut\">"));
+        assert!(checkstyle.contains("message=\"bad \u{fffd}[1mvalue\u{fffd}&#9;now\""));
+    }
+
+    #[test]
+    fn github() {
+        assert_eq!(
+            display(OutputFormat::Github),
+            "::error file=dir/a%2Cb.nut,line=2,col=11,endLine=2,endColumn=11,title=E0107::bad \"value\"%0Anote: 50%25 sure
+::warning file=dir/a%2Cb.nut,line=1,col=1,endLine=1,endColumn=3::unused"
+        );
+    }
+
+    #[test]
+    fn format_names() {
+        for format in [
+            OutputFormat::Human,
+            OutputFormat::Json,
+            OutputFormat::Sarif,
+            OutputFormat::Checkstyle,
+            OutputFormat::Github,
+        ] {
+            assert_eq!(OutputFormat::from_name(format.name()), Some(format));
+        }
+        assert_eq!(OutputFormat::from_name("xml"), None);
+    }
+}
@@ -0,0 +1,607 @@
+use crate::annotation::gutter::Gutter;
+use crate::annotation::repeat::repeat;
+use crate::annotation::{get_line_containing, Annotation, Mode};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+// Lines longer than this are cut down to this many characters around their highlights.
+const MAX_LINE_LENGTH: usize = 120;
+
+// Annotations with at most this many lines between them are printed in the same snippet.
+const MAX_HIDDEN_LINES: usize = 3;
+
+// The lines and columns that an annotation highlights.
+//
+// Lines are numbered from 1, and columns are character offsets from the start of the line.
+pub struct Span {
+    pub first_line: usize,
+    pub first_column: usize,
+    pub last_line: usize,
+
+    // For a single-line span this is the end of the highlight. For a multi-line span it is the
+    // column of the last highlighted character, where the span is closed.
+    pub last_column: usize,
+
+    // Lines that must be printed for the span. Lines in the middle of a multi-line span are folded
+    // unless they are in the annotation's `visible` range.
+    required_lines: Vec<usize>,
+}
+
+impl Span {
+    pub fn new(source: &str, annotation: &Annotation) -> Self {
+        let highlight = &annotation.highlight;
+        let (first_line, first_start) = get_line_containing(highlight.start, source);
+        let (last_line, last_start) =
+            get_line_containing(highlight.start.max(highlight.end.saturating_sub(1)), source);
+
+        if last_line == first_line {
+            let line_end = source[first_start..]
+                .find('\n')
+                .map_or(source.len(), |index| first_start + index);
+            return Span {
+                first_line,
+                first_column: column(source, first_start, highlight.start),
+                last_line,
+                last_column: column(
+                    source,
+                    first_start,
+                    highlight.end.min(line_end).max(highlight.start),
+                ),
+                required_lines: vec![first_line],
+            };
+        }
+
+        // The first two and last two lines are always printed, along with any visible lines.
+        let visible = &annotation.visible;
+        let (first_visible, _) = get_line_containing(visible.start, source);
+        let (last_visible, _) =
+            get_line_containing(visible.start.max(visible.end.saturating_sub(1)), source);
+        let visible_lines =
+            first_visible.max(first_line)..=last_visible.min(last_line).max(first_line);
+        let required_lines = [first_line, first_line + 1, last_line - 1, last_line]
+            .into_iter()
+            .chain(visible_lines)
+            .collect();
+
+        Span {
+            first_line,
+            first_column: column(source, first_start, highlight.start),
+            last_line,
+            last_column: column(source, last_start, highlight.end).saturating_sub(1),
+            required_lines,
+        }
+    }
+
+    pub fn is_multi_line(&self) -> bool {
+        self.last_line > self.first_line
+    }
+}
+
+// Returns the number of characters between the start of a line and a byte offset in the source.
+fn column(source: &str, line_start: usize, index: usize) -> usize {
+    (source[line_start..].char_indices())
+        .take_while(|&(offset, _)| line_start + offset < index)
+        .count()
+}
+
+// Groups spans that are on the same or nearby lines, returning the indices of the spans in each
+// group.
+//
+// Groups are ordered by the first span in each group, so the first span is always in the first
+// group.
+pub fn group_spans(spans: &[Span]) -> Vec<Vec<usize>> {
+    let mut order: Vec<_> = (0..spans.len()).collect();
+    order.sort_by_key(|&index| spans[index].first_line);
+
+    let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+    for index in order {
+        let span = &spans[index];
+        match groups.last_mut() {
+            Some((last_line, group)) if span.first_line <= *last_line + MAX_HIDDEN_LINES + 1 => {
+                *last_line = (*last_line).max(span.last_line);
+                group.push(index);
+            }
+            _ => groups.push((span.last_line, vec![index])),
+        }
+    }
+
+    let mut groups: Vec<_> = (groups.into_iter())
+        .map(|(_, mut group)| {
+            group.sort_unstable();
+            group
+        })
+        .collect();
+    groups.sort_by_key(|group| group[0]);
+    groups
+}
+
+// Prints a group of annotations as one snippet of source code.
+//
+// Single-line annotations are underlined below their line. If there are several on a line, the
+// label of the last one is printed beside the underlines and the others are stacked below:
+// ```text
+// 1 | local sum = first + second
+//   |       ---   ^^^^^ - value
+//   |       |     |
+//   |       |     error
+//   |       name
+// ```
+//
+// Multi-line annotations are drawn with a bar in the gutter from their start to their end, and
+// overlapping ones are given separate bars:
+// ```text
+// 1 |     x = function() {
+//   |  __________________^
+// 2 | |       return {
+//   | |  ____________-
+// 3 | | |         a = 1
+// 4 | | |     }
+//   | | |_____- table
+// 5 | |   }
+//   | |___^ function
+// ```
+pub struct SnippetDisplay<'a> {
+    pub gutter: Gutter,
+    pub lines: &'a [&'a str],
+    pub annotations: &'a [Annotation],
+    pub spans: &'a [Span],
+    pub group: &'a [usize],
+}
+
+impl Display for SnippetDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let slots = self.assign_slots();
+        let slot_count = slots
+            .iter()
+            .flatten()
+            .map(|slot| slot + 1)
+            .max()
+            .unwrap_or(0);
+        let text_position = |column: usize| 2 * slot_count + 1 + column;
+
+        // Lines between shown lines are folded, unless there is only one.
+        let mut shown_lines: BTreeSet<usize> = (self.group.iter())
+            .flat_map(|&index| self.spans[index].required_lines.iter().copied())
+            .collect();
+        let gaps: Vec<_> = (shown_lines.iter().zip(shown_lines.iter().skip(1)))
+            .filter(|(&line, &next_line)| next_line == line + 2)
+            .map(|(&line, _)| line + 1)
+            .collect();
+        shown_lines.extend(gaps);
+
+        write!(f, "{}", self.gutter.empty())?;
+        let mut previous_line = None;
+        for &line_number in &shown_lines {
+            if previous_line.is_some_and(|previous| line_number > previous + 1) {
+                let bars = Bars(self.active_slots(&slots, slot_count, line_number - 1));
+                write!(f, "\n{}{bars}", self.gutter.ellipsis())?;
+            }
+            previous_line = Some(line_number);
+
+            let line = self.lines.get(line_number - 1).copied().unwrap_or("");
+            let window = self.window(line, line_number);
+            let mut active = self.active_slots(&slots, slot_count, line_number);
+            write!(
+                f,
+                "\n{}{} {}",
+                self.gutter.number(line_number),
+                Bars(active.clone()),
+                window.text(line).replace('\t', " "),
+            )?;
+
+            // Labels of single-line annotations, sorted by where they start.
+            let mut labels: Vec<_> = (self.group.iter())
+                .filter(|&&index| {
+                    let span = &self.spans[index];
+                    !span.is_multi_line() && span.first_line == line_number
+                })
+                .map(|&index| {
+                    let span = &self.spans[index];
+                    let start = window.column(span.first_column);
+                    let end = window.column(span.last_column).max(start + 1);
+                    (start, end, &self.annotations[index])
+                })
+                .collect();
+            labels.sort_by_key(|&(start, end, _)| (start, end));
+
+            if let Some(&(_, last_end, last)) = labels.last() {
+                let mut row = Row::new(&active);
+                let max_end = labels.iter().map(|&(_, end, _)| end).max().unwrap_or(0);
+                for &(start, end, annotation) in &labels {
+                    let underline = repeat(end - start, annotation.mode.underline()).to_string();
+                    row.put(text_position(start), &underline, Some(annotation.mode));
+                }
+
+                // The last label is printed beside the underlines if nothing is underlined after
+                // it, and the others are stacked below.
+                let mut stacked = labels.as_slice();
+                if last_end == max_end {
+                    row.put(text_position(last_end) + 1, &last.text, Some(last.mode));
+                    stacked = &labels[..labels.len() - 1];
+                }
+                let stacked: Vec<_> = (stacked.iter())
+                    .filter(|(_, _, annotation)| !annotation.text.is_empty())
+                    .collect();
+                write!(f, "\n{}{row}", self.gutter.empty())?;
+
+                if !stacked.is_empty() {
+                    let mut row = Row::new(&active);
+                    for &&(start, _, annotation) in &stacked {
+                        row.put(text_position(start), "|", Some(annotation.mode));
+                    }
+                    write!(f, "\n{}{row}", self.gutter.empty())?;
+                }
+                for (stack_index, &&(start, _, annotation)) in stacked.iter().enumerate().rev() {
+                    let mut row = Row::new(&active);
+                    for &&(start, _, annotation) in &stacked[..stack_index] {
+                        row.put(text_position(start), "|", Some(annotation.mode));
+                    }
+                    row.put(
+                        text_position(start),
+                        &annotation.text,
+                        Some(annotation.mode),
+                    );
+                    write!(f, "\n{}{row}", self.gutter.empty())?;
+                }
+            }
+
+            // Close multi-line spans that end on this line, innermost first.
+            let mut closing: Vec<_> = (self.group.iter().zip(&slots))
+                .filter_map(|(&index, &slot)| Some((slot?, index)))
+                .filter(|&(_, index)| self.spans[index].last_line == line_number)
+                .collect();
+            closing.sort_unstable_by(|a, b| b.cmp(a));
+            for (slot, index) in closing {
+                let span = &self.spans[index];
+                let annotation = &self.annotations[index];
+                let mode = Some(annotation.mode);
+                let column = text_position(window.column(span.last_column));
+
+                let mut row = Row::new(&active);
+                row.put(
+                    2 * slot + 2,
+                    &repeat(column - 2 * slot - 2, '_').to_string(),
+                    mode,
+                );
+                row.put(column, &annotation.mode.underline().to_string(), mode);
+                row.put(column + 2, &annotation.text, mode);
+                write!(f, "\n{}{row}", self.gutter.empty())?;
+                active[slot] = None;
+            }
+
+            // Open multi-line spans that start on this line, outermost first.
+            let mut opening: Vec<_> = (self.group.iter().zip(&slots))
+                .filter_map(|(&index, &slot)| Some((slot?, index)))
+                .filter(|&(_, index)| self.spans[index].first_line == line_number)
+                .collect();
+            opening.sort_unstable();
+            for (slot, index) in opening {
+                let span = &self.spans[index];
+                let annotation = &self.annotations[index];
+                let mode = Some(annotation.mode);
+                let column = text_position(window.column(span.first_column));
+
+                let mut row = Row::new(&active);
+                row.put(
+                    2 * slot + 2,
+                    &repeat(column - 2 * slot - 2, '_').to_string(),
+                    mode,
+                );
+                row.put(column, &annotation.mode.underline().to_string(), mode);
+                write!(f, "\n{}{row}", self.gutter.empty())?;
+                active[slot] = Some(annotation.mode);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SnippetDisplay<'_> {
+    // Assigns a gutter bar to each multi-line span, reusing bars from spans that have ended.
+    //
+    // Spans that start earlier get bars further left, so a span's bar is outside the bars of the
+    // spans nested in it.
+    fn assign_slots(&self) -> Vec<Option<usize>> {
+        let mut order: Vec<_> = (0..self.group.len())
+            .filter(|&member| self.spans[self.group[member]].is_multi_line())
+            .collect();
+        order.sort_by_key(|&member| self.spans[self.group[member]].first_line);
+
+        let mut slots = vec![None; self.group.len()];
+        let mut slot_last_lines: Vec<usize> = Vec::new();
+        for member in order {
+            let span = &self.spans[self.group[member]];
+            let free_slot = (slot_last_lines.iter()).position(|&last| last < span.first_line);
+            let slot = match free_slot {
+                Some(slot) => {
+                    slot_last_lines[slot] = span.last_line;
+                    slot
+                }
+                None => {
+                    slot_last_lines.push(span.last_line);
+                    slot_last_lines.len() - 1
+                }
+            };
+            slots[member] = Some(slot);
+        }
+        slots
+    }
+
+    // Returns the mode of each bar drawn beside a line, for spans that started on an earlier line.
+    fn active_slots(
+        &self,
+        slots: &[Option<usize>],
+        slot_count: usize,
+        line_number: usize,
+    ) -> Vec<Option<Mode>> {
+        let mut active = vec![None; slot_count];
+        for (&index, &slot) in self.group.iter().zip(slots) {
+            let span = &self.spans[index];
+            if let Some(slot) = slot {
+                if span.first_line < line_number && line_number <= span.last_line {
+                    active[slot] = Some(self.annotations[index].mode);
+                }
+            }
+        }
+        active
+    }
+
+    // Returns the part of a line to print, centered around its single-line highlights if the line
+    // is too long.
+    fn window(&self, line: &str, line_number: usize) -> Window {
+        let highlights = (self.group.iter())
+            .map(|&index| &self.spans[index])
+            .filter(|span| !span.is_multi_line() && span.first_line == line_number);
+        let start = highlights.clone().map(|span| span.first_column).min();
+        let end = highlights.map(|span| span.last_column).max();
+        let (Some(start), Some(end)) = (start, end) else {
+            return Window::full();
+        };
+        let line_length = line.chars().count();
+        if line_length <= MAX_LINE_LENGTH {
+            return Window::full();
+        }
+
+        let center = (start + end) / 2;
+        let start = center
+            .saturating_sub(MAX_LINE_LENGTH / 2)
+            .min(line_length - MAX_LINE_LENGTH);
+        let end = start + MAX_LINE_LENGTH;
+        Window {
+            start,
+            end,
+            is_start_elided: start > 4,
+            is_end_elided: end < line_length - 4,
+        }
+    }
+}
+
+// The part of a line that is printed, as character columns.
+struct Window {
+    start: usize,
+    end: usize,
+    is_start_elided: bool,
+    is_end_elided: bool,
+}
+
+impl Window {
+    fn full() -> Self {
+        Window {
+            start: 0,
+            end: usize::MAX,
+            is_start_elided: false,
+            is_end_elided: false,
+        }
+    }
+
+    // Returns the text to print, with `...` replacing the ends of the line that are cut off.
+    fn text(&self, line: &str) -> String {
+        let start = if self.is_start_elided {
+            self.start + 4
+        } else {
+            self.start
+        };
+        let end = if self.is_end_elided {
+            self.end - 4
+        } else {
+            self.end
+        };
+        let byte_offset = |column: usize| {
+            (line.char_indices())
+                .nth(column)
+                .map_or(line.len(), |(offset, _)| offset)
+        };
+        format!(
+            "{}{}{}",
+            if self.is_start_elided { "... " } else { "" },
+            &line[byte_offset(start)..byte_offset(end)],
+            if self.is_end_elided { " ..." } else { "" },
+        )
+    }
+
+    // Returns where a column of the line is printed.
+    fn column(&self, column: usize) -> usize {
+        column.clamp(self.start, self.end) - self.start
+    }
+}
+
+// The gutter bars beside a line of source code.
+struct Bars(Vec<Option<Mode>>);
+
+impl Display for Bars {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for mode in &self.0 {
+            match mode {
+                Some(mode) => write!(f, "{}", mode.display(" |"))?,
+                None => write!(f, "  ")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// A row of underlines and labels printed below a line of source code, starting with the gutter
+// bars.
+struct Row {
+    cells: Vec<(char, Option<Mode>)>,
+}
+
+impl Row {
+    fn new(active: &[Option<Mode>]) -> Self {
+        let mut row = Row { cells: Vec::new() };
+        for (slot, mode) in active.iter().enumerate() {
+            if mode.is_some() {
+                row.put(2 * slot + 1, "|", *mode);
+            }
+        }
+        row
+    }
+
+    fn put(&mut self, position: usize, text: &str, mode: Option<Mode>) {
+        for (offset, char) in text.chars().enumerate() {
+            let position = position + offset;
+            if self.cells.len() <= position {
+                self.cells.resize(position + 1, (' ', None));
+            }
+            self.cells[position] = (char, mode);
+        }
+    }
+}
+
+impl Display for Row {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let len = (self.cells.iter())
+            .rposition(|&(char, _)| char != ' ')
+            .map_or(0, |index| index + 1);
+        let mut cells = self.cells[..len].iter().peekable();
+        while let Some(&(char, mode)) = cells.next() {
+            let mut text = char.to_string();
+            while let Some(&(char, _)) = cells.next_if(|(_, next_mode)| *next_mode == mode) {
+                text.push(char);
+            }
+            match mode {
+                Some(mode) => write!(f, "{}", mode.display(text))?,
+                None => write!(f, "{text}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::annotation::{display_annotations, Annotation, Mode};
+
+    // Creates an annotation from the start of `start` to the end of the last `end` in the source.
+    fn annotation(source: &str, mode: Mode, text: &str, start: &str, end: &str) -> Annotation {
+        let start = source.find(start).unwrap();
+        let end = source.rfind(end).unwrap() + end.len();
+        Annotation {
+            mode,
+            text: text.to_string(),
+            note: "".to_string(),
+            highlight: start..end,
+            visible: start..end,
+        }
+    }
+
+    #[test]
+    fn nested_multi_line() {
+        yansi::Paint::disable();
+        let source = "x = function() {\n    return {\n        a = 1\n    }\n}";
+        let annotations = [
+            annotation(source, Mode::Error, "function", "{", "}"),
+            annotation(source, Mode::Info, "table", "{\n        a", "    }"),
+        ];
+        assert_eq!(
+            display_annotations(None, source, &annotations).to_string(),
+            " --> 1:16
+  |
+1 |     x = function() {
+  |  __________________^
+2 | |       return {
+  | |  ____________-
+3 | | |         a = 1
+4 | | |     }
+  | | |_____- table
+5 | |   }
+  | |___^ function"
+        );
+    }
+
+    #[test]
+    fn single_and_multi_line() {
+        yansi::Paint::disable();
+        let source = "if (x) {\n    y()\n} else { z() }";
+        let annotations = [
+            annotation(source, Mode::Warning, "block", "{", "} else"),
+            annotation(source, Mode::Info, "else", "else", "else"),
+            annotation(source, Mode::Info, "", "if", "if"),
+        ];
+        assert_eq!(
+            display_annotations(None, source, &annotations).to_string(),
+            " --> 1:8
+  |
+1 |   if (x) {
+  |   --
+  |  ________^
+2 | |     y()
+3 | | } else { z() }
+  | |   ---- else
+  | |______^ block"
+        );
+    }
+
+    #[test]
+    fn separate_snippets() {
+        yansi::Paint::disable();
+        let source = "local a = 1\n\n\n\n\n\n\nlocal a = 2\nprint(a)";
+        let mut first = annotation(source, Mode::Info, "first", "a = 1", "a = 1");
+        first.note = "a note".to_string();
+        let annotations = [
+            annotation(source, Mode::Error, "second", "a = 2", "a = 2"),
+            first,
+            annotation(source, Mode::Info, "use", "print", "print"),
+        ];
+        assert_eq!(
+            display_annotations(Some("f.nut"), source, &annotations).to_string(),
+            " --> f.nut:8:7
+  |
+8 | local a = 2
+  |       ^^^^^ second
+9 | print(a)
+  | ----- use
+  |
+ ::: f.nut:1:7
+  |
+1 | local a = 1
+  |       ----- first
+  |
+  = a note"
+        );
+    }
+
+    #[test]
+    fn long_non_ascii_line() {
+        yansi::Paint::disable();
+        let source = format!(
+            "local s = \"{}\" + x + \"{}\"",
+            "é".repeat(100),
+            "ü".repeat(100)
+        );
+        let annotations = [annotation(&source, Mode::Error, "here", "x", "x")];
+        assert_eq!(
+            display_annotations(None, &source, &annotations).to_string(),
+            format!(
+                " --> 1:116
+  |
+1 | ... {}\" + x + \"{} ...
+  | {}^ here",
+                "é".repeat(52),
+                "ü".repeat(51),
+                " ".repeat(60),
+            )
+        );
+    }
+}
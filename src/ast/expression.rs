@@ -3,6 +3,7 @@ use crate::ast::{
     Identifier, MethodIdentifier, PostfixOperator, PrefixOperator, SeparatedList1, TableSlot, Type,
 };
 use crate::token::{LiteralToken, Token};
+use crate::ParseError;
 
 /// An expression.
 ///
@@ -32,6 +33,9 @@ pub enum Expression<'s> {
     Delegate(DelegateExpression<'s>),
     Vector(VectorExpression<'s>),
     Expect(ExpectExpression<'s>),
+
+    // Error recovery
+    Error(ErrorExpression),
 }
 
 /// An expression enclosed in parentheses.
@@ -240,3 +244,16 @@ pub struct CallExpression<'s> {
 pub struct CommaExpression<'s> {
     pub values: SeparatedList1<'s, Expression<'s>>,
 }
+
+/// An expression that is missing.
+///
+/// These are only emitted by [`parse_recover`], in places where an expression is required but
+/// there is no token that can start one. The expression does not contain any tokens.
+///
+/// Grammar: &lt;empty>
+///
+/// [`parse_recover`]: crate::parse_recover
+#[derive(Debug, Clone)]
+pub struct ErrorExpression {
+    pub error: ParseError,
+}
@@ -52,6 +52,19 @@ pub enum FunctionParams<'s> {
     },
 }
 
+impl<'s> FunctionParams<'s> {
+    /// Returns an iterator over the named parameters, excluding a vararg.
+    pub fn iter(&self) -> impl Iterator<Item = &FunctionParam<'s>> {
+        let (params, variable_params) = match self {
+            FunctionParams::NonVariable { params } => (params.as_ref(), None),
+            FunctionParams::EmptyVariable { .. } => (None, None),
+            FunctionParams::NonEmptyVariable { params, .. } => (None, Some(params)),
+        };
+        (params.into_iter().flat_map(|params| params.iter()))
+            .chain(variable_params.into_iter().flat_map(|params| params.iter()))
+    }
+}
+
 /// Parameter definition in a [`FunctionParams`] list.
 ///
 /// Grammar: [Type]? [Identifier] [VarInitializer]?
@@ -83,6 +83,11 @@ impl<'s, T> SeparatedList1<'s, T> {
         ));
     }
 
+    /// Returns an iterator over the items in the list, without separators.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (self.items.iter().map(|(item, _)| item)).chain(std::iter::once(&*self.last_item))
+    }
+
     /// Adds a trailing separator, converting the list to a [`SeparatedListTrailing1`].
     pub fn into_trailing(self, trailing: Option<&'s Token<'s>>) -> SeparatedListTrailing1<'s, T> {
         SeparatedListTrailing1 {
@@ -92,3 +97,10 @@ impl<'s, T> SeparatedList1<'s, T> {
         }
     }
 }
+
+impl<T> SeparatedListTrailing1<'_, T> {
+    /// Returns an iterator over the items in the list, without separators.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (self.items.iter().map(|(item, _)| item)).chain(std::iter::once(&*self.last_item))
+    }
+}
@@ -18,11 +18,14 @@ mod list;
 mod operator;
 mod precedence;
 mod slot;
+mod span;
 mod statement;
 mod struct_;
 mod table;
 mod type_;
 mod variable;
+mod visit;
+mod visit_mut;
 
 pub use self::array::*;
 pub use self::class::*;
@@ -36,11 +39,14 @@ pub use self::list::*;
 pub use self::operator::*;
 pub use self::precedence::*;
 pub use self::slot::*;
+pub use self::span::*;
 pub use self::statement::*;
 pub use self::struct_::*;
 pub use self::table::*;
 pub use self::type_::*;
 pub use self::variable::*;
+pub use self::visit::*;
+pub use self::visit_mut::*;
 
 /// Contains statements that form a program.
 ///
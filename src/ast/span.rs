@@ -0,0 +1,663 @@
+use crate::ast::*;
+use crate::token::Token;
+use std::ops::Range;
+
+/// Implemented by every syntax tree node, to find where the node is in the source code.
+///
+/// A node's span covers its first token to its last token. Some nodes can be empty, like a
+/// [`Program`] with no statements or a [`FunctionParams`] with no parameters, so these return
+/// [`None`] if the node has no tokens.
+///
+/// Use [`line_column`] to convert an offset in the span to a line and column.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::annotation::line_column;
+/// use sqparse::ast::{Spanned, StatementType};
+///
+/// let source = "global function Add\n\nint function Add( int a, int b ) {\n    return a + b\n}";
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// let StatementType::FunctionDefinition(function) = &program.statements[1].ty else { unreachable!() };
+/// let span = function.definition.params.span().unwrap();
+/// assert_eq!(&source[span.clone()], "int a, int b");
+/// assert_eq!(line_column(source, span.start), (3, 19));
+/// ```
+///
+/// [`line_column`]: crate::annotation::line_column
+pub trait Spanned<'s> {
+    /// Returns the first token in the node.
+    fn first_token(&self) -> Option<&'s Token<'s>>;
+
+    /// Returns the last token in the node.
+    fn last_token(&self) -> Option<&'s Token<'s>>;
+
+    /// Returns the character range of the node in the source code, from the start of the first
+    /// token to the end of the last token.
+    ///
+    /// This does not include comments attached to the tokens.
+    fn span(&self) -> Option<Range<usize>> {
+        let first = self.first_token()?;
+        let last = self.last_token()?;
+        Some(first.range.start..last.range.end)
+    }
+}
+
+// Returns the first token of the first non-empty node.
+macro_rules! first_token {
+    ($($node:expr),+) => {
+        None$(.or_else(|| $node.first_token()))+
+    };
+}
+
+// Returns the last token of the last non-empty node.
+macro_rules! last_token {
+    ($node:expr $(, $rest:expr)*) => {
+        last_token!($($rest),*).or_else(|| $node.last_token())
+    };
+    () => {
+        None
+    };
+}
+
+// Implements `Spanned` for structs, with fields listed in source order.
+macro_rules! spanned_structs {
+    ($($ty:ident { $($field:ident),+ })+) => {
+        $(
+            impl<'s> Spanned<'s> for $ty<'s> {
+                fn first_token(&self) -> Option<&'s Token<'s>> {
+                    first_token!($(self.$field),+)
+                }
+
+                fn last_token(&self) -> Option<&'s Token<'s>> {
+                    last_token!($(self.$field),+)
+                }
+            }
+        )+
+    };
+}
+
+// Implements `Spanned` for enums where every variant has a single value.
+macro_rules! spanned_enums {
+    ($($ty:ident { $($variant:ident),+ })+) => {
+        $(
+            impl<'s> Spanned<'s> for $ty<'s> {
+                fn first_token(&self) -> Option<&'s Token<'s>> {
+                    match self {
+                        $($ty::$variant(node) => node.first_token(),)+
+                    }
+                }
+
+                fn last_token(&self) -> Option<&'s Token<'s>> {
+                    match self {
+                        $($ty::$variant(node) => node.last_token(),)+
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl<'s> Spanned<'s> for &'s Token<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        Some(self)
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        Some(self)
+    }
+}
+
+impl<'s, T: Spanned<'s>> Spanned<'s> for Box<T> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        (**self).first_token()
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        (**self).last_token()
+    }
+}
+
+impl<'s, T: Spanned<'s>> Spanned<'s> for Option<T> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        self.as_ref().and_then(T::first_token)
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        self.as_ref().and_then(T::last_token)
+    }
+}
+
+impl<'s, T: Spanned<'s>> Spanned<'s> for Vec<T> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        self.iter().find_map(T::first_token)
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        self.iter().rev().find_map(T::last_token)
+    }
+}
+
+impl<'s, A: Spanned<'s>, B: Spanned<'s>> Spanned<'s> for (A, B) {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        first_token!(self.0, self.1)
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        last_token!(self.0, self.1)
+    }
+}
+
+impl<'s, T: Spanned<'s>> Spanned<'s> for SeparatedList1<'s, T> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        first_token!(self.items, self.last_item)
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        last_token!(self.items, self.last_item)
+    }
+}
+
+impl<'s, T: Spanned<'s>> Spanned<'s> for SeparatedListTrailing1<'s, T> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        first_token!(self.items, self.last_item, self.trailing)
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        last_token!(self.items, self.last_item, self.trailing)
+    }
+}
+
+spanned_structs! {
+    Program { statements }
+    Statement { ty, semicolon }
+    EmptyStatement { empty }
+    BlockStatement { open, statements, close }
+    IfStatement { if_, open, condition, close, ty }
+    WhileStatement { while_, open, condition, close, body }
+    DoWhileStatement { do_, body, while_, open, condition, close }
+    SwitchStatement { switch, open_condition, condition, close_condition, open_cases, cases, close_cases }
+    ForStatement { for_, open, initializer, semicolon_1, condition, semicolon_2, increment, close, body }
+    ForeachStatement { foreach, open, index, value_type, value_name, in_, array, close, body }
+    BreakStatement { break_ }
+    ContinueStatement { continue_ }
+    ReturnStatement { return_, value }
+    YieldStatement { yield_, value }
+    VarDefinitionStatement { type_, definitions }
+    ConstructorDefinitionStatement { function, namespaces, last_name, last_namespace, constructor, definition }
+    FunctionDefinitionStatement { return_type, function, name, definition }
+    ClassDefinitionStatement { class, name, definition }
+    TryCatchStatement { try_, body, catch, open, catch_name, close, catch_body }
+    ThrowStatement { throw, value }
+    ConstDefinitionStatement { const_, const_type, name, initializer }
+    EnumDefinitionStatement { enum_, name, open, entries, close }
+    ExpressionStatement { value }
+    ThreadStatement { thread, value }
+    DelayThreadStatement { delay_thread, open, duration, close, value }
+    WaitThreadStatement { wait_thread, value }
+    WaitThreadSoloStatement { wait_thread_solo, value }
+    WaitStatement { wait, value }
+    StructDefinitionStatement { struct_, name, definition }
+    TypeDefinitionStatement { typedef, name, type_ }
+    GlobalStatement { global, definition }
+    GlobalizeAllFunctionsStatement { globalize_all_functions }
+    UntypedStatement { untyped }
+    SwitchCase { condition, colon, body }
+    ForeachIndex { type_, name, comma }
+    EnumEntry { name, initializer, comma }
+    VarDefinition { name, initializer }
+    VarInitializer { assign, value }
+    ClassDefinition { extends, open, members, close }
+    ClassExtends { extends, name }
+    ClassMember { attributes, static_, slot, semicolon }
+    StructDefinition { open, properties, close }
+    StructProperty { type_, name, initializer, comma }
+    FunctionDefinition { environment, open, params, close, captures, body }
+    FunctionEnvironment { open, value, close }
+    FunctionParam { type_, name, initializer }
+    FunctionCaptures { colon, open, names, close }
+    FunctionRefParam { type_, name, initializer }
+    CallArgument { value, comma }
+    ArrayValue { value, separator }
+    TableSlot { ty, comma }
+    Identifier { token }
+    ParensExpression { open, value, close }
+    LiteralExpression { token }
+    VarExpression { name }
+    RootVarExpression { root, name }
+    IndexExpression { base, open, index, close }
+    PropertyExpression { base, dot, property }
+    TernaryExpression { condition, question, true_value, separator, false_value }
+    BinaryExpression { left, operator, right }
+    PrefixExpression { operator, value }
+    PostfixExpression { value, operator }
+    CommaExpression { values }
+    TableExpression { open, slots, spread, close }
+    ClassExpression { class, definition }
+    ArrayExpression { open, values, spread, close }
+    FunctionExpression { return_type, function, definition }
+    LambdaExpression { at, open, params, close, value }
+    CallExpression { function, open, arguments, close, post_initializer }
+    DelegateExpression { delegate, parent, colon, value }
+    VectorExpression { open, x, comma_1, y, comma_2, z, close }
+    ExpectExpression { expect, ty, open, value, close }
+    LocalType { local }
+    VarType { var }
+    PlainType { name }
+    ArrayType { base, open, len, close }
+    GenericType { base, open, params, close }
+    FunctionRefType { return_type, functionref, open, params, close }
+    StructType { struct_, definition }
+    ReferenceType { base, reference }
+    NullableType { base, ornull }
+    ConditionalStatement { if_, else_ifs, else_ }
+    ConditionalBranch { statements }
+    ErrorStatement { tokens }
+}
+
+spanned_enums! {
+    StatementType {
+        Empty, Block, If, While, DoWhile, Switch, For, Foreach, Break, Continue, Return, Yield,
+        VarDefinition, ConstructorDefinition, FunctionDefinition, ClassDefinition, TryCatch,
+        Throw, Const, EnumDefinition, Expression, Thread, DelayThread, WaitThread,
+        WaitThreadSolo, Wait, StructDefinition, TypeDefinition, Global, GlobalizeAllFunctions,
+        Untyped, Conditional, Error
+    }
+    Expression {
+        Parens, Literal, Var, RootVar, Index, Property, Ternary, Binary, Prefix, Postfix, Comma,
+        Table, Class, Array, Function, Lambda, Call, Delegate, Vector, Expect, Error
+    }
+    Type { Local, Var, Plain, Array, Generic, FunctionRef, Struct, Reference, Nullable }
+    ForDefinition { Expression, Definition }
+    MethodIdentifier { Identifier, Constructor }
+}
+
+impl<'s> Spanned<'s> for ErrorExpression {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        None
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        None
+    }
+}
+
+impl<'s> Spanned<'s> for IfStatementType<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            IfStatementType::NoElse { body } => body.first_token(),
+            IfStatementType::Else {
+                body,
+                else_,
+                else_body,
+            } => first_token!(body, else_, else_body),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            IfStatementType::NoElse { body } => body.last_token(),
+            IfStatementType::Else {
+                body,
+                else_,
+                else_body,
+            } => last_token!(body, else_, else_body),
+        }
+    }
+}
+
+impl<'s> Spanned<'s> for SwitchCaseCondition<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            SwitchCaseCondition::Default { default } => Some(default),
+            SwitchCaseCondition::Case { case, value } => first_token!(case, value),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            SwitchCaseCondition::Default { default } => Some(default),
+            SwitchCaseCondition::Case { case, value } => last_token!(case, value),
+        }
+    }
+}
+
+impl<'s> Spanned<'s> for GlobalDefinition<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            GlobalDefinition::Function { function, name } => first_token!(function, name),
+            GlobalDefinition::UntypedVar { name, initializer } => first_token!(name, initializer),
+            GlobalDefinition::TypedVar(definition) => definition.first_token(),
+            GlobalDefinition::Const(definition) => definition.first_token(),
+            GlobalDefinition::Enum(definition) => definition.first_token(),
+            GlobalDefinition::Class(definition) => definition.first_token(),
+            GlobalDefinition::Struct(definition) => definition.first_token(),
+            GlobalDefinition::Type(definition) => definition.first_token(),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            GlobalDefinition::Function { function, name } => last_token!(function, name),
+            GlobalDefinition::UntypedVar { name, initializer } => last_token!(name, initializer),
+            GlobalDefinition::TypedVar(definition) => definition.last_token(),
+            GlobalDefinition::Const(definition) => definition.last_token(),
+            GlobalDefinition::Enum(definition) => definition.last_token(),
+            GlobalDefinition::Class(definition) => definition.last_token(),
+            GlobalDefinition::Struct(definition) => definition.last_token(),
+            GlobalDefinition::Type(definition) => definition.last_token(),
+        }
+    }
+}
+
+impl<'s> Spanned<'s> for FunctionParams<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            FunctionParams::NonVariable { params } => params.first_token(),
+            FunctionParams::EmptyVariable { vararg } => Some(vararg),
+            FunctionParams::NonEmptyVariable {
+                params,
+                comma,
+                vararg,
+            } => first_token!(params, comma, vararg),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            FunctionParams::NonVariable { params } => params.last_token(),
+            FunctionParams::EmptyVariable { vararg } => Some(vararg),
+            FunctionParams::NonEmptyVariable {
+                params,
+                comma,
+                vararg,
+            } => last_token!(params, comma, vararg),
+        }
+    }
+}
+
+impl<'s> Spanned<'s> for Slot<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            Slot::Property { name, initializer } => first_token!(name, initializer),
+            Slot::ComputedProperty {
+                open,
+                name,
+                close,
+                initializer,
+            } => first_token!(open, name, close, initializer),
+            Slot::Constructor {
+                function,
+                constructor,
+                definition,
+            } => first_token!(function, constructor, definition),
+            Slot::Function {
+                return_type,
+                function,
+                name,
+                definition,
+            } => first_token!(return_type, function, name, definition),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            Slot::Property { name, initializer } => last_token!(name, initializer),
+            Slot::ComputedProperty {
+                open,
+                name,
+                close,
+                initializer,
+            } => last_token!(open, name, close, initializer),
+            Slot::Constructor {
+                function,
+                constructor,
+                definition,
+            } => last_token!(function, constructor, definition),
+            Slot::Function {
+                return_type,
+                function,
+                name,
+                definition,
+            } => last_token!(return_type, function, name, definition),
+        }
+    }
+}
+
+impl<'s> Spanned<'s> for TableSlotType<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            TableSlotType::Slot(slot) => slot.first_token(),
+            TableSlotType::JsonProperty {
+                name_token,
+                colon,
+                value,
+                ..
+            } => first_token!(name_token, colon, value),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        match self {
+            TableSlotType::Slot(slot) => slot.last_token(),
+            TableSlotType::JsonProperty {
+                name_token,
+                colon,
+                value,
+                ..
+            } => last_token!(name_token, colon, value),
+        }
+    }
+}
+
+impl<'s> Spanned<'s> for BinaryOperator<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match *self {
+            BinaryOperator::Assign(token)
+            | BinaryOperator::AssignAdd(token)
+            | BinaryOperator::AssignSubtract(token)
+            | BinaryOperator::AssignMultiply(token)
+            | BinaryOperator::AssignDivide(token)
+            | BinaryOperator::AssignModulo(token)
+            | BinaryOperator::Add(token)
+            | BinaryOperator::Subtract(token)
+            | BinaryOperator::Multiply(token)
+            | BinaryOperator::Divide(token)
+            | BinaryOperator::Modulo(token)
+            | BinaryOperator::Equal(token)
+            | BinaryOperator::NotEqual(token)
+            | BinaryOperator::Less(token)
+            | BinaryOperator::LessEqual(token)
+            | BinaryOperator::Greater(token)
+            | BinaryOperator::GreaterEqual(token)
+            | BinaryOperator::ThreeWay(token)
+            | BinaryOperator::LogicalAnd(token)
+            | BinaryOperator::LogicalOr(token)
+            | BinaryOperator::BitwiseAnd(token)
+            | BinaryOperator::BitwiseOr(token)
+            | BinaryOperator::BitwiseXor(token)
+            | BinaryOperator::In(token)
+            | BinaryOperator::Instanceof(token)
+            | BinaryOperator::AssignNewSlot(token, _)
+            | BinaryOperator::ShiftLeft(token, _)
+            | BinaryOperator::ShiftRight(token, _)
+            | BinaryOperator::UnsignedShiftRight(token, _, _) => Some(token),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        match *self {
+            BinaryOperator::Assign(token)
+            | BinaryOperator::AssignAdd(token)
+            | BinaryOperator::AssignSubtract(token)
+            | BinaryOperator::AssignMultiply(token)
+            | BinaryOperator::AssignDivide(token)
+            | BinaryOperator::AssignModulo(token)
+            | BinaryOperator::Add(token)
+            | BinaryOperator::Subtract(token)
+            | BinaryOperator::Multiply(token)
+            | BinaryOperator::Divide(token)
+            | BinaryOperator::Modulo(token)
+            | BinaryOperator::Equal(token)
+            | BinaryOperator::NotEqual(token)
+            | BinaryOperator::Less(token)
+            | BinaryOperator::LessEqual(token)
+            | BinaryOperator::Greater(token)
+            | BinaryOperator::GreaterEqual(token)
+            | BinaryOperator::ThreeWay(token)
+            | BinaryOperator::LogicalAnd(token)
+            | BinaryOperator::LogicalOr(token)
+            | BinaryOperator::BitwiseAnd(token)
+            | BinaryOperator::BitwiseOr(token)
+            | BinaryOperator::BitwiseXor(token)
+            | BinaryOperator::In(token)
+            | BinaryOperator::Instanceof(token)
+            | BinaryOperator::AssignNewSlot(_, token)
+            | BinaryOperator::ShiftLeft(_, token)
+            | BinaryOperator::ShiftRight(_, token)
+            | BinaryOperator::UnsignedShiftRight(_, _, token) => Some(token),
+        }
+    }
+}
+
+impl<'s> Spanned<'s> for PrefixOperator<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match *self {
+            PrefixOperator::Negate(token)
+            | PrefixOperator::LogicalNot(token)
+            | PrefixOperator::BitwiseNot(token)
+            | PrefixOperator::Typeof(token)
+            | PrefixOperator::Clone(token)
+            | PrefixOperator::Delete(token)
+            | PrefixOperator::Increment(token)
+            | PrefixOperator::Decrement(token) => Some(token),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        self.first_token()
+    }
+}
+
+impl<'s> Spanned<'s> for PostfixOperator<'s> {
+    fn first_token(&self) -> Option<&'s Token<'s>> {
+        match *self {
+            PostfixOperator::Increment(token) | PostfixOperator::Decrement(token) => Some(token),
+        }
+    }
+
+    fn last_token(&self) -> Option<&'s Token<'s>> {
+        self.first_token()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::*;
+    use crate::token::Token;
+    use crate::{parse, parse_recover, tokenize, Flavor};
+
+    #[derive(Default)]
+    struct TokenCollector<'s> {
+        tokens: Vec<&'s Token<'s>>,
+    }
+
+    impl<'s> Visit<'s> for TokenCollector<'s> {
+        fn visit_token(&mut self, token: &'s Token<'s>) {
+            self.tokens.push(token);
+        }
+    }
+
+    // Checks that the first and last tokens of a node match the first and last visited tokens.
+    fn check<'s, T: Spanned<'s>>(node: &'s T, visit: fn(&mut TokenCollector<'s>, &'s T)) {
+        let mut collector = TokenCollector::default();
+        visit(&mut collector, node);
+        let (first, last) = (node.first_token(), node.last_token());
+        assert_eq!(
+            first.map(|t| t as *const _),
+            collector.tokens.first().map(|&t| t as *const _)
+        );
+        assert_eq!(
+            last.map(|t| t as *const _),
+            collector.tokens.last().map(|&t| t as *const _)
+        );
+    }
+
+    struct SpanChecker;
+
+    impl<'s> Visit<'s> for SpanChecker {
+        fn visit_statement(&mut self, node: &'s Statement<'s>) {
+            check(node, TokenCollector::visit_statement);
+            check(&node.ty, TokenCollector::visit_statement_type);
+            walk_statement(self, node);
+        }
+
+        fn visit_expression(&mut self, node: &'s Expression<'s>) {
+            check(node, TokenCollector::visit_expression);
+            walk_expression(self, node);
+        }
+
+        fn visit_type(&mut self, node: &'s Type<'s>) {
+            check(node, TokenCollector::visit_type);
+            walk_type(self, node);
+        }
+
+        fn visit_function_definition(&mut self, node: &'s FunctionDefinition<'s>) {
+            check(node, TokenCollector::visit_function_definition);
+            check(&node.params, TokenCollector::visit_function_params);
+            walk_function_definition(self, node);
+        }
+
+        fn visit_class_member(&mut self, node: &'s ClassMember<'s>) {
+            check(node, TokenCollector::visit_class_member);
+            check(&node.slot, TokenCollector::visit_slot);
+            walk_class_member(self, node);
+        }
+
+        fn visit_table_slot(&mut self, node: &'s TableSlot<'s>) {
+            check(node, TokenCollector::visit_table_slot);
+            walk_table_slot(self, node);
+        }
+    }
+
+    #[test]
+    fn spans_match_visited_tokens() {
+        let source = include_str!("../../examples/print_ast_script.nut");
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+
+        check(&program, TokenCollector::visit_program);
+        SpanChecker.visit_program(&program);
+    }
+
+    #[test]
+    fn empty_spans() {
+        let source = "function A() {}\nlocal b = ";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let (program, _) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+
+        let StatementType::FunctionDefinition(function) = &program.statements[0].ty else {
+            panic!("expected a function");
+        };
+        assert_eq!(function.definition.params.span(), None);
+        assert_eq!(function.span(), Some(0..15));
+
+        let StatementType::VarDefinition(var) = &program.statements[1].ty else {
+            panic!("expected a variable definition");
+        };
+        let initializer = var.definitions.last_item.initializer.as_ref().unwrap();
+        assert_eq!(initializer.value.span(), None);
+        assert_eq!(initializer.span(), Some(24..25));
+        assert_eq!(var.span(), Some(16..25));
+
+        assert_eq!(
+            Program {
+                statements: Vec::new()
+            }
+            .span(),
+            None
+        );
+    }
+}
@@ -4,6 +4,7 @@ use crate::ast::{
     StructDefinition, SwitchCase, Type, VarDefinition, VarInitializer,
 };
 use crate::token::Token;
+use crate::{Condition, ParseError};
 
 /// A statement.
 ///
@@ -53,6 +54,12 @@ pub enum StatementType<'s> {
     Global(GlobalStatement<'s>),
     GlobalizeAllFunctions(GlobalizeAllFunctionsStatement<'s>),
     Untyped(UntypedStatement<'s>),
+
+    // Preprocessor
+    Conditional(ConditionalStatement<'s>),
+
+    // Error recovery
+    Error(ErrorStatement<'s>),
 }
 
 /// An empty statement.
@@ -379,3 +386,60 @@ pub struct GlobalizeAllFunctionsStatement<'s> {
 pub struct UntypedStatement<'s> {
     pub untyped: &'s Token<'s>,
 }
+
+/// A block of statements in preprocessor conditionals.
+///
+/// Directives are stored as [`Comment::ScriptStyle`] comments on the token that follows them. When
+/// directives are before statements in a program or a block statement, the parser also groups the
+/// statements in each branch into one of these. Directives anywhere else, like between switch cases
+/// or inside an expression, are left as comments. If some directives of a conditional are before
+/// statements and others are not, all of them are left as comments.
+///
+/// Grammar: `#if` [Condition] [Statement]* (`#elseif` [Condition] [Statement]*)* (`#else`
+/// [Statement]*)? `#endif`
+///
+/// [`Comment::ScriptStyle`]: crate::token::Comment::ScriptStyle
+#[derive(Debug, Clone)]
+pub struct ConditionalStatement<'s> {
+    pub if_: ConditionalBranch<'s>,
+    pub else_ifs: Vec<ConditionalBranch<'s>>,
+    pub else_: Option<Vec<Statement<'s>>>,
+}
+
+/// A branch of a [`ConditionalStatement`] with a condition.
+///
+/// Grammar: [Condition] [Statement]*
+#[derive(Debug, Clone)]
+pub struct ConditionalBranch<'s> {
+    pub condition: Condition<'s>,
+    pub statements: Vec<Statement<'s>>,
+}
+
+impl<'s> ConditionalStatement<'s> {
+    /// Returns the statements in the branch that is active with a set of defines.
+    ///
+    /// This is the first branch with a condition that is true, or the `#else` branch if there is
+    /// one. Returns an empty slice if no branches are active.
+    pub fn active_statements(&self, defines: &[&str]) -> &[Statement<'s>] {
+        std::iter::once(&self.if_)
+            .chain(&self.else_ifs)
+            .find(|branch| branch.condition.evaluate(defines))
+            .map(|branch| branch.statements.as_slice())
+            .or(self.else_.as_deref())
+            .unwrap_or(&[])
+    }
+}
+
+/// A statement that could not be parsed.
+///
+/// These are only emitted by [`parse_recover`]. The statement includes every token that was skipped
+/// over, up to the start of the next statement.
+///
+/// Grammar: &lt;any tokens>
+///
+/// [`parse_recover`]: crate::parse_recover
+#[derive(Debug, Clone)]
+pub struct ErrorStatement<'s> {
+    pub error: ParseError,
+    pub tokens: Vec<&'s Token<'s>>,
+}
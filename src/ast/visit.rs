@@ -0,0 +1,1679 @@
+use crate::ast::{
+    ArrayExpression, ArrayType, ArrayValue, BinaryExpression, BinaryOperator, BlockStatement,
+    BreakStatement, CallArgument, CallExpression, ClassDefinition, ClassDefinitionStatement,
+    ClassExpression, ClassExtends, ClassMember, CommaExpression, ConditionalBranch,
+    ConditionalStatement, ConstDefinitionStatement, ConstructorDefinitionStatement,
+    ContinueStatement, DelayThreadStatement, DelegateExpression, DoWhileStatement, EmptyStatement,
+    EnumDefinitionStatement, EnumEntry, ErrorExpression, ErrorStatement, ExpectExpression,
+    Expression, ExpressionStatement, ForDefinition, ForStatement, ForeachIndex, ForeachStatement,
+    FunctionCaptures, FunctionDefinition, FunctionDefinitionStatement, FunctionEnvironment,
+    FunctionExpression, FunctionParam, FunctionParams, FunctionRefParam, FunctionRefType,
+    GenericType, GlobalDefinition, GlobalStatement, GlobalizeAllFunctionsStatement, Identifier,
+    IfStatement, IfStatementType, IndexExpression, LambdaExpression, LiteralExpression, LocalType,
+    MethodIdentifier, NullableType, ParensExpression, PlainType, PostfixExpression,
+    PostfixOperator, PrefixExpression, PrefixOperator, Program, PropertyExpression, ReferenceType,
+    ReturnStatement, RootVarExpression, SeparatedList1, SeparatedListTrailing1, Slot, Statement,
+    StatementType, StructDefinition, StructDefinitionStatement, StructProperty, StructType,
+    SwitchCase, SwitchCaseCondition, SwitchStatement, TableExpression, TableSlot, TableSlotType,
+    TernaryExpression, ThreadStatement, ThrowStatement, TryCatchStatement, Type,
+    TypeDefinitionStatement, UntypedStatement, VarDefinition, VarDefinitionStatement,
+    VarExpression, VarInitializer, VarType, VectorExpression, WaitStatement,
+    WaitThreadSoloStatement, WaitThreadStatement, WhileStatement, YieldStatement,
+};
+use crate::token::Token;
+
+/// Visits nodes in a syntax tree by shared reference.
+///
+/// There is a `visit_*` method for each node type in the tree. The default implementation of each
+/// method calls the matching `walk_*` function, which visits the children of the node in source
+/// order. Override a method to act on a node, and call the `walk_*` function from it to continue
+/// into the node's children.
+///
+/// Every token in the tree is passed to [`visit_token`] exactly once, in the order that the tokens
+/// appear in the source code.
+///
+/// # Example
+/// ```
+/// use sqparse::ast::{walk_function_definition_statement, FunctionDefinitionStatement, Visit};
+/// use sqparse::{parse, tokenize, Flavor};
+///
+/// #[derive(Default)]
+/// struct FunctionNames<'s> {
+///     names: Vec<&'s str>,
+/// }
+///
+/// impl<'s> Visit<'s> for FunctionNames<'s> {
+///     fn visit_function_definition_statement(&mut self, node: &'s FunctionDefinitionStatement<'s>) {
+///         self.names.push(node.name.last_item.value);
+///         walk_function_definition_statement(self, node);
+///     }
+/// }
+///
+/// let source = "void function A() { int function B() { return 1 } } void function C() {}";
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// let mut visitor = FunctionNames::default();
+/// visitor.visit_program(&program);
+/// assert_eq!(visitor.names, ["A", "B", "C"]);
+/// ```
+///
+/// [`visit_token`]: Visit::visit_token
+pub trait Visit<'s> {
+    fn visit_program(&mut self, node: &'s Program<'s>) {
+        walk_program(self, node);
+    }
+
+    fn visit_statement(&mut self, node: &'s Statement<'s>) {
+        walk_statement(self, node);
+    }
+
+    fn visit_statement_type(&mut self, node: &'s StatementType<'s>) {
+        walk_statement_type(self, node);
+    }
+
+    fn visit_empty_statement(&mut self, node: &'s EmptyStatement<'s>) {
+        walk_empty_statement(self, node);
+    }
+
+    fn visit_block_statement(&mut self, node: &'s BlockStatement<'s>) {
+        walk_block_statement(self, node);
+    }
+
+    fn visit_if_statement(&mut self, node: &'s IfStatement<'s>) {
+        walk_if_statement(self, node);
+    }
+
+    fn visit_while_statement(&mut self, node: &'s WhileStatement<'s>) {
+        walk_while_statement(self, node);
+    }
+
+    fn visit_do_while_statement(&mut self, node: &'s DoWhileStatement<'s>) {
+        walk_do_while_statement(self, node);
+    }
+
+    fn visit_switch_statement(&mut self, node: &'s SwitchStatement<'s>) {
+        walk_switch_statement(self, node);
+    }
+
+    fn visit_for_statement(&mut self, node: &'s ForStatement<'s>) {
+        walk_for_statement(self, node);
+    }
+
+    fn visit_foreach_statement(&mut self, node: &'s ForeachStatement<'s>) {
+        walk_foreach_statement(self, node);
+    }
+
+    fn visit_break_statement(&mut self, node: &'s BreakStatement<'s>) {
+        walk_break_statement(self, node);
+    }
+
+    fn visit_continue_statement(&mut self, node: &'s ContinueStatement<'s>) {
+        walk_continue_statement(self, node);
+    }
+
+    fn visit_return_statement(&mut self, node: &'s ReturnStatement<'s>) {
+        walk_return_statement(self, node);
+    }
+
+    fn visit_yield_statement(&mut self, node: &'s YieldStatement<'s>) {
+        walk_yield_statement(self, node);
+    }
+
+    fn visit_var_definition_statement(&mut self, node: &'s VarDefinitionStatement<'s>) {
+        walk_var_definition_statement(self, node);
+    }
+
+    fn visit_constructor_definition_statement(
+        &mut self,
+        node: &'s ConstructorDefinitionStatement<'s>,
+    ) {
+        walk_constructor_definition_statement(self, node);
+    }
+
+    fn visit_function_definition_statement(&mut self, node: &'s FunctionDefinitionStatement<'s>) {
+        walk_function_definition_statement(self, node);
+    }
+
+    fn visit_class_definition_statement(&mut self, node: &'s ClassDefinitionStatement<'s>) {
+        walk_class_definition_statement(self, node);
+    }
+
+    fn visit_try_catch_statement(&mut self, node: &'s TryCatchStatement<'s>) {
+        walk_try_catch_statement(self, node);
+    }
+
+    fn visit_throw_statement(&mut self, node: &'s ThrowStatement<'s>) {
+        walk_throw_statement(self, node);
+    }
+
+    fn visit_const_definition_statement(&mut self, node: &'s ConstDefinitionStatement<'s>) {
+        walk_const_definition_statement(self, node);
+    }
+
+    fn visit_enum_definition_statement(&mut self, node: &'s EnumDefinitionStatement<'s>) {
+        walk_enum_definition_statement(self, node);
+    }
+
+    fn visit_expression_statement(&mut self, node: &'s ExpressionStatement<'s>) {
+        walk_expression_statement(self, node);
+    }
+
+    fn visit_thread_statement(&mut self, node: &'s ThreadStatement<'s>) {
+        walk_thread_statement(self, node);
+    }
+
+    fn visit_delay_thread_statement(&mut self, node: &'s DelayThreadStatement<'s>) {
+        walk_delay_thread_statement(self, node);
+    }
+
+    fn visit_wait_thread_statement(&mut self, node: &'s WaitThreadStatement<'s>) {
+        walk_wait_thread_statement(self, node);
+    }
+
+    fn visit_wait_thread_solo_statement(&mut self, node: &'s WaitThreadSoloStatement<'s>) {
+        walk_wait_thread_solo_statement(self, node);
+    }
+
+    fn visit_wait_statement(&mut self, node: &'s WaitStatement<'s>) {
+        walk_wait_statement(self, node);
+    }
+
+    fn visit_struct_definition_statement(&mut self, node: &'s StructDefinitionStatement<'s>) {
+        walk_struct_definition_statement(self, node);
+    }
+
+    fn visit_type_definition_statement(&mut self, node: &'s TypeDefinitionStatement<'s>) {
+        walk_type_definition_statement(self, node);
+    }
+
+    fn visit_global_statement(&mut self, node: &'s GlobalStatement<'s>) {
+        walk_global_statement(self, node);
+    }
+
+    fn visit_globalize_all_functions_statement(
+        &mut self,
+        node: &'s GlobalizeAllFunctionsStatement<'s>,
+    ) {
+        walk_globalize_all_functions_statement(self, node);
+    }
+
+    fn visit_untyped_statement(&mut self, node: &'s UntypedStatement<'s>) {
+        walk_untyped_statement(self, node);
+    }
+
+    fn visit_conditional_statement(&mut self, node: &'s ConditionalStatement<'s>) {
+        walk_conditional_statement(self, node);
+    }
+
+    fn visit_conditional_branch(&mut self, node: &'s ConditionalBranch<'s>) {
+        walk_conditional_branch(self, node);
+    }
+
+    fn visit_error_statement(&mut self, node: &'s ErrorStatement<'s>) {
+        walk_error_statement(self, node);
+    }
+
+    fn visit_if_statement_type(&mut self, node: &'s IfStatementType<'s>) {
+        walk_if_statement_type(self, node);
+    }
+
+    fn visit_switch_case(&mut self, node: &'s SwitchCase<'s>) {
+        walk_switch_case(self, node);
+    }
+
+    fn visit_switch_case_condition(&mut self, node: &'s SwitchCaseCondition<'s>) {
+        walk_switch_case_condition(self, node);
+    }
+
+    fn visit_for_definition(&mut self, node: &'s ForDefinition<'s>) {
+        walk_for_definition(self, node);
+    }
+
+    fn visit_foreach_index(&mut self, node: &'s ForeachIndex<'s>) {
+        walk_foreach_index(self, node);
+    }
+
+    fn visit_enum_entry(&mut self, node: &'s EnumEntry<'s>) {
+        walk_enum_entry(self, node);
+    }
+
+    fn visit_global_definition(&mut self, node: &'s GlobalDefinition<'s>) {
+        walk_global_definition(self, node);
+    }
+
+    fn visit_var_definition(&mut self, node: &'s VarDefinition<'s>) {
+        walk_var_definition(self, node);
+    }
+
+    fn visit_var_initializer(&mut self, node: &'s VarInitializer<'s>) {
+        walk_var_initializer(self, node);
+    }
+
+    fn visit_class_definition(&mut self, node: &'s ClassDefinition<'s>) {
+        walk_class_definition(self, node);
+    }
+
+    fn visit_class_extends(&mut self, node: &'s ClassExtends<'s>) {
+        walk_class_extends(self, node);
+    }
+
+    fn visit_class_member(&mut self, node: &'s ClassMember<'s>) {
+        walk_class_member(self, node);
+    }
+
+    fn visit_struct_definition(&mut self, node: &'s StructDefinition<'s>) {
+        walk_struct_definition(self, node);
+    }
+
+    fn visit_struct_property(&mut self, node: &'s StructProperty<'s>) {
+        walk_struct_property(self, node);
+    }
+
+    fn visit_function_definition(&mut self, node: &'s FunctionDefinition<'s>) {
+        walk_function_definition(self, node);
+    }
+
+    fn visit_function_environment(&mut self, node: &'s FunctionEnvironment<'s>) {
+        walk_function_environment(self, node);
+    }
+
+    fn visit_function_params(&mut self, node: &'s FunctionParams<'s>) {
+        walk_function_params(self, node);
+    }
+
+    fn visit_function_param(&mut self, node: &'s FunctionParam<'s>) {
+        walk_function_param(self, node);
+    }
+
+    fn visit_function_captures(&mut self, node: &'s FunctionCaptures<'s>) {
+        walk_function_captures(self, node);
+    }
+
+    fn visit_function_ref_param(&mut self, node: &'s FunctionRefParam<'s>) {
+        walk_function_ref_param(self, node);
+    }
+
+    fn visit_slot(&mut self, node: &'s Slot<'s>) {
+        walk_slot(self, node);
+    }
+
+    fn visit_table_slot(&mut self, node: &'s TableSlot<'s>) {
+        walk_table_slot(self, node);
+    }
+
+    fn visit_table_slot_type(&mut self, node: &'s TableSlotType<'s>) {
+        walk_table_slot_type(self, node);
+    }
+
+    fn visit_array_value(&mut self, node: &'s ArrayValue<'s>) {
+        walk_array_value(self, node);
+    }
+
+    fn visit_call_argument(&mut self, node: &'s CallArgument<'s>) {
+        walk_call_argument(self, node);
+    }
+
+    fn visit_identifier(&mut self, node: &'s Identifier<'s>) {
+        walk_identifier(self, node);
+    }
+
+    fn visit_method_identifier(&mut self, node: &'s MethodIdentifier<'s>) {
+        walk_method_identifier(self, node);
+    }
+
+    fn visit_expression(&mut self, node: &'s Expression<'s>) {
+        walk_expression(self, node);
+    }
+
+    fn visit_parens_expression(&mut self, node: &'s ParensExpression<'s>) {
+        walk_parens_expression(self, node);
+    }
+
+    fn visit_literal_expression(&mut self, node: &'s LiteralExpression<'s>) {
+        walk_literal_expression(self, node);
+    }
+
+    fn visit_var_expression(&mut self, node: &'s VarExpression<'s>) {
+        walk_var_expression(self, node);
+    }
+
+    fn visit_root_var_expression(&mut self, node: &'s RootVarExpression<'s>) {
+        walk_root_var_expression(self, node);
+    }
+
+    fn visit_index_expression(&mut self, node: &'s IndexExpression<'s>) {
+        walk_index_expression(self, node);
+    }
+
+    fn visit_property_expression(&mut self, node: &'s PropertyExpression<'s>) {
+        walk_property_expression(self, node);
+    }
+
+    fn visit_ternary_expression(&mut self, node: &'s TernaryExpression<'s>) {
+        walk_ternary_expression(self, node);
+    }
+
+    fn visit_binary_expression(&mut self, node: &'s BinaryExpression<'s>) {
+        walk_binary_expression(self, node);
+    }
+
+    fn visit_prefix_expression(&mut self, node: &'s PrefixExpression<'s>) {
+        walk_prefix_expression(self, node);
+    }
+
+    fn visit_postfix_expression(&mut self, node: &'s PostfixExpression<'s>) {
+        walk_postfix_expression(self, node);
+    }
+
+    fn visit_comma_expression(&mut self, node: &'s CommaExpression<'s>) {
+        walk_comma_expression(self, node);
+    }
+
+    fn visit_table_expression(&mut self, node: &'s TableExpression<'s>) {
+        walk_table_expression(self, node);
+    }
+
+    fn visit_class_expression(&mut self, node: &'s ClassExpression<'s>) {
+        walk_class_expression(self, node);
+    }
+
+    fn visit_array_expression(&mut self, node: &'s ArrayExpression<'s>) {
+        walk_array_expression(self, node);
+    }
+
+    fn visit_function_expression(&mut self, node: &'s FunctionExpression<'s>) {
+        walk_function_expression(self, node);
+    }
+
+    fn visit_lambda_expression(&mut self, node: &'s LambdaExpression<'s>) {
+        walk_lambda_expression(self, node);
+    }
+
+    fn visit_call_expression(&mut self, node: &'s CallExpression<'s>) {
+        walk_call_expression(self, node);
+    }
+
+    fn visit_delegate_expression(&mut self, node: &'s DelegateExpression<'s>) {
+        walk_delegate_expression(self, node);
+    }
+
+    fn visit_vector_expression(&mut self, node: &'s VectorExpression<'s>) {
+        walk_vector_expression(self, node);
+    }
+
+    fn visit_expect_expression(&mut self, node: &'s ExpectExpression<'s>) {
+        walk_expect_expression(self, node);
+    }
+
+    fn visit_error_expression(&mut self, node: &'s ErrorExpression) {
+        walk_error_expression(self, node);
+    }
+
+    fn visit_binary_operator(&mut self, node: &'s BinaryOperator<'s>) {
+        walk_binary_operator(self, node);
+    }
+
+    fn visit_prefix_operator(&mut self, node: &'s PrefixOperator<'s>) {
+        walk_prefix_operator(self, node);
+    }
+
+    fn visit_postfix_operator(&mut self, node: &'s PostfixOperator<'s>) {
+        walk_postfix_operator(self, node);
+    }
+
+    fn visit_type(&mut self, node: &'s Type<'s>) {
+        walk_type(self, node);
+    }
+
+    fn visit_local_type(&mut self, node: &'s LocalType<'s>) {
+        walk_local_type(self, node);
+    }
+
+    fn visit_var_type(&mut self, node: &'s VarType<'s>) {
+        walk_var_type(self, node);
+    }
+
+    fn visit_plain_type(&mut self, node: &'s PlainType<'s>) {
+        walk_plain_type(self, node);
+    }
+
+    fn visit_array_type(&mut self, node: &'s ArrayType<'s>) {
+        walk_array_type(self, node);
+    }
+
+    fn visit_generic_type(&mut self, node: &'s GenericType<'s>) {
+        walk_generic_type(self, node);
+    }
+
+    fn visit_function_ref_type(&mut self, node: &'s FunctionRefType<'s>) {
+        walk_function_ref_type(self, node);
+    }
+
+    fn visit_struct_type(&mut self, node: &'s StructType<'s>) {
+        walk_struct_type(self, node);
+    }
+
+    fn visit_reference_type(&mut self, node: &'s ReferenceType<'s>) {
+        walk_reference_type(self, node);
+    }
+
+    fn visit_nullable_type(&mut self, node: &'s NullableType<'s>) {
+        walk_nullable_type(self, node);
+    }
+
+    fn visit_token(&mut self, _token: &'s Token<'s>) {}
+}
+
+pub fn walk_program<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s Program<'s>) {
+    for val in &node.statements {
+        visitor.visit_statement(val);
+    }
+}
+
+pub fn walk_statement<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s Statement<'s>) {
+    visitor.visit_statement_type(&node.ty);
+    if let Some(token) = node.semicolon {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_statement_type<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s StatementType<'s>,
+) {
+    match node {
+        StatementType::Empty(val) => visitor.visit_empty_statement(val),
+        StatementType::Block(val) => visitor.visit_block_statement(val),
+        StatementType::If(val) => visitor.visit_if_statement(val),
+        StatementType::While(val) => visitor.visit_while_statement(val),
+        StatementType::DoWhile(val) => visitor.visit_do_while_statement(val),
+        StatementType::Switch(val) => visitor.visit_switch_statement(val),
+        StatementType::For(val) => visitor.visit_for_statement(val),
+        StatementType::Foreach(val) => visitor.visit_foreach_statement(val),
+        StatementType::Break(val) => visitor.visit_break_statement(val),
+        StatementType::Continue(val) => visitor.visit_continue_statement(val),
+        StatementType::Return(val) => visitor.visit_return_statement(val),
+        StatementType::Yield(val) => visitor.visit_yield_statement(val),
+        StatementType::VarDefinition(val) => visitor.visit_var_definition_statement(val),
+        StatementType::ConstructorDefinition(val) => {
+            visitor.visit_constructor_definition_statement(val)
+        }
+        StatementType::FunctionDefinition(val) => visitor.visit_function_definition_statement(val),
+        StatementType::ClassDefinition(val) => visitor.visit_class_definition_statement(val),
+        StatementType::TryCatch(val) => visitor.visit_try_catch_statement(val),
+        StatementType::Throw(val) => visitor.visit_throw_statement(val),
+        StatementType::Const(val) => visitor.visit_const_definition_statement(val),
+        StatementType::EnumDefinition(val) => visitor.visit_enum_definition_statement(val),
+        StatementType::Expression(val) => visitor.visit_expression_statement(val),
+        StatementType::Thread(val) => visitor.visit_thread_statement(val),
+        StatementType::DelayThread(val) => visitor.visit_delay_thread_statement(val),
+        StatementType::WaitThread(val) => visitor.visit_wait_thread_statement(val),
+        StatementType::WaitThreadSolo(val) => visitor.visit_wait_thread_solo_statement(val),
+        StatementType::Wait(val) => visitor.visit_wait_statement(val),
+        StatementType::StructDefinition(val) => visitor.visit_struct_definition_statement(val),
+        StatementType::TypeDefinition(val) => visitor.visit_type_definition_statement(val),
+        StatementType::Global(val) => visitor.visit_global_statement(val),
+        StatementType::GlobalizeAllFunctions(val) => {
+            visitor.visit_globalize_all_functions_statement(val)
+        }
+        StatementType::Untyped(val) => visitor.visit_untyped_statement(val),
+        StatementType::Conditional(val) => visitor.visit_conditional_statement(val),
+        StatementType::Error(val) => visitor.visit_error_statement(val),
+    }
+}
+
+pub fn walk_empty_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s EmptyStatement<'s>,
+) {
+    if let Some(token) = node.empty {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_block_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s BlockStatement<'s>,
+) {
+    visitor.visit_token(node.open);
+    for val in &node.statements {
+        visitor.visit_statement(val);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_if_statement<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s IfStatement<'s>) {
+    visitor.visit_token(node.if_);
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.condition);
+    visitor.visit_token(node.close);
+    visitor.visit_if_statement_type(&node.ty);
+}
+
+pub fn walk_while_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s WhileStatement<'s>,
+) {
+    visitor.visit_token(node.while_);
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.condition);
+    visitor.visit_token(node.close);
+    visitor.visit_statement_type(&node.body);
+}
+
+pub fn walk_do_while_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s DoWhileStatement<'s>,
+) {
+    visitor.visit_token(node.do_);
+    visitor.visit_statement(&node.body);
+    visitor.visit_token(node.while_);
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.condition);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_switch_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s SwitchStatement<'s>,
+) {
+    visitor.visit_token(node.switch);
+    visitor.visit_token(node.open_condition);
+    visitor.visit_expression(&node.condition);
+    visitor.visit_token(node.close_condition);
+    visitor.visit_token(node.open_cases);
+    for val in &node.cases {
+        visitor.visit_switch_case(val);
+    }
+    visitor.visit_token(node.close_cases);
+}
+
+pub fn walk_for_statement<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s ForStatement<'s>) {
+    visitor.visit_token(node.for_);
+    visitor.visit_token(node.open);
+    if let Some(val) = &node.initializer {
+        visitor.visit_for_definition(val);
+    }
+    visitor.visit_token(node.semicolon_1);
+    if let Some(val) = &node.condition {
+        visitor.visit_expression(val);
+    }
+    visitor.visit_token(node.semicolon_2);
+    if let Some(val) = &node.increment {
+        visitor.visit_expression(val);
+    }
+    visitor.visit_token(node.close);
+    visitor.visit_statement_type(&node.body);
+}
+
+pub fn walk_foreach_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ForeachStatement<'s>,
+) {
+    visitor.visit_token(node.foreach);
+    visitor.visit_token(node.open);
+    if let Some(val) = &node.index {
+        visitor.visit_foreach_index(val);
+    }
+    if let Some(val) = &node.value_type {
+        visitor.visit_type(val);
+    }
+    visitor.visit_identifier(&node.value_name);
+    visitor.visit_token(node.in_);
+    visitor.visit_expression(&node.array);
+    visitor.visit_token(node.close);
+    visitor.visit_statement_type(&node.body);
+}
+
+pub fn walk_break_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s BreakStatement<'s>,
+) {
+    visitor.visit_token(node.break_);
+}
+
+pub fn walk_continue_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ContinueStatement<'s>,
+) {
+    visitor.visit_token(node.continue_);
+}
+
+pub fn walk_return_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ReturnStatement<'s>,
+) {
+    visitor.visit_token(node.return_);
+    if let Some(val) = &node.value {
+        visitor.visit_expression(val);
+    }
+}
+
+pub fn walk_yield_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s YieldStatement<'s>,
+) {
+    visitor.visit_token(node.yield_);
+    if let Some(val) = &node.value {
+        visitor.visit_expression(val);
+    }
+}
+
+pub fn walk_var_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s VarDefinitionStatement<'s>,
+) {
+    visitor.visit_type(&node.type_);
+    walk_separated_list_trailing1(visitor, &node.definitions, V::visit_var_definition);
+}
+
+pub fn walk_constructor_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ConstructorDefinitionStatement<'s>,
+) {
+    visitor.visit_token(node.function);
+    for (val, token) in &node.namespaces {
+        visitor.visit_identifier(val);
+        visitor.visit_token(token);
+    }
+    visitor.visit_identifier(&node.last_name);
+    visitor.visit_token(node.last_namespace);
+    visitor.visit_token(node.constructor);
+    visitor.visit_function_definition(&node.definition);
+}
+
+pub fn walk_function_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionDefinitionStatement<'s>,
+) {
+    if let Some(val) = &node.return_type {
+        visitor.visit_type(val);
+    }
+    visitor.visit_token(node.function);
+    walk_separated_list1(visitor, &node.name, V::visit_identifier);
+    visitor.visit_function_definition(&node.definition);
+}
+
+pub fn walk_class_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ClassDefinitionStatement<'s>,
+) {
+    visitor.visit_token(node.class);
+    visitor.visit_expression(&node.name);
+    visitor.visit_class_definition(&node.definition);
+}
+
+pub fn walk_try_catch_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s TryCatchStatement<'s>,
+) {
+    visitor.visit_token(node.try_);
+    visitor.visit_statement(&node.body);
+    visitor.visit_token(node.catch);
+    visitor.visit_token(node.open);
+    visitor.visit_identifier(&node.catch_name);
+    visitor.visit_token(node.close);
+    visitor.visit_statement_type(&node.catch_body);
+}
+
+pub fn walk_throw_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ThrowStatement<'s>,
+) {
+    visitor.visit_token(node.throw);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_const_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ConstDefinitionStatement<'s>,
+) {
+    visitor.visit_token(node.const_);
+    if let Some(val) = &node.const_type {
+        visitor.visit_type(val);
+    }
+    visitor.visit_identifier(&node.name);
+    visitor.visit_var_initializer(&node.initializer);
+}
+
+pub fn walk_enum_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s EnumDefinitionStatement<'s>,
+) {
+    visitor.visit_token(node.enum_);
+    visitor.visit_identifier(&node.name);
+    visitor.visit_token(node.open);
+    for val in &node.entries {
+        visitor.visit_enum_entry(val);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_expression_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ExpressionStatement<'s>,
+) {
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_thread_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ThreadStatement<'s>,
+) {
+    visitor.visit_token(node.thread);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_delay_thread_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s DelayThreadStatement<'s>,
+) {
+    visitor.visit_token(node.delay_thread);
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.duration);
+    visitor.visit_token(node.close);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_wait_thread_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s WaitThreadStatement<'s>,
+) {
+    visitor.visit_token(node.wait_thread);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_wait_thread_solo_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s WaitThreadSoloStatement<'s>,
+) {
+    visitor.visit_token(node.wait_thread_solo);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_wait_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s WaitStatement<'s>,
+) {
+    visitor.visit_token(node.wait);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_struct_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s StructDefinitionStatement<'s>,
+) {
+    visitor.visit_token(node.struct_);
+    visitor.visit_identifier(&node.name);
+    visitor.visit_struct_definition(&node.definition);
+}
+
+pub fn walk_type_definition_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s TypeDefinitionStatement<'s>,
+) {
+    visitor.visit_token(node.typedef);
+    visitor.visit_identifier(&node.name);
+    visitor.visit_type(&node.type_);
+}
+
+pub fn walk_global_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s GlobalStatement<'s>,
+) {
+    visitor.visit_token(node.global);
+    visitor.visit_global_definition(&node.definition);
+}
+
+pub fn walk_globalize_all_functions_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s GlobalizeAllFunctionsStatement<'s>,
+) {
+    visitor.visit_token(node.globalize_all_functions);
+}
+
+pub fn walk_untyped_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s UntypedStatement<'s>,
+) {
+    visitor.visit_token(node.untyped);
+}
+
+pub fn walk_conditional_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ConditionalStatement<'s>,
+) {
+    visitor.visit_conditional_branch(&node.if_);
+    for val in &node.else_ifs {
+        visitor.visit_conditional_branch(val);
+    }
+    if let Some(statements) = &node.else_ {
+        for val in statements {
+            visitor.visit_statement(val);
+        }
+    }
+}
+
+pub fn walk_conditional_branch<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ConditionalBranch<'s>,
+) {
+    for val in &node.statements {
+        visitor.visit_statement(val);
+    }
+}
+
+pub fn walk_error_statement<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ErrorStatement<'s>,
+) {
+    for token in &node.tokens {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_if_statement_type<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s IfStatementType<'s>,
+) {
+    match node {
+        IfStatementType::NoElse { body } => visitor.visit_statement_type(body),
+        IfStatementType::Else {
+            body,
+            else_,
+            else_body,
+        } => {
+            visitor.visit_statement(body);
+            visitor.visit_token(else_);
+            visitor.visit_statement_type(else_body);
+        }
+    }
+}
+
+pub fn walk_switch_case<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s SwitchCase<'s>) {
+    visitor.visit_switch_case_condition(&node.condition);
+    visitor.visit_token(node.colon);
+    for val in &node.body {
+        visitor.visit_statement(val);
+    }
+}
+
+pub fn walk_switch_case_condition<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s SwitchCaseCondition<'s>,
+) {
+    match node {
+        SwitchCaseCondition::Default { default } => visitor.visit_token(default),
+        SwitchCaseCondition::Case { case, value } => {
+            visitor.visit_token(case);
+            visitor.visit_expression(value);
+        }
+    }
+}
+
+pub fn walk_for_definition<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ForDefinition<'s>,
+) {
+    match node {
+        ForDefinition::Expression(value) => visitor.visit_expression(value),
+        ForDefinition::Definition(definition) => visitor.visit_var_definition_statement(definition),
+    }
+}
+
+pub fn walk_foreach_index<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s ForeachIndex<'s>) {
+    if let Some(val) = &node.type_ {
+        visitor.visit_type(val);
+    }
+    visitor.visit_identifier(&node.name);
+    visitor.visit_token(node.comma);
+}
+
+pub fn walk_enum_entry<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s EnumEntry<'s>) {
+    visitor.visit_identifier(&node.name);
+    if let Some(val) = &node.initializer {
+        visitor.visit_var_initializer(val);
+    }
+    if let Some(token) = node.comma {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_global_definition<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s GlobalDefinition<'s>,
+) {
+    match node {
+        GlobalDefinition::Function { function, name } => {
+            visitor.visit_token(function);
+            visitor.visit_identifier(name);
+        }
+        GlobalDefinition::UntypedVar { name, initializer } => {
+            visitor.visit_identifier(name);
+            visitor.visit_var_initializer(initializer);
+        }
+        GlobalDefinition::TypedVar(definition) => {
+            visitor.visit_var_definition_statement(definition)
+        }
+        GlobalDefinition::Const(definition) => visitor.visit_const_definition_statement(definition),
+        GlobalDefinition::Enum(definition) => visitor.visit_enum_definition_statement(definition),
+        GlobalDefinition::Class(definition) => visitor.visit_class_definition_statement(definition),
+        GlobalDefinition::Struct(definition) => {
+            visitor.visit_struct_definition_statement(definition)
+        }
+        GlobalDefinition::Type(definition) => visitor.visit_type_definition_statement(definition),
+    }
+}
+
+pub fn walk_var_definition<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s VarDefinition<'s>,
+) {
+    visitor.visit_identifier(&node.name);
+    if let Some(val) = &node.initializer {
+        visitor.visit_var_initializer(val);
+    }
+}
+
+pub fn walk_var_initializer<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s VarInitializer<'s>,
+) {
+    visitor.visit_token(node.assign);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_class_definition<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ClassDefinition<'s>,
+) {
+    if let Some(val) = &node.extends {
+        visitor.visit_class_extends(val);
+    }
+    visitor.visit_token(node.open);
+    for val in &node.members {
+        visitor.visit_class_member(val);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_class_extends<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s ClassExtends<'s>) {
+    visitor.visit_token(node.extends);
+    visitor.visit_expression(&node.name);
+}
+
+pub fn walk_class_member<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s ClassMember<'s>) {
+    if let Some(val) = &node.attributes {
+        visitor.visit_table_expression(val);
+    }
+    if let Some(token) = node.static_ {
+        visitor.visit_token(token);
+    }
+    visitor.visit_slot(&node.slot);
+    if let Some(token) = node.semicolon {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_struct_definition<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s StructDefinition<'s>,
+) {
+    visitor.visit_token(node.open);
+    for val in &node.properties {
+        visitor.visit_struct_property(val);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_struct_property<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s StructProperty<'s>,
+) {
+    visitor.visit_type(&node.type_);
+    visitor.visit_identifier(&node.name);
+    if let Some(val) = &node.initializer {
+        visitor.visit_var_initializer(val);
+    }
+    if let Some(token) = node.comma {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_function_definition<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionDefinition<'s>,
+) {
+    if let Some(val) = &node.environment {
+        visitor.visit_function_environment(val);
+    }
+    visitor.visit_token(node.open);
+    visitor.visit_function_params(&node.params);
+    visitor.visit_token(node.close);
+    if let Some(val) = &node.captures {
+        visitor.visit_function_captures(val);
+    }
+    visitor.visit_statement_type(&node.body);
+}
+
+pub fn walk_function_environment<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionEnvironment<'s>,
+) {
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.value);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_function_params<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionParams<'s>,
+) {
+    match node {
+        FunctionParams::NonVariable { params } => {
+            if let Some(list) = params {
+                walk_separated_list_trailing1(visitor, list, V::visit_function_param);
+            }
+        }
+        FunctionParams::EmptyVariable { vararg } => visitor.visit_token(vararg),
+        FunctionParams::NonEmptyVariable {
+            params,
+            comma,
+            vararg,
+        } => {
+            walk_separated_list1(visitor, params, V::visit_function_param);
+            visitor.visit_token(comma);
+            visitor.visit_token(vararg);
+        }
+    }
+}
+
+pub fn walk_function_param<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionParam<'s>,
+) {
+    if let Some(val) = &node.type_ {
+        visitor.visit_type(val);
+    }
+    visitor.visit_identifier(&node.name);
+    if let Some(val) = &node.initializer {
+        visitor.visit_var_initializer(val);
+    }
+}
+
+pub fn walk_function_captures<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionCaptures<'s>,
+) {
+    visitor.visit_token(node.colon);
+    visitor.visit_token(node.open);
+    if let Some(list) = &node.names {
+        walk_separated_list_trailing1(visitor, list, V::visit_identifier);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_function_ref_param<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionRefParam<'s>,
+) {
+    visitor.visit_type(&node.type_);
+    if let Some(val) = &node.name {
+        visitor.visit_identifier(val);
+    }
+    if let Some(val) = &node.initializer {
+        visitor.visit_var_initializer(val);
+    }
+}
+
+pub fn walk_slot<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s Slot<'s>) {
+    match node {
+        Slot::Property { name, initializer } => {
+            visitor.visit_identifier(name);
+            visitor.visit_var_initializer(initializer);
+        }
+        Slot::ComputedProperty {
+            open,
+            name,
+            close,
+            initializer,
+        } => {
+            visitor.visit_token(open);
+            visitor.visit_expression(name);
+            visitor.visit_token(close);
+            visitor.visit_var_initializer(initializer);
+        }
+        Slot::Constructor {
+            function,
+            constructor,
+            definition,
+        } => {
+            if let Some(token) = *function {
+                visitor.visit_token(token);
+            }
+            visitor.visit_token(constructor);
+            visitor.visit_function_definition(definition);
+        }
+        Slot::Function {
+            return_type,
+            function,
+            name,
+            definition,
+        } => {
+            if let Some(val) = return_type {
+                visitor.visit_type(val);
+            }
+            visitor.visit_token(function);
+            visitor.visit_identifier(name);
+            visitor.visit_function_definition(definition);
+        }
+    }
+}
+
+pub fn walk_table_slot<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s TableSlot<'s>) {
+    visitor.visit_table_slot_type(&node.ty);
+    if let Some(token) = node.comma {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_table_slot_type<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s TableSlotType<'s>,
+) {
+    match node {
+        TableSlotType::Slot(slot) => visitor.visit_slot(slot),
+        TableSlotType::JsonProperty {
+            name_token,
+            colon,
+            value,
+            ..
+        } => {
+            visitor.visit_token(name_token);
+            visitor.visit_token(colon);
+            visitor.visit_expression(value);
+        }
+    }
+}
+
+pub fn walk_array_value<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s ArrayValue<'s>) {
+    visitor.visit_expression(&node.value);
+    if let Some(token) = node.separator {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_call_argument<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s CallArgument<'s>) {
+    visitor.visit_expression(&node.value);
+    if let Some(token) = node.comma {
+        visitor.visit_token(token);
+    }
+}
+
+pub fn walk_identifier<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s Identifier<'s>) {
+    visitor.visit_token(node.token);
+}
+
+pub fn walk_method_identifier<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s MethodIdentifier<'s>,
+) {
+    match node {
+        MethodIdentifier::Identifier(identifier) => visitor.visit_identifier(identifier),
+        MethodIdentifier::Constructor(constructor) => visitor.visit_token(constructor),
+    }
+}
+
+pub fn walk_expression<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s Expression<'s>) {
+    match node {
+        Expression::Parens(val) => visitor.visit_parens_expression(val),
+        Expression::Literal(val) => visitor.visit_literal_expression(val),
+        Expression::Var(val) => visitor.visit_var_expression(val),
+        Expression::RootVar(val) => visitor.visit_root_var_expression(val),
+        Expression::Index(val) => visitor.visit_index_expression(val),
+        Expression::Property(val) => visitor.visit_property_expression(val),
+        Expression::Ternary(val) => visitor.visit_ternary_expression(val),
+        Expression::Binary(val) => visitor.visit_binary_expression(val),
+        Expression::Prefix(val) => visitor.visit_prefix_expression(val),
+        Expression::Postfix(val) => visitor.visit_postfix_expression(val),
+        Expression::Comma(val) => visitor.visit_comma_expression(val),
+        Expression::Table(val) => visitor.visit_table_expression(val),
+        Expression::Class(val) => visitor.visit_class_expression(val),
+        Expression::Array(val) => visitor.visit_array_expression(val),
+        Expression::Function(val) => visitor.visit_function_expression(val),
+        Expression::Lambda(val) => visitor.visit_lambda_expression(val),
+        Expression::Call(val) => visitor.visit_call_expression(val),
+        Expression::Delegate(val) => visitor.visit_delegate_expression(val),
+        Expression::Vector(val) => visitor.visit_vector_expression(val),
+        Expression::Expect(val) => visitor.visit_expect_expression(val),
+        Expression::Error(val) => visitor.visit_error_expression(val),
+    }
+}
+
+pub fn walk_parens_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ParensExpression<'s>,
+) {
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.value);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_literal_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s LiteralExpression<'s>,
+) {
+    visitor.visit_token(node.token);
+}
+
+pub fn walk_var_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s VarExpression<'s>,
+) {
+    visitor.visit_identifier(&node.name);
+}
+
+pub fn walk_root_var_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s RootVarExpression<'s>,
+) {
+    visitor.visit_token(node.root);
+    visitor.visit_identifier(&node.name);
+}
+
+pub fn walk_index_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s IndexExpression<'s>,
+) {
+    visitor.visit_expression(&node.base);
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.index);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_property_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s PropertyExpression<'s>,
+) {
+    visitor.visit_expression(&node.base);
+    visitor.visit_token(node.dot);
+    visitor.visit_method_identifier(&node.property);
+}
+
+pub fn walk_ternary_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s TernaryExpression<'s>,
+) {
+    visitor.visit_expression(&node.condition);
+    visitor.visit_token(node.question);
+    visitor.visit_expression(&node.true_value);
+    visitor.visit_token(node.separator);
+    visitor.visit_expression(&node.false_value);
+}
+
+pub fn walk_binary_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s BinaryExpression<'s>,
+) {
+    visitor.visit_expression(&node.left);
+    visitor.visit_binary_operator(&node.operator);
+    visitor.visit_expression(&node.right);
+}
+
+pub fn walk_prefix_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s PrefixExpression<'s>,
+) {
+    visitor.visit_prefix_operator(&node.operator);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_postfix_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s PostfixExpression<'s>,
+) {
+    visitor.visit_expression(&node.value);
+    visitor.visit_postfix_operator(&node.operator);
+}
+
+pub fn walk_comma_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s CommaExpression<'s>,
+) {
+    walk_separated_list1(visitor, &node.values, V::visit_expression);
+}
+
+pub fn walk_table_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s TableExpression<'s>,
+) {
+    visitor.visit_token(node.open);
+    for val in &node.slots {
+        visitor.visit_table_slot(val);
+    }
+    if let Some(token) = node.spread {
+        visitor.visit_token(token);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_class_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ClassExpression<'s>,
+) {
+    visitor.visit_token(node.class);
+    visitor.visit_class_definition(&node.definition);
+}
+
+pub fn walk_array_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ArrayExpression<'s>,
+) {
+    visitor.visit_token(node.open);
+    for val in &node.values {
+        visitor.visit_array_value(val);
+    }
+    if let Some(token) = node.spread {
+        visitor.visit_token(token);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_function_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionExpression<'s>,
+) {
+    if let Some(val) = &node.return_type {
+        visitor.visit_type(val);
+    }
+    visitor.visit_token(node.function);
+    visitor.visit_function_definition(&node.definition);
+}
+
+pub fn walk_lambda_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s LambdaExpression<'s>,
+) {
+    visitor.visit_token(node.at);
+    visitor.visit_token(node.open);
+    visitor.visit_function_params(&node.params);
+    visitor.visit_token(node.close);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_call_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s CallExpression<'s>,
+) {
+    visitor.visit_expression(&node.function);
+    visitor.visit_token(node.open);
+    for val in &node.arguments {
+        visitor.visit_call_argument(val);
+    }
+    visitor.visit_token(node.close);
+    if let Some(val) = &node.post_initializer {
+        visitor.visit_table_expression(val);
+    }
+}
+
+pub fn walk_delegate_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s DelegateExpression<'s>,
+) {
+    visitor.visit_token(node.delegate);
+    visitor.visit_expression(&node.parent);
+    visitor.visit_token(node.colon);
+    visitor.visit_expression(&node.value);
+}
+
+pub fn walk_vector_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s VectorExpression<'s>,
+) {
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.x);
+    visitor.visit_token(node.comma_1);
+    visitor.visit_expression(&node.y);
+    visitor.visit_token(node.comma_2);
+    visitor.visit_expression(&node.z);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_expect_expression<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ExpectExpression<'s>,
+) {
+    visitor.visit_token(node.expect);
+    visitor.visit_type(&node.ty);
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.value);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_error_expression<'s, V: Visit<'s> + ?Sized>(
+    _visitor: &mut V,
+    _node: &'s ErrorExpression,
+) {
+}
+
+pub fn walk_binary_operator<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s BinaryOperator<'s>,
+) {
+    match node {
+        BinaryOperator::Assign(token)
+        | BinaryOperator::AssignAdd(token)
+        | BinaryOperator::AssignSubtract(token)
+        | BinaryOperator::AssignMultiply(token)
+        | BinaryOperator::AssignDivide(token)
+        | BinaryOperator::AssignModulo(token)
+        | BinaryOperator::Add(token)
+        | BinaryOperator::Subtract(token)
+        | BinaryOperator::Multiply(token)
+        | BinaryOperator::Divide(token)
+        | BinaryOperator::Modulo(token)
+        | BinaryOperator::Equal(token)
+        | BinaryOperator::NotEqual(token)
+        | BinaryOperator::Less(token)
+        | BinaryOperator::LessEqual(token)
+        | BinaryOperator::Greater(token)
+        | BinaryOperator::GreaterEqual(token)
+        | BinaryOperator::ThreeWay(token)
+        | BinaryOperator::LogicalAnd(token)
+        | BinaryOperator::LogicalOr(token)
+        | BinaryOperator::BitwiseAnd(token)
+        | BinaryOperator::BitwiseOr(token)
+        | BinaryOperator::BitwiseXor(token)
+        | BinaryOperator::In(token)
+        | BinaryOperator::Instanceof(token) => visitor.visit_token(token),
+        BinaryOperator::AssignNewSlot(token_1, token_2)
+        | BinaryOperator::ShiftLeft(token_1, token_2)
+        | BinaryOperator::ShiftRight(token_1, token_2) => {
+            visitor.visit_token(token_1);
+            visitor.visit_token(token_2);
+        }
+        BinaryOperator::UnsignedShiftRight(token_1, token_2, token_3) => {
+            visitor.visit_token(token_1);
+            visitor.visit_token(token_2);
+            visitor.visit_token(token_3);
+        }
+    }
+}
+
+pub fn walk_prefix_operator<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s PrefixOperator<'s>,
+) {
+    match node {
+        PrefixOperator::Negate(token)
+        | PrefixOperator::LogicalNot(token)
+        | PrefixOperator::BitwiseNot(token)
+        | PrefixOperator::Typeof(token)
+        | PrefixOperator::Clone(token)
+        | PrefixOperator::Delete(token)
+        | PrefixOperator::Increment(token)
+        | PrefixOperator::Decrement(token) => visitor.visit_token(token),
+    }
+}
+
+pub fn walk_postfix_operator<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s PostfixOperator<'s>,
+) {
+    match node {
+        PostfixOperator::Increment(token) | PostfixOperator::Decrement(token) => {
+            visitor.visit_token(token)
+        }
+    }
+}
+
+pub fn walk_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s Type<'s>) {
+    match node {
+        Type::Local(val) => visitor.visit_local_type(val),
+        Type::Var(val) => visitor.visit_var_type(val),
+        Type::Plain(val) => visitor.visit_plain_type(val),
+        Type::Array(val) => visitor.visit_array_type(val),
+        Type::Generic(val) => visitor.visit_generic_type(val),
+        Type::FunctionRef(val) => visitor.visit_function_ref_type(val),
+        Type::Struct(val) => visitor.visit_struct_type(val),
+        Type::Reference(val) => visitor.visit_reference_type(val),
+        Type::Nullable(val) => visitor.visit_nullable_type(val),
+    }
+}
+
+pub fn walk_local_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s LocalType<'s>) {
+    visitor.visit_token(node.local);
+}
+
+pub fn walk_var_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s VarType<'s>) {
+    visitor.visit_token(node.var);
+}
+
+pub fn walk_plain_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s PlainType<'s>) {
+    visitor.visit_identifier(&node.name);
+}
+
+pub fn walk_array_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s ArrayType<'s>) {
+    visitor.visit_type(&node.base);
+    visitor.visit_token(node.open);
+    visitor.visit_expression(&node.len);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_generic_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s GenericType<'s>) {
+    visitor.visit_type(&node.base);
+    visitor.visit_token(node.open);
+    walk_separated_list_trailing1(visitor, &node.params, V::visit_type);
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_function_ref_type<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s FunctionRefType<'s>,
+) {
+    if let Some(val) = &node.return_type {
+        visitor.visit_type(val);
+    }
+    visitor.visit_token(node.functionref);
+    visitor.visit_token(node.open);
+    if let Some(list) = &node.params {
+        walk_separated_list_trailing1(visitor, list, V::visit_function_ref_param);
+    }
+    visitor.visit_token(node.close);
+}
+
+pub fn walk_struct_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s StructType<'s>) {
+    visitor.visit_token(node.struct_);
+    visitor.visit_struct_definition(&node.definition);
+}
+
+pub fn walk_reference_type<'s, V: Visit<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &'s ReferenceType<'s>,
+) {
+    visitor.visit_type(&node.base);
+    visitor.visit_token(node.reference);
+}
+
+pub fn walk_nullable_type<'s, V: Visit<'s> + ?Sized>(visitor: &mut V, node: &'s NullableType<'s>) {
+    visitor.visit_type(&node.base);
+    visitor.visit_token(node.ornull);
+}
+
+fn walk_separated_list1<'s, V: Visit<'s> + ?Sized, T>(
+    visitor: &mut V,
+    list: &'s SeparatedList1<'s, T>,
+    visit: fn(&mut V, &'s T),
+) {
+    for (item, separator) in &list.items {
+        visit(visitor, item);
+        visitor.visit_token(separator);
+    }
+    visit(visitor, &list.last_item);
+}
+
+fn walk_separated_list_trailing1<'s, V: Visit<'s> + ?Sized, T>(
+    visitor: &mut V,
+    list: &'s SeparatedListTrailing1<'s, T>,
+    visit: fn(&mut V, &'s T),
+) {
+    for (item, separator) in &list.items {
+        visit(visitor, item);
+        visitor.visit_token(separator);
+    }
+    visit(visitor, &list.last_item);
+    if let Some(trailing) = list.trailing {
+        visitor.visit_token(trailing);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::{walk_var_expression, VarExpression, Visit};
+    use crate::token::Token;
+    use crate::{parse, tokenize, Flavor};
+
+    #[derive(Default)]
+    struct Collector<'s> {
+        tokens: Vec<&'s Token<'s>>,
+        vars: Vec<&'s str>,
+    }
+
+    impl<'s> Visit<'s> for Collector<'s> {
+        fn visit_var_expression(&mut self, node: &'s VarExpression<'s>) {
+            self.vars.push(node.name.value);
+            walk_var_expression(self, node);
+        }
+
+        fn visit_token(&mut self, token: &'s Token<'s>) {
+            self.tokens.push(token);
+        }
+    }
+
+    #[test]
+    fn visits_tokens_in_order() {
+        let source = include_str!("../../examples/print_ast_script.nut");
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+
+        let mut collector = Collector::default();
+        collector.visit_program(&program);
+
+        assert_eq!(collector.tokens.len(), tokens.len());
+        for (visited, item) in collector.tokens.iter().zip(&tokens) {
+            assert!(std::ptr::eq(*visited, &item.token));
+        }
+    }
+
+    #[test]
+    fn visits_nested_nodes() {
+        let source = "a = b[c](@(d) e + f)";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+
+        let mut collector = Collector::default();
+        collector.visit_program(&program);
+        assert_eq!(collector.vars, ["a", "b", "c", "e", "f"]);
+    }
+}
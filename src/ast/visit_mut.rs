@@ -0,0 +1,1782 @@
+use crate::ast::{
+    ArrayExpression, ArrayType, ArrayValue, BinaryExpression, BinaryOperator, BlockStatement,
+    BreakStatement, CallArgument, CallExpression, ClassDefinition, ClassDefinitionStatement,
+    ClassExpression, ClassExtends, ClassMember, CommaExpression, ConditionalBranch,
+    ConditionalStatement, ConstDefinitionStatement, ConstructorDefinitionStatement,
+    ContinueStatement, DelayThreadStatement, DelegateExpression, DoWhileStatement, EmptyStatement,
+    EnumDefinitionStatement, EnumEntry, ErrorExpression, ErrorStatement, ExpectExpression,
+    Expression, ExpressionStatement, ForDefinition, ForStatement, ForeachIndex, ForeachStatement,
+    FunctionCaptures, FunctionDefinition, FunctionDefinitionStatement, FunctionEnvironment,
+    FunctionExpression, FunctionParam, FunctionParams, FunctionRefParam, FunctionRefType,
+    GenericType, GlobalDefinition, GlobalStatement, GlobalizeAllFunctionsStatement, Identifier,
+    IfStatement, IfStatementType, IndexExpression, LambdaExpression, LiteralExpression, LocalType,
+    MethodIdentifier, NullableType, ParensExpression, PlainType, PostfixExpression,
+    PostfixOperator, PrefixExpression, PrefixOperator, Program, PropertyExpression, ReferenceType,
+    ReturnStatement, RootVarExpression, SeparatedList1, SeparatedListTrailing1, Slot, Statement,
+    StatementType, StructDefinition, StructDefinitionStatement, StructProperty, StructType,
+    SwitchCase, SwitchCaseCondition, SwitchStatement, TableExpression, TableSlot, TableSlotType,
+    TernaryExpression, ThreadStatement, ThrowStatement, TryCatchStatement, Type,
+    TypeDefinitionStatement, UntypedStatement, VarDefinition, VarDefinitionStatement,
+    VarExpression, VarInitializer, VarType, VectorExpression, WaitStatement,
+    WaitThreadSoloStatement, WaitThreadStatement, WhileStatement, YieldStatement,
+};
+use crate::token::Token;
+
+/// Visits nodes in a syntax tree by mutable reference, allowing the tree to be rewritten in place.
+///
+/// This is the mutable counterpart to [`Visit`]. There is a `visit_*_mut` method for each node
+/// type, which defaults to calling the matching `walk_*_mut` function to visit the node's children
+/// in source order. A method can replace the node it is given entirely, or modify its children
+/// before or after walking them.
+///
+/// Nodes reference tokens with the lifetime of the token list they were parsed from. Tokens for new
+/// nodes can be created with a [`TokenArena`], which must outlive the tree. A rewritten tree can
+/// still be printed with [`print_program`], which uses the original source text for tokens that
+/// came from the source and generates text for the rest.
+///
+/// # Example
+/// ```
+/// use sqparse::ast::{
+///     walk_expression_mut, Expression, Identifier, LiteralExpression, PlainType, VisitMut,
+/// };
+/// use sqparse::token::{LiteralToken, TokenArena, TokenType};
+/// use sqparse::{parse, print_program, tokenize, Flavor};
+///
+/// struct Rewriter<'s> {
+///     arena: &'s TokenArena<'s>,
+/// }
+///
+/// impl<'s> VisitMut<'s> for Rewriter<'s> {
+///     // Rename `int` types to `float`.
+///     fn visit_plain_type_mut(&mut self, node: &mut PlainType<'s>) {
+///         if node.name.value == "int" {
+///             let token = self.arena.replace(node.name.token, TokenType::Identifier("float"));
+///             node.name = Identifier { value: "float", token };
+///         }
+///     }
+///
+///     // Replace calls to `Zero()` with `0.0`.
+///     fn visit_expression_mut(&mut self, node: &mut Expression<'s>) {
+///         if let Expression::Call(call) = node {
+///             if let Expression::Var(var) = &*call.function {
+///                 if var.name.value == "Zero" && call.arguments.is_empty() {
+///                     let literal = LiteralToken::Float(0.0);
+///                     let token = self.arena.replace(var.name.token, TokenType::Literal(literal));
+///                     *node = Expression::Literal(LiteralExpression { literal, token });
+///                 }
+///             }
+///         }
+///         walk_expression_mut(self, node);
+///     }
+/// }
+///
+/// let source = "int function Add( int a ) {\n    return Zero() + a\n}";
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let arena = TokenArena::new();
+/// let mut program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// Rewriter { arena: &arena }.visit_program_mut(&mut program);
+/// assert_eq!(
+///     print_program(&program, source),
+///     "float function Add( float a ) {\n    return 0.0 + a\n}"
+/// );
+/// ```
+///
+/// [`Visit`]: crate::ast::Visit
+/// [`TokenArena`]: crate::token::TokenArena
+/// [`print_program`]: crate::print_program
+pub trait VisitMut<'s> {
+    fn visit_program_mut(&mut self, node: &mut Program<'s>) {
+        walk_program_mut(self, node);
+    }
+
+    fn visit_statement_mut(&mut self, node: &mut Statement<'s>) {
+        walk_statement_mut(self, node);
+    }
+
+    fn visit_statement_type_mut(&mut self, node: &mut StatementType<'s>) {
+        walk_statement_type_mut(self, node);
+    }
+
+    fn visit_empty_statement_mut(&mut self, node: &mut EmptyStatement<'s>) {
+        walk_empty_statement_mut(self, node);
+    }
+
+    fn visit_block_statement_mut(&mut self, node: &mut BlockStatement<'s>) {
+        walk_block_statement_mut(self, node);
+    }
+
+    fn visit_if_statement_mut(&mut self, node: &mut IfStatement<'s>) {
+        walk_if_statement_mut(self, node);
+    }
+
+    fn visit_while_statement_mut(&mut self, node: &mut WhileStatement<'s>) {
+        walk_while_statement_mut(self, node);
+    }
+
+    fn visit_do_while_statement_mut(&mut self, node: &mut DoWhileStatement<'s>) {
+        walk_do_while_statement_mut(self, node);
+    }
+
+    fn visit_switch_statement_mut(&mut self, node: &mut SwitchStatement<'s>) {
+        walk_switch_statement_mut(self, node);
+    }
+
+    fn visit_for_statement_mut(&mut self, node: &mut ForStatement<'s>) {
+        walk_for_statement_mut(self, node);
+    }
+
+    fn visit_foreach_statement_mut(&mut self, node: &mut ForeachStatement<'s>) {
+        walk_foreach_statement_mut(self, node);
+    }
+
+    fn visit_break_statement_mut(&mut self, node: &mut BreakStatement<'s>) {
+        walk_break_statement_mut(self, node);
+    }
+
+    fn visit_continue_statement_mut(&mut self, node: &mut ContinueStatement<'s>) {
+        walk_continue_statement_mut(self, node);
+    }
+
+    fn visit_return_statement_mut(&mut self, node: &mut ReturnStatement<'s>) {
+        walk_return_statement_mut(self, node);
+    }
+
+    fn visit_yield_statement_mut(&mut self, node: &mut YieldStatement<'s>) {
+        walk_yield_statement_mut(self, node);
+    }
+
+    fn visit_var_definition_statement_mut(&mut self, node: &mut VarDefinitionStatement<'s>) {
+        walk_var_definition_statement_mut(self, node);
+    }
+
+    fn visit_constructor_definition_statement_mut(
+        &mut self,
+        node: &mut ConstructorDefinitionStatement<'s>,
+    ) {
+        walk_constructor_definition_statement_mut(self, node);
+    }
+
+    fn visit_function_definition_statement_mut(
+        &mut self,
+        node: &mut FunctionDefinitionStatement<'s>,
+    ) {
+        walk_function_definition_statement_mut(self, node);
+    }
+
+    fn visit_class_definition_statement_mut(&mut self, node: &mut ClassDefinitionStatement<'s>) {
+        walk_class_definition_statement_mut(self, node);
+    }
+
+    fn visit_try_catch_statement_mut(&mut self, node: &mut TryCatchStatement<'s>) {
+        walk_try_catch_statement_mut(self, node);
+    }
+
+    fn visit_throw_statement_mut(&mut self, node: &mut ThrowStatement<'s>) {
+        walk_throw_statement_mut(self, node);
+    }
+
+    fn visit_const_definition_statement_mut(&mut self, node: &mut ConstDefinitionStatement<'s>) {
+        walk_const_definition_statement_mut(self, node);
+    }
+
+    fn visit_enum_definition_statement_mut(&mut self, node: &mut EnumDefinitionStatement<'s>) {
+        walk_enum_definition_statement_mut(self, node);
+    }
+
+    fn visit_expression_statement_mut(&mut self, node: &mut ExpressionStatement<'s>) {
+        walk_expression_statement_mut(self, node);
+    }
+
+    fn visit_thread_statement_mut(&mut self, node: &mut ThreadStatement<'s>) {
+        walk_thread_statement_mut(self, node);
+    }
+
+    fn visit_delay_thread_statement_mut(&mut self, node: &mut DelayThreadStatement<'s>) {
+        walk_delay_thread_statement_mut(self, node);
+    }
+
+    fn visit_wait_thread_statement_mut(&mut self, node: &mut WaitThreadStatement<'s>) {
+        walk_wait_thread_statement_mut(self, node);
+    }
+
+    fn visit_wait_thread_solo_statement_mut(&mut self, node: &mut WaitThreadSoloStatement<'s>) {
+        walk_wait_thread_solo_statement_mut(self, node);
+    }
+
+    fn visit_wait_statement_mut(&mut self, node: &mut WaitStatement<'s>) {
+        walk_wait_statement_mut(self, node);
+    }
+
+    fn visit_struct_definition_statement_mut(&mut self, node: &mut StructDefinitionStatement<'s>) {
+        walk_struct_definition_statement_mut(self, node);
+    }
+
+    fn visit_type_definition_statement_mut(&mut self, node: &mut TypeDefinitionStatement<'s>) {
+        walk_type_definition_statement_mut(self, node);
+    }
+
+    fn visit_global_statement_mut(&mut self, node: &mut GlobalStatement<'s>) {
+        walk_global_statement_mut(self, node);
+    }
+
+    fn visit_globalize_all_functions_statement_mut(
+        &mut self,
+        node: &mut GlobalizeAllFunctionsStatement<'s>,
+    ) {
+        walk_globalize_all_functions_statement_mut(self, node);
+    }
+
+    fn visit_untyped_statement_mut(&mut self, node: &mut UntypedStatement<'s>) {
+        walk_untyped_statement_mut(self, node);
+    }
+
+    fn visit_conditional_statement_mut(&mut self, node: &mut ConditionalStatement<'s>) {
+        walk_conditional_statement_mut(self, node);
+    }
+
+    fn visit_conditional_branch_mut(&mut self, node: &mut ConditionalBranch<'s>) {
+        walk_conditional_branch_mut(self, node);
+    }
+
+    fn visit_error_statement_mut(&mut self, node: &mut ErrorStatement<'s>) {
+        walk_error_statement_mut(self, node);
+    }
+
+    fn visit_if_statement_type_mut(&mut self, node: &mut IfStatementType<'s>) {
+        walk_if_statement_type_mut(self, node);
+    }
+
+    fn visit_switch_case_mut(&mut self, node: &mut SwitchCase<'s>) {
+        walk_switch_case_mut(self, node);
+    }
+
+    fn visit_switch_case_condition_mut(&mut self, node: &mut SwitchCaseCondition<'s>) {
+        walk_switch_case_condition_mut(self, node);
+    }
+
+    fn visit_for_definition_mut(&mut self, node: &mut ForDefinition<'s>) {
+        walk_for_definition_mut(self, node);
+    }
+
+    fn visit_foreach_index_mut(&mut self, node: &mut ForeachIndex<'s>) {
+        walk_foreach_index_mut(self, node);
+    }
+
+    fn visit_enum_entry_mut(&mut self, node: &mut EnumEntry<'s>) {
+        walk_enum_entry_mut(self, node);
+    }
+
+    fn visit_global_definition_mut(&mut self, node: &mut GlobalDefinition<'s>) {
+        walk_global_definition_mut(self, node);
+    }
+
+    fn visit_var_definition_mut(&mut self, node: &mut VarDefinition<'s>) {
+        walk_var_definition_mut(self, node);
+    }
+
+    fn visit_var_initializer_mut(&mut self, node: &mut VarInitializer<'s>) {
+        walk_var_initializer_mut(self, node);
+    }
+
+    fn visit_class_definition_mut(&mut self, node: &mut ClassDefinition<'s>) {
+        walk_class_definition_mut(self, node);
+    }
+
+    fn visit_class_extends_mut(&mut self, node: &mut ClassExtends<'s>) {
+        walk_class_extends_mut(self, node);
+    }
+
+    fn visit_class_member_mut(&mut self, node: &mut ClassMember<'s>) {
+        walk_class_member_mut(self, node);
+    }
+
+    fn visit_struct_definition_mut(&mut self, node: &mut StructDefinition<'s>) {
+        walk_struct_definition_mut(self, node);
+    }
+
+    fn visit_struct_property_mut(&mut self, node: &mut StructProperty<'s>) {
+        walk_struct_property_mut(self, node);
+    }
+
+    fn visit_function_definition_mut(&mut self, node: &mut FunctionDefinition<'s>) {
+        walk_function_definition_mut(self, node);
+    }
+
+    fn visit_function_environment_mut(&mut self, node: &mut FunctionEnvironment<'s>) {
+        walk_function_environment_mut(self, node);
+    }
+
+    fn visit_function_params_mut(&mut self, node: &mut FunctionParams<'s>) {
+        walk_function_params_mut(self, node);
+    }
+
+    fn visit_function_param_mut(&mut self, node: &mut FunctionParam<'s>) {
+        walk_function_param_mut(self, node);
+    }
+
+    fn visit_function_captures_mut(&mut self, node: &mut FunctionCaptures<'s>) {
+        walk_function_captures_mut(self, node);
+    }
+
+    fn visit_function_ref_param_mut(&mut self, node: &mut FunctionRefParam<'s>) {
+        walk_function_ref_param_mut(self, node);
+    }
+
+    fn visit_slot_mut(&mut self, node: &mut Slot<'s>) {
+        walk_slot_mut(self, node);
+    }
+
+    fn visit_table_slot_mut(&mut self, node: &mut TableSlot<'s>) {
+        walk_table_slot_mut(self, node);
+    }
+
+    fn visit_table_slot_type_mut(&mut self, node: &mut TableSlotType<'s>) {
+        walk_table_slot_type_mut(self, node);
+    }
+
+    fn visit_array_value_mut(&mut self, node: &mut ArrayValue<'s>) {
+        walk_array_value_mut(self, node);
+    }
+
+    fn visit_call_argument_mut(&mut self, node: &mut CallArgument<'s>) {
+        walk_call_argument_mut(self, node);
+    }
+
+    fn visit_identifier_mut(&mut self, node: &mut Identifier<'s>) {
+        walk_identifier_mut(self, node);
+    }
+
+    fn visit_method_identifier_mut(&mut self, node: &mut MethodIdentifier<'s>) {
+        walk_method_identifier_mut(self, node);
+    }
+
+    fn visit_expression_mut(&mut self, node: &mut Expression<'s>) {
+        walk_expression_mut(self, node);
+    }
+
+    fn visit_parens_expression_mut(&mut self, node: &mut ParensExpression<'s>) {
+        walk_parens_expression_mut(self, node);
+    }
+
+    fn visit_literal_expression_mut(&mut self, node: &mut LiteralExpression<'s>) {
+        walk_literal_expression_mut(self, node);
+    }
+
+    fn visit_var_expression_mut(&mut self, node: &mut VarExpression<'s>) {
+        walk_var_expression_mut(self, node);
+    }
+
+    fn visit_root_var_expression_mut(&mut self, node: &mut RootVarExpression<'s>) {
+        walk_root_var_expression_mut(self, node);
+    }
+
+    fn visit_index_expression_mut(&mut self, node: &mut IndexExpression<'s>) {
+        walk_index_expression_mut(self, node);
+    }
+
+    fn visit_property_expression_mut(&mut self, node: &mut PropertyExpression<'s>) {
+        walk_property_expression_mut(self, node);
+    }
+
+    fn visit_ternary_expression_mut(&mut self, node: &mut TernaryExpression<'s>) {
+        walk_ternary_expression_mut(self, node);
+    }
+
+    fn visit_binary_expression_mut(&mut self, node: &mut BinaryExpression<'s>) {
+        walk_binary_expression_mut(self, node);
+    }
+
+    fn visit_prefix_expression_mut(&mut self, node: &mut PrefixExpression<'s>) {
+        walk_prefix_expression_mut(self, node);
+    }
+
+    fn visit_postfix_expression_mut(&mut self, node: &mut PostfixExpression<'s>) {
+        walk_postfix_expression_mut(self, node);
+    }
+
+    fn visit_comma_expression_mut(&mut self, node: &mut CommaExpression<'s>) {
+        walk_comma_expression_mut(self, node);
+    }
+
+    fn visit_table_expression_mut(&mut self, node: &mut TableExpression<'s>) {
+        walk_table_expression_mut(self, node);
+    }
+
+    fn visit_class_expression_mut(&mut self, node: &mut ClassExpression<'s>) {
+        walk_class_expression_mut(self, node);
+    }
+
+    fn visit_array_expression_mut(&mut self, node: &mut ArrayExpression<'s>) {
+        walk_array_expression_mut(self, node);
+    }
+
+    fn visit_function_expression_mut(&mut self, node: &mut FunctionExpression<'s>) {
+        walk_function_expression_mut(self, node);
+    }
+
+    fn visit_lambda_expression_mut(&mut self, node: &mut LambdaExpression<'s>) {
+        walk_lambda_expression_mut(self, node);
+    }
+
+    fn visit_call_expression_mut(&mut self, node: &mut CallExpression<'s>) {
+        walk_call_expression_mut(self, node);
+    }
+
+    fn visit_delegate_expression_mut(&mut self, node: &mut DelegateExpression<'s>) {
+        walk_delegate_expression_mut(self, node);
+    }
+
+    fn visit_vector_expression_mut(&mut self, node: &mut VectorExpression<'s>) {
+        walk_vector_expression_mut(self, node);
+    }
+
+    fn visit_expect_expression_mut(&mut self, node: &mut ExpectExpression<'s>) {
+        walk_expect_expression_mut(self, node);
+    }
+
+    fn visit_error_expression_mut(&mut self, node: &mut ErrorExpression) {
+        walk_error_expression_mut(self, node);
+    }
+
+    fn visit_binary_operator_mut(&mut self, node: &mut BinaryOperator<'s>) {
+        walk_binary_operator_mut(self, node);
+    }
+
+    fn visit_prefix_operator_mut(&mut self, node: &mut PrefixOperator<'s>) {
+        walk_prefix_operator_mut(self, node);
+    }
+
+    fn visit_postfix_operator_mut(&mut self, node: &mut PostfixOperator<'s>) {
+        walk_postfix_operator_mut(self, node);
+    }
+
+    fn visit_type_mut(&mut self, node: &mut Type<'s>) {
+        walk_type_mut(self, node);
+    }
+
+    fn visit_local_type_mut(&mut self, node: &mut LocalType<'s>) {
+        walk_local_type_mut(self, node);
+    }
+
+    fn visit_var_type_mut(&mut self, node: &mut VarType<'s>) {
+        walk_var_type_mut(self, node);
+    }
+
+    fn visit_plain_type_mut(&mut self, node: &mut PlainType<'s>) {
+        walk_plain_type_mut(self, node);
+    }
+
+    fn visit_array_type_mut(&mut self, node: &mut ArrayType<'s>) {
+        walk_array_type_mut(self, node);
+    }
+
+    fn visit_generic_type_mut(&mut self, node: &mut GenericType<'s>) {
+        walk_generic_type_mut(self, node);
+    }
+
+    fn visit_function_ref_type_mut(&mut self, node: &mut FunctionRefType<'s>) {
+        walk_function_ref_type_mut(self, node);
+    }
+
+    fn visit_struct_type_mut(&mut self, node: &mut StructType<'s>) {
+        walk_struct_type_mut(self, node);
+    }
+
+    fn visit_reference_type_mut(&mut self, node: &mut ReferenceType<'s>) {
+        walk_reference_type_mut(self, node);
+    }
+
+    fn visit_nullable_type_mut(&mut self, node: &mut NullableType<'s>) {
+        walk_nullable_type_mut(self, node);
+    }
+
+    fn visit_token_mut(&mut self, _token: &mut &'s Token<'s>) {}
+}
+
+pub fn walk_program_mut<'s, V: VisitMut<'s> + ?Sized>(visitor: &mut V, node: &mut Program<'s>) {
+    for val in &mut node.statements {
+        visitor.visit_statement_mut(val);
+    }
+}
+
+pub fn walk_statement_mut<'s, V: VisitMut<'s> + ?Sized>(visitor: &mut V, node: &mut Statement<'s>) {
+    visitor.visit_statement_type_mut(&mut node.ty);
+    if let Some(token) = &mut node.semicolon {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_statement_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut StatementType<'s>,
+) {
+    match node {
+        StatementType::Empty(val) => visitor.visit_empty_statement_mut(val),
+        StatementType::Block(val) => visitor.visit_block_statement_mut(val),
+        StatementType::If(val) => visitor.visit_if_statement_mut(val),
+        StatementType::While(val) => visitor.visit_while_statement_mut(val),
+        StatementType::DoWhile(val) => visitor.visit_do_while_statement_mut(val),
+        StatementType::Switch(val) => visitor.visit_switch_statement_mut(val),
+        StatementType::For(val) => visitor.visit_for_statement_mut(val),
+        StatementType::Foreach(val) => visitor.visit_foreach_statement_mut(val),
+        StatementType::Break(val) => visitor.visit_break_statement_mut(val),
+        StatementType::Continue(val) => visitor.visit_continue_statement_mut(val),
+        StatementType::Return(val) => visitor.visit_return_statement_mut(val),
+        StatementType::Yield(val) => visitor.visit_yield_statement_mut(val),
+        StatementType::VarDefinition(val) => visitor.visit_var_definition_statement_mut(val),
+        StatementType::ConstructorDefinition(val) => {
+            visitor.visit_constructor_definition_statement_mut(val)
+        }
+        StatementType::FunctionDefinition(val) => {
+            visitor.visit_function_definition_statement_mut(val)
+        }
+        StatementType::ClassDefinition(val) => visitor.visit_class_definition_statement_mut(val),
+        StatementType::TryCatch(val) => visitor.visit_try_catch_statement_mut(val),
+        StatementType::Throw(val) => visitor.visit_throw_statement_mut(val),
+        StatementType::Const(val) => visitor.visit_const_definition_statement_mut(val),
+        StatementType::EnumDefinition(val) => visitor.visit_enum_definition_statement_mut(val),
+        StatementType::Expression(val) => visitor.visit_expression_statement_mut(val),
+        StatementType::Thread(val) => visitor.visit_thread_statement_mut(val),
+        StatementType::DelayThread(val) => visitor.visit_delay_thread_statement_mut(val),
+        StatementType::WaitThread(val) => visitor.visit_wait_thread_statement_mut(val),
+        StatementType::WaitThreadSolo(val) => visitor.visit_wait_thread_solo_statement_mut(val),
+        StatementType::Wait(val) => visitor.visit_wait_statement_mut(val),
+        StatementType::StructDefinition(val) => visitor.visit_struct_definition_statement_mut(val),
+        StatementType::TypeDefinition(val) => visitor.visit_type_definition_statement_mut(val),
+        StatementType::Global(val) => visitor.visit_global_statement_mut(val),
+        StatementType::GlobalizeAllFunctions(val) => {
+            visitor.visit_globalize_all_functions_statement_mut(val)
+        }
+        StatementType::Untyped(val) => visitor.visit_untyped_statement_mut(val),
+        StatementType::Conditional(val) => visitor.visit_conditional_statement_mut(val),
+        StatementType::Error(val) => visitor.visit_error_statement_mut(val),
+    }
+}
+
+pub fn walk_empty_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut EmptyStatement<'s>,
+) {
+    if let Some(token) = &mut node.empty {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_block_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut BlockStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.open);
+    for val in &mut node.statements {
+        visitor.visit_statement_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_if_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut IfStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.if_);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.condition);
+    visitor.visit_token_mut(&mut node.close);
+    visitor.visit_if_statement_type_mut(&mut node.ty);
+}
+
+pub fn walk_while_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut WhileStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.while_);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.condition);
+    visitor.visit_token_mut(&mut node.close);
+    visitor.visit_statement_type_mut(&mut node.body);
+}
+
+pub fn walk_do_while_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut DoWhileStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.do_);
+    visitor.visit_statement_mut(&mut node.body);
+    visitor.visit_token_mut(&mut node.while_);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.condition);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_switch_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut SwitchStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.switch);
+    visitor.visit_token_mut(&mut node.open_condition);
+    visitor.visit_expression_mut(&mut node.condition);
+    visitor.visit_token_mut(&mut node.close_condition);
+    visitor.visit_token_mut(&mut node.open_cases);
+    for val in &mut node.cases {
+        visitor.visit_switch_case_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.close_cases);
+}
+
+pub fn walk_for_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ForStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.for_);
+    visitor.visit_token_mut(&mut node.open);
+    if let Some(val) = &mut node.initializer {
+        visitor.visit_for_definition_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.semicolon_1);
+    if let Some(val) = &mut node.condition {
+        visitor.visit_expression_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.semicolon_2);
+    if let Some(val) = &mut node.increment {
+        visitor.visit_expression_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.close);
+    visitor.visit_statement_type_mut(&mut node.body);
+}
+
+pub fn walk_foreach_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ForeachStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.foreach);
+    visitor.visit_token_mut(&mut node.open);
+    if let Some(val) = &mut node.index {
+        visitor.visit_foreach_index_mut(val);
+    }
+    if let Some(val) = &mut node.value_type {
+        visitor.visit_type_mut(val);
+    }
+    visitor.visit_identifier_mut(&mut node.value_name);
+    visitor.visit_token_mut(&mut node.in_);
+    visitor.visit_expression_mut(&mut node.array);
+    visitor.visit_token_mut(&mut node.close);
+    visitor.visit_statement_type_mut(&mut node.body);
+}
+
+pub fn walk_break_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut BreakStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.break_);
+}
+
+pub fn walk_continue_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ContinueStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.continue_);
+}
+
+pub fn walk_return_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ReturnStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.return_);
+    if let Some(val) = &mut node.value {
+        visitor.visit_expression_mut(val);
+    }
+}
+
+pub fn walk_yield_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut YieldStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.yield_);
+    if let Some(val) = &mut node.value {
+        visitor.visit_expression_mut(val);
+    }
+}
+
+pub fn walk_var_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut VarDefinitionStatement<'s>,
+) {
+    visitor.visit_type_mut(&mut node.type_);
+    walk_separated_list_trailing1_mut(visitor, &mut node.definitions, V::visit_var_definition_mut);
+}
+
+pub fn walk_constructor_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ConstructorDefinitionStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.function);
+    for (val, token) in &mut node.namespaces {
+        visitor.visit_identifier_mut(val);
+        visitor.visit_token_mut(token);
+    }
+    visitor.visit_identifier_mut(&mut node.last_name);
+    visitor.visit_token_mut(&mut node.last_namespace);
+    visitor.visit_token_mut(&mut node.constructor);
+    visitor.visit_function_definition_mut(&mut node.definition);
+}
+
+pub fn walk_function_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionDefinitionStatement<'s>,
+) {
+    if let Some(val) = &mut node.return_type {
+        visitor.visit_type_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.function);
+    walk_separated_list1_mut(visitor, &mut node.name, V::visit_identifier_mut);
+    visitor.visit_function_definition_mut(&mut node.definition);
+}
+
+pub fn walk_class_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ClassDefinitionStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.class);
+    visitor.visit_expression_mut(&mut node.name);
+    visitor.visit_class_definition_mut(&mut node.definition);
+}
+
+pub fn walk_try_catch_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut TryCatchStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.try_);
+    visitor.visit_statement_mut(&mut node.body);
+    visitor.visit_token_mut(&mut node.catch);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_identifier_mut(&mut node.catch_name);
+    visitor.visit_token_mut(&mut node.close);
+    visitor.visit_statement_type_mut(&mut node.catch_body);
+}
+
+pub fn walk_throw_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ThrowStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.throw);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_const_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ConstDefinitionStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.const_);
+    if let Some(val) = &mut node.const_type {
+        visitor.visit_type_mut(val);
+    }
+    visitor.visit_identifier_mut(&mut node.name);
+    visitor.visit_var_initializer_mut(&mut node.initializer);
+}
+
+pub fn walk_enum_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut EnumDefinitionStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.enum_);
+    visitor.visit_identifier_mut(&mut node.name);
+    visitor.visit_token_mut(&mut node.open);
+    for val in &mut node.entries {
+        visitor.visit_enum_entry_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_expression_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ExpressionStatement<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_thread_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ThreadStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.thread);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_delay_thread_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut DelayThreadStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.delay_thread);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.duration);
+    visitor.visit_token_mut(&mut node.close);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_wait_thread_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut WaitThreadStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.wait_thread);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_wait_thread_solo_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut WaitThreadSoloStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.wait_thread_solo);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_wait_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut WaitStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.wait);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_struct_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut StructDefinitionStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.struct_);
+    visitor.visit_identifier_mut(&mut node.name);
+    visitor.visit_struct_definition_mut(&mut node.definition);
+}
+
+pub fn walk_type_definition_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut TypeDefinitionStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.typedef);
+    visitor.visit_identifier_mut(&mut node.name);
+    visitor.visit_type_mut(&mut node.type_);
+}
+
+pub fn walk_global_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut GlobalStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.global);
+    visitor.visit_global_definition_mut(&mut node.definition);
+}
+
+pub fn walk_globalize_all_functions_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut GlobalizeAllFunctionsStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.globalize_all_functions);
+}
+
+pub fn walk_untyped_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut UntypedStatement<'s>,
+) {
+    visitor.visit_token_mut(&mut node.untyped);
+}
+
+pub fn walk_conditional_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ConditionalStatement<'s>,
+) {
+    visitor.visit_conditional_branch_mut(&mut node.if_);
+    for val in &mut node.else_ifs {
+        visitor.visit_conditional_branch_mut(val);
+    }
+    if let Some(statements) = &mut node.else_ {
+        for val in statements {
+            visitor.visit_statement_mut(val);
+        }
+    }
+}
+
+pub fn walk_conditional_branch_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ConditionalBranch<'s>,
+) {
+    for val in &mut node.statements {
+        visitor.visit_statement_mut(val);
+    }
+}
+
+pub fn walk_error_statement_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ErrorStatement<'s>,
+) {
+    for token in &mut node.tokens {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_if_statement_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut IfStatementType<'s>,
+) {
+    match node {
+        IfStatementType::NoElse { body } => visitor.visit_statement_type_mut(body),
+        IfStatementType::Else {
+            body,
+            else_,
+            else_body,
+        } => {
+            visitor.visit_statement_mut(body);
+            visitor.visit_token_mut(else_);
+            visitor.visit_statement_type_mut(else_body);
+        }
+    }
+}
+
+pub fn walk_switch_case_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut SwitchCase<'s>,
+) {
+    visitor.visit_switch_case_condition_mut(&mut node.condition);
+    visitor.visit_token_mut(&mut node.colon);
+    for val in &mut node.body {
+        visitor.visit_statement_mut(val);
+    }
+}
+
+pub fn walk_switch_case_condition_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut SwitchCaseCondition<'s>,
+) {
+    match node {
+        SwitchCaseCondition::Default { default } => visitor.visit_token_mut(default),
+        SwitchCaseCondition::Case { case, value } => {
+            visitor.visit_token_mut(case);
+            visitor.visit_expression_mut(value);
+        }
+    }
+}
+
+pub fn walk_for_definition_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ForDefinition<'s>,
+) {
+    match node {
+        ForDefinition::Expression(value) => visitor.visit_expression_mut(value),
+        ForDefinition::Definition(definition) => {
+            visitor.visit_var_definition_statement_mut(definition)
+        }
+    }
+}
+
+pub fn walk_foreach_index_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ForeachIndex<'s>,
+) {
+    if let Some(val) = &mut node.type_ {
+        visitor.visit_type_mut(val);
+    }
+    visitor.visit_identifier_mut(&mut node.name);
+    visitor.visit_token_mut(&mut node.comma);
+}
+
+pub fn walk_enum_entry_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut EnumEntry<'s>,
+) {
+    visitor.visit_identifier_mut(&mut node.name);
+    if let Some(val) = &mut node.initializer {
+        visitor.visit_var_initializer_mut(val);
+    }
+    if let Some(token) = &mut node.comma {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_global_definition_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut GlobalDefinition<'s>,
+) {
+    match node {
+        GlobalDefinition::Function { function, name } => {
+            visitor.visit_token_mut(function);
+            visitor.visit_identifier_mut(name);
+        }
+        GlobalDefinition::UntypedVar { name, initializer } => {
+            visitor.visit_identifier_mut(name);
+            visitor.visit_var_initializer_mut(initializer);
+        }
+        GlobalDefinition::TypedVar(definition) => {
+            visitor.visit_var_definition_statement_mut(definition)
+        }
+        GlobalDefinition::Const(definition) => {
+            visitor.visit_const_definition_statement_mut(definition)
+        }
+        GlobalDefinition::Enum(definition) => {
+            visitor.visit_enum_definition_statement_mut(definition)
+        }
+        GlobalDefinition::Class(definition) => {
+            visitor.visit_class_definition_statement_mut(definition)
+        }
+        GlobalDefinition::Struct(definition) => {
+            visitor.visit_struct_definition_statement_mut(definition)
+        }
+        GlobalDefinition::Type(definition) => {
+            visitor.visit_type_definition_statement_mut(definition)
+        }
+    }
+}
+
+pub fn walk_var_definition_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut VarDefinition<'s>,
+) {
+    visitor.visit_identifier_mut(&mut node.name);
+    if let Some(val) = &mut node.initializer {
+        visitor.visit_var_initializer_mut(val);
+    }
+}
+
+pub fn walk_var_initializer_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut VarInitializer<'s>,
+) {
+    visitor.visit_token_mut(&mut node.assign);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_class_definition_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ClassDefinition<'s>,
+) {
+    if let Some(val) = &mut node.extends {
+        visitor.visit_class_extends_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.open);
+    for val in &mut node.members {
+        visitor.visit_class_member_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_class_extends_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ClassExtends<'s>,
+) {
+    visitor.visit_token_mut(&mut node.extends);
+    visitor.visit_expression_mut(&mut node.name);
+}
+
+pub fn walk_class_member_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ClassMember<'s>,
+) {
+    if let Some(val) = &mut node.attributes {
+        visitor.visit_table_expression_mut(val);
+    }
+    if let Some(token) = &mut node.static_ {
+        visitor.visit_token_mut(token);
+    }
+    visitor.visit_slot_mut(&mut node.slot);
+    if let Some(token) = &mut node.semicolon {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_struct_definition_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut StructDefinition<'s>,
+) {
+    visitor.visit_token_mut(&mut node.open);
+    for val in &mut node.properties {
+        visitor.visit_struct_property_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_struct_property_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut StructProperty<'s>,
+) {
+    visitor.visit_type_mut(&mut node.type_);
+    visitor.visit_identifier_mut(&mut node.name);
+    if let Some(val) = &mut node.initializer {
+        visitor.visit_var_initializer_mut(val);
+    }
+    if let Some(token) = &mut node.comma {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_function_definition_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionDefinition<'s>,
+) {
+    if let Some(val) = &mut node.environment {
+        visitor.visit_function_environment_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_function_params_mut(&mut node.params);
+    visitor.visit_token_mut(&mut node.close);
+    if let Some(val) = &mut node.captures {
+        visitor.visit_function_captures_mut(val);
+    }
+    visitor.visit_statement_type_mut(&mut node.body);
+}
+
+pub fn walk_function_environment_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionEnvironment<'s>,
+) {
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.value);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_function_params_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionParams<'s>,
+) {
+    match node {
+        FunctionParams::NonVariable { params } => {
+            if let Some(list) = params {
+                walk_separated_list_trailing1_mut(visitor, list, V::visit_function_param_mut);
+            }
+        }
+        FunctionParams::EmptyVariable { vararg } => visitor.visit_token_mut(vararg),
+        FunctionParams::NonEmptyVariable {
+            params,
+            comma,
+            vararg,
+        } => {
+            walk_separated_list1_mut(visitor, params, V::visit_function_param_mut);
+            visitor.visit_token_mut(comma);
+            visitor.visit_token_mut(vararg);
+        }
+    }
+}
+
+pub fn walk_function_param_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionParam<'s>,
+) {
+    if let Some(val) = &mut node.type_ {
+        visitor.visit_type_mut(val);
+    }
+    visitor.visit_identifier_mut(&mut node.name);
+    if let Some(val) = &mut node.initializer {
+        visitor.visit_var_initializer_mut(val);
+    }
+}
+
+pub fn walk_function_captures_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionCaptures<'s>,
+) {
+    visitor.visit_token_mut(&mut node.colon);
+    visitor.visit_token_mut(&mut node.open);
+    if let Some(list) = &mut node.names {
+        walk_separated_list_trailing1_mut(visitor, list, V::visit_identifier_mut);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_function_ref_param_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionRefParam<'s>,
+) {
+    visitor.visit_type_mut(&mut node.type_);
+    if let Some(val) = &mut node.name {
+        visitor.visit_identifier_mut(val);
+    }
+    if let Some(val) = &mut node.initializer {
+        visitor.visit_var_initializer_mut(val);
+    }
+}
+
+pub fn walk_slot_mut<'s, V: VisitMut<'s> + ?Sized>(visitor: &mut V, node: &mut Slot<'s>) {
+    match node {
+        Slot::Property { name, initializer } => {
+            visitor.visit_identifier_mut(name);
+            visitor.visit_var_initializer_mut(initializer);
+        }
+        Slot::ComputedProperty {
+            open,
+            name,
+            close,
+            initializer,
+        } => {
+            visitor.visit_token_mut(open);
+            visitor.visit_expression_mut(name);
+            visitor.visit_token_mut(close);
+            visitor.visit_var_initializer_mut(initializer);
+        }
+        Slot::Constructor {
+            function,
+            constructor,
+            definition,
+        } => {
+            if let Some(token) = function {
+                visitor.visit_token_mut(token);
+            }
+            visitor.visit_token_mut(constructor);
+            visitor.visit_function_definition_mut(definition);
+        }
+        Slot::Function {
+            return_type,
+            function,
+            name,
+            definition,
+        } => {
+            if let Some(val) = return_type {
+                visitor.visit_type_mut(val);
+            }
+            visitor.visit_token_mut(function);
+            visitor.visit_identifier_mut(name);
+            visitor.visit_function_definition_mut(definition);
+        }
+    }
+}
+
+pub fn walk_table_slot_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut TableSlot<'s>,
+) {
+    visitor.visit_table_slot_type_mut(&mut node.ty);
+    if let Some(token) = &mut node.comma {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_table_slot_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut TableSlotType<'s>,
+) {
+    match node {
+        TableSlotType::Slot(slot) => visitor.visit_slot_mut(slot),
+        TableSlotType::JsonProperty {
+            name_token,
+            colon,
+            value,
+            ..
+        } => {
+            visitor.visit_token_mut(name_token);
+            visitor.visit_token_mut(colon);
+            visitor.visit_expression_mut(value);
+        }
+    }
+}
+
+pub fn walk_array_value_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ArrayValue<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.value);
+    if let Some(token) = &mut node.separator {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_call_argument_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut CallArgument<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.value);
+    if let Some(token) = &mut node.comma {
+        visitor.visit_token_mut(token);
+    }
+}
+
+pub fn walk_identifier_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut Identifier<'s>,
+) {
+    visitor.visit_token_mut(&mut node.token);
+}
+
+pub fn walk_method_identifier_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut MethodIdentifier<'s>,
+) {
+    match node {
+        MethodIdentifier::Identifier(identifier) => visitor.visit_identifier_mut(identifier),
+        MethodIdentifier::Constructor(constructor) => visitor.visit_token_mut(constructor),
+    }
+}
+
+pub fn walk_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut Expression<'s>,
+) {
+    match node {
+        Expression::Parens(val) => visitor.visit_parens_expression_mut(val),
+        Expression::Literal(val) => visitor.visit_literal_expression_mut(val),
+        Expression::Var(val) => visitor.visit_var_expression_mut(val),
+        Expression::RootVar(val) => visitor.visit_root_var_expression_mut(val),
+        Expression::Index(val) => visitor.visit_index_expression_mut(val),
+        Expression::Property(val) => visitor.visit_property_expression_mut(val),
+        Expression::Ternary(val) => visitor.visit_ternary_expression_mut(val),
+        Expression::Binary(val) => visitor.visit_binary_expression_mut(val),
+        Expression::Prefix(val) => visitor.visit_prefix_expression_mut(val),
+        Expression::Postfix(val) => visitor.visit_postfix_expression_mut(val),
+        Expression::Comma(val) => visitor.visit_comma_expression_mut(val),
+        Expression::Table(val) => visitor.visit_table_expression_mut(val),
+        Expression::Class(val) => visitor.visit_class_expression_mut(val),
+        Expression::Array(val) => visitor.visit_array_expression_mut(val),
+        Expression::Function(val) => visitor.visit_function_expression_mut(val),
+        Expression::Lambda(val) => visitor.visit_lambda_expression_mut(val),
+        Expression::Call(val) => visitor.visit_call_expression_mut(val),
+        Expression::Delegate(val) => visitor.visit_delegate_expression_mut(val),
+        Expression::Vector(val) => visitor.visit_vector_expression_mut(val),
+        Expression::Expect(val) => visitor.visit_expect_expression_mut(val),
+        Expression::Error(val) => visitor.visit_error_expression_mut(val),
+    }
+}
+
+pub fn walk_parens_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ParensExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.value);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_literal_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut LiteralExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.token);
+}
+
+pub fn walk_var_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut VarExpression<'s>,
+) {
+    visitor.visit_identifier_mut(&mut node.name);
+}
+
+pub fn walk_root_var_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut RootVarExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.root);
+    visitor.visit_identifier_mut(&mut node.name);
+}
+
+pub fn walk_index_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut IndexExpression<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.base);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.index);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_property_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut PropertyExpression<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.base);
+    visitor.visit_token_mut(&mut node.dot);
+    visitor.visit_method_identifier_mut(&mut node.property);
+}
+
+pub fn walk_ternary_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut TernaryExpression<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.condition);
+    visitor.visit_token_mut(&mut node.question);
+    visitor.visit_expression_mut(&mut node.true_value);
+    visitor.visit_token_mut(&mut node.separator);
+    visitor.visit_expression_mut(&mut node.false_value);
+}
+
+pub fn walk_binary_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut BinaryExpression<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.left);
+    visitor.visit_binary_operator_mut(&mut node.operator);
+    visitor.visit_expression_mut(&mut node.right);
+}
+
+pub fn walk_prefix_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut PrefixExpression<'s>,
+) {
+    visitor.visit_prefix_operator_mut(&mut node.operator);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_postfix_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut PostfixExpression<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.value);
+    visitor.visit_postfix_operator_mut(&mut node.operator);
+}
+
+pub fn walk_comma_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut CommaExpression<'s>,
+) {
+    walk_separated_list1_mut(visitor, &mut node.values, V::visit_expression_mut);
+}
+
+pub fn walk_table_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut TableExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.open);
+    for val in &mut node.slots {
+        visitor.visit_table_slot_mut(val);
+    }
+    if let Some(token) = &mut node.spread {
+        visitor.visit_token_mut(token);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_class_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ClassExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.class);
+    visitor.visit_class_definition_mut(&mut node.definition);
+}
+
+pub fn walk_array_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ArrayExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.open);
+    for val in &mut node.values {
+        visitor.visit_array_value_mut(val);
+    }
+    if let Some(token) = &mut node.spread {
+        visitor.visit_token_mut(token);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_function_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionExpression<'s>,
+) {
+    if let Some(val) = &mut node.return_type {
+        visitor.visit_type_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.function);
+    visitor.visit_function_definition_mut(&mut node.definition);
+}
+
+pub fn walk_lambda_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut LambdaExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.at);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_function_params_mut(&mut node.params);
+    visitor.visit_token_mut(&mut node.close);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_call_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut CallExpression<'s>,
+) {
+    visitor.visit_expression_mut(&mut node.function);
+    visitor.visit_token_mut(&mut node.open);
+    for val in &mut node.arguments {
+        visitor.visit_call_argument_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.close);
+    if let Some(val) = &mut node.post_initializer {
+        visitor.visit_table_expression_mut(val);
+    }
+}
+
+pub fn walk_delegate_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut DelegateExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.delegate);
+    visitor.visit_expression_mut(&mut node.parent);
+    visitor.visit_token_mut(&mut node.colon);
+    visitor.visit_expression_mut(&mut node.value);
+}
+
+pub fn walk_vector_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut VectorExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.x);
+    visitor.visit_token_mut(&mut node.comma_1);
+    visitor.visit_expression_mut(&mut node.y);
+    visitor.visit_token_mut(&mut node.comma_2);
+    visitor.visit_expression_mut(&mut node.z);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_expect_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ExpectExpression<'s>,
+) {
+    visitor.visit_token_mut(&mut node.expect);
+    visitor.visit_type_mut(&mut node.ty);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.value);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_error_expression_mut<'s, V: VisitMut<'s> + ?Sized>(
+    _visitor: &mut V,
+    _node: &mut ErrorExpression,
+) {
+}
+
+pub fn walk_binary_operator_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut BinaryOperator<'s>,
+) {
+    match node {
+        BinaryOperator::Assign(token)
+        | BinaryOperator::AssignAdd(token)
+        | BinaryOperator::AssignSubtract(token)
+        | BinaryOperator::AssignMultiply(token)
+        | BinaryOperator::AssignDivide(token)
+        | BinaryOperator::AssignModulo(token)
+        | BinaryOperator::Add(token)
+        | BinaryOperator::Subtract(token)
+        | BinaryOperator::Multiply(token)
+        | BinaryOperator::Divide(token)
+        | BinaryOperator::Modulo(token)
+        | BinaryOperator::Equal(token)
+        | BinaryOperator::NotEqual(token)
+        | BinaryOperator::Less(token)
+        | BinaryOperator::LessEqual(token)
+        | BinaryOperator::Greater(token)
+        | BinaryOperator::GreaterEqual(token)
+        | BinaryOperator::ThreeWay(token)
+        | BinaryOperator::LogicalAnd(token)
+        | BinaryOperator::LogicalOr(token)
+        | BinaryOperator::BitwiseAnd(token)
+        | BinaryOperator::BitwiseOr(token)
+        | BinaryOperator::BitwiseXor(token)
+        | BinaryOperator::In(token)
+        | BinaryOperator::Instanceof(token) => visitor.visit_token_mut(token),
+        BinaryOperator::AssignNewSlot(token_1, token_2)
+        | BinaryOperator::ShiftLeft(token_1, token_2)
+        | BinaryOperator::ShiftRight(token_1, token_2) => {
+            visitor.visit_token_mut(token_1);
+            visitor.visit_token_mut(token_2);
+        }
+        BinaryOperator::UnsignedShiftRight(token_1, token_2, token_3) => {
+            visitor.visit_token_mut(token_1);
+            visitor.visit_token_mut(token_2);
+            visitor.visit_token_mut(token_3);
+        }
+    }
+}
+
+pub fn walk_prefix_operator_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut PrefixOperator<'s>,
+) {
+    match node {
+        PrefixOperator::Negate(token)
+        | PrefixOperator::LogicalNot(token)
+        | PrefixOperator::BitwiseNot(token)
+        | PrefixOperator::Typeof(token)
+        | PrefixOperator::Clone(token)
+        | PrefixOperator::Delete(token)
+        | PrefixOperator::Increment(token)
+        | PrefixOperator::Decrement(token) => visitor.visit_token_mut(token),
+    }
+}
+
+pub fn walk_postfix_operator_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut PostfixOperator<'s>,
+) {
+    match node {
+        PostfixOperator::Increment(token) | PostfixOperator::Decrement(token) => {
+            visitor.visit_token_mut(token)
+        }
+    }
+}
+
+pub fn walk_type_mut<'s, V: VisitMut<'s> + ?Sized>(visitor: &mut V, node: &mut Type<'s>) {
+    match node {
+        Type::Local(val) => visitor.visit_local_type_mut(val),
+        Type::Var(val) => visitor.visit_var_type_mut(val),
+        Type::Plain(val) => visitor.visit_plain_type_mut(val),
+        Type::Array(val) => visitor.visit_array_type_mut(val),
+        Type::Generic(val) => visitor.visit_generic_type_mut(val),
+        Type::FunctionRef(val) => visitor.visit_function_ref_type_mut(val),
+        Type::Struct(val) => visitor.visit_struct_type_mut(val),
+        Type::Reference(val) => visitor.visit_reference_type_mut(val),
+        Type::Nullable(val) => visitor.visit_nullable_type_mut(val),
+    }
+}
+
+pub fn walk_local_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut LocalType<'s>,
+) {
+    visitor.visit_token_mut(&mut node.local);
+}
+
+pub fn walk_var_type_mut<'s, V: VisitMut<'s> + ?Sized>(visitor: &mut V, node: &mut VarType<'s>) {
+    visitor.visit_token_mut(&mut node.var);
+}
+
+pub fn walk_plain_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut PlainType<'s>,
+) {
+    visitor.visit_identifier_mut(&mut node.name);
+}
+
+pub fn walk_array_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ArrayType<'s>,
+) {
+    visitor.visit_type_mut(&mut node.base);
+    visitor.visit_token_mut(&mut node.open);
+    visitor.visit_expression_mut(&mut node.len);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_generic_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut GenericType<'s>,
+) {
+    visitor.visit_type_mut(&mut node.base);
+    visitor.visit_token_mut(&mut node.open);
+    walk_separated_list_trailing1_mut(visitor, &mut node.params, V::visit_type_mut);
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_function_ref_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut FunctionRefType<'s>,
+) {
+    if let Some(val) = &mut node.return_type {
+        visitor.visit_type_mut(val);
+    }
+    visitor.visit_token_mut(&mut node.functionref);
+    visitor.visit_token_mut(&mut node.open);
+    if let Some(list) = &mut node.params {
+        walk_separated_list_trailing1_mut(visitor, list, V::visit_function_ref_param_mut);
+    }
+    visitor.visit_token_mut(&mut node.close);
+}
+
+pub fn walk_struct_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut StructType<'s>,
+) {
+    visitor.visit_token_mut(&mut node.struct_);
+    visitor.visit_struct_definition_mut(&mut node.definition);
+}
+
+pub fn walk_reference_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ReferenceType<'s>,
+) {
+    visitor.visit_type_mut(&mut node.base);
+    visitor.visit_token_mut(&mut node.reference);
+}
+
+pub fn walk_nullable_type_mut<'s, V: VisitMut<'s> + ?Sized>(
+    visitor: &mut V,
+    node: &mut NullableType<'s>,
+) {
+    visitor.visit_type_mut(&mut node.base);
+    visitor.visit_token_mut(&mut node.ornull);
+}
+
+fn walk_separated_list1_mut<'s, V: VisitMut<'s> + ?Sized, T>(
+    visitor: &mut V,
+    list: &mut SeparatedList1<'s, T>,
+    visit: fn(&mut V, &mut T),
+) {
+    for (item, separator) in &mut list.items {
+        visit(visitor, item);
+        visitor.visit_token_mut(separator);
+    }
+    visit(visitor, &mut list.last_item);
+}
+
+fn walk_separated_list_trailing1_mut<'s, V: VisitMut<'s> + ?Sized, T>(
+    visitor: &mut V,
+    list: &mut SeparatedListTrailing1<'s, T>,
+    visit: fn(&mut V, &mut T),
+) {
+    for (item, separator) in &mut list.items {
+        visit(visitor, item);
+        visitor.visit_token_mut(separator);
+    }
+    visit(visitor, &mut list.last_item);
+    if let Some(trailing) = &mut list.trailing {
+        visitor.visit_token_mut(trailing);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::VisitMut;
+    use crate::token::{TerminalToken, Token, TokenArena, TokenType};
+    use crate::{parse, print_program, tokenize, Flavor};
+
+    struct Renamer<'s> {
+        arena: &'s TokenArena<'s>,
+        visited: usize,
+    }
+
+    impl<'s> VisitMut<'s> for Renamer<'s> {
+        fn visit_token_mut(&mut self, token: &mut &'s Token<'s>) {
+            self.visited += 1;
+            let new_name = match token.ty {
+                TokenType::Identifier("a") => "first",
+                TokenType::Identifier("b") => "second",
+                _ => return,
+            };
+            *token = self.arena.replace(token, TokenType::Identifier(new_name));
+        }
+    }
+
+    #[test]
+    fn rewrites_tokens() {
+        let source =
+            "// add them\nint function Add( int a, int b )\n{\n\treturn a + b // done\n}\n";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let arena = TokenArena::new();
+        let mut program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+
+        let mut renamer = Renamer {
+            arena: &arena,
+            visited: 0,
+        };
+        renamer.visit_program_mut(&mut program);
+
+        assert_eq!(renamer.visited, tokens.len());
+        assert_eq!(arena.len(), 4);
+        assert_eq!(
+            print_program(&program, source),
+            "// add them\nint function Add( int first, int second )\n{\n\treturn first + second // done\n}\n"
+        );
+    }
+
+    #[test]
+    fn prints_new_tokens() {
+        let source = "a = 1";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let arena = TokenArena::new();
+        let mut program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+
+        let semicolon = arena.token(TokenType::Terminal(TerminalToken::Semicolon));
+        program.statements[0].semicolon = Some(semicolon);
+        assert_eq!(print_program(&program, source), "a = 1 ;");
+    }
+}
@@ -0,0 +1,255 @@
+use sqparse::annotation::{display_diagnostics, Diagnostic, FileDiagnostics, OutputFormat};
+use sqparse::{format_program, parse, tokenize, BraceStyle, Flavor, FormatOptions, TrailingCommas};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: sqfmt [options] [paths...]
+
+Formats Squirrel scripts. Directories are searched recursively for .nut and .gnut files.
+With no options, formatted code is written to stdout.
+
+Options:
+  --check                      Check if files are formatted, and list any that are not
+  -w, --write                  Format files in-place
+  --spaces                     Indent with spaces instead of tabs
+  --indent-width <n>           Spaces per indent level, if using spaces (default: 4)
+  --no-paren-spacing           Don't add spaces inside parentheses
+  --no-bracket-spacing         Don't add spaces inside square brackets
+  --brace-style <style>        next-line or same-line (default: next-line)
+  --max-blank-lines <n>        Maximum consecutive blank lines (default: 1)
+  --trailing-commas <rule>     preserve, never, always or vertical (default: preserve)
+  --error-format <format>      human, json, sarif, checkstyle or github (default: human).
+                               Other formats are written to stdout, and need --check or --write
+  -h, --help                   Print this message";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Print,
+    Check,
+    Write,
+}
+
+// An error formatting a file.
+enum Error {
+    Message(String),
+
+    // A syntax error, printed in the error format.
+    Diagnostic {
+        file_name: String,
+        source: String,
+        diagnostic: Box<Diagnostic>,
+    },
+}
+
+fn main() -> ExitCode {
+    let mut mode = Mode::Print;
+    let mut options = FormatOptions::default();
+    let mut error_format = OutputFormat::Human;
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let result = match arg.as_str() {
+            "--check" => {
+                mode = Mode::Check;
+                Ok(())
+            }
+            "-w" | "--write" => {
+                mode = Mode::Write;
+                Ok(())
+            }
+            "--spaces" => {
+                options.hard_tabs = false;
+                Ok(())
+            }
+            "--no-paren-spacing" => {
+                options.paren_spacing = false;
+                Ok(())
+            }
+            "--no-bracket-spacing" => {
+                options.bracket_spacing = false;
+                Ok(())
+            }
+            "--indent-width" => parse_value(&arg, args.next(), |val| {
+                options.indent_width = val.parse().ok()?;
+                Some(())
+            }),
+            "--max-blank-lines" => parse_value(&arg, args.next(), |val| {
+                options.max_blank_lines = val.parse().ok()?;
+                Some(())
+            }),
+            "--brace-style" => parse_value(&arg, args.next(), |val| {
+                options.brace_style = match val {
+                    "next-line" => BraceStyle::NextLine,
+                    "same-line" => BraceStyle::SameLine,
+                    _ => return None,
+                };
+                Some(())
+            }),
+            "--trailing-commas" => parse_value(&arg, args.next(), |val| {
+                options.trailing_commas = match val {
+                    "preserve" => TrailingCommas::Preserve,
+                    "never" => TrailingCommas::Never,
+                    "always" => TrailingCommas::Always,
+                    "vertical" => TrailingCommas::Vertical,
+                    _ => return None,
+                };
+                Some(())
+            }),
+            "--error-format" => parse_value(&arg, args.next(), |val| {
+                error_format = OutputFormat::from_name(val)?;
+                Some(())
+            }),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with('-') => Err(format!("unknown option `{arg}`")),
+            _ => {
+                paths.push(PathBuf::from(arg));
+                Ok(())
+            }
+        };
+
+        if let Err(err) = result {
+            eprintln!("error: {err}");
+            eprintln!();
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    // Machine-readable diagnostics must be the only thing on stdout, so they can't be mixed with formatted code.
+    if mode == Mode::Print && error_format != OutputFormat::Human {
+        eprintln!("error: `--error-format` can only be used with `--check` or `--write`");
+        eprintln!();
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    let mut has_errors = false;
+    let mut has_unformatted = false;
+    let mut diagnostics = Vec::new();
+    for path in &paths {
+        visit(path, &mut |path| match format_file(path, mode, &options) {
+            Ok(true) => {}
+            Ok(false) => {
+                // Unformatted files are listed on stderr when stdout is reserved for diagnostics.
+                if mode == Mode::Check {
+                    if error_format == OutputFormat::Human {
+                        println!("{}", path.display());
+                    } else {
+                        eprintln!("{}", path.display());
+                    }
+                }
+                has_unformatted = true;
+            }
+            Err(Error::Message(message)) => {
+                eprintln!("{message}");
+                has_errors = true;
+            }
+            Err(Error::Diagnostic {
+                file_name,
+                source,
+                diagnostic,
+            }) => {
+                if error_format == OutputFormat::Human {
+                    eprintln!("{}", diagnostic.display(&source, Some(&file_name)));
+                } else {
+                    diagnostics.push((file_name, source, [*diagnostic]));
+                }
+                has_errors = true;
+            }
+        });
+    }
+
+    // Machine-readable formats are printed to stdout as one document after every file is checked.
+    if error_format != OutputFormat::Human {
+        let files: Vec<_> = (diagnostics.iter())
+            .map(|(file_name, source, diagnostics)| FileDiagnostics {
+                file_name,
+                source,
+                diagnostics,
+            })
+            .collect();
+        let output = display_diagnostics(error_format, &files).to_string();
+        if !output.is_empty() {
+            println!("{output}");
+        }
+    }
+
+    if has_errors {
+        ExitCode::from(2)
+    } else if mode == Mode::Check && has_unformatted {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn parse_value<F: FnOnce(&str) -> Option<()>>(
+    arg: &str,
+    value: Option<String>,
+    apply: F,
+) -> Result<(), String> {
+    match value {
+        Some(value) => apply(&value).ok_or_else(|| format!("invalid value `{value}` for `{arg}`")),
+        None => Err(format!("missing value for `{arg}`")),
+    }
+}
+
+// Formats a file, returning whether it was already formatted.
+fn format_file(path: &Path, mode: Mode, options: &FormatOptions) -> Result<bool, Error> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|err| Error::Message(format!("{}: could not read: {err}", path.display())))?;
+    let diagnostic_error = |diagnostic| Error::Diagnostic {
+        file_name: path.display().to_string(),
+        source: source.clone(),
+        diagnostic: Box::new(diagnostic),
+    };
+
+    let tokens = tokenize(&source, Flavor::SquirrelRespawn)
+        .map_err(|err| diagnostic_error(err.to_diagnostic()))?;
+    let program = parse(&tokens, Flavor::SquirrelRespawn)
+        .map_err(|err| diagnostic_error(err.to_diagnostic(&tokens)))?;
+
+    let formatted = format_program(&program, &source, options);
+    let is_formatted = formatted == source;
+
+    match mode {
+        Mode::Print => print!("{formatted}"),
+        Mode::Check => {}
+        Mode::Write => {
+            if !is_formatted {
+                std::fs::write(path, formatted).map_err(|err| {
+                    Error::Message(format!("{}: could not write: {err}", path.display()))
+                })?;
+            }
+        }
+    }
+
+    Ok(is_formatted)
+}
+
+fn visit<F: FnMut(&Path)>(path: &Path, cb: &mut F) {
+    if path.is_dir() {
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return;
+        };
+        let mut entry_paths: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+        entry_paths.sort();
+        for entry_path in entry_paths {
+            let extension = entry_path.extension().and_then(|val| val.to_str());
+            if entry_path.is_dir() || matches!(extension, Some("nut") | Some("gnut")) {
+                visit(&entry_path, cb);
+            }
+        }
+    } else {
+        cb(path);
+    }
+}
@@ -0,0 +1,1641 @@
+use crate::ast::{
+    ArrayExpression, BinaryOperator, BlockStatement, CallExpression, ClassDefinition, ClassMember,
+    EnumDefinitionStatement, Expression, ForDefinition, FunctionCaptures, FunctionDefinition,
+    FunctionParam, FunctionParams, FunctionRefParam, GlobalDefinition, Identifier, IfStatementType,
+    MethodIdentifier, PostfixOperator, PrefixOperator, Program, SeparatedList1,
+    SeparatedListTrailing1, Slot, Statement, StatementType, StructDefinition, SwitchCaseCondition,
+    TableExpression, TableSlotType, Type, VarDefinition, VarDefinitionStatement, VarInitializer,
+};
+use crate::printer::{comment_text, token_text};
+use crate::token::{Comment, TerminalToken, Token, TokenLine, TokenType};
+
+/// Placement of opening braces for blocks, classes, structs, enums and switch cases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BraceStyle {
+    /// The opening brace is placed on its own line.
+    ///
+    /// # Example
+    /// ```text
+    /// if ( a )
+    /// {
+    ///     b()
+    /// }
+    /// ```
+    NextLine,
+
+    /// The opening brace is placed on the same line as the code before it.
+    ///
+    /// # Example
+    /// ```text
+    /// if ( a ) {
+    ///     b()
+    /// }
+    /// ```
+    SameLine,
+}
+
+/// Rules for trailing separators in a [`SeparatedListTrailing1`] that is enclosed in delimiters,
+/// like function parameters and generic type parameters.
+///
+/// Trailing separators that have comments attached are never removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingCommas {
+    /// Trailing separators are left as they are.
+    Preserve,
+
+    /// Trailing separators are removed.
+    Never,
+
+    /// Trailing separators are added to every list.
+    Always,
+
+    /// Trailing separators are added if the closing delimiter is on a different line to the last
+    /// item, and removed otherwise.
+    Vertical,
+}
+
+/// Options for [`format_program`].
+///
+/// The default options match the style used in Respawn's scripts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Indent with tabs instead of spaces.
+    pub hard_tabs: bool,
+
+    /// Number of spaces in each indentation level, if `hard_tabs` is not set.
+    pub indent_width: usize,
+
+    /// Add spaces inside non-empty parentheses, e.g. `Func( a, b )`.
+    pub paren_spacing: bool,
+
+    /// Add spaces inside non-empty square brackets, e.g. `values[ 0 ]`.
+    pub bracket_spacing: bool,
+
+    /// Placement of opening braces.
+    pub brace_style: BraceStyle,
+
+    /// Maximum number of consecutive blank lines. Blank lines at the start of a block, or before
+    /// the end of a block, are always removed.
+    pub max_blank_lines: usize,
+
+    /// Rules for trailing separators in delimited lists.
+    pub trailing_commas: TrailingCommas,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            hard_tabs: true,
+            indent_width: 4,
+            paren_spacing: true,
+            bracket_spacing: true,
+            brace_style: BraceStyle::NextLine,
+            max_blank_lines: 1,
+            trailing_commas: TrailingCommas::Preserve,
+        }
+    }
+}
+
+/// Formats a syntax tree into source code.
+///
+/// Statements, blocks and members are laid out according to `options`, and spacing between tokens
+/// is normalized. Line breaks inside statements and expressions are kept, so the formatted code
+/// always parses to the same tree. Each comment remains attached to the same token as in the
+/// original source.
+///
+/// `source` must be the string the tree was parsed from.
+///
+/// Trees returned by [`parse_recover`] can be formatted too. The tokens in each [`ErrorStatement`]
+/// are written exactly as they are in the source, and stay on the line they started on.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, format_program, parse, tokenize, FormatOptions};
+///
+/// let source = "void function MyFunction(int a,string b){print(a+b)}";
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// let formatted = format_program(&program, source, &FormatOptions::default());
+/// assert_eq!(
+///     formatted,
+///     "void function MyFunction( int a, string b )\n{\n\tprint( a + b )\n}\n"
+/// );
+/// ```
+///
+/// [`parse_recover`]: crate::parse_recover
+/// [`ErrorStatement`]: crate::ast::ErrorStatement
+pub fn format_program(program: &Program, source: &str, options: &FormatOptions) -> String {
+    let mut formatter = Formatter::new(source, options);
+    formatter.statements(&program.statements);
+    formatter.finish()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Space {
+    // No space, unless one is needed to separate the tokens.
+    None,
+    // A single space.
+    Single,
+    // Start a new line.
+    Newline,
+    // A single space, removing a line break from the source if possible.
+    Join,
+    // A single space, or a new line at the statement's indentation if there is a line break in
+    // the source.
+    KeepLine,
+}
+
+struct Level {
+    indent: usize,
+    statement_indent: usize,
+}
+
+struct Formatter<'a, 's> {
+    source: &'s str,
+    options: &'a FormatOptions,
+    output: String,
+
+    levels: Vec<Level>,
+    // Indentation of the line currently being written.
+    line_indent: usize,
+    // Indentation of the line the current statement started on.
+    statement_indent: usize,
+
+    // The line ending after the last written token, if there is one in the source.
+    pending_line: Option<&'s TokenLine<'s>>,
+    // Set if the next token must start a new line.
+    pending_newline: bool,
+    // Set if the last written token opened a block.
+    after_open: bool,
+    last_text: String,
+}
+
+impl<'a, 's> Formatter<'a, 's> {
+    fn new(source: &'s str, options: &'a FormatOptions) -> Self {
+        Formatter {
+            source,
+            options,
+            output: String::with_capacity(source.len()),
+            levels: Vec::new(),
+            line_indent: 0,
+            statement_indent: 0,
+            pending_line: None,
+            pending_newline: false,
+            after_open: false,
+            last_text: String::new(),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush_pending_line();
+        if !self.output.is_empty() && !self.output.ends_with('\n') {
+            self.end_line();
+        }
+        self.output
+    }
+
+    fn paren_space(&self) -> Space {
+        if self.options.paren_spacing {
+            Space::Single
+        } else {
+            Space::None
+        }
+    }
+
+    fn bracket_space(&self) -> Space {
+        if self.options.bracket_spacing {
+            Space::Single
+        } else {
+            Space::None
+        }
+    }
+
+    fn brace_space(&self) -> Space {
+        match self.options.brace_style {
+            BraceStyle::NextLine => Space::Newline,
+            BraceStyle::SameLine => Space::Join,
+        }
+    }
+
+    // Space before a keyword like `else` that follows a statement body.
+    fn continuation_space(&self, body: &StatementType) -> Space {
+        match (body, self.options.brace_style) {
+            (StatementType::Block(_), BraceStyle::NextLine) => Space::Newline,
+            (StatementType::Block(_), BraceStyle::SameLine) => Space::Join,
+            _ => Space::KeepLine,
+        }
+    }
+
+    fn newline(&mut self) {
+        self.pending_newline = true;
+    }
+
+    fn open(&mut self, token: &'s Token<'s>, space: Space) {
+        self.token(token, space);
+        self.push_indent();
+        self.after_open = true;
+    }
+
+    fn close(&mut self, token: &'s Token<'s>, space: Space) {
+        let level = self.levels.pop().expect("unbalanced indentation");
+        self.write_token(token, space, Some(level.indent.saturating_sub(1)));
+        self.statement_indent = level.statement_indent;
+    }
+
+    fn push_indent(&mut self) {
+        self.levels.push(Level {
+            indent: self.line_indent + 1,
+            statement_indent: self.statement_indent,
+        });
+    }
+
+    fn pop_indent(&mut self) {
+        let level = self.levels.pop().expect("unbalanced indentation");
+        self.statement_indent = level.statement_indent;
+    }
+
+    fn token(&mut self, token: &'s Token<'s>, space: Space) {
+        self.write_token(token, space, None);
+    }
+
+    fn maybe_token(&mut self, token: Option<&'s Token<'s>>, space: Space) {
+        if let Some(token) = token {
+            self.token(token, space);
+        }
+    }
+
+    // Writes text that does not correspond to a token in the tree.
+    fn synthetic(&mut self, text: &str) {
+        self.output.push_str(text);
+        self.last_text = text.to_string();
+    }
+
+    // Skips a token in the tree, keeping any line break after it.
+    fn skip(&mut self, token: &'s Token<'s>) {
+        if self.pending_line.is_none() {
+            self.pending_line = token.new_line.as_ref();
+        }
+    }
+
+    fn write_token(&mut self, token: &'s Token<'s>, space: Space, close_indent: Option<usize>) {
+        let space = if std::mem::take(&mut self.pending_newline) {
+            Space::Newline
+        } else {
+            space
+        };
+
+        let is_start = self.output.is_empty() && self.pending_line.is_none();
+        let has_comment_lines = token
+            .before_lines
+            .iter()
+            .any(|line| !line.comments.is_empty());
+        let has_source_newline = self.pending_line.is_some();
+        let is_locked = self.flush_pending_line();
+
+        let is_newline = !is_start
+            && match space {
+                Space::Newline => true,
+                Space::Join => is_locked || has_comment_lines,
+                Space::None | Space::Single | Space::KeepLine => has_source_newline,
+            };
+
+        let is_empty = matches!(token.ty, TokenType::Empty);
+        let text = token_text(token, self.source);
+
+        if is_start || is_newline {
+            let is_structural = matches!(space, Space::Newline | Space::Join | Space::KeepLine);
+            let indent = match close_indent {
+                Some(indent) => indent,
+                None => self.newline_indent(is_structural),
+            };
+
+            if is_newline {
+                self.end_line();
+            }
+
+            let mut allow_blank_lines = !is_start && !self.after_open;
+            let mut blank_lines = 0;
+            for line in &token.before_lines {
+                if line.comments.is_empty() {
+                    blank_lines += 1;
+                    continue;
+                }
+
+                if allow_blank_lines {
+                    self.blank_lines(blank_lines);
+                }
+                blank_lines = 0;
+                allow_blank_lines = true;
+
+                // Preprocessor directives always start at the beginning of the line.
+                if !matches!(line.comments.first(), Some(Comment::ScriptStyle(_))) {
+                    self.indent(indent);
+                }
+                self.comments(&line.comments);
+                self.end_line();
+            }
+            if allow_blank_lines && close_indent.is_none() && !is_empty {
+                self.blank_lines(blank_lines);
+            }
+
+            if is_empty && token.comments.is_empty() {
+                return;
+            }
+            self.indent(indent);
+            self.line_indent = indent;
+            if is_structural {
+                self.statement_indent = indent;
+            }
+        } else {
+            let first_text = match token.comments.first() {
+                Some(_) => "/",
+                None => &text,
+            };
+            if space != Space::None
+                || !token.comments.is_empty()
+                || needs_space(&self.last_text, first_text)
+            {
+                self.output.push(' ');
+            }
+        }
+
+        self.comments(&token.comments);
+        if !text.is_empty() {
+            if !token.comments.is_empty() {
+                self.output.push(' ');
+            }
+            self.output.push_str(&text);
+            self.last_text = text.into_owned();
+        }
+
+        self.pending_line = token.new_line.as_ref();
+        self.after_open = false;
+    }
+
+    // Writes the comments at the end of the previous line. Returns true if they prevent the line
+    // break from being removed.
+    fn flush_pending_line(&mut self) -> bool {
+        match self.pending_line.take() {
+            Some(line) if !line.comments.is_empty() => {
+                self.output.push(' ');
+                self.comments(&line.comments);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn newline_indent(&self, is_structural: bool) -> usize {
+        let top = self.levels.last().map_or(0, |level| level.indent);
+        if is_structural || top > self.statement_indent {
+            top
+        } else {
+            self.statement_indent + 1
+        }
+    }
+
+    fn comments(&mut self, comments: &[Comment]) {
+        for (index, comment) in comments.iter().enumerate() {
+            if index > 0 {
+                self.output.push(' ');
+            }
+            self.output.push_str(&comment_text(comment, self.source));
+        }
+        if !comments.is_empty() {
+            self.last_text.clear();
+        }
+    }
+
+    fn indent(&mut self, indent: usize) {
+        if self.options.hard_tabs {
+            self.output.push_str(&"\t".repeat(indent));
+        } else {
+            let width = indent * self.options.indent_width;
+            self.output.push_str(&" ".repeat(width));
+        }
+    }
+
+    fn end_line(&mut self) {
+        let trimmed_len = self.output.trim_end_matches([' ', '\t']).len();
+        self.output.truncate(trimmed_len);
+        self.output.push('\n');
+    }
+
+    fn blank_lines(&mut self, count: usize) {
+        for _ in 0..count.min(self.options.max_blank_lines) {
+            self.output.push('\n');
+        }
+    }
+
+    fn statements(&mut self, statements: &'s [Statement<'s>]) {
+        for statement in statements {
+            // Error statements stay on the line they started on, so they are not parsed as valid
+            // code after formatting.
+            if !matches!(statement.ty, StatementType::Error(_)) {
+                self.newline();
+            }
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &'s Statement<'s>) {
+        self.statement_type(&statement.ty);
+        self.maybe_token(statement.semicolon, Space::None);
+    }
+
+    // Writes the body of a control statement or function.
+    fn body(&mut self, body: &'s StatementType<'s>) {
+        match body {
+            StatementType::Block(block) => self.block(block, self.brace_space()),
+            _ => {
+                self.push_indent();
+                self.statement_type(body);
+                self.pop_indent();
+            }
+        }
+    }
+
+    fn block(&mut self, block: &'s BlockStatement<'s>, space: Space) {
+        self.open(block.open, space);
+        self.statements(&block.statements);
+        self.close(block.close, Space::Newline);
+    }
+
+    fn statement_type(&mut self, ty: &'s StatementType<'s>) {
+        match ty {
+            StatementType::Empty(statement) => self.maybe_token(statement.empty, Space::Single),
+            StatementType::Block(block) => self.block(block, Space::Single),
+            StatementType::If(statement) => {
+                self.token(statement.if_, Space::Single);
+                self.parens(
+                    statement.open,
+                    &statement.condition,
+                    statement.close,
+                    Space::Single,
+                );
+                match &statement.ty {
+                    IfStatementType::NoElse { body } => self.body(body),
+                    IfStatementType::Else {
+                        body,
+                        else_,
+                        else_body,
+                    } => {
+                        self.body(&body.ty);
+                        self.maybe_token(body.semicolon, Space::None);
+                        self.token(else_, self.continuation_space(&body.ty));
+                        match else_body.as_ref() {
+                            // Keep `else if` chains on one line.
+                            StatementType::If(_) => self.statement_type(else_body),
+                            _ => self.body(else_body),
+                        }
+                    }
+                }
+            }
+            StatementType::While(statement) => {
+                self.token(statement.while_, Space::Single);
+                self.parens(
+                    statement.open,
+                    &statement.condition,
+                    statement.close,
+                    Space::Single,
+                );
+                self.body(&statement.body);
+            }
+            StatementType::DoWhile(statement) => {
+                self.token(statement.do_, Space::Single);
+                self.body(&statement.body.ty);
+                self.maybe_token(statement.body.semicolon, Space::None);
+                self.token(
+                    statement.while_,
+                    self.continuation_space(&statement.body.ty),
+                );
+                self.parens(
+                    statement.open,
+                    &statement.condition,
+                    statement.close,
+                    Space::Single,
+                );
+            }
+            StatementType::Switch(statement) => {
+                self.token(statement.switch, Space::Single);
+                self.parens(
+                    statement.open_condition,
+                    &statement.condition,
+                    statement.close_condition,
+                    Space::Single,
+                );
+                self.open(statement.open_cases, self.brace_space());
+                for case in &statement.cases {
+                    match &case.condition {
+                        SwitchCaseCondition::Default { default } => {
+                            self.token(default, Space::Newline)
+                        }
+                        SwitchCaseCondition::Case { case, value } => {
+                            self.token(case, Space::Newline);
+                            self.expression(value, Space::Single);
+                        }
+                    }
+                    self.token(case.colon, Space::None);
+                    self.push_indent();
+                    self.statements(&case.body);
+                    self.pop_indent();
+                }
+                self.close(statement.close_cases, Space::Newline);
+            }
+            StatementType::For(statement) => {
+                self.token(statement.for_, Space::Single);
+                self.open(statement.open, Space::Single);
+                match &statement.initializer {
+                    Some(ForDefinition::Expression(value)) => {
+                        self.expression(value, self.paren_space())
+                    }
+                    Some(ForDefinition::Definition(definition)) => {
+                        self.var_definition_statement(definition, self.paren_space())
+                    }
+                    None => {}
+                }
+                let space = match statement.initializer {
+                    Some(_) => Space::None,
+                    None => self.paren_space(),
+                };
+                self.token(statement.semicolon_1, space);
+                if let Some(condition) = &statement.condition {
+                    self.expression(condition, Space::Single);
+                }
+                self.token(statement.semicolon_2, Space::None);
+                if let Some(increment) = &statement.increment {
+                    self.expression(increment, Space::Single);
+                }
+                self.close(statement.close, self.paren_space());
+                self.body(&statement.body);
+            }
+            StatementType::Foreach(statement) => {
+                self.token(statement.foreach, Space::Single);
+                self.open(statement.open, Space::Single);
+                let mut space = self.paren_space();
+                if let Some(index) = &statement.index {
+                    self.maybe_type(index.type_.as_ref(), space);
+                    if index.type_.is_some() {
+                        space = Space::Single;
+                    }
+                    self.identifier(&index.name, space);
+                    self.token(index.comma, Space::None);
+                    space = Space::Single;
+                }
+                self.maybe_type(statement.value_type.as_ref(), space);
+                if statement.value_type.is_some() {
+                    space = Space::Single;
+                }
+                self.identifier(&statement.value_name, space);
+                self.token(statement.in_, Space::Single);
+                self.expression(&statement.array, Space::Single);
+                self.close(statement.close, self.paren_space());
+                self.body(&statement.body);
+            }
+            StatementType::Break(statement) => self.token(statement.break_, Space::Single),
+            StatementType::Continue(statement) => self.token(statement.continue_, Space::Single),
+            StatementType::Return(statement) => {
+                self.token(statement.return_, Space::Single);
+                if let Some(value) = &statement.value {
+                    self.expression(value, Space::Single);
+                }
+            }
+            StatementType::Yield(statement) => {
+                self.token(statement.yield_, Space::Single);
+                if let Some(value) = &statement.value {
+                    self.expression(value, Space::Single);
+                }
+            }
+            StatementType::VarDefinition(statement) => {
+                self.var_definition_statement(statement, Space::Single)
+            }
+            StatementType::ConstructorDefinition(statement) => {
+                self.token(statement.function, Space::Single);
+                let mut space = Space::Single;
+                for (namespace, separator) in &statement.namespaces {
+                    self.identifier(namespace, space);
+                    self.token(separator, Space::None);
+                    space = Space::None;
+                }
+                self.identifier(&statement.last_name, space);
+                self.token(statement.last_namespace, Space::None);
+                self.token(statement.constructor, Space::None);
+                self.function_definition(&statement.definition);
+            }
+            StatementType::FunctionDefinition(statement) => {
+                self.maybe_type(statement.return_type.as_ref(), Space::Single);
+                self.token(statement.function, Space::Single);
+                self.separated_list1(
+                    &statement.name,
+                    Space::Single,
+                    Space::None,
+                    |f, name, space| f.identifier(name, space),
+                );
+                self.function_definition(&statement.definition);
+            }
+            StatementType::ClassDefinition(statement) => {
+                self.token(statement.class, Space::Single);
+                self.expression(&statement.name, Space::Single);
+                self.class_definition(&statement.definition);
+            }
+            StatementType::TryCatch(statement) => {
+                self.token(statement.try_, Space::Single);
+                self.body(&statement.body.ty);
+                self.maybe_token(statement.body.semicolon, Space::None);
+                self.token(statement.catch, self.continuation_space(&statement.body.ty));
+                self.open(statement.open, Space::Single);
+                self.identifier(&statement.catch_name, self.paren_space());
+                self.close(statement.close, self.paren_space());
+                self.body(&statement.catch_body);
+            }
+            StatementType::Throw(statement) => {
+                self.token(statement.throw, Space::Single);
+                self.expression(&statement.value, Space::Single);
+            }
+            StatementType::Const(statement) => {
+                self.token(statement.const_, Space::Single);
+                self.maybe_type(statement.const_type.as_ref(), Space::Single);
+                self.identifier(&statement.name, Space::Single);
+                self.var_initializer(&statement.initializer);
+            }
+            StatementType::EnumDefinition(statement) => self.enum_definition(statement),
+            StatementType::Expression(statement) => {
+                self.expression(&statement.value, Space::Single)
+            }
+            StatementType::Thread(statement) => {
+                self.token(statement.thread, Space::Single);
+                self.expression(&statement.value, Space::Single);
+            }
+            StatementType::DelayThread(statement) => {
+                self.token(statement.delay_thread, Space::Single);
+                self.parens(
+                    statement.open,
+                    &statement.duration,
+                    statement.close,
+                    Space::None,
+                );
+                self.expression(&statement.value, Space::Single);
+            }
+            StatementType::WaitThread(statement) => {
+                self.token(statement.wait_thread, Space::Single);
+                self.expression(&statement.value, Space::Single);
+            }
+            StatementType::WaitThreadSolo(statement) => {
+                self.token(statement.wait_thread_solo, Space::Single);
+                self.expression(&statement.value, Space::Single);
+            }
+            StatementType::Wait(statement) => {
+                self.token(statement.wait, Space::Single);
+                self.expression(&statement.value, Space::Single);
+            }
+            StatementType::StructDefinition(statement) => {
+                self.token(statement.struct_, Space::Single);
+                self.identifier(&statement.name, Space::Single);
+                self.struct_definition(&statement.definition);
+            }
+            StatementType::TypeDefinition(statement) => {
+                self.token(statement.typedef, Space::Single);
+                self.identifier(&statement.name, Space::Single);
+                self.type_(&statement.type_, Space::Single);
+            }
+            StatementType::Global(statement) => {
+                self.token(statement.global, Space::Single);
+                self.global_definition(&statement.definition);
+            }
+            StatementType::GlobalizeAllFunctions(statement) => {
+                self.token(statement.globalize_all_functions, Space::Single)
+            }
+            StatementType::Untyped(statement) => self.token(statement.untyped, Space::Single),
+            // Directives are comments on the tokens in each branch, so are written along with them.
+            StatementType::Conditional(statement) => {
+                self.statements(&statement.if_.statements);
+                for branch in &statement.else_ifs {
+                    self.statements(&branch.statements);
+                }
+                if let Some(statements) = &statement.else_ {
+                    self.statements(statements);
+                }
+            }
+            StatementType::Error(statement) => self.error_tokens(&statement.tokens),
+        }
+    }
+
+    // Writes tokens that could not be parsed exactly as they are in the source, including the
+    // spacing and comments between them, since there is no tree to format them with.
+    fn error_tokens(&mut self, tokens: &[&'s Token<'s>]) {
+        let (Some(&first), Some(&last)) = (tokens.first(), tokens.last()) else {
+            return;
+        };
+        self.token(first, Space::KeepLine);
+        if last.range.end > first.range.end {
+            self.output
+                .push_str(&self.source[first.range.end..last.range.end]);
+            self.last_text = token_text(last, self.source).into_owned();
+            self.pending_line = last.new_line.as_ref();
+        }
+    }
+
+    fn global_definition(&mut self, definition: &'s GlobalDefinition<'s>) {
+        match definition {
+            GlobalDefinition::Const(statement) => {
+                self.token(statement.const_, Space::Single);
+                self.maybe_type(statement.const_type.as_ref(), Space::Single);
+                self.identifier(&statement.name, Space::Single);
+                self.var_initializer(&statement.initializer);
+            }
+            GlobalDefinition::Enum(statement) => self.enum_definition(statement),
+            GlobalDefinition::Class(statement) => {
+                self.token(statement.class, Space::Single);
+                self.expression(&statement.name, Space::Single);
+                self.class_definition(&statement.definition);
+            }
+            GlobalDefinition::Struct(statement) => {
+                self.token(statement.struct_, Space::Single);
+                self.identifier(&statement.name, Space::Single);
+                self.struct_definition(&statement.definition);
+            }
+            GlobalDefinition::Type(statement) => {
+                self.token(statement.typedef, Space::Single);
+                self.identifier(&statement.name, Space::Single);
+                self.type_(&statement.type_, Space::Single);
+            }
+            GlobalDefinition::Function { function, name } => {
+                self.token(function, Space::Single);
+                self.identifier(name, Space::Single);
+            }
+            GlobalDefinition::UntypedVar { name, initializer } => {
+                self.identifier(name, Space::Single);
+                self.var_initializer(initializer);
+            }
+            GlobalDefinition::TypedVar(definition) => {
+                self.var_definition_statement(definition, Space::Single)
+            }
+        }
+    }
+
+    fn enum_definition(&mut self, statement: &'s EnumDefinitionStatement<'s>) {
+        self.token(statement.enum_, Space::Single);
+        self.identifier(&statement.name, Space::Single);
+        self.open(statement.open, self.brace_space());
+        for entry in &statement.entries {
+            self.identifier(&entry.name, Space::Newline);
+            if let Some(initializer) = &entry.initializer {
+                self.var_initializer(initializer);
+            }
+            self.maybe_token(entry.comma, Space::None);
+        }
+        self.close(statement.close, Space::Newline);
+    }
+
+    fn var_definition_statement(
+        &mut self,
+        statement: &'s VarDefinitionStatement<'s>,
+        space: Space,
+    ) {
+        self.type_(&statement.type_, space);
+        self.separated_list_trailing1(
+            &statement.definitions,
+            false,
+            Space::Single,
+            |f, definition: &'s VarDefinition<'s>, space| {
+                f.identifier(&definition.name, space);
+                if let Some(initializer) = &definition.initializer {
+                    f.var_initializer(initializer);
+                }
+            },
+        );
+    }
+
+    fn var_initializer(&mut self, initializer: &'s VarInitializer<'s>) {
+        self.token(initializer.assign, Space::Single);
+        self.expression(&initializer.value, Space::Single);
+    }
+
+    fn identifier(&mut self, identifier: &'s Identifier<'s>, space: Space) {
+        self.token(identifier.token, space);
+    }
+
+    fn class_definition(&mut self, definition: &'s ClassDefinition<'s>) {
+        if let Some(extends) = &definition.extends {
+            self.token(extends.extends, Space::Single);
+            self.expression(&extends.name, Space::Single);
+        }
+        self.open(definition.open, self.brace_space());
+        for member in &definition.members {
+            self.newline();
+            self.class_member(member);
+        }
+        self.close(definition.close, Space::Newline);
+    }
+
+    fn class_member(&mut self, member: &'s ClassMember<'s>) {
+        let mut space = Space::Single;
+        if let Some(attributes) = &member.attributes {
+            self.table(attributes, space);
+            space = Space::KeepLine;
+        }
+        if let Some(static_) = member.static_ {
+            self.token(static_, space);
+            space = Space::Single;
+        }
+        self.slot(&member.slot, space);
+        self.maybe_token(member.semicolon, Space::None);
+    }
+
+    fn struct_definition(&mut self, definition: &'s StructDefinition<'s>) {
+        self.open(definition.open, self.brace_space());
+        for property in &definition.properties {
+            self.type_(&property.type_, Space::Newline);
+            self.identifier(&property.name, Space::Single);
+            if let Some(initializer) = &property.initializer {
+                self.var_initializer(initializer);
+            }
+            self.maybe_token(property.comma, Space::None);
+        }
+        self.close(definition.close, Space::Newline);
+    }
+
+    fn function_definition(&mut self, definition: &'s FunctionDefinition<'s>) {
+        if let Some(environment) = &definition.environment {
+            self.open(environment.open, Space::None);
+            self.expression(&environment.value, self.bracket_space());
+            self.close(environment.close, self.bracket_space());
+        }
+        self.open(definition.open, Space::None);
+        let is_empty = self.function_params(&definition.params);
+        self.close(
+            definition.close,
+            self.inner_space(is_empty, self.paren_space()),
+        );
+        if let Some(captures) = &definition.captures {
+            self.function_captures(captures);
+        }
+        self.body(&definition.body);
+    }
+
+    // Writes function parameters, returning true if there are none.
+    fn function_params(&mut self, params: &'s FunctionParams<'s>) -> bool {
+        let space = self.paren_space();
+        match params {
+            FunctionParams::NonVariable { params: None } => return true,
+            FunctionParams::NonVariable {
+                params: Some(params),
+            } => self.separated_list_trailing1(params, true, space, Self::function_param),
+            FunctionParams::EmptyVariable { vararg } => self.token(vararg, space),
+            FunctionParams::NonEmptyVariable {
+                params,
+                comma,
+                vararg,
+            } => {
+                self.separated_list1(params, space, Space::Single, Self::function_param);
+                self.token(comma, Space::None);
+                self.token(vararg, Space::Single);
+            }
+        }
+        false
+    }
+
+    fn function_param(&mut self, param: &'s FunctionParam<'s>, space: Space) {
+        let mut space = space;
+        if let Some(type_) = &param.type_ {
+            self.type_(type_, space);
+            space = Space::Single;
+        }
+        self.identifier(&param.name, space);
+        if let Some(initializer) = &param.initializer {
+            self.var_initializer(initializer);
+        }
+    }
+
+    fn function_captures(&mut self, captures: &'s FunctionCaptures<'s>) {
+        self.token(captures.colon, Space::Single);
+        self.open(captures.open, Space::Single);
+        if let Some(names) = &captures.names {
+            self.separated_list_trailing1(names, true, self.paren_space(), |f, name, space| {
+                f.identifier(name, space)
+            });
+        }
+        let space = self.inner_space(captures.names.is_none(), self.paren_space());
+        self.close(captures.close, space);
+    }
+
+    fn function_ref_param(&mut self, param: &'s FunctionRefParam<'s>, space: Space) {
+        self.type_(&param.type_, space);
+        if let Some(name) = &param.name {
+            self.identifier(name, Space::Single);
+        }
+        if let Some(initializer) = &param.initializer {
+            self.var_initializer(initializer);
+        }
+    }
+
+    fn slot(&mut self, slot: &'s Slot<'s>, space: Space) {
+        match slot {
+            Slot::Property { name, initializer } => {
+                self.identifier(name, space);
+                self.var_initializer(initializer);
+            }
+            Slot::ComputedProperty {
+                open,
+                name,
+                close,
+                initializer,
+            } => {
+                self.open(open, space);
+                self.expression(name, self.bracket_space());
+                self.close(close, self.bracket_space());
+                self.var_initializer(initializer);
+            }
+            Slot::Constructor {
+                function,
+                constructor,
+                definition,
+            } => {
+                let mut space = space;
+                if let Some(function) = function {
+                    self.token(function, space);
+                    space = Space::Single;
+                }
+                self.token(constructor, space);
+                self.function_definition(definition);
+            }
+            Slot::Function {
+                return_type,
+                function,
+                name,
+                definition,
+            } => {
+                let mut space = space;
+                if let Some(return_type) = return_type {
+                    self.type_(return_type, space);
+                    space = Space::Single;
+                }
+                self.token(function, space);
+                self.identifier(name, Space::Single);
+                self.function_definition(definition);
+            }
+        }
+    }
+
+    fn maybe_type(&mut self, type_: Option<&'s Type<'s>>, space: Space) {
+        if let Some(type_) = type_ {
+            self.type_(type_, space);
+        }
+    }
+
+    fn type_(&mut self, type_: &'s Type<'s>, space: Space) {
+        match type_ {
+            Type::Local(ty) => self.token(ty.local, space),
+            Type::Var(ty) => self.token(ty.var, space),
+            Type::Plain(ty) => self.identifier(&ty.name, space),
+            Type::Array(ty) => {
+                self.type_(&ty.base, space);
+                self.open(ty.open, Space::None);
+                self.expression(&ty.len, Space::None);
+                self.close(ty.close, Space::None);
+            }
+            Type::Generic(ty) => {
+                self.type_(&ty.base, space);
+                self.open(ty.open, Space::None);
+                self.separated_list_trailing1(&ty.params, true, Space::None, |f, param, space| {
+                    f.type_(param, space)
+                });
+                self.close(ty.close, Space::None);
+            }
+            Type::FunctionRef(ty) => {
+                let mut space = space;
+                if let Some(return_type) = &ty.return_type {
+                    self.type_(return_type, space);
+                    space = Space::Single;
+                }
+                self.token(ty.functionref, space);
+                self.open(ty.open, Space::None);
+                if let Some(params) = &ty.params {
+                    self.separated_list_trailing1(
+                        params,
+                        true,
+                        self.paren_space(),
+                        Self::function_ref_param,
+                    );
+                }
+                let space = self.inner_space(ty.params.is_none(), self.paren_space());
+                self.close(ty.close, space);
+            }
+            Type::Struct(ty) => {
+                self.token(ty.struct_, space);
+                self.struct_definition(&ty.definition);
+            }
+            Type::Reference(ty) => {
+                self.type_(&ty.base, space);
+                self.token(ty.reference, Space::None);
+            }
+            Type::Nullable(ty) => {
+                self.type_(&ty.base, space);
+                self.token(ty.ornull, Space::Single);
+            }
+        }
+    }
+
+    fn parens(
+        &mut self,
+        open: &'s Token<'s>,
+        value: &'s Expression<'s>,
+        close: &'s Token<'s>,
+        space: Space,
+    ) {
+        self.open(open, space);
+        self.expression(value, self.paren_space());
+        self.close(close, self.paren_space());
+    }
+
+    fn inner_space(&self, is_empty: bool, space: Space) -> Space {
+        if is_empty {
+            Space::None
+        } else {
+            space
+        }
+    }
+
+    fn expression(&mut self, expression: &'s Expression<'s>, space: Space) {
+        match expression {
+            Expression::Parens(expr) => self.parens(expr.open, &expr.value, expr.close, space),
+            Expression::Literal(expr) => self.token(expr.token, space),
+            Expression::Var(expr) => self.identifier(&expr.name, space),
+            Expression::RootVar(expr) => {
+                self.token(expr.root, space);
+                self.identifier(&expr.name, Space::None);
+            }
+            Expression::Index(expr) => {
+                self.expression(&expr.base, space);
+                self.open(expr.open, Space::None);
+                self.expression(&expr.index, self.bracket_space());
+                self.close(expr.close, self.bracket_space());
+            }
+            Expression::Property(expr) => {
+                self.expression(&expr.base, space);
+                self.token(expr.dot, Space::None);
+                match &expr.property {
+                    MethodIdentifier::Identifier(identifier) => {
+                        self.identifier(identifier, Space::None)
+                    }
+                    MethodIdentifier::Constructor(constructor) => {
+                        self.token(constructor, Space::None)
+                    }
+                }
+            }
+            Expression::Ternary(expr) => {
+                self.expression(&expr.condition, space);
+                self.token(expr.question, Space::Single);
+                self.expression(&expr.true_value, Space::Single);
+                self.token(expr.separator, Space::Single);
+                self.expression(&expr.false_value, Space::Single);
+            }
+            Expression::Binary(expr) => {
+                self.expression(&expr.left, space);
+                self.binary_operator(expr.operator);
+                self.expression(&expr.right, Space::Single);
+            }
+            Expression::Prefix(expr) => {
+                let (operator, value_space) = match expr.operator {
+                    PrefixOperator::Negate(token)
+                    | PrefixOperator::LogicalNot(token)
+                    | PrefixOperator::BitwiseNot(token)
+                    | PrefixOperator::Increment(token)
+                    | PrefixOperator::Decrement(token) => (token, Space::None),
+                    PrefixOperator::Typeof(token)
+                    | PrefixOperator::Clone(token)
+                    | PrefixOperator::Delete(token) => (token, Space::Single),
+                };
+                self.token(operator, space);
+                self.expression(&expr.value, value_space);
+            }
+            Expression::Postfix(expr) => {
+                self.expression(&expr.value, space);
+                match expr.operator {
+                    PostfixOperator::Increment(token) | PostfixOperator::Decrement(token) => {
+                        self.token(token, Space::None)
+                    }
+                }
+            }
+            Expression::Comma(expr) => {
+                self.separated_list1(&expr.values, space, Space::Single, |f, value, space| {
+                    f.expression(value, space)
+                });
+            }
+            Expression::Table(expr) => self.table(expr, space),
+            Expression::Class(expr) => {
+                self.token(expr.class, space);
+                self.class_definition(&expr.definition);
+            }
+            Expression::Array(expr) => self.array(expr, space),
+            Expression::Function(expr) => {
+                let mut space = space;
+                if let Some(return_type) = &expr.return_type {
+                    self.type_(return_type, space);
+                    space = Space::Single;
+                }
+                self.token(expr.function, space);
+                self.function_definition(&expr.definition);
+            }
+            Expression::Lambda(expr) => {
+                self.token(expr.at, space);
+                self.open(expr.open, Space::None);
+                let is_empty = self.function_params(&expr.params);
+                self.close(expr.close, self.inner_space(is_empty, self.paren_space()));
+                self.expression(&expr.value, Space::Single);
+            }
+            Expression::Call(expr) => self.call(expr, space),
+            Expression::Delegate(expr) => {
+                self.token(expr.delegate, space);
+                self.expression(&expr.parent, Space::Single);
+                self.token(expr.colon, Space::Single);
+                self.expression(&expr.value, Space::Single);
+            }
+            Expression::Vector(expr) => {
+                // Vectors always have inner spaces, since `<-` would be parsed as an operator.
+                self.open(expr.open, space);
+                self.expression(&expr.x, Space::Single);
+                self.token(expr.comma_1, Space::None);
+                self.expression(&expr.y, Space::Single);
+                self.token(expr.comma_2, Space::None);
+                self.expression(&expr.z, Space::Single);
+                self.close(expr.close, Space::Single);
+            }
+            Expression::Expect(expr) => {
+                self.token(expr.expect, space);
+                self.type_(&expr.ty, Space::Single);
+                self.open(expr.open, Space::None);
+                self.expression(&expr.value, self.paren_space());
+                self.close(expr.close, self.paren_space());
+            }
+            // Error expressions are inserted where an expression is missing, so have no tokens.
+            Expression::Error(_) => {}
+        }
+    }
+
+    fn binary_operator(&mut self, operator: BinaryOperator<'s>) {
+        match operator {
+            BinaryOperator::AssignNewSlot(a, b)
+            | BinaryOperator::ShiftLeft(a, b)
+            | BinaryOperator::ShiftRight(a, b) => {
+                self.token(a, Space::Single);
+                self.token(b, Space::None);
+            }
+            BinaryOperator::UnsignedShiftRight(a, b, c) => {
+                self.token(a, Space::Single);
+                self.token(b, Space::None);
+                self.token(c, Space::None);
+            }
+            BinaryOperator::Assign(a)
+            | BinaryOperator::AssignAdd(a)
+            | BinaryOperator::AssignSubtract(a)
+            | BinaryOperator::AssignMultiply(a)
+            | BinaryOperator::AssignDivide(a)
+            | BinaryOperator::AssignModulo(a)
+            | BinaryOperator::Add(a)
+            | BinaryOperator::Subtract(a)
+            | BinaryOperator::Multiply(a)
+            | BinaryOperator::Divide(a)
+            | BinaryOperator::Modulo(a)
+            | BinaryOperator::Equal(a)
+            | BinaryOperator::NotEqual(a)
+            | BinaryOperator::Less(a)
+            | BinaryOperator::LessEqual(a)
+            | BinaryOperator::Greater(a)
+            | BinaryOperator::GreaterEqual(a)
+            | BinaryOperator::ThreeWay(a)
+            | BinaryOperator::LogicalAnd(a)
+            | BinaryOperator::LogicalOr(a)
+            | BinaryOperator::BitwiseAnd(a)
+            | BinaryOperator::BitwiseOr(a)
+            | BinaryOperator::BitwiseXor(a)
+            | BinaryOperator::In(a)
+            | BinaryOperator::Instanceof(a) => self.token(a, Space::Single),
+        }
+    }
+
+    fn table(&mut self, table: &'s TableExpression<'s>, space: Space) {
+        self.open(table.open, space);
+        for slot in &table.slots {
+            match &slot.ty {
+                TableSlotType::Slot(slot) => self.slot(slot, Space::Single),
+                TableSlotType::JsonProperty {
+                    name_token,
+                    colon,
+                    value,
+                    ..
+                } => {
+                    self.token(name_token, Space::Single);
+                    self.token(colon, Space::None);
+                    self.expression(value, Space::Single);
+                }
+            }
+            self.maybe_token(slot.comma, Space::None);
+        }
+        self.maybe_token(table.spread, Space::Single);
+        let is_empty = table.slots.is_empty() && table.spread.is_none();
+        self.close(table.close, self.inner_space(is_empty, Space::Single));
+    }
+
+    fn array(&mut self, array: &'s ArrayExpression<'s>, space: Space) {
+        self.open(array.open, space);
+        let mut value_space = self.bracket_space();
+        for value in &array.values {
+            self.expression(&value.value, value_space);
+            self.maybe_token(value.separator, Space::None);
+            value_space = Space::Single;
+        }
+        self.maybe_token(array.spread, value_space);
+        let is_empty = array.values.is_empty() && array.spread.is_none();
+        self.close(
+            array.close,
+            self.inner_space(is_empty, self.bracket_space()),
+        );
+    }
+
+    fn call(&mut self, call: &'s CallExpression<'s>, space: Space) {
+        self.expression(&call.function, space);
+        self.open(call.open, Space::None);
+        let mut argument_space = self.paren_space();
+        for argument in &call.arguments {
+            self.expression(&argument.value, argument_space);
+            self.maybe_token(argument.comma, Space::None);
+            argument_space = Space::Single;
+        }
+        let is_empty = call.arguments.is_empty();
+        self.close(call.close, self.inner_space(is_empty, self.paren_space()));
+        if let Some(post_initializer) = &call.post_initializer {
+            self.table(post_initializer, Space::Single);
+        }
+    }
+
+    fn separated_list1<T, F: FnMut(&mut Self, &'s T, Space)>(
+        &mut self,
+        list: &'s SeparatedList1<'s, T>,
+        first_space: Space,
+        item_space: Space,
+        mut item: F,
+    ) {
+        let mut space = first_space;
+        for (value, separator) in &list.items {
+            item(self, value, space);
+            self.token(separator, Space::None);
+            space = item_space;
+        }
+        item(self, &list.last_item, space);
+    }
+
+    fn separated_list_trailing1<T, F: FnMut(&mut Self, &'s T, Space)>(
+        &mut self,
+        list: &'s SeparatedListTrailing1<'s, T>,
+        is_delimited: bool,
+        first_space: Space,
+        mut item: F,
+    ) {
+        let mut space = first_space;
+        for (value, separator) in &list.items {
+            item(self, value, space);
+            self.token(separator, Space::None);
+            space = Space::Single;
+        }
+        item(self, &list.last_item, space);
+
+        let rule = if is_delimited {
+            self.options.trailing_commas
+        } else {
+            match self.options.trailing_commas {
+                TrailingCommas::Never => TrailingCommas::Never,
+                _ => TrailingCommas::Preserve,
+            }
+        };
+        let is_vertical = match list.trailing {
+            Some(trailing) => trailing.new_line.is_some(),
+            None => self.pending_line.is_some(),
+        };
+        let keep = match rule {
+            TrailingCommas::Preserve => true,
+            TrailingCommas::Never => false,
+            TrailingCommas::Always => true,
+            TrailingCommas::Vertical => is_vertical,
+        };
+
+        match list.trailing {
+            Some(trailing) if keep || has_comments(trailing) => self.token(trailing, Space::None),
+            Some(trailing) => self.skip(trailing),
+            None if rule == TrailingCommas::Always
+                || (keep && rule == TrailingCommas::Vertical) =>
+            {
+                self.synthetic(TerminalToken::Comma.as_str())
+            }
+            None => {}
+        }
+    }
+}
+
+fn has_comments(token: &Token) -> bool {
+    !token.comments.is_empty()
+        || token
+            .before_lines
+            .iter()
+            .any(|line| !line.comments.is_empty())
+        || token
+            .new_line
+            .as_ref()
+            .is_some_and(|line| !line.comments.is_empty())
+}
+
+// Returns true if there must be a space between two tokens, to prevent them from being lexed as a
+// single token.
+fn needs_space(previous: &str, next: &str) -> bool {
+    let (Some(last), Some(first)) = (previous.chars().last(), next.chars().next()) else {
+        return false;
+    };
+
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    if is_word(last) && is_word(first) {
+        return true;
+    }
+
+    // A `.` after a number would be lexed as part of a float.
+    if previous.starts_with(|c: char| c.is_ascii_digit()) && first == '.' {
+        return true;
+    }
+
+    let mut pair = String::with_capacity(2);
+    pair.push(last);
+    pair.push(first);
+    pair == "//"
+        || pair == "/*"
+        || TerminalToken::SYMBOLS
+            .iter()
+            .any(|(_, symbol)| symbol.starts_with(&pair))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::has_comments;
+    use crate::token::{Comment, TerminalToken, TokenType};
+    use crate::{
+        format_program, parse, parse_recover, tokenize, BraceStyle, Flavor, FormatOptions,
+    };
+    use crate::{TokenItem, TrailingCommas};
+
+    fn format(source: &str, options: &FormatOptions) -> String {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let formatted = format_program(&program, source, options);
+
+        // The formatted code must contain the same tokens with the same comments attached.
+        let formatted_tokens = tokenize(&formatted, Flavor::SquirrelRespawn).unwrap();
+        assert_eq!(
+            token_comments(&tokens),
+            token_comments(&formatted_tokens),
+            "comments changed in:\n{formatted}"
+        );
+        parse(&formatted_tokens, Flavor::SquirrelRespawn).unwrap();
+
+        // Formatting must be idempotent.
+        let program = parse(&formatted_tokens, Flavor::SquirrelRespawn).unwrap();
+        assert_eq!(format_program(&program, &formatted, options), formatted);
+
+        formatted
+    }
+
+    type TokenComments<'s> = (String, Vec<Comment<'s>>, Vec<Comment<'s>>, Vec<Comment<'s>>);
+
+    fn token_comments<'s>(tokens: &'s [TokenItem<'s>]) -> Vec<TokenComments<'s>> {
+        tokens
+            .iter()
+            // Trailing commas may be added or removed if they don't have comments.
+            .filter(|item| {
+                item.token.ty != TokenType::Terminal(TerminalToken::Comma)
+                    || has_comments(&item.token)
+            })
+            .map(|item| {
+                let token = &item.token;
+                (
+                    format!("{:?}", token.ty),
+                    token
+                        .before_lines
+                        .iter()
+                        .flat_map(|line| line.comments.iter().copied())
+                        .collect(),
+                    token.comments.clone(),
+                    token
+                        .new_line
+                        .iter()
+                        .flat_map(|line| line.comments.iter().copied())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn format_example_script() {
+        let source = include_str!("../examples/print_ast_script.nut");
+        format(source, &FormatOptions::default());
+        format(
+            source,
+            &FormatOptions {
+                hard_tabs: false,
+                paren_spacing: false,
+                bracket_spacing: false,
+                brace_style: BraceStyle::SameLine,
+                ..FormatOptions::default()
+            },
+        );
+    }
+
+    #[test]
+    fn format_respawn_style() {
+        let source = "
+global function Foo
+
+
+void function Foo(int a,array<string> b=[]){
+  if(a>0){print(b[0])}else if(a<0)
+  return
+  else {
+foreach(i,v in b){
+      // comment
+      print(v)   /* trailing */
+    }
+  }
+}";
+        let expected = "global function Foo
+
+void function Foo( int a, array<string> b = [] )
+{
+\tif ( a > 0 )
+\t{
+\t\tprint( b[ 0 ] )
+\t}
+\telse if ( a < 0 )
+\t\treturn
+\telse
+\t{
+\t\tforeach ( i, v in b )
+\t\t{
+\t\t\t// comment
+\t\t\tprint( v ) /* trailing */
+\t\t}
+\t}
+}
+";
+        assert_eq!(format(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn format_same_line_braces() {
+        let source = "switch(x){case 1:a()\nbreak\ndefault:b()}\nstruct Foo\n{int a,string b}";
+        let options = FormatOptions {
+            hard_tabs: false,
+            indent_width: 2,
+            paren_spacing: false,
+            brace_style: BraceStyle::SameLine,
+            ..FormatOptions::default()
+        };
+        let expected = "switch (x) {
+  case 1:
+    a()
+    break
+  default:
+    b()
+}
+struct Foo {
+  int a,
+  string b
+}
+";
+        assert_eq!(format(source, &options), expected);
+    }
+
+    #[test]
+    fn format_keeps_line_breaks() {
+        let source = "local a = [\n1,\n  2\n]\nlocal b = c +\nd\nFoo(a,\nb)";
+        let expected = "local a = [\n\t1,\n\t2\n]\nlocal b = c +\n\td\nFoo( a,\n\tb )\n";
+        assert_eq!(format(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn format_trailing_commas() {
+        let source = "void function Foo(int a,){}\nvoid function Bar(\nint a,\nint b\n){}";
+        let options = |trailing_commas| FormatOptions {
+            trailing_commas,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format(source, &options(TrailingCommas::Never)),
+            "void function Foo( int a )\n{\n}\nvoid function Bar(\n\tint a,\n\tint b\n)\n{\n}\n"
+        );
+        assert_eq!(
+            format(source, &options(TrailingCommas::Vertical)),
+            "void function Foo( int a )\n{\n}\nvoid function Bar(\n\tint a,\n\tint b,\n)\n{\n}\n"
+        );
+        assert_eq!(
+            format(source, &options(TrailingCommas::Always)),
+            "void function Foo( int a, )\n{\n}\nvoid function Bar(\n\tint a,\n\tint b,\n)\n{\n}\n"
+        );
+    }
+
+    #[test]
+    fn format_separates_tokens() {
+        let source = "a = - -b\nb = 1 .tostring()\nc = a<-<1,-2,3>\nd = a<<b>>c";
+        let expected = "a = - -b\nb = 1 .tostring()\nc = a <- < 1, -2, 3 >\nd = a << b >> c\n";
+        assert_eq!(format(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn format_blank_lines_and_directives() {
+        let source = "\n\n#if SERVER\n\n\n\nint a = 1\n#endif\n\n\n\n// end\n\n";
+        let expected = "#if SERVER\n\nint a = 1\n#endif\n\n// end\n";
+        assert_eq!(format(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn format_conditional_statements() {
+        let source = "void function F()\n{\na()\n#if SERVER\n  b()\n#else\n      c()\n#endif\n}\n";
+        let expected = "void function F()\n{\n\ta()\n#if SERVER\n\tb()\n#else\n\tc()\n#endif\n}\n";
+        assert_eq!(format(source, &FormatOptions::default()), expected);
+    }
+
+    #[test]
+    fn format_all_statements() {
+        // Checks that every construct formats without changing tokens or comments.
+        let source = r#"untyped
+globalize_all_functions
+global const int MAX=10
+global enum Foo{A,B=2,C}
+global struct Bar{int a=1,string b}
+global typedef FnType void functionref(int,string)
+const float PI=3.14
+class Animal extends Base {
+  name = null
+  </ attr = 1 />
+  static count = 0;
+  constructor(n){ this.name = n }
+  function Speak(){ return "..." }
+}
+table<string,int> t = {a=1,["b"]=2, "json": 3}
+void function Test(entity player, ...) : (t) {
+    for(local i=0;i<10;i++){ continue }
+    for(;;) break
+    do{ wait 1.0 }while(true)
+    try{ throw "x" }catch(e){ print(e) }
+    thread Foo()
+    delaythread(1.0) Foo()
+    waitthread Foo()
+    local f = @(a) a+1
+    local g = function(a=2) { return a } // trailing
+    local v = <1,2,3>
+    local d = delegate p : {}
+    local e = expect int(x)
+    local x = a ? b : c
+    local y = typeof a
+    local z = clone a
+    delete t.a
+    ::root <- 5
+    x = -a + !b - ~c
+    a.b.c[d](e)(f)
+    if(x)
+      a()
+    else
+      b()
+    local arr = [1,2,3,...]
+    return
+}
+function Animal::constructor(a) {}
+entity ornull function G(array<int>& a) { return null }
+/* end */
+"#;
+        format(source, &FormatOptions::default());
+        format(
+            source,
+            &FormatOptions {
+                brace_style: BraceStyle::SameLine,
+                trailing_commas: TrailingCommas::Always,
+                ..FormatOptions::default()
+            },
+        );
+    }
+
+    #[test]
+    fn recovered_errors() {
+        let source = "void function A(){\nlocal a=\nlocal b=2 3  +(  4 // keep\n  )\nc( ) }\n";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let (program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+        assert_eq!(errors.len(), 2);
+
+        // Error statements are written as they are in the source.
+        let formatted = format_program(&program, source, &FormatOptions::default());
+        assert_eq!(
+            formatted,
+            "void function A()\n{\n\tlocal a =\n\tlocal b = 2 3  +(  4 // keep\n  )\n\tc()\n}\n"
+        );
+        let formatted_tokens = tokenize(&formatted, Flavor::SquirrelRespawn).unwrap();
+        let (_, formatted_errors) = parse_recover(&formatted_tokens, Flavor::SquirrelRespawn);
+        assert_eq!(formatted_errors.len(), 2);
+    }
+}
@@ -1,7 +1,6 @@
-use crate::annotation::{display_annotations, Annotation, Mode};
+use crate::annotation::{Diagnostic, Label, Level};
 use crate::token::TokenType;
 use std::ops::Range;
-use yansi::Paint;
 
 /// Type of [`LexerError`].
 ///
@@ -46,6 +45,34 @@ pub enum LexerErrorType<'s> {
         open: TokenType<'s>,
         close: TokenType<'s>,
     },
+
+    /// A preprocessor directive was not formed correctly.
+    ///
+    /// # Example
+    /// ```text
+    /// #if SERVER &&
+    ///              ^ error
+    /// ```
+    InvalidDirective,
+
+    /// An `#elseif`, `#else` or `#endif` directive was not preceded by an `#if`, or appeared after
+    /// an `#else`.
+    ///
+    /// # Example
+    /// ```text
+    /// #endif
+    /// ^^^^^^ error
+    /// ```
+    UnmatchedDirective,
+
+    /// An `#if` directive was not closed by an `#endif`.
+    ///
+    /// # Example
+    /// ```text
+    /// #if SERVER
+    /// ^^^^^^^^^^ error
+    /// ```
+    UnterminatedDirective,
 }
 
 /// An error emitted while trying to tokenize an input string.
@@ -67,15 +94,23 @@ impl<'s> LexerError<'s> {
         LexerError { ty, range }
     }
 
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`].
+    ///
+    /// [`code`]: LexerErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        Diagnostic::new(Level::Error, self.ty.to_string(), label).with_code(self.ty.code())
+    }
+
     /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
-    /// context using [`display_annotations`].
+    /// context using [`Diagnostic::display`].
     pub fn display<'a>(
         &'a self,
         source: &'a str,
         file_name: Option<&'s str>,
     ) -> impl std::fmt::Display + 'a {
         Display {
-            error: self,
+            diagnostic: self.to_diagnostic(),
             source,
             file_name,
         }
@@ -83,6 +118,21 @@ impl<'s> LexerError<'s> {
 }
 
 impl<'a> LexerErrorType<'a> {
+    /// Returns a stable code for the error type, from `E0001` to `E0099`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(self) -> &'static str {
+        match self {
+            LexerErrorType::EndOfInputInsideString => "E0001",
+            LexerErrorType::EndOfLineInsideString => "E0002",
+            LexerErrorType::InvalidInput => "E0003",
+            LexerErrorType::UnmatchedOpener { .. } => "E0004",
+            LexerErrorType::InvalidDirective => "E0005",
+            LexerErrorType::UnmatchedDirective => "E0006",
+            LexerErrorType::UnterminatedDirective => "E0007",
+        }
+    }
+
     pub fn inline_display(self) -> impl std::fmt::Display + 'a {
         LexerErrorInlineDisplay(self)
     }
@@ -101,6 +151,11 @@ impl std::fmt::Display for LexerErrorType<'_> {
             LexerErrorType::UnmatchedOpener { open, .. } => {
                 write!(f, "unclosed delimiter {open}")
             }
+            LexerErrorType::InvalidDirective => write!(f, "invalid preprocessor directive"),
+            LexerErrorType::UnmatchedDirective => {
+                write!(f, "preprocessor directive without a matching `#if`")
+            }
+            LexerErrorType::UnterminatedDirective => write!(f, "unterminated `#if` block"),
         }
     }
 }
@@ -117,39 +172,25 @@ impl std::fmt::Display for LexerErrorInlineDisplay<'_> {
                 write!(f, "does not have a matching {close}")
             }
             LexerErrorType::InvalidInput => write!(f, "not sure what this is"),
+            LexerErrorType::InvalidDirective => write!(f, "not valid here"),
+            LexerErrorType::UnmatchedDirective => write!(f, "no `#if` for this"),
+            LexerErrorType::UnterminatedDirective => write!(f, "missing an `#endif`"),
         }
     }
 }
 
 struct Display<'s> {
-    error: &'s LexerError<'s>,
+    diagnostic: Diagnostic,
     source: &'s str,
     file_name: Option<&'s str>,
 }
 
 impl std::fmt::Display for Display<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(
-            f,
-            "{}{}{}",
-            Paint::red("error").bold(),
-            Paint::white(": ").bold(),
-            Paint::white(self.error.ty).bold(),
-        )?;
-
-        let annotations = [Annotation {
-            mode: Mode::Error,
-            text: format!("{}", self.error.ty.inline_display()),
-            note: "".to_string(),
-            highlight: self.error.range.clone(),
-            visible: self.error.range.clone(),
-        }];
-
         write!(
             f,
             "{}",
-            display_annotations(self.file_name, self.source, &annotations)
-        )?;
-        Ok(())
+            self.diagnostic.display(self.source, self.file_name)
+        )
     }
 }
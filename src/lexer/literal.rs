@@ -16,6 +16,36 @@ pub fn try_literal(val: ParseStr) -> Result<Option<(LiteralToken, ParseStr)>, Le
     Ok(None)
 }
 
+// Returns a string or char literal that is cut off at `end_offset`, for recovering from a string
+// that is missing its closing delimiter.
+pub fn try_unterminated_literal(
+    val: ParseStr,
+    end_offset: usize,
+) -> Option<(LiteralToken, ParseStr)> {
+    let end = end_offset.checked_sub(val.start_offset())?;
+    let (prefix_len, make_literal): (usize, fn(&str) -> LiteralToken) =
+        if val.as_str().starts_with('\'') {
+            (1, |val| LiteralToken::Char(val))
+        } else if val.as_str().starts_with("@\"") {
+            (2, |val| LiteralToken::String(StringToken::Verbatim(val)))
+        } else if val.as_str().starts_with("$\"") {
+            (2, |val| LiteralToken::String(StringToken::Asset(val)))
+        } else if val.as_str().starts_with('"') {
+            (1, |val| LiteralToken::String(StringToken::Literal(val)))
+        } else {
+            return None;
+        };
+
+    // Don't include the `\r` of a `\r\n` line ending in the string.
+    let end = if val.as_str()[..end].ends_with('\r') {
+        end - 1
+    } else {
+        end
+    };
+    let literal_val = val.as_str().get(prefix_len..end)?;
+    Some((make_literal(literal_val), val.from(end)))
+}
+
 fn starts_with_octal(val: &str) -> bool {
     let mut chars = val.chars();
     match chars.next() {
@@ -1,13 +1,17 @@
+use crate::lexer::literal::try_literal;
+use crate::lexer::parse_str::ParseStr;
 use crate::lexer::token_iter::TokenIter;
-use crate::token::{TerminalToken, Token, TokenType};
+use crate::preprocessor::{link_directives, parse_directive, DirectiveItem};
+use crate::token::{Comment, LiteralToken, TerminalToken, Token, TokenType};
 use crate::Flavor;
 use std::collections::VecDeque;
+use std::ops::Range;
 
-mod comment;
+pub(crate) mod comment;
 mod error;
 mod identifier;
 mod literal;
-mod parse_str;
+pub(crate) mod parse_str;
 mod symbol;
 mod token_iter;
 
@@ -30,6 +34,41 @@ pub struct TokenItem<'s> {
     /// In this example, the opening `{` token would have a `close_index` of 5, the index of the
     /// closing delimiter.
     pub close_index: Option<usize>,
+
+    /// The character ranges of the token's comments, in the order they appear in the source: the
+    /// comments in `before_lines`, then `comments`, then the comments in `new_line`. Each range
+    /// includes the comment's delimiters, like the `//` of a single line comment.
+    pub comment_ranges: Vec<Range<usize>>,
+
+    // The preprocessor directives in the comments before the token, linked to the directives that
+    // continue them.
+    pub(crate) directives: Vec<DirectiveItem<'s>>,
+}
+
+impl<'s> TokenItem<'s> {
+    /// Returns the token's comments with their character ranges, in the order they appear in the
+    /// source.
+    ///
+    /// # Example
+    /// ```
+    /// use sqparse::{Flavor, tokenize};
+    /// use sqparse::token::Comment;
+    ///
+    /// let source = "// first\na /* second */ // third\n";
+    /// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+    /// let comments: Vec<_> = tokens[0].comments().collect();
+    /// assert_eq!(comments.len(), 3);
+    /// assert_eq!(comments[2], (&Comment::SingleLine(" third"), 24..32));
+    /// assert_eq!(&source[comments[0].1.clone()], "// first");
+    /// ```
+    pub fn comments(&self) -> impl Iterator<Item = (&Comment<'s>, Range<usize>)> + '_ {
+        let token = &self.token;
+        let comments = (token.before_lines.iter())
+            .flat_map(|line| &line.comments)
+            .chain(&token.comments)
+            .chain(token.new_line.iter().flat_map(|line| &line.comments));
+        comments.zip(self.comment_ranges.iter().cloned())
+    }
 }
 
 // Returns the token that closes a tree, if the provided token is a valid opening token.
@@ -51,6 +90,14 @@ fn closing_token(opening: TokenType) -> Option<TokenType> {
     }
 }
 
+// Returns the literal if the entire input string is a single literal token.
+pub(crate) fn literal_from_str(val: &str) -> Option<LiteralToken<'_>> {
+    match try_literal(ParseStr::new(val)) {
+        Ok(Some((literal, remaining))) if remaining.is_ended() => Some(literal),
+        _ => None,
+    }
+}
+
 struct Layer<'s> {
     open_index: usize,
     close_ty: TokenType<'s>,
@@ -82,44 +129,246 @@ pub fn tokenize(val: &str, flavor: Flavor) -> Result<Vec<TokenItem<'_>>, LexerEr
     let mut layers = VecDeque::<Layer>::new();
 
     for maybe_token in TokenIter::new(val, flavor) {
-        let token = maybe_token?;
-        let token_index = items.len();
-
-        // If this token matches the top layer's close token, pop the layer.
-        if let Some(top_layer) = layers.back() {
-            if top_layer.close_ty == token.ty {
-                items[top_layer.open_index].close_index = Some(token_index);
-                layers.pop_back();
+        let (token, comment_ranges) = maybe_token?;
+        push_item(&mut items, &mut layers, token, comment_ranges);
+    }
+    link_directives(&mut items);
+
+    // If there are remaining layers, there are one or more unmatched opening tokens. Otherwise
+    // at this point tokenization is successful.
+    match layers.back() {
+        None => Ok(items),
+        Some(layer) => Err(unmatched_opener_error(&items, layer)),
+    }
+}
+
+/// Parses an input string into a list of tokens, continuing past any errors.
+///
+/// Unlike [`tokenize`], this always returns a full list of tokens along with every error that was
+/// encountered:
+///  - Unrecognized input is emitted as an [`Invalid`] token.
+///  - Strings that are missing a closing delimiter are closed at the end of the line, or the end
+///    of input for verbatim strings.
+///  - Opening delimiters without a matching closing delimiter are reported, and are left with a
+///    `close_index` of `None`. A closing delimiter closes the nearest opener it matches, even if
+///    there are unclosed openers inside it.
+///
+/// If no errors are returned, the tokens are identical to those returned by [`tokenize`]. The
+/// tokens can be parsed with [`parse_recover`].
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, LexerErrorType, tokenize_lossy};
+/// use sqparse::token::TokenType;
+///
+/// let source = "local a = `oops`\nlocal b = \"unterminated\nlocal c = (1";
+/// let (tokens, errors) = tokenize_lossy(source, Flavor::SquirrelRespawn);
+///
+/// assert_eq!(tokens[3].token.ty, TokenType::Invalid("`"));
+/// assert_eq!(errors.len(), 4);
+/// assert!(matches!(errors[0].ty, LexerErrorType::InvalidInput));
+/// assert!(matches!(errors[2].ty, LexerErrorType::EndOfLineInsideString));
+/// assert!(matches!(errors[3].ty, LexerErrorType::UnmatchedOpener { .. }));
+/// ```
+///
+/// [`Invalid`]: crate::token::TokenType::Invalid
+/// [`parse_recover`]: crate::parse_recover
+pub fn tokenize_lossy(val: &str, flavor: Flavor) -> (Vec<TokenItem<'_>>, Vec<LexerError<'_>>) {
+    let mut items = Vec::<TokenItem>::new();
+    let mut layers = VecDeque::<Layer>::new();
+    let mut errors = Vec::new();
+
+    let mut iter = TokenIter::new(val, flavor).lossy();
+    for maybe_token in &mut iter {
+        let (token, comment_ranges) = match maybe_token {
+            Ok(token) => token,
+            Err(err) => {
+                errors.push(err);
+                break;
             }
-        }
+        };
 
-        // If this token is a valid opening token, push a new layer.
-        if let Some(close_ty) = closing_token(token.ty) {
-            layers.push_back(Layer {
-                open_index: token_index,
-                close_ty,
-            });
+        // If this token closes a layer further down the stack, the layers above it are unmatched.
+        if let Some(layer_index) = layers.iter().rposition(|layer| layer.close_ty == token.ty) {
+            for layer in layers.drain(layer_index + 1..) {
+                errors.push(unmatched_opener_error(&items, &layer));
+            }
         }
 
-        items.push(TokenItem {
-            token,
-            close_index: None,
-        });
+        push_item(&mut items, &mut layers, token, comment_ranges);
     }
+    link_directives(&mut items);
+
+    errors.extend(
+        layers
+            .iter()
+            .map(|layer| unmatched_opener_error(&items, layer)),
+    );
+    errors.extend(iter.take_errors());
+    errors.sort_by_key(|error| error.range.start);
+
+    (items, errors)
+}
+
+// Links opening and closing delimiters in a list of already lexed tokens, with the ranges of their
+// comments.
+pub(crate) fn link_tokens<'s>(
+    tokens: impl IntoIterator<Item = (Token<'s>, Vec<Range<usize>>)>,
+) -> Result<Vec<TokenItem<'s>>, LexerError<'s>> {
+    let mut items = Vec::<TokenItem>::new();
+    let mut layers = VecDeque::<Layer>::new();
+
+    for (token, comment_ranges) in tokens {
+        push_item(&mut items, &mut layers, token, comment_ranges);
+    }
+    link_directives(&mut items);
 
-    // If there are remaining layers, there are one or more unmatched opening tokens. Otherwise
-    // at this point tokenization is successful.
     match layers.back() {
         None => Ok(items),
-        Some(layer) => {
-            let open_token = &items[layer.open_index].token;
-            Err(LexerError::new(
-                LexerErrorType::UnmatchedOpener {
-                    open: open_token.ty,
-                    close: layer.close_ty,
-                },
-                open_token.range.clone(),
-            ))
+        Some(layer) => Err(unmatched_opener_error(&items, layer)),
+    }
+}
+
+// Pushes a token, linking it to its opening token if it closes the top layer.
+fn push_item<'s>(
+    items: &mut Vec<TokenItem<'s>>,
+    layers: &mut VecDeque<Layer<'s>>,
+    token: Token<'s>,
+    comment_ranges: Vec<Range<usize>>,
+) {
+    let token_index = items.len();
+
+    // If this token matches the top layer's close token, pop the layer.
+    if let Some(top_layer) = layers.back() {
+        if top_layer.close_ty == token.ty {
+            items[top_layer.open_index].close_index = Some(token_index);
+            layers.pop_back();
         }
     }
+
+    // If this token is a valid opening token, push a new layer.
+    if let Some(close_ty) = closing_token(token.ty) {
+        layers.push_back(Layer {
+            open_index: token_index,
+            close_ty,
+        });
+    }
+
+    let mut item = TokenItem {
+        token,
+        close_index: None,
+        comment_ranges,
+        directives: Vec::new(),
+    };
+    item.directives = directive_items(&item);
+    items.push(item);
+}
+
+// Parses the directives in the comments before a token. Directives are always on their own line,
+// so can only be in lines before a token, or before the empty token at the end of the input.
+fn directive_items<'s>(item: &TokenItem<'s>) -> Vec<DirectiveItem<'s>> {
+    let before_count = (item.token.before_lines.iter())
+        .map(|line| line.comments.len())
+        .sum::<usize>()
+        + item.token.comments.len();
+    (item.comments().take(before_count))
+        .filter_map(|(comment, range)| {
+            let Comment::ScriptStyle(text) = *comment else {
+                return None;
+            };
+            // The directive's text starts after the `#`.
+            let offset = range.start + 1;
+            let directive = parse_directive(text)?
+                .map_err(|error| (offset + error.start)..(offset + error.end));
+            Some(DirectiveItem::new(directive, range))
+        })
+        .collect()
+}
+
+fn unmatched_opener_error<'s>(items: &[TokenItem<'s>], layer: &Layer<'s>) -> LexerError<'s> {
+    let open_token = &items[layer.open_index].token;
+    LexerError::new(
+        LexerErrorType::UnmatchedOpener {
+            open: open_token.ty,
+            close: layer.close_ty,
+        },
+        open_token.range.clone(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::token::{LiteralToken, StringToken, TerminalToken, TokenType};
+    use crate::{tokenize, tokenize_lossy, Flavor, LexerErrorType};
+
+    #[test]
+    fn lossy_matches_tokenize() {
+        let source = include_str!("../../examples/print_ast_script.nut");
+        let (tokens, errors) = tokenize_lossy(source, Flavor::SquirrelRespawn);
+
+        assert!(errors.is_empty());
+        assert_eq!(tokens, tokenize(source, Flavor::SquirrelRespawn).unwrap());
+    }
+
+    #[test]
+    fn lossy_invalid_input() {
+        let (tokens, errors) = tokenize_lossy("a = `` b", Flavor::SquirrelRespawn);
+        let types: Vec<_> = tokens.iter().map(|item| item.token.ty).collect();
+
+        assert_eq!(
+            types,
+            [
+                TokenType::Identifier("a"),
+                TokenType::Terminal(TerminalToken::Assign),
+                TokenType::Invalid("``"),
+                TokenType::Identifier("b"),
+            ]
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].ty, LexerErrorType::InvalidInput));
+        assert_eq!(errors[0].range, 4..6);
+    }
+
+    #[test]
+    fn lossy_unterminated_string() {
+        let (tokens, errors) =
+            tokenize_lossy("a = \"hello\r\nb = @\"world", Flavor::SquirrelRespawn);
+
+        assert_eq!(
+            tokens[2].token.ty,
+            TokenType::Literal(LiteralToken::String(StringToken::Literal("hello")))
+        );
+        assert_eq!(tokens[3].token.ty, TokenType::Identifier("b"));
+        assert_eq!(
+            tokens[5].token.ty,
+            TokenType::Literal(LiteralToken::String(StringToken::Verbatim("world")))
+        );
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[0].ty,
+            LexerErrorType::EndOfLineInsideString
+        ));
+        assert!(matches!(
+            errors[1].ty,
+            LexerErrorType::EndOfInputInsideString
+        ));
+    }
+
+    #[test]
+    fn lossy_unmatched_openers() {
+        let (tokens, errors) = tokenize_lossy("{ a( [ } ]", Flavor::SquirrelRespawn);
+        let close_indices: Vec<_> = tokens.iter().map(|item| item.close_index).collect();
+
+        assert_eq!(close_indices, [Some(4), None, None, None, None, None]);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[0].ty,
+            LexerErrorType::UnmatchedOpener {
+                open: TokenType::Terminal(TerminalToken::OpenBracket),
+                ..
+            }
+        ));
+        assert_eq!(errors[0].range, 3..4);
+        assert_eq!(errors[1].range, 5..6);
+    }
 }
@@ -1,11 +1,12 @@
 use crate::lexer::comment::try_comment;
 use crate::lexer::error::{LexerError, LexerErrorType};
 use crate::lexer::identifier::try_identifier;
-use crate::lexer::literal::try_literal;
+use crate::lexer::literal::{try_literal, try_unterminated_literal};
 use crate::lexer::parse_str::ParseStr;
 use crate::lexer::symbol::try_symbol;
 use crate::token::{Comment, Token, TokenLine, TokenType};
 use crate::Flavor;
+use std::ops::Range;
 
 macro_rules! try_some {
     ($val:expr) => {
@@ -16,12 +17,17 @@ macro_rules! try_some {
     };
 }
 
+// Yields each token with the ranges of its comments, in the order they appear in the source.
 pub struct TokenIter<'s> {
     val: ParseStr<'s>,
     flavor: Flavor,
-    current_token: Option<Token<'s>>,
+    current_token: Option<(Token<'s>, Vec<Range<usize>>)>,
     before_lines: Vec<TokenLine<'s>>,
+    before_ranges: Vec<Range<usize>>,
     current_line_comments: Vec<Comment<'s>>,
+    current_line_ranges: Vec<Range<usize>>,
+    lossy: bool,
+    errors: Vec<LexerError<'s>>,
 }
 
 impl<'s> TokenIter<'s> {
@@ -31,13 +37,57 @@ impl<'s> TokenIter<'s> {
             flavor,
             current_token: None,
             before_lines: Vec::new(),
+            before_ranges: Vec::new(),
             current_line_comments: Vec::new(),
+            current_line_ranges: Vec::new(),
+            lossy: false,
+            errors: Vec::new(),
+        }
+    }
+
+    // In lossy mode, invalid input is emitted as `Invalid` tokens and unterminated strings are
+    // closed at the end of the line. Errors are collected instead of ending iteration.
+    pub fn lossy(self) -> Self {
+        TokenIter {
+            lossy: true,
+            ..self
+        }
+    }
+
+    pub fn take_errors(&mut self) -> Vec<LexerError<'s>> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // Returns the next token type, recovering from errors if in lossy mode.
+    fn token_ty(&mut self) -> Result<(TokenType<'s>, ParseStr<'s>), LexerError<'s>> {
+        let start_offset = self.val.start_offset();
+        match try_token_ty(self.val, self.flavor) {
+            Ok(Some(val)) => Ok(val),
+            Ok(None) if self.lossy => {
+                let (ty, remaining) = invalid_token_ty(self.val, self.flavor);
+                self.errors.push(LexerError::new(
+                    LexerErrorType::InvalidInput,
+                    start_offset..remaining.start_offset(),
+                ));
+                Ok((ty, remaining))
+            }
+            Ok(None) => Err(LexerError::new(
+                LexerErrorType::InvalidInput,
+                start_offset..start_offset,
+            )),
+            Err(err) if self.lossy => {
+                let (literal, remaining) = try_unterminated_literal(self.val, err.range.start)
+                    .ok_or_else(|| err.clone())?;
+                self.errors.push(err);
+                Ok((TokenType::Literal(literal), remaining))
+            }
+            Err(err) => Err(err),
         }
     }
 }
 
 impl<'s> Iterator for TokenIter<'s> {
-    type Item = Result<Token<'s>, LexerError<'s>>;
+    type Item = Result<(Token<'s>, Vec<Range<usize>>), LexerError<'s>>;
 
     fn next(&mut self) -> Option<Self::Item> {
         // Scan through any newlines and comments.
@@ -59,20 +109,23 @@ impl<'s> Iterator for TokenIter<'s> {
 
                 // If there is an existing token, a newline indicates no more comment information
                 // can be added to it, so it can be yielded.
-                if let Some(mut current_token) = self.current_token.take() {
+                if let Some((mut current_token, mut ranges)) = self.current_token.take() {
                     debug_assert!(current_token.new_line.is_none());
                     current_token.new_line = Some(line);
-                    return Some(Ok(current_token));
+                    ranges.append(&mut self.current_line_ranges);
+                    return Some(Ok((current_token, ranges)));
                 }
 
                 // Add the previous line of comments to the next tokens `before_lines` list.
                 self.before_lines.push(line);
+                self.before_ranges.append(&mut self.current_line_ranges);
             } else if let Some((comment, remaining)) = try_some!(try_comment(self.val)) {
-                self.val = remaining;
                 self.current_line_comments.push(comment);
-            } else if let Some((token_ty, remaining)) =
-                try_some!(try_token_ty(self.val, self.flavor))
-            {
+                self.current_line_ranges
+                    .push(self.val.start_offset()..remaining.start_offset());
+                self.val = remaining;
+            } else {
+                let (token_ty, remaining) = try_some!(self.token_ty());
                 let token = Token {
                     ty: token_ty,
                     range: self.val.start_offset()..remaining.start_offset(),
@@ -83,22 +136,18 @@ impl<'s> Iterator for TokenIter<'s> {
                     // May be filled later if a \n is encountered.
                     new_line: None,
                 };
+                let mut ranges = std::mem::take(&mut self.before_ranges);
+                ranges.append(&mut self.current_line_ranges);
                 self.val = remaining;
 
                 // Store the new token, so continuing comments can be attached to it before either
                 // a newline or another token is encountered.
                 // If there is an existing token stored, that existing token can now be yielded.
-                let existing_token = self.current_token.replace(token);
+                let existing_token = self.current_token.replace((token, ranges));
 
                 if let Some(token) = existing_token {
                     return Some(Ok(token));
                 }
-            } else {
-                // Not a newline, not a comment, not a token.
-                return Some(Err(LexerError::new(
-                    LexerErrorType::InvalidInput,
-                    self.val.start_offset()..self.val.start_offset(),
-                )));
             }
         }
 
@@ -107,13 +156,16 @@ impl<'s> Iterator for TokenIter<'s> {
             return Some(Ok(token));
         }
         if !self.current_line_comments.is_empty() || !self.before_lines.is_empty() {
-            return Some(Ok(Token {
+            let token = Token {
                 ty: TokenType::Empty,
                 range: self.val.end_offset()..self.val.end_offset(),
                 comments: std::mem::take(&mut self.current_line_comments),
                 before_lines: std::mem::take(&mut self.before_lines),
                 new_line: None,
-            }));
+            };
+            let mut ranges = std::mem::take(&mut self.before_ranges);
+            ranges.append(&mut self.current_line_ranges);
+            return Some(Ok((token, ranges)));
         }
 
         None
@@ -136,3 +188,19 @@ fn try_token_ty(
 
     Ok(None)
 }
+
+// Returns a token containing a run of invalid input, ending at whitespace or at the start of
+// something valid.
+fn invalid_token_ty(val: ParseStr, flavor: Flavor) -> (TokenType, ParseStr) {
+    let mut chars = val.as_str().char_indices().skip(1);
+    let end_index = chars
+        .find(|&(index, c)| {
+            let remaining = val.from(index);
+            c.is_whitespace()
+                || !matches!(try_comment(remaining), Ok(None))
+                || !matches!(try_token_ty(remaining, flavor), Ok(None))
+        })
+        .map(|(index, _)| index);
+    let (text, remaining) = val.split_at(end_index);
+    (TokenType::Invalid(text), remaining)
+}
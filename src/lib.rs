@@ -37,13 +37,25 @@
 #![deny(rustdoc::broken_intra_doc_links)]
 #![deny(rustdoc::private_intra_doc_links)]
 
+pub mod analysis;
 pub mod annotation;
 pub mod ast;
 mod flavor;
+mod format;
 mod lexer;
+pub mod lint;
 mod parser;
+mod preprocessor;
+mod printer;
+pub mod project;
+pub mod rson;
 pub mod token;
 
 pub use self::flavor::Flavor;
-pub use self::lexer::{tokenize, LexerError, LexerErrorType, TokenItem};
-pub use self::parser::{parse, ContextType, ParseError, ParseErrorContext, ParseErrorType};
+pub use self::format::{format_program, BraceStyle, FormatOptions, TrailingCommas};
+pub use self::lexer::{tokenize, tokenize_lossy, LexerError, LexerErrorType, TokenItem};
+pub use self::parser::{
+    parse, parse_recover, ContextType, ParseError, ParseErrorContext, ParseErrorType,
+};
+pub use self::preprocessor::{preprocess, Condition, Directive};
+pub use self::printer::print_program;
@@ -0,0 +1,417 @@
+use crate::annotation::{Diagnostic, Label, Level};
+use crate::lint::Severity;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Lint settings for a project, usually read from a [`sqlint.toml`](LintConfig::FILE_NAME) file.
+///
+/// The file is a small subset of TOML, with a `[rules]` table that sets the severity of rules by
+/// name. Rules that are not listed use their default severity.
+///
+/// ```toml
+/// # sqlint.toml
+/// [rules]
+/// unused-local = "deny"
+/// todo-comment = "warn"
+/// dead-store = "allow"
+/// ```
+///
+/// # Example
+/// ```
+/// use sqparse::lint::{LintConfig, Severity};
+///
+/// let config = LintConfig::parse("[rules]\nunused-local = \"deny\"").unwrap();
+/// assert_eq!(config.severity("unused-local"), Some(Severity::Deny));
+/// assert_eq!(config.severity("dead-store"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    severities: HashMap<String, Severity>,
+}
+
+/// Type of [`ConfigError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigErrorType<'s> {
+    /// A line that is not a table header, a key-value pair or a comment.
+    ///
+    /// # Example
+    /// ```text
+    /// unused-local
+    /// ^^^^^^^^^^^^ error
+    /// ```
+    InvalidLine,
+
+    /// A table that is not `[rules]`.
+    ///
+    /// # Example
+    /// ```text
+    /// [lints]
+    ///  ^^^^^ error
+    /// ```
+    UnknownTable { name: &'s str },
+
+    /// A key-value pair before any table header.
+    ///
+    /// # Example
+    /// ```text
+    /// unused-local = "deny"
+    /// ^^^^^^^^^^^^ error
+    /// [rules]
+    /// ```
+    KeyOutsideTable { key: &'s str },
+
+    /// A rule is given a severity more than once.
+    ///
+    /// # Example
+    /// ```text
+    /// [rules]
+    /// unused-local = "deny"
+    /// unused-local = "warn"
+    /// ^^^^^^^^^^^^ error
+    /// ```
+    DuplicateKey { key: &'s str },
+
+    /// A value that is not a string.
+    ///
+    /// # Example
+    /// ```text
+    /// unused-local = deny
+    ///                ^^^^ error
+    /// ```
+    ExpectedString,
+
+    /// A string is missing its closing quote before the end of the line.
+    ///
+    /// # Example
+    /// ```text
+    /// unused-local = "deny
+    ///                ^^^^^ error
+    /// ```
+    UnterminatedString,
+
+    /// A string that is not `allow`, `warn` or `deny`.
+    ///
+    /// # Example
+    /// ```text
+    /// unused-local = "error"
+    ///                ^^^^^^^ error
+    /// ```
+    InvalidSeverity { value: &'s str },
+}
+
+/// An error emitted while parsing a [`LintConfig`].
+///
+/// Each error has a type with more information, and a range indicating where in the source string
+/// the error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError<'s> {
+    /// The type of error.
+    pub ty: ConfigErrorType<'s>,
+
+    /// The character range of where the error occurred.
+    pub range: Range<usize>,
+}
+
+impl LintConfig {
+    /// The name of the config file that tools look for in a project's directory.
+    pub const FILE_NAME: &'static str = "sqlint.toml";
+
+    /// Creates an empty config, where every rule uses its default severity.
+    pub fn new() -> Self {
+        LintConfig::default()
+    }
+
+    /// Parses a config file.
+    pub fn parse(source: &str) -> Result<Self, ConfigError<'_>> {
+        let mut config = LintConfig::new();
+        let mut in_rules = false;
+        let mut offset = 0;
+        for line in source.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            let line = strip_comment(line);
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let trimmed_start = start + (line.len() - line.trim_start().len());
+            let trimmed_range = trimmed_start..trimmed_start + trimmed.len();
+
+            if let Some(header) = trimmed.strip_prefix('[') {
+                let name = header.strip_suffix(']').ok_or_else(|| {
+                    ConfigError::new(ConfigErrorType::InvalidLine, trimmed_range.clone())
+                })?;
+                let name_start = trimmed_start + 1 + (name.len() - name.trim_start().len());
+                let name = name.trim();
+                if name != "rules" {
+                    return Err(ConfigError::new(
+                        ConfigErrorType::UnknownTable { name },
+                        name_start..name_start + name.len(),
+                    ));
+                }
+                in_rules = true;
+                continue;
+            }
+
+            let Some(equals) = trimmed.find('=') else {
+                return Err(ConfigError::new(
+                    ConfigErrorType::InvalidLine,
+                    trimmed_range,
+                ));
+            };
+            let key = trimmed[..equals].trim_end();
+            let key_range = trimmed_start..trimmed_start + key.len();
+            let key = unquote(key).unwrap_or(key);
+            if key.is_empty() {
+                return Err(ConfigError::new(
+                    ConfigErrorType::InvalidLine,
+                    trimmed_range,
+                ));
+            }
+            if !in_rules {
+                return Err(ConfigError::new(
+                    ConfigErrorType::KeyOutsideTable { key },
+                    key_range,
+                ));
+            }
+
+            let value = trimmed[equals + 1..].trim_start();
+            let value_start = trimmed_range.end - value.len();
+            let value_range = value_start..trimmed_range.end;
+            let value = match unquote(value) {
+                Some(value) => value,
+                None if value.starts_with(['"', '\'']) => {
+                    return Err(ConfigError::new(
+                        ConfigErrorType::UnterminatedString,
+                        value_range,
+                    ))
+                }
+                None => {
+                    return Err(ConfigError::new(
+                        ConfigErrorType::ExpectedString,
+                        value_range,
+                    ))
+                }
+            };
+            let severity = Severity::from_name(value).ok_or_else(|| {
+                ConfigError::new(ConfigErrorType::InvalidSeverity { value }, value_range)
+            })?;
+            if config.severities.contains_key(key) {
+                return Err(ConfigError::new(
+                    ConfigErrorType::DuplicateKey { key },
+                    key_range,
+                ));
+            }
+            config.set_severity(key, severity);
+        }
+        Ok(config)
+    }
+
+    /// Returns the severity set for a rule, or `None` if the rule uses its default severity.
+    pub fn severity(&self, rule: &str) -> Option<Severity> {
+        self.severities.get(rule).copied()
+    }
+
+    /// Sets the severity of a rule, replacing any severity already set.
+    pub fn set_severity(&mut self, rule: impl Into<String>, severity: Severity) {
+        self.severities.insert(rule.into(), severity);
+    }
+
+    /// Returns the names of the rules that have a severity set, in no particular order.
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        self.severities.keys().map(|rule| rule.as_str())
+    }
+}
+
+impl<'s> ConfigError<'s> {
+    /// Creates a new `ConfigError`.
+    pub fn new(ty: ConfigErrorType<'s>, range: Range<usize>) -> Self {
+        ConfigError { ty, range }
+    }
+
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`].
+    ///
+    /// [`code`]: ConfigErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        Diagnostic::new(Level::Error, self.ty.to_string(), label).with_code(self.ty.code())
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
+    /// context using [`Diagnostic::display`].
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source,
+            file_name,
+        }
+    }
+}
+
+impl<'a> ConfigErrorType<'a> {
+    /// Returns a stable code for the error type, from `E0901` to `E0999`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(self) -> &'static str {
+        match self {
+            ConfigErrorType::InvalidLine => "E0901",
+            ConfigErrorType::UnknownTable { .. } => "E0902",
+            ConfigErrorType::KeyOutsideTable { .. } => "E0903",
+            ConfigErrorType::DuplicateKey { .. } => "E0904",
+            ConfigErrorType::ExpectedString => "E0905",
+            ConfigErrorType::UnterminatedString => "E0906",
+            ConfigErrorType::InvalidSeverity { .. } => "E0907",
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(self) -> impl std::fmt::Display + 'a {
+        ConfigErrorInlineDisplay(self)
+    }
+}
+
+impl std::fmt::Display for ConfigErrorType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigErrorType::InvalidLine => {
+                write!(f, "expected a `[table]` header or a `key = \"value\"` pair")
+            }
+            ConfigErrorType::UnknownTable { name } => write!(f, "unknown table `{name}`"),
+            ConfigErrorType::KeyOutsideTable { key } => {
+                write!(f, "`{key}` must be in the `[rules]` table")
+            }
+            ConfigErrorType::DuplicateKey { key } => write!(f, "`{key}` is set more than once"),
+            ConfigErrorType::ExpectedString => write!(f, "expected a string"),
+            ConfigErrorType::UnterminatedString => write!(f, "strings cannot span multiple lines"),
+            ConfigErrorType::InvalidSeverity { value } => write!(f, "unknown severity `{value}`"),
+        }
+    }
+}
+
+struct ConfigErrorInlineDisplay<'a>(ConfigErrorType<'a>);
+
+impl std::fmt::Display for ConfigErrorInlineDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            ConfigErrorType::InvalidLine => write!(f, "not valid here"),
+            ConfigErrorType::UnknownTable { .. } => write!(f, "help: use `[rules]`"),
+            ConfigErrorType::KeyOutsideTable { .. } => {
+                write!(f, "help: add `[rules]` before this")
+            }
+            ConfigErrorType::DuplicateKey { .. } => write!(f, "set again here"),
+            ConfigErrorType::ExpectedString => write!(f, "help: add quotes around this"),
+            ConfigErrorType::UnterminatedString => write!(f, "help: add a closing quote"),
+            ConfigErrorType::InvalidSeverity { .. } => {
+                write!(f, "expected `allow`, `warn` or `deny`")
+            }
+        }
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
+
+// Removes a `#` comment from the end of a line, ignoring `#` in strings.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '#') => return &line[..index],
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            _ => {}
+        }
+    }
+    line.trim_end_matches(['\r', '\n'])
+}
+
+// Returns the contents of a quoted string, or `None` if the value is not a complete string.
+fn unquote(value: &str) -> Option<&str> {
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let contents = value[1..].strip_suffix(quote)?;
+    (!contents.contains(quote)).then_some(contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConfigErrorType, LintConfig};
+    use crate::lint::Severity;
+
+    #[test]
+    fn parse() {
+        let config = LintConfig::parse(
+            r#"
+# Project lints.
+[rules]
+unused-local = "deny"  # no dead locals
+'dead-store' = 'allow'
+"todo-comment" = "warn"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.severity("unused-local"), Some(Severity::Deny));
+        assert_eq!(config.severity("dead-store"), Some(Severity::Allow));
+        assert_eq!(config.severity("todo-comment"), Some(Severity::Warn));
+        assert_eq!(config.rules().count(), 3);
+        assert_eq!(LintConfig::parse("").unwrap(), LintConfig::new());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source: &'static str| {
+            let error = LintConfig::parse(source).unwrap_err();
+            (error.ty, &source[error.range])
+        };
+        assert_eq!(
+            error("[ lints ]"),
+            (ConfigErrorType::UnknownTable { name: "lints" }, "lints")
+        );
+        assert_eq!(
+            error("a = \"deny\""),
+            (ConfigErrorType::KeyOutsideTable { key: "a" }, "a")
+        );
+        assert_eq!(
+            error("[rules]\na = \"deny\"\na = \"warn\""),
+            (ConfigErrorType::DuplicateKey { key: "a" }, "a")
+        );
+        assert_eq!(
+            error("[rules]\na = deny # comment"),
+            (ConfigErrorType::ExpectedString, "deny")
+        );
+        assert_eq!(
+            error("[rules]\na = \"deny"),
+            (ConfigErrorType::UnterminatedString, "\"deny")
+        );
+        assert_eq!(
+            error("[rules]\na = \"error\""),
+            (
+                ConfigErrorType::InvalidSeverity { value: "error" },
+                "\"error\""
+            )
+        );
+        assert_eq!(
+            error("[rules]\nunused-local"),
+            (ConfigErrorType::InvalidLine, "unused-local")
+        );
+    }
+}
@@ -0,0 +1,423 @@
+//! Configurable lints for Squirrel scripts.
+//!
+//! Each lint is a [`Rule`] that runs over a parsed [`Program`] and reports problems through a
+//! [`LintContext`]. A [`LintRegistry`] holds the rules by name, and [`LintRegistry::run`] runs them
+//! with the [`Severity`] set for each rule in a [`LintConfig`], usually read from a `sqlint.toml`
+//! file.
+//!
+//! Lints can be allowed for a single line with a comment. A comment on its own line applies to the
+//! next line with code, and a comment after code applies to its own line:
+//!
+//! ```text
+//! // sqlint: allow(unused-local)
+//! int count = 0
+//! int total = 0 // sqlint: allow(unused-local, dead-store)
+//! ```
+//!
+//! # Example
+//! ```
+//! use sqparse::{Flavor, parse, tokenize};
+//! use sqparse::lint::{LintConfig, LintRegistry, Severity};
+//!
+//! let source = r#"
+//! void function Main()
+//! {
+//!     int count = 0
+//!     int total = 0 // sqlint: allow(unused-local)
+//! }
+//! "#;
+//! let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+//! let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+//!
+//! let registry = LintRegistry::with_builtin_rules();
+//! let config = LintConfig::parse("[rules]\nunused-local = \"deny\"").unwrap();
+//! let lints = registry.run(source, &tokens, &program, &config);
+//! assert_eq!(lints.len(), 1);
+//! assert_eq!(lints[0].rule, "unused-local");
+//! assert_eq!(lints[0].severity, Severity::Deny);
+//! assert_eq!(&source[lints[0].range.clone()], "count");
+//! ```
+//!
+//! [`Program`]: crate::ast::Program
+
+mod config;
+mod rules;
+mod suppress;
+
+pub use self::config::{ConfigError, ConfigErrorType, LintConfig};
+
+use crate::analysis::{check_dataflow, check_flow, DataflowError, FlowError, Resolution};
+use crate::annotation::{Diagnostic, Label, Level};
+use crate::ast::Program;
+use crate::TokenItem;
+use std::cell::{OnceCell, RefCell};
+use std::ops::Range;
+
+/// How a lint from a [`Rule`] is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The rule does not run.
+    Allow,
+
+    /// Lints from the rule are warnings.
+    Warn,
+
+    /// Lints from the rule are errors.
+    Deny,
+}
+
+/// A check that runs over a program and reports lints.
+///
+/// Rules usually walk the program with a [`Visit`] implementation, look at comments through
+/// [`LintContext::tokens`], or use semantic information like [`LintContext::resolution`] and
+/// [`LintContext::dataflow`].
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, tokenize};
+/// use sqparse::ast::{walk_call_expression, CallExpression, Expression, Spanned, Visit};
+/// use sqparse::lint::{LintConfig, LintContext, LintRegistry, Rule};
+///
+/// #[derive(Debug)]
+/// struct NoPrint;
+///
+/// struct Finder<'c, 'a, 's>(&'c LintContext<'a, 's>);
+///
+/// impl<'s> Visit<'s> for Finder<'_, '_, 's> {
+///     fn visit_call_expression(&mut self, node: &'s CallExpression<'s>) {
+///         if let Expression::Var(var) = &*node.function {
+///             if var.name.value == "print" {
+///                 self.0.report(node.span().unwrap(), "`print` call", "remove this");
+///             }
+///         }
+///         walk_call_expression(self, node);
+///     }
+/// }
+///
+/// impl Rule for NoPrint {
+///     fn name(&self) -> &'static str {
+///         "no-print"
+///     }
+///
+///     fn description(&self) -> &'static str {
+///         "Finds calls to `print`."
+///     }
+///
+///     fn check<'s>(&self, context: &LintContext<'_, 's>) {
+///         Finder(context).visit_program(context.program());
+///     }
+/// }
+///
+/// let source = "print( 1 )";
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// let mut registry = LintRegistry::new();
+/// registry.register(NoPrint);
+/// let lints = registry.run(source, &tokens, &program, &LintConfig::new());
+/// assert_eq!(lints[0].message, "`print` call");
+/// ```
+///
+/// [`Visit`]: crate::ast::Visit
+pub trait Rule {
+    /// Returns the name of the rule, like `unused-local`, used in configs and `allow` comments.
+    fn name(&self) -> &'static str;
+
+    /// Returns a sentence describing what the rule finds.
+    fn description(&self) -> &'static str;
+
+    /// Returns the severity of the rule when it is not set in the config.
+    fn default_severity(&self) -> Severity {
+        Severity::Warn
+    }
+
+    /// Runs the rule over a program, reporting lints with [`LintContext::report`].
+    fn check<'s>(&self, context: &LintContext<'_, 's>);
+}
+
+/// The program a [`Rule`] is checking, and where the rule reports lints.
+///
+/// Semantic information is computed the first time a rule asks for it, and shared with the other
+/// rules.
+pub struct LintContext<'a, 's> {
+    source: &'a str,
+    tokens: &'s [TokenItem<'s>],
+    program: &'s Program<'s>,
+
+    resolution: OnceCell<Resolution<'s>>,
+    flow: OnceCell<Vec<FlowError>>,
+    dataflow: OnceCell<Vec<DataflowError<'s>>>,
+
+    // The rule that is running, and the lints reported so far.
+    rule: (&'static str, Severity),
+    lints: RefCell<Vec<Lint>>,
+}
+
+/// A problem reported by a [`Rule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    /// The name of the rule that reported the lint.
+    pub rule: &'static str,
+
+    /// The severity of the rule, which is never [`Severity::Allow`].
+    pub severity: Severity,
+
+    /// A message describing the problem.
+    pub message: String,
+
+    /// A short message to show inline with the source code.
+    pub label: String,
+
+    /// The character range of the code with the problem.
+    pub range: Range<usize>,
+}
+
+/// A set of [`Rule`]s, identified by name.
+#[derive(Default)]
+pub struct LintRegistry {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Severity {
+    /// Returns the severity with a name used in configs: `allow`, `warn` or `deny`.
+    pub fn from_name(name: &str) -> Option<Severity> {
+        match name {
+            "allow" => Some(Severity::Allow),
+            "warn" => Some(Severity::Warn),
+            "deny" => Some(Severity::Deny),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the severity used in configs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Allow => "allow",
+            Severity::Warn => "warn",
+            Severity::Deny => "deny",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl<'a, 's> LintContext<'a, 's> {
+    /// Returns the source code of the program.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns the tokens of the program, including their comments.
+    pub fn tokens(&self) -> &'s [TokenItem<'s>] {
+        self.tokens
+    }
+
+    /// Returns the program being checked.
+    pub fn program(&self) -> &'s Program<'s> {
+        self.program
+    }
+
+    /// Returns the scopes and names of the program.
+    pub fn resolution(&self) -> &Resolution<'s> {
+        self.resolution
+            .get_or_init(|| Resolution::new(self.program))
+    }
+
+    /// Returns the control flow problems in the program, found with [`check_flow`].
+    ///
+    /// [`check_flow`]: crate::analysis::check_flow
+    pub fn flow(&self) -> &[FlowError] {
+        self.flow.get_or_init(|| check_flow(self.program))
+    }
+
+    /// Returns the variable problems in the program, found with [`check_dataflow`].
+    ///
+    /// [`check_dataflow`]: crate::analysis::check_dataflow
+    pub fn dataflow(&self) -> &[DataflowError<'s>] {
+        (self.dataflow).get_or_init(|| check_dataflow(self.program, self.resolution()))
+    }
+
+    /// Reports a lint from the running rule.
+    ///
+    /// `message` describes the problem, and `label` is shown inline with the code at `range`.
+    pub fn report(
+        &self,
+        range: Range<usize>,
+        message: impl Into<String>,
+        label: impl Into<String>,
+    ) {
+        let (rule, severity) = self.rule;
+        self.lints.borrow_mut().push(Lint {
+            rule,
+            severity,
+            message: message.into(),
+            label: label.into(),
+            range,
+        });
+    }
+}
+
+impl std::fmt::Debug for LintContext<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LintContext")
+            .field("rule", &self.rule)
+            .field("lints", &self.lints)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Lint {
+    /// Converts the lint to a [`Diagnostic`], with the rule's name as its code.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let level = match self.severity {
+            Severity::Deny => Level::Error,
+            Severity::Warn | Severity::Allow => Level::Warning,
+        };
+        let label = Label::new(self.range.clone(), self.label.clone());
+        Diagnostic::new(level, self.message.clone(), label).with_code(self.rule)
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the lint using
+    /// [`Diagnostic::display`].
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source,
+            file_name,
+        }
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
+
+impl LintRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        LintRegistry::default()
+    }
+
+    /// Creates a registry with the rules built into sqparse.
+    ///
+    /// | Rule                    | Default | Finds                                                 |
+    /// |-------------------------|---------|-------------------------------------------------------|
+    /// | `missing-return`        | deny    | functions that can end without returning a value      |
+    /// | `unreachable-code`      | warn    | code that can never run                               |
+    /// | `use-before-assignment` | deny    | locals that are read before they are assigned         |
+    /// | `unused-local`          | warn    | locals that are never read                            |
+    /// | `unused-parameter`      | warn    | parameters that are never read                        |
+    /// | `dead-store`            | warn    | assignments that are overwritten before they are read |
+    /// | `todo-comment`          | allow   | `TODO` and `FIXME` comments                           |
+    pub fn with_builtin_rules() -> Self {
+        let mut registry = LintRegistry::new();
+        for rule in rules::builtin_rules() {
+            registry.rules.push(rule);
+        }
+        registry
+    }
+
+    /// Adds a rule to the registry.
+    ///
+    /// # Panics
+    /// Panics if the registry already has a rule with the same name.
+    pub fn register(&mut self, rule: impl Rule + 'static) {
+        assert!(
+            self.rule(rule.name()).is_none(),
+            "rule `{}` is already registered",
+            rule.name()
+        );
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Returns the rule with a name.
+    pub fn rule(&self, name: &str) -> Option<&dyn Rule> {
+        (self.rules()).find(|rule| rule.name() == name)
+    }
+
+    /// Returns the rules in the registry, in the order they were added.
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    /// Returns the severity of a rule with a config, or `None` if the registry has no such rule.
+    pub fn severity(&self, name: &str, config: &LintConfig) -> Option<Severity> {
+        let rule = self.rule(name)?;
+        Some(config.severity(name).unwrap_or(rule.default_severity()))
+    }
+
+    /// Returns the rules named in a config that are not in the registry, sorted by name.
+    pub fn unknown_rules<'c>(&self, config: &'c LintConfig) -> Vec<&'c str> {
+        let mut unknown: Vec<_> = (config.rules())
+            .filter(|name| self.rule(name).is_none())
+            .collect();
+        unknown.sort_unstable();
+        unknown
+    }
+
+    /// Runs the rules that are not allowed by the config over a program.
+    ///
+    /// Lints on lines with a `sqlint: allow(...)` comment for their rule are removed. The lints are
+    /// sorted by where they start in the source.
+    pub fn run<'s>(
+        &self,
+        source: &str,
+        tokens: &'s [TokenItem<'s>],
+        program: &'s Program<'s>,
+        config: &LintConfig,
+    ) -> Vec<Lint> {
+        let mut context = LintContext {
+            source,
+            tokens,
+            program,
+            resolution: OnceCell::new(),
+            flow: OnceCell::new(),
+            dataflow: OnceCell::new(),
+            rule: ("", Severity::Allow),
+            lints: RefCell::new(Vec::new()),
+        };
+        for rule in self.rules() {
+            let severity = config
+                .severity(rule.name())
+                .unwrap_or(rule.default_severity());
+            if severity != Severity::Allow {
+                context.rule = (rule.name(), severity);
+                rule.check(&context);
+            }
+        }
+
+        let suppressions = suppress::Suppressions::new(source, tokens);
+        let mut lints = context.lints.into_inner();
+        lints.retain(|lint| !suppressions.is_allowed(lint.rule, &lint.range));
+        lints.sort_by_key(|lint| lint.range.start);
+        lints
+    }
+}
+
+impl std::fmt::Debug for LintRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.rules().map(|rule| rule.name()))
+            .finish()
+    }
+}
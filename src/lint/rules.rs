@@ -0,0 +1,238 @@
+use crate::analysis::{DataflowErrorType, FlowErrorType};
+use crate::lint::{LintContext, Rule, Severity};
+use crate::token::Comment;
+
+pub(crate) fn builtin_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(FlowRule {
+            name: "missing-return",
+            description:
+                "Finds functions with a return type that can end without returning a value.",
+            severity: Severity::Deny,
+            matches: |ty| matches!(ty, FlowErrorType::MissingReturn { .. }),
+        }),
+        Box::new(FlowRule {
+            name: "unreachable-code",
+            description:
+                "Finds code after a `return`, `break`, `continue` or `throw` that can never run.",
+            severity: Severity::Warn,
+            matches: |ty| matches!(ty, FlowErrorType::UnreachableCode),
+        }),
+        Box::new(DataflowRule {
+            name: "use-before-assignment",
+            description: "Finds local variables that are read before they are assigned.",
+            severity: Severity::Deny,
+            matches: |ty| matches!(ty, DataflowErrorType::UseBeforeAssignment { .. }),
+        }),
+        Box::new(DataflowRule {
+            name: "unused-local",
+            description: "Finds local variables that are never read.",
+            severity: Severity::Warn,
+            matches: |ty| matches!(ty, DataflowErrorType::UnusedVariable { .. }),
+        }),
+        Box::new(DataflowRule {
+            name: "unused-parameter",
+            description: "Finds function parameters that are never read.",
+            severity: Severity::Warn,
+            matches: |ty| matches!(ty, DataflowErrorType::UnusedParameter { .. }),
+        }),
+        Box::new(DataflowRule {
+            name: "dead-store",
+            description:
+                "Finds values assigned to variables that are overwritten before they are read.",
+            severity: Severity::Warn,
+            matches: |ty| matches!(ty, DataflowErrorType::DeadStore { .. }),
+        }),
+        Box::new(TodoComment),
+    ]
+}
+
+// Reports the errors of one type from `check_flow`.
+struct FlowRule {
+    name: &'static str,
+    description: &'static str,
+    severity: Severity,
+    matches: fn(&FlowErrorType) -> bool,
+}
+
+impl Rule for FlowRule {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn default_severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn check<'s>(&self, context: &LintContext<'_, 's>) {
+        for error in context.flow() {
+            if (self.matches)(&error.ty) {
+                let label = error.ty.inline_display().to_string();
+                context.report(error.range.clone(), error.ty.to_string(), label);
+            }
+        }
+    }
+}
+
+// Reports the errors of one type from `check_dataflow`.
+struct DataflowRule {
+    name: &'static str,
+    description: &'static str,
+    severity: Severity,
+    matches: fn(&DataflowErrorType) -> bool,
+}
+
+impl Rule for DataflowRule {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn default_severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn check<'s>(&self, context: &LintContext<'_, 's>) {
+        for error in context.dataflow() {
+            if (self.matches)(&error.ty) {
+                let label = error.ty.inline_display().to_string();
+                context.report(error.range.clone(), error.ty.to_string(), label);
+            }
+        }
+    }
+}
+
+struct TodoComment;
+
+impl Rule for TodoComment {
+    fn name(&self) -> &'static str {
+        "todo-comment"
+    }
+
+    fn description(&self) -> &'static str {
+        "Finds `TODO` and `FIXME` comments."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Allow
+    }
+
+    fn check<'s>(&self, context: &LintContext<'_, 's>) {
+        for item in context.tokens() {
+            for (comment, comment_range) in item.comments() {
+                let (Comment::MultiLine(text) | Comment::SingleLine(text)) = comment else {
+                    continue;
+                };
+                let Some((index, marker)) = ["TODO", "FIXME"]
+                    .iter()
+                    .filter_map(|marker| Some((text.find(marker)?, *marker)))
+                    .min()
+                else {
+                    continue;
+                };
+
+                // The comment's text starts after the `//` or `/*`.
+                let start = comment_range.start + 2 + index;
+                let range = start..start + text[index..].trim_end().len();
+                context.report(range, format!("{marker} comment"), "unfinished work");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::{LintConfig, LintRegistry, Severity};
+    use crate::{parse, tokenize, Flavor};
+
+    // Returns the rule, severity and source code of each lint.
+    fn lints<'s>(source: &'s str, config: &str) -> Vec<(&'static str, Severity, &'s str)> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let config = LintConfig::parse(config).unwrap();
+        LintRegistry::with_builtin_rules()
+            .run(source, &tokens, &program, &config)
+            .into_iter()
+            .map(|lint| (lint.rule, lint.severity, &source[lint.range]))
+            .collect()
+    }
+
+    #[test]
+    fn builtin_rules() {
+        let source = r#"
+            int function Count( int unused ) {
+                local a
+                int b = a /* FIXME later */
+                b = 1
+                return b
+                print( b )
+            }
+            // TODO: remove this
+        "#;
+        assert_eq!(
+            lints(source, ""),
+            [
+                ("unused-parameter", Severity::Warn, "unused"),
+                ("dead-store", Severity::Warn, "b = a"),
+                ("use-before-assignment", Severity::Deny, "a"),
+                ("unreachable-code", Severity::Warn, "print( b )"),
+            ]
+        );
+        assert_eq!(
+            lints(
+                source,
+                "[rules]\nunused-parameter = \"allow\"\ndead-store = \"deny\"\ntodo-comment = \"warn\""
+            ),
+            [
+                ("dead-store", Severity::Deny, "b = a"),
+                ("use-before-assignment", Severity::Deny, "a"),
+                ("todo-comment", Severity::Warn, "FIXME later"),
+                ("unreachable-code", Severity::Warn, "print( b )"),
+                ("todo-comment", Severity::Warn, "TODO: remove this"),
+            ]
+        );
+    }
+
+    #[test]
+    fn suppressions() {
+        let source = r#"
+            int function Count( int unused ) { // sqlint: allow(unused-parameter)
+                // sqlint: allow(unused-local)
+                int a = 1
+                int b = 2
+            }
+        "#;
+        assert_eq!(
+            lints(source, ""),
+            [
+                ("unused-local", Severity::Warn, "b"),
+                ("missing-return", Severity::Deny, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn registry() {
+        let registry = LintRegistry::with_builtin_rules();
+        let config =
+            LintConfig::parse("[rules]\nunused-local = \"deny\"\nno-such-rule = \"warn\"").unwrap();
+        assert_eq!(registry.unknown_rules(&config), ["no-such-rule"]);
+        assert_eq!(
+            registry.severity("unused-local", &config),
+            Some(Severity::Deny)
+        );
+        assert_eq!(
+            registry.severity("todo-comment", &config),
+            Some(Severity::Allow)
+        );
+        assert_eq!(registry.severity("no-such-rule", &config), None);
+        assert!(registry.rules().all(|rule| !rule.description().is_empty()));
+    }
+}
@@ -0,0 +1,99 @@
+use crate::token::{Comment, Token, TokenLine};
+use crate::TokenItem;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+// Rules that are allowed on each line by `sqlint: allow(...)` comments.
+//
+// A comment on its own line allows rules on the next line with code, and a comment after code
+// allows rules on the line it is on.
+#[derive(Debug, Default)]
+pub(crate) struct Suppressions<'s> {
+    // Offsets where each line of the source starts.
+    line_starts: Vec<usize>,
+    allowed: HashMap<usize, HashSet<&'s str>>,
+}
+
+impl<'s> Suppressions<'s> {
+    pub(crate) fn new(source: &str, tokens: &'s [TokenItem<'s>]) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        let mut suppressions = Suppressions {
+            line_starts,
+            allowed: HashMap::new(),
+        };
+        for item in tokens {
+            suppressions.add_token(&item.token);
+        }
+        suppressions
+    }
+
+    // Returns true if a rule is allowed where a lint starts.
+    pub(crate) fn is_allowed(&self, rule: &str, range: &Range<usize>) -> bool {
+        (self.allowed.get(&self.line(range.start))).is_some_and(|rules| rules.contains(rule))
+    }
+
+    fn add_token(&mut self, token: &'s Token<'s>) {
+        let line = self.line(token.range.start);
+        let before = (token.before_lines.iter())
+            .chain(&token.new_line)
+            .flat_map(|line: &TokenLine| &line.comments)
+            .chain(&token.comments);
+        let rules: Vec<_> = before.flat_map(allowed_rules).collect();
+        self.allowed.entry(line).or_default().extend(rules);
+    }
+
+    fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+}
+
+// Parses the rules in a `sqlint: allow(rule, other-rule)` comment.
+fn allowed_rules<'s>(comment: &Comment<'s>) -> impl Iterator<Item = &'s str> {
+    let text = match comment {
+        Comment::MultiLine(text) | Comment::SingleLine(text) => text.trim(),
+        Comment::ScriptStyle(_) => "",
+    };
+    let rules = (text.strip_prefix("sqlint:"))
+        .and_then(|rest| rest.trim_start().strip_prefix("allow"))
+        .and_then(|rest| rest.trim_start().strip_prefix('('))
+        .and_then(|rest| rest.split_once(')'))
+        .map_or("", |(rules, _)| rules);
+    (rules.split(','))
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::Suppressions;
+    use crate::{tokenize, Flavor};
+
+    #[test]
+    fn allowed_lines() {
+        let source = "// sqlint: allow(unused-local)
+int a = 1
+int b = 2 // sqlint: allow( dead-store, unused-local )
+/* sqlint: allow(todo-comment) */ int c = 3
+int d = 4 // sqlint: deny(unused-local)";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let suppressions = Suppressions::new(source, &tokens);
+        let line = |line: usize| {
+            let start = source
+                .split_inclusive('\n')
+                .take(line - 1)
+                .map(str::len)
+                .sum();
+            start..start + 1
+        };
+
+        assert!(suppressions.is_allowed("unused-local", &line(2)));
+        assert!(!suppressions.is_allowed("dead-store", &line(2)));
+        assert!(suppressions.is_allowed("dead-store", &line(3)));
+        assert!(suppressions.is_allowed("unused-local", &line(3)));
+        assert!(suppressions.is_allowed("todo-comment", &line(4)));
+        assert!(!suppressions.is_allowed("unused-local", &line(5)));
+        assert!(!suppressions.is_allowed("unused-local", &line(1)));
+    }
+}
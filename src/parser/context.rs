@@ -368,6 +368,18 @@ pub enum ContextType {
     /// ```
     GlobalStatement,
 
+    /// A preprocessor conditional block.
+    ///
+    /// # Example
+    /// ```text
+    ///   #if SERVER
+    ///  _^^^^^^^^^^
+    /// | void function OnPlayerKilled( entity player ) {}
+    /// | #endif
+    /// |_^^^^^^ `#if` block
+    /// ```
+    ConditionalStatement,
+
     /// Class definition.
     ///
     /// # Example
@@ -583,6 +595,7 @@ impl std::fmt::Display for ContextType {
             ContextType::WaitThreadSoloStatement => write!(f, "`waitthreadsolo` statement"),
             ContextType::WaitStatement => write!(f, "`wait` statement"),
             ContextType::GlobalStatement => write!(f, "`global` statement"),
+            ContextType::ConditionalStatement => write!(f, "`#if` block"),
             ContextType::ClassDefinition => write!(f, "`class` definition"),
             ContextType::EnumDefinition => write!(f, "`enum` definition"),
             ContextType::FunctionDefinition => write!(f, "`function` definition"),
@@ -5,7 +5,9 @@ use crate::ast::{
 use crate::parser::expression::expression;
 use crate::parser::identifier::identifier;
 use crate::parser::parse_result_ext::ParseResultExt;
-use crate::parser::statement::{statement, statement_type, typed_var_definition_statement};
+use crate::parser::statement::{
+    recover_statements, statement, statement_type, typed_var_definition_statement,
+};
 use crate::parser::token_list::TokenList;
 use crate::parser::token_list_ext::TokenListExt;
 use crate::parser::type_::type_;
@@ -46,7 +48,15 @@ pub fn if_statement_type(tokens: TokenList) -> ParseResult<IfStatementType> {
 pub fn switch_case(tokens: TokenList) -> ParseResult<SwitchCase> {
     switch_case_condition(tokens).determines(|tokens, condition| {
         let (tokens, colon) = tokens.terminal(TerminalToken::Colon)?;
-        let (tokens, body) = tokens.many(statement)?;
+        let (tokens, body) = if tokens.is_recovering() {
+            recover_statements(tokens, |tokens| {
+                tokens.is_ended()
+                    || tokens.terminal(TerminalToken::Case).is_ok()
+                    || tokens.terminal(TerminalToken::Default).is_ok()
+            })
+        } else {
+            tokens.many(statement)?
+        };
         Ok((
             tokens,
             SwitchCase {
@@ -1,9 +1,8 @@
-use crate::annotation::{display_annotations, Annotation, Mode};
+use crate::annotation::{Diagnostic, Label, Level};
 use crate::parser::context::ContextType;
 use crate::token::TerminalToken;
 use crate::TokenItem;
 use std::ops::Range;
-use yansi::Paint;
 
 /// Type of [`ParseError`].
 ///
@@ -145,6 +144,38 @@ pub enum ParseErrorType {
 
     /// Expected a string literal.
     ExpectedStringLiteral,
+
+    /// Expected an `#endif` to close a preprocessor conditional.
+    ///
+    /// # Example
+    /// ```text
+    /// {
+    ///     #if SERVER
+    ///     KillPlayer()
+    /// }
+    /// ^ error
+    /// ```
+    ExpectedEndIf,
+
+    /// Found an `#elseif`, `#else` or `#endif` directive that does not continue an `#if`.
+    ///
+    /// # Example
+    /// ```text
+    /// #endif
+    /// ^^^^^^ error
+    /// KillPlayer()
+    /// ```
+    UnmatchedDirective,
+
+    /// Found a preprocessor directive that could not be parsed.
+    ///
+    /// # Example
+    /// ```text
+    /// #if SERVER CLIENT
+    ///            ^ error
+    /// KillPlayer()
+    /// ```
+    InvalidDirective,
 }
 
 /// An error emitted while trying to parse a token list.
@@ -166,6 +197,12 @@ pub struct ParseError {
     pub context: Option<ParseErrorContext>,
 
     pub(crate) is_fatal: bool,
+
+    // The range of the directive comment with the error, for errors in preprocessor directives.
+    pub(crate) directive_range: Option<Range<usize>>,
+
+    // The range to show the context at, if it is not a token, like the `#if` of a conditional.
+    pub(crate) context_range: Option<Range<usize>>,
 }
 
 /// Affinity of the token index in [`ParseError`].
@@ -244,6 +281,8 @@ impl ParseError {
             token_affinity,
             context: None,
             is_fatal: false,
+            directive_range: None,
+            context_range: None,
         }
     }
 
@@ -303,8 +342,63 @@ impl ParseError {
         self
     }
 
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`].
+    ///
+    /// The tokens must be the ones that were parsed. If the error has context, it is included as a
+    /// secondary label.
+    ///
+    /// # Example
+    /// ```
+    /// use sqparse::{Flavor, parse, tokenize};
+    ///
+    /// let source = "local sum = 1 + ?";
+    /// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+    /// let diagnostic = parse(&tokens, Flavor::SquirrelRespawn).unwrap_err().to_diagnostic(&tokens);
+    /// assert_eq!(diagnostic.code, Some("E0107"));
+    /// assert_eq!(diagnostic.message, "expected a value, found a `?`");
+    /// assert_eq!(&source[diagnostic.primary.range], "?");
+    /// assert_eq!(diagnostic.secondary[0].text, "for this expression");
+    /// ```
+    ///
+    /// [`code`]: ParseErrorType::code
+    pub fn to_diagnostic(&self, tokens: &[TokenItem]) -> Diagnostic {
+        let message = match tokens.get(self.token_index) {
+            // Directives are in comments before the token, so the token is not the problem.
+            _ if self.ty.is_directive() => self.ty.to_string(),
+            Some(item) => format!("{}, found a {}", self.ty, item.token.ty),
+            None => format!("{}, found the end of input", self.ty),
+        };
+        let src_range = (self.directive_range.clone())
+            .unwrap_or_else(|| token_src_range(self.token_index, self.token_affinity, tokens));
+        let mut diagnostic = Diagnostic::new(
+            Level::Error,
+            message,
+            Label::new(src_range, self.ty.to_string()),
+        )
+        .with_code(self.ty.code());
+
+        if let Some(context) = &self.context {
+            let is_end = self.token_index + 1 == context.token_range.end
+                && context.end_affinity == TokenAffinity::Before;
+            let context_text = if is_end { "for this" } else { "in this" };
+            let context_range = self.context_range.clone().unwrap_or_else(|| {
+                let start_range =
+                    token_src_range(context.token_range.start, TokenAffinity::Inline, tokens);
+                let end_range =
+                    token_src_range(context.token_range.end - 1, context.end_affinity, tokens);
+                start_range.start..end_range.end
+            });
+            diagnostic = diagnostic.with_label(Label::new(
+                context_range,
+                format!("{context_text} {}", context.ty),
+            ));
+        }
+
+        diagnostic
+    }
+
     /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error and context
-    /// using [`display_annotations`].
+    /// using [`Diagnostic::display`].
     pub fn display<'s>(
         &'s self,
         source: &'s str,
@@ -312,9 +406,8 @@ impl ParseError {
         file_name: Option<&'s str>,
     ) -> impl std::fmt::Display + 's {
         Display {
-            error: self,
+            diagnostic: self.to_diagnostic(tokens),
             source,
-            tokens,
             file_name,
         }
     }
@@ -330,6 +423,48 @@ impl ParseError {
     }
 }
 
+impl ParseErrorType {
+    /// Returns a stable code for the error type, from `E0101` to `E0199`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(self) -> &'static str {
+        match self {
+            ParseErrorType::ExpectedTerminal(_) => "E0101",
+            ParseErrorType::ExpectedCompound2(_, _) => "E0102",
+            ParseErrorType::ExpectedCompound3(_, _, _) => "E0103",
+            ParseErrorType::ExpectedIdentifier => "E0104",
+            ParseErrorType::ExpectedLiteral => "E0105",
+            ParseErrorType::ExpectedExpression => "E0106",
+            ParseErrorType::ExpectedValue => "E0107",
+            ParseErrorType::ExpectedOperator => "E0108",
+            ParseErrorType::ExpectedPrefixOperator => "E0109",
+            ParseErrorType::ExpectedPostfixOperator => "E0110",
+            ParseErrorType::ExpectedBinaryOperator => "E0111",
+            ParseErrorType::ExpectedType => "E0112",
+            ParseErrorType::ExpectedTypeModifier => "E0113",
+            ParseErrorType::ExpectedTableSlot => "E0114",
+            ParseErrorType::ExpectedClassMember => "E0115",
+            ParseErrorType::ExpectedStatement => "E0116",
+            ParseErrorType::ExpectedEndOfStatement => "E0117",
+            ParseErrorType::ExpectedGlobalDefinition => "E0118",
+            ParseErrorType::IllegalLineBreak => "E0119",
+            ParseErrorType::Precedence => "E0120",
+            ParseErrorType::ExpectedSlot => "E0121",
+            ParseErrorType::ExpectedStringLiteral => "E0122",
+            ParseErrorType::ExpectedEndIf => "E0123",
+            ParseErrorType::UnmatchedDirective => "E0124",
+            ParseErrorType::InvalidDirective => "E0125",
+        }
+    }
+
+    fn is_directive(self) -> bool {
+        matches!(
+            self,
+            ParseErrorType::UnmatchedDirective | ParseErrorType::InvalidDirective
+        )
+    }
+}
+
 impl std::fmt::Display for ParseErrorType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -365,6 +500,11 @@ impl std::fmt::Display for ParseErrorType {
             ParseErrorType::ExpectedGlobalDefinition => write!(f, "expected a global definition"),
             ParseErrorType::ExpectedSlot => write!(f, "expected a slot"),
             ParseErrorType::ExpectedStringLiteral => write!(f, "expected a string literal"),
+            ParseErrorType::ExpectedEndIf => write!(f, "expected `#endif`"),
+            ParseErrorType::UnmatchedDirective => {
+                write!(f, "preprocessor directive without a matching `#if`")
+            }
+            ParseErrorType::InvalidDirective => write!(f, "invalid preprocessor directive"),
 
             // todo: these need rewording to fit with "<>, found a <>"
             ParseErrorType::IllegalLineBreak => {
@@ -376,80 +516,18 @@ impl std::fmt::Display for ParseErrorType {
 }
 
 struct Display<'s> {
-    error: &'s ParseError,
+    diagnostic: Diagnostic,
     source: &'s str,
-    tokens: &'s [TokenItem<'s>],
     file_name: Option<&'s str>,
 }
 
 impl std::fmt::Display for Display<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}{}{}",
-            Paint::red("error").bold(),
-            Paint::white(": ").bold(),
-            Paint::white(self.error.ty).bold()
-        )?;
-        match self.tokens.get(self.error.token_index) {
-            Some(item) => writeln!(
-                f,
-                "{}{}",
-                Paint::white(", found a ").bold(),
-                Paint::white(item.token.ty).bold()
-            )?,
-            None => writeln!(f, "{}", Paint::white(", found the end of input").bold())?,
-        }
-
-        let src_range = token_src_range(
-            self.error.token_index,
-            self.error.token_affinity,
-            self.tokens,
-        );
-        let note = match &self.error.context {
-            Some(context) => {
-                let is_end = self.error.token_index + 1 == context.token_range.end
-                    && context.end_affinity == TokenAffinity::Before;
-                let context_text = if is_end { "for this" } else { "in this" };
-                format!("{} {}:", context_text, context.ty)
-            }
-            None => "".to_string(),
-        };
-
-        let mut annotations = vec![Annotation {
-            mode: Mode::Error,
-            text: format!("{}", self.error.ty),
-            note,
-            highlight: src_range.clone(),
-            visible: src_range.clone(),
-        }];
-
-        if let Some(context) = &self.error.context {
-            let start_range = token_src_range(
-                context.token_range.start,
-                TokenAffinity::Inline,
-                self.tokens,
-            );
-            let end_range = token_src_range(
-                context.token_range.end - 1,
-                context.end_affinity,
-                self.tokens,
-            );
-            annotations.push(Annotation {
-                mode: Mode::Info,
-                text: "".to_string(),
-                note: "".to_string(),
-                highlight: start_range.start..end_range.end,
-                visible: src_range,
-            });
-        }
-
         write!(
             f,
             "{}",
-            display_annotations(self.file_name, self.source, &annotations)
-        )?;
-        Ok(())
+            self.diagnostic.display(self.source, self.file_name)
+        )
     }
 }
 
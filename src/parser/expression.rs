@@ -1,9 +1,9 @@
 use crate::ast::{
     ArrayExpression, BinaryExpression, CallExpression, ClassExpression, CommaExpression,
-    DelegateExpression, ExpectExpression, Expression, FunctionExpression, IndexExpression,
-    LambdaExpression, LiteralExpression, ParensExpression, PostfixExpression, Precedence,
-    PrefixExpression, PropertyExpression, RootVarExpression, TableExpression, TernaryExpression,
-    VarExpression, VectorExpression,
+    DelegateExpression, ErrorExpression, ExpectExpression, Expression, FunctionExpression,
+    IndexExpression, LambdaExpression, LiteralExpression, ParensExpression, PostfixExpression,
+    Precedence, PrefixExpression, PropertyExpression, RootVarExpression, TableExpression,
+    TernaryExpression, VarExpression, VectorExpression,
 };
 use crate::parser::array::array_value;
 use crate::parser::class::class_definition;
@@ -37,6 +37,34 @@ pub fn expression(tokens: TokenList, precedence: Precedence) -> ParseResult<Box<
     }
 }
 
+// Parses an expression that must be present, like the right side of a binary operator. When
+// recovering from errors, a missing expression is replaced with an error expression.
+pub fn required_expression(
+    tokens: TokenList,
+    precedence: Precedence,
+) -> ParseResult<Box<Expression>> {
+    match expression(tokens, precedence) {
+        Err(error)
+            if tokens.is_recovering()
+                && error.ty == ParseErrorType::ExpectedValue
+                && error.token_index == tokens.start_index() =>
+        {
+            // If the expression is missing at the end of a line, point at the end of the line
+            // instead of the start of the next one.
+            let error = if tokens.is_newline() {
+                tokens.error_before(ParseErrorType::ExpectedValue)
+            } else {
+                error
+            };
+            Ok((
+                tokens,
+                Box::new(Expression::Error(ErrorExpression { error })),
+            ))
+        }
+        result => result,
+    }
+}
+
 fn value(tokens: TokenList) -> ParseResult<Box<Expression>> {
     function(tokens)
         .map_val(Expression::Function)
@@ -82,7 +110,7 @@ pub fn parens(tokens: TokenList) -> ParseResult<ParensExpression> {
             ContextType::Expression,
             |tokens| tokens.terminal(TerminalToken::CloseBracket),
             |tokens, open, close| {
-                expression(tokens, Precedence::None).map_val(|value| ParensExpression {
+                required_expression(tokens, Precedence::None).map_val(|value| ParensExpression {
                     open,
                     value,
                     close,
@@ -212,7 +240,8 @@ pub fn vector(tokens: TokenList) -> ParseResult<VectorExpression> {
 
 pub fn prefix(tokens: TokenList) -> ParseResult<PrefixExpression> {
     prefix_operator(tokens).determines(|tokens, operator| {
-        expression(tokens, Precedence::Prefix).map_val(|value| PrefixExpression { operator, value })
+        required_expression(tokens, Precedence::Prefix)
+            .map_val(|value| PrefixExpression { operator, value })
     })
 }
 
@@ -352,9 +381,9 @@ fn ternary<'s>(
     tokens
         .terminal(TerminalToken::Question)
         .determines(|tokens, question| {
-            let (tokens, true_value) = expression(tokens, Precedence::None)?;
+            let (tokens, true_value) = required_expression(tokens, Precedence::None)?;
             let (tokens, separator) = tokens.terminal(TerminalToken::Colon)?;
-            let (tokens, false_value) = expression(tokens, Precedence::Ternary)?;
+            let (tokens, false_value) = required_expression(tokens, Precedence::Ternary)?;
 
             Ok((
                 tokens,
@@ -384,7 +413,7 @@ fn binary<'s>(
             }
         })
         .determines(|tokens, operator| {
-            expression(tokens, operator.precedence()).map_val(|right| BinaryExpression {
+            required_expression(tokens, operator.precedence()).map_val(|right| BinaryExpression {
                 left: left.take(),
                 operator,
                 right,
@@ -408,7 +437,7 @@ fn index<'s>(
             ContextType::Expression,
             |tokens| tokens.terminal(TerminalToken::CloseSquare),
             |tokens, open, close| {
-                expression(tokens, Precedence::None).map_val(|index| IndexExpression {
+                required_expression(tokens, Precedence::None).map_val(|index| IndexExpression {
                     base: left.take(),
                     open,
                     index,
@@ -21,13 +21,12 @@ mod variable;
 
 pub use self::context::ContextType;
 pub use self::error::{ParseError, ParseErrorContext, ParseErrorType};
-use crate::ast::Program;
+use crate::ast::{walk_error_statement, ErrorExpression, ErrorStatement, Program, Visit};
 use crate::Flavor;
 
 use crate::lexer::TokenItem;
-use crate::parser::statement::statement;
+use crate::parser::statement::statements;
 use crate::parser::token_list::TokenList;
-use crate::parser::token_list_ext::TokenListExt;
 
 type ParseResult<'s, T> = Result<(TokenList<'s>, T), ParseError>;
 
@@ -55,7 +54,415 @@ type ParseResult<'s, T> = Result<(TokenList<'s>, T), ParseError>;
 /// ```
 pub fn parse<'s>(items: &'s [TokenItem<'s>], flavor: Flavor) -> Result<Program<'s>, ParseError> {
     let tokens = TokenList::new(flavor, items);
-    let (tokens, statements) = tokens.many_until_ended(statement)?;
+    let (tokens, statements) = statements(tokens)?;
     assert!(tokens.is_ended());
     Ok(Program { statements })
 }
+
+/// Parses an input token list into a syntax tree, recovering from any errors.
+///
+/// Unlike [`parse`], parsing does not stop at the first error. When a statement can't be parsed,
+/// the parser skips to the start of the next statement after the error (following a newline, `;`
+/// or `}`) and continues from there. The skipped tokens are kept in an [`ErrorStatement`], so the
+/// tree still contains every token in the input. Where an expression is required but missing, for
+/// example after the `=` in `local a =`, an [`ErrorExpression`] is inserted instead.
+///
+/// Returns the syntax tree along with the errors encountered, in the order they appear in the
+/// input. Only the first error at each token is returned. If there are no errors, the tree is the
+/// same as the one returned by [`parse`].
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse_recover, print_program, tokenize, ParseErrorType};
+///
+/// let source = r#"
+/// local a =
+/// local b = 2 3
+/// local c = a + b
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+///
+/// let (program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors[0].ty, ParseErrorType::ExpectedValue);
+/// assert_eq!(errors[1].ty, ParseErrorType::ExpectedEndOfStatement);
+///
+/// // The tree still contains every token.
+/// assert_eq!(print_program(&program, source), source);
+/// ```
+///
+/// [`ErrorStatement`]: crate::ast::ErrorStatement
+/// [`ErrorExpression`]: crate::ast::ErrorExpression
+pub fn parse_recover<'s>(
+    items: &'s [TokenItem<'s>],
+    flavor: Flavor,
+) -> (Program<'s>, Vec<ParseError>) {
+    let tokens = TokenList::new(flavor, items).recovering();
+    let (tokens, statements) = statements(tokens).expect("recovering parser should not fail");
+    assert!(tokens.is_ended());
+    let program = Program { statements };
+
+    let mut collector = ErrorCollector { errors: Vec::new() };
+    collector.visit_program(&program);
+    (program, collector.errors)
+}
+
+struct ErrorCollector {
+    errors: Vec<ParseError>,
+}
+
+impl ErrorCollector {
+    fn push(&mut self, error: &ParseError) {
+        // Recovering from one error can cause another at the same token, for example a missing
+        // expression followed by a missing end of statement. Only the first is useful.
+        let is_duplicate = self
+            .errors
+            .last()
+            .is_some_and(|last| last.token_index == error.token_index);
+        if !is_duplicate {
+            self.errors.push(error.clone());
+        }
+    }
+}
+
+impl<'s> Visit<'s> for ErrorCollector {
+    fn visit_error_statement(&mut self, node: &'s ErrorStatement<'s>) {
+        self.push(&node.error);
+        walk_error_statement(self, node);
+    }
+
+    fn visit_error_expression(&mut self, node: &'s ErrorExpression) {
+        self.push(&node.error);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::{Expression, StatementType, VarDefinitionStatement};
+    use crate::{
+        parse, parse_recover, print_program, tokenize, Condition, ContextType, Flavor,
+        ParseErrorType,
+    };
+
+    #[test]
+    fn recover_valid_input() {
+        let source = include_str!("../../examples/print_ast_script.nut");
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        let (recovered_program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+
+        assert!(errors.is_empty());
+        assert_eq!(format!("{recovered_program:?}"), format!("{program:?}"));
+    }
+
+    #[test]
+    fn recover_statements() {
+        let source = "
+void function Foo( int a )
+{
+    local x = = 1
+    if ( a > )
+    {
+        return
+    }
+    switch ( a )
+    {
+        case 1:
+            a = ()
+        case 2:
+            > what
+        default:
+            break
+    }
+    foo.bar[]
+}
+
+global function Fine
+";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let (program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+
+        let error_types: Vec<_> = errors.iter().map(|error| error.ty).collect();
+        assert_eq!(
+            error_types,
+            [
+                ParseErrorType::ExpectedValue,
+                ParseErrorType::ExpectedValue,
+                ParseErrorType::ExpectedValue,
+                ParseErrorType::ExpectedStatement,
+                ParseErrorType::ExpectedValue,
+            ]
+        );
+
+        // Errors inside the function don't prevent the function or later statements from parsing.
+        assert_eq!(program.statements.len(), 2);
+        assert!(matches!(
+            program.statements[0].ty,
+            StatementType::FunctionDefinition(_)
+        ));
+        assert!(matches!(program.statements[1].ty, StatementType::Global(_)));
+        assert_eq!(print_program(&program, source), source);
+    }
+
+    #[test]
+    fn recover_skips_to_next_statement() {
+        let source = "a = b c d\nlocal e = (f +\n    g h) i\nj()";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let (program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+
+        assert_eq!(errors.len(), 2);
+        let types: Vec<_> = program
+            .statements
+            .iter()
+            .map(|statement| match &statement.ty {
+                StatementType::Expression(_) => "expression",
+                StatementType::VarDefinition(_) => "var",
+                StatementType::Error(_) => "error",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(types, ["expression", "error", "error", "expression"]);
+        assert_eq!(print_program(&program, source), source);
+    }
+
+    #[test]
+    fn recover_missing_expression() {
+        let source = "local a = 1 + ;";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let (program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].ty, ParseErrorType::ExpectedValue);
+        assert_eq!(errors[0].token_index, 5);
+
+        let StatementType::VarDefinition(VarDefinitionStatement { definitions, .. }) =
+            &program.statements[0].ty
+        else {
+            panic!("expected a var definition");
+        };
+        let value = &definitions.last_item.initializer.as_ref().unwrap().value;
+        let Expression::Binary(binary) = &**value else {
+            panic!("expected a binary expression");
+        };
+        assert!(matches!(*binary.right, Expression::Error(_)));
+    }
+
+    #[test]
+    fn conditional_statements() {
+        let source = "
+#if SERVER
+global function Foo
+#elseif CLIENT
+global function Bar
+#else
+#endif
+
+void function Foo()
+{
+    a()
+    #if DEV
+    b()
+    #if !UI
+    c()
+    #endif
+    #endif
+}
+";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        assert_eq!(program.statements.len(), 2);
+
+        let StatementType::Conditional(conditional) = &program.statements[0].ty else {
+            panic!("expected a conditional statement");
+        };
+        assert_eq!(conditional.if_.condition, Condition::Define("SERVER"));
+        assert_eq!(conditional.if_.statements.len(), 1);
+        assert_eq!(conditional.else_ifs.len(), 1);
+        assert_eq!(conditional.else_ifs[0].statements.len(), 1);
+        assert!(conditional.else_.as_ref().unwrap().is_empty());
+        assert_eq!(conditional.active_statements(&["CLIENT"]).len(), 1);
+        assert!(conditional.active_statements(&["UI"]).is_empty());
+
+        let StatementType::FunctionDefinition(function) = &program.statements[1].ty else {
+            panic!("expected a function definition");
+        };
+        let StatementType::Block(block) = &*function.definition.body else {
+            panic!("expected a block");
+        };
+        assert_eq!(block.statements.len(), 2);
+        let StatementType::Conditional(conditional) = &block.statements[1].ty else {
+            panic!("expected a conditional statement");
+        };
+        assert_eq!(conditional.if_.statements.len(), 2);
+        assert!(matches!(
+            conditional.if_.statements[1].ty,
+            StatementType::Conditional(_)
+        ));
+
+        let (recovered_program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+        assert!(errors.is_empty());
+        assert_eq!(format!("{recovered_program:?}"), format!("{program:?}"));
+        assert_eq!(print_program(&program, source), source);
+    }
+
+    #[test]
+    fn conditional_errors() {
+        let error = |source| {
+            let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+            let error = parse(&tokens, Flavor::SquirrelRespawn).unwrap_err();
+            (
+                error.ty,
+                error.token_index,
+                error.context.map(|context| context.ty),
+            )
+        };
+
+        assert_eq!(
+            error("a()\n#endif\nb()"),
+            (ParseErrorType::UnmatchedDirective, 3, None)
+        );
+        assert_eq!(
+            error("#if SERVER\na()\n#else\nb()\n#elseif CLIENT\nc()\n#endif"),
+            (
+                ParseErrorType::UnmatchedDirective,
+                6,
+                Some(ContextType::ConditionalStatement)
+            )
+        );
+        assert_eq!(
+            error("{\n#if SERVER\na()\n}"),
+            (
+                ParseErrorType::ExpectedEndIf,
+                4,
+                Some(ContextType::ConditionalStatement)
+            )
+        );
+        assert_eq!(
+            error("#if SERVER ||\na()\n#endif"),
+            (ParseErrorType::InvalidDirective, 0, None)
+        );
+    }
+
+    #[test]
+    fn conditional_error_ranges() {
+        // Returns the line with the error, with the highlighted part in brackets.
+        let highlight = |source: &str| {
+            let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+            let error = parse(&tokens, Flavor::SquirrelRespawn).unwrap_err();
+            let range = error.to_diagnostic(&tokens).primary.range;
+            let line_start = source[..range.start]
+                .rfind('\n')
+                .map_or(0, |index| index + 1);
+            let line_end = source[range.end..].find('\n').unwrap() + range.end;
+            format!(
+                "{}[{}]{}",
+                &source[line_start..range.start],
+                &source[range.clone()],
+                &source[range.end..line_end]
+            )
+        };
+
+        assert_eq!(highlight("a()\n#endif\nb()"), "[#endif]");
+        assert_eq!(
+            highlight("#if SERVER\na()\n#else\nb()\n#else\nc()\n#endif"),
+            "[#else]"
+        );
+        assert_eq!(
+            highlight("a()\n#if SERVER CLIENT\nb()\n#endif"),
+            "#if SERVER [C]LIENT"
+        );
+
+        // The context is shown at the `#if`, not at the statement after it.
+        let source = "#if SERVER\na()\n#else\nb()\n#else\nc()\n#endif";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let error = parse(&tokens, Flavor::SquirrelRespawn).unwrap_err();
+        let diagnostic = error.to_diagnostic(&tokens);
+        assert_eq!(&source[diagnostic.secondary[0].range.clone()], "#if SERVER");
+    }
+
+    #[test]
+    fn conditional_at_end_of_input() {
+        for source in [
+            "#if SERVER\na()\n#endif\n",
+            "a()\n#if SERVER\nb()\n#endif // end",
+        ] {
+            let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+            let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+            let Some(StatementType::Conditional(conditional)) =
+                program.statements.last().map(|statement| &statement.ty)
+            else {
+                panic!("expected a conditional statement");
+            };
+            assert!(matches!(
+                conditional.if_.statements[0].ty,
+                StatementType::Expression(_)
+            ));
+            assert_eq!(print_program(&program, source), source);
+
+            let (recovered_program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+            assert!(errors.is_empty());
+            assert_eq!(format!("{recovered_program:?}"), format!("{program:?}"));
+        }
+    }
+
+    #[test]
+    fn directives_inside_statements() {
+        let sources = [
+            "if ( a ) {}\n#if SERVER\nelse if ( b ) {}\n#endif\n",
+            "if ( x )\n#if SERVER\nFoo()\n#else\nBar()\n#endif\n",
+            "1 +\n#if SERVER\n2\n#else\n3\n#endif\n",
+            "#if SERVER\na()\n#else\nb(\n#endif\n)\n",
+        ];
+        for source in sources {
+            let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+            let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+            assert!(program
+                .statements
+                .iter()
+                .all(|statement| !matches!(statement.ty, StatementType::Conditional(_))));
+            assert_eq!(print_program(&program, source), source);
+
+            let (recovered_program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+            assert!(errors.is_empty());
+            assert_eq!(format!("{recovered_program:?}"), format!("{program:?}"));
+        }
+    }
+
+    #[test]
+    fn recover_conditional_errors() {
+        let source = "
+#endif
+a()
+{
+    #if SERVER
+    b()
+}
+c()
+";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let (program, errors) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+
+        let error_types: Vec<_> = errors.iter().map(|error| error.ty).collect();
+        assert_eq!(
+            error_types,
+            [
+                ParseErrorType::UnmatchedDirective,
+                ParseErrorType::ExpectedEndIf
+            ]
+        );
+
+        // The unterminated conditional is closed at the end of the block.
+        let StatementType::Block(block) = &program.statements[2].ty else {
+            panic!("expected a block");
+        };
+        assert!(matches!(
+            block.statements[0].ty,
+            StatementType::Conditional(_)
+        ));
+        assert!(matches!(
+            program.statements[3].ty,
+            StatementType::Expression(_)
+        ));
+        assert_eq!(print_program(&program, source), source);
+    }
+}
@@ -100,7 +100,13 @@ pub trait ParseResultExt<'s, T>: Sized {
         inner: FInner,
     ) -> ParseResult<'s, Out> {
         let (tokens, open_val) = self.into_parse_result()?;
-        let close_index = tokens.previous().unwrap().close_index.unwrap();
+        // Openers from `tokenize_lossy` might not be closed, in which case everything up to the end
+        // of the token list is inside.
+        let close_index = tokens
+            .previous()
+            .unwrap()
+            .close_index
+            .unwrap_or_else(|| tokens.end_index());
         let span_range = (tokens.start_index() - 1)..(close_index + 1);
 
         let (inner_tokens, outer_tokens) = tokens.split_at(close_index);
@@ -1,7 +1,8 @@
 use crate::ast::{
-    BlockStatement, BreakStatement, ClassDefinitionStatement, ConstDefinitionStatement,
-    ConstructorDefinitionStatement, ContinueStatement, DelayThreadStatement, DoWhileStatement,
-    EmptyStatement, EnumDefinitionStatement, ExpressionStatement, ForStatement, ForeachStatement,
+    BlockStatement, BreakStatement, ClassDefinitionStatement, ConditionalBranch,
+    ConditionalStatement, ConstDefinitionStatement, ConstructorDefinitionStatement,
+    ContinueStatement, DelayThreadStatement, DoWhileStatement, EmptyStatement,
+    EnumDefinitionStatement, ErrorStatement, ExpressionStatement, ForStatement, ForeachStatement,
     FunctionDefinitionStatement, GlobalStatement, GlobalizeAllFunctionsStatement, IfStatement,
     Precedence, ReturnStatement, SeparatedList1, Statement, StatementType,
     StructDefinitionStatement, SwitchStatement, ThreadStatement, ThrowStatement, TryCatchStatement,
@@ -13,6 +14,7 @@ use crate::parser::control::{
     for_definition, foreach_index, foreach_value, if_statement_type, switch_case,
 };
 use crate::parser::enum_::enum_entry;
+use crate::parser::error::TokenAffinity;
 use crate::parser::expression::expression;
 use crate::parser::function::function_definition;
 use crate::parser::global::global_definition;
@@ -24,33 +26,376 @@ use crate::parser::token_list_ext::TokenListExt;
 use crate::parser::type_::type_;
 use crate::parser::variable::{var_definition, var_initializer};
 use crate::parser::ParseResult;
+use crate::preprocessor::{DirectiveIndex, DirectiveItem};
 use crate::token::{TerminalToken, Token, TokenType};
-use crate::{ContextType, ParseErrorType};
+use crate::{ContextType, Directive, ParseError, ParseErrorType, TokenItem};
 
 pub fn statement(tokens: TokenList) -> ParseResult<Statement> {
     let (next_tokens, statement) = inner_statement(tokens)?;
 
     // Statement must end with a semicolon, newline, or end of input.
-    if statement.semicolon.is_some() || next_tokens.is_newline() || next_tokens.is_ended() {
+    if statement.semicolon.is_some() || is_statement_boundary(next_tokens) {
         return Ok((next_tokens, statement));
     }
 
-    // Statement can end if the last token was a `}`.
-    if let Some(last_item) = next_tokens.previous() {
-        if let TokenType::Terminal(TerminalToken::CloseBrace) = last_item.token.ty {
-            return Ok((next_tokens, statement));
+    Err(end_of_statement_error(tokens, next_tokens))
+}
+
+// Parses statements until the end of the token list. Statements between preprocessor directives
+// are grouped into conditional statements. When recovering from errors, statements that fail to
+// parse are replaced with error statements.
+pub fn statements(mut tokens: TokenList) -> ParseResult<Vec<Statement>> {
+    let mut list = StatementList::new(tokens);
+    while let Some(item) = tokens.next() {
+        // The empty token at the end of the input only holds comments, so it is added before its
+        // directives are applied. An `#endif` there closes the branch the empty statement is in,
+        // instead of adding an empty statement after the conditional.
+        let token_index = tokens.start_index();
+        let is_empty = item.token.ty == TokenType::Empty;
+        if !is_empty {
+            list.directives(token_index, item)?;
+        }
+        if tokens.is_recovering() {
+            tokens = recover_statement(tokens, list.current(), |tokens| tokens.is_ended());
+        } else {
+            let (next_tokens, statement) = statement(tokens)?;
+            list.current().push(statement);
+            tokens = next_tokens;
+        }
+        if is_empty {
+            list.directives(token_index, item)?;
         }
     }
 
-    // Statement can end if the next token is an empty statement.
-    if let Some(last_item) = next_tokens.next() {
-        if let TokenType::Empty = last_item.token.ty {
-            return Ok((next_tokens, statement));
+    // Directives at the end of the list are before the token after it, like the `}` of a block.
+    if let Some(item) = tokens.end_token() {
+        list.directives(tokens.end_index(), item)?;
+    }
+    list.finish(tokens)
+}
+
+// Parses statements until `is_end` returns true, replacing statements that fail to parse with
+// error statements.
+pub fn recover_statements<'s>(
+    mut tokens: TokenList<'s>,
+    mut is_end: impl FnMut(TokenList<'s>) -> bool,
+) -> (TokenList<'s>, Vec<Statement<'s>>) {
+    let mut statements = Vec::new();
+    while !is_end(tokens) {
+        tokens = recover_statement(tokens, &mut statements, &mut is_end);
+    }
+    (tokens, statements)
+}
+
+// Parses a statement, followed by an error statement if it could not be parsed or did not end
+// properly.
+fn recover_statement<'s>(
+    tokens: TokenList<'s>,
+    statements: &mut Vec<Statement<'s>>,
+    is_end: impl FnMut(TokenList<'s>) -> bool,
+) -> TokenList<'s> {
+    let (tokens, error) = match inner_statement(tokens) {
+        Ok((next_tokens, statement)) => {
+            let is_end_of_statement =
+                statement.semicolon.is_some() || is_statement_boundary(next_tokens);
+            let error = end_of_statement_error(tokens, next_tokens);
+            statements.push(statement);
+
+            if is_end_of_statement {
+                return next_tokens;
+            }
+            (next_tokens, error)
+        }
+        Err(error) => (tokens, error),
+    };
+
+    let (tokens, statement) = error_statement(tokens, error, is_end);
+    statements.push(statement);
+    tokens
+}
+
+// Statements in a list, along with the preprocessor conditionals that are open at the current
+// point in the list.
+//
+// Only directives before statements in the list are seen. If an `#if` and the directives that
+// continue it are not all before statements in the same list, for example if the `#if` is inside
+// an expression, the whole conditional is left as comments.
+struct StatementList<'s> {
+    items: &'s [TokenItem<'s>],
+    statements: Vec<Statement<'s>>,
+    conditionals: Vec<OpenConditional<'s>>,
+    recover: bool,
+}
+
+// A conditional statement that has not been closed with an `#endif` yet.
+struct OpenConditional<'s> {
+    // The `#if` directive, and the `#elseif`, `#else` or `#endif` expected to continue it next.
+    if_: DirectiveIndex,
+    next: Option<DirectiveIndex>,
+    has_end_if: bool,
+
+    statement: ConditionalStatement<'s>,
+}
+
+impl<'s> StatementList<'s> {
+    fn new(tokens: TokenList<'s>) -> Self {
+        StatementList {
+            items: tokens.items(),
+            statements: Vec::new(),
+            conditionals: Vec::new(),
+            recover: tokens.is_recovering(),
         }
     }
 
-    Err(next_tokens.error_before(ParseErrorType::ExpectedEndOfStatement))
-        .with_context_from(ContextType::Statement, tokens)
+    // Returns the statements in the innermost branch, where the next statement will be added.
+    fn current(&mut self) -> &mut Vec<Statement<'s>> {
+        let Some(conditional) = self.conditionals.last_mut() else {
+            return &mut self.statements;
+        };
+        let statement = &mut conditional.statement;
+        match &mut statement.else_ {
+            Some(statements) => statements,
+            None => {
+                &mut statement
+                    .else_ifs
+                    .last_mut()
+                    .unwrap_or(&mut statement.if_)
+                    .statements
+            }
+        }
+    }
+
+    // Applies the directives in the comments before a token.
+    fn directives(
+        &mut self,
+        token_index: usize,
+        item: &'s TokenItem<'s>,
+    ) -> Result<(), ParseError> {
+        for (directive_index, directive) in item.directives.iter().enumerate() {
+            let result = match &directive.directive {
+                Ok(value) => self.directive((token_index, directive_index), value.clone()),
+                Err(range) => {
+                    let mut error = self.error(ParseErrorType::InvalidDirective, token_index);
+                    error.directive_range = Some(range.clone());
+                    Err(error)
+                }
+            };
+            if let Err(error) = result {
+                self.recover_error(error)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn directive(
+        &mut self,
+        index: DirectiveIndex,
+        directive: Directive<'s>,
+    ) -> Result<(), ParseError> {
+        let item = self.directive_item(index);
+        if let Directive::If(condition) = directive {
+            self.conditionals.push(OpenConditional {
+                if_: index,
+                next: item.next,
+                has_end_if: self.has_end_if(index),
+                statement: ConditionalStatement {
+                    if_: ConditionalBranch {
+                        condition,
+                        statements: Vec::new(),
+                    },
+                    else_ifs: Vec::new(),
+                    else_: None,
+                },
+            });
+            return Ok(());
+        }
+
+        let Some(if_) = item.if_ else {
+            let mut error = self.error(ParseErrorType::UnmatchedDirective, index.0);
+            error.directive_range = Some(item.range.clone());
+            return Err(error);
+        };
+        let Some(owner) =
+            (self.conditionals.iter()).rposition(|conditional| conditional.if_ == if_)
+        else {
+            // The `#if` is somewhere else, so this is left as a comment.
+            return Ok(());
+        };
+
+        // Conditionals opened after this one are continued somewhere else.
+        while self.conditionals.len() > owner + 1 {
+            self.dissolve();
+        }
+
+        // If an earlier directive was skipped, the branches can't be grouped.
+        let conditional = &mut self.conditionals[owner];
+        if conditional.next != Some(index) {
+            self.dissolve();
+            return Ok(());
+        }
+        conditional.next = item.next;
+
+        let statement = &mut conditional.statement;
+        match directive {
+            Directive::ElseIf(condition) => statement.else_ifs.push(ConditionalBranch {
+                condition,
+                statements: Vec::new(),
+            }),
+            Directive::Else => statement.else_ = Some(Vec::new()),
+            _ => self.close(),
+        }
+        Ok(())
+    }
+
+    fn directive_item(&self, index: DirectiveIndex) -> &'s DirectiveItem<'s> {
+        &self.items[index.0].directives[index.1]
+    }
+
+    // Returns true if the directives that continue an `#if` end with an `#endif`.
+    fn has_end_if(&self, if_: DirectiveIndex) -> bool {
+        let mut index = if_;
+        while let Some(next) = self.directive_item(index).next {
+            index = next;
+        }
+        self.directive_item(index).directive == Ok(Directive::EndIf)
+    }
+
+    // Closes the innermost conditional, adding it to the enclosing list of statements.
+    fn close(&mut self) {
+        let conditional = self.conditionals.pop().expect("no open conditional");
+        self.current().push(Statement {
+            ty: StatementType::Conditional(conditional.statement),
+            semicolon: None,
+        });
+    }
+
+    // Removes the innermost conditional, adding the statements in its branches to the enclosing
+    // list of statements as if its directives were comments.
+    fn dissolve(&mut self) {
+        let conditional = self.conditionals.pop().expect("no open conditional");
+        let ConditionalStatement {
+            if_,
+            else_ifs,
+            else_,
+        } = conditional.statement;
+        let statements = (std::iter::once(if_.statements))
+            .chain(else_ifs.into_iter().map(|branch| branch.statements))
+            .chain(else_)
+            .flatten();
+        self.current().extend(statements);
+    }
+
+    // Creates an error, in the context of the innermost conditional. The context is shown at the
+    // conditional's `#if`.
+    fn error(&self, ty: ParseErrorType, token_index: usize) -> ParseError {
+        let error = ParseError::new(ty, token_index, TokenAffinity::Inline);
+        let Some(conditional) = self.conditionals.last() else {
+            return error;
+        };
+        let mut error = error.with_context(
+            ContextType::ConditionalStatement,
+            conditional.if_.0..(token_index + 1),
+            TokenAffinity::Inline,
+        );
+        error.context_range = Some(self.directive_item(conditional.if_).range.clone());
+        error
+    }
+
+    // Returns the error if not recovering, otherwise adds an error statement in its place.
+    fn recover_error(&mut self, error: ParseError) -> Result<(), ParseError> {
+        if !self.recover {
+            return Err(error);
+        }
+        self.current().push(Statement {
+            ty: StatementType::Error(ErrorStatement {
+                error,
+                tokens: Vec::new(),
+            }),
+            semicolon: None,
+        });
+        Ok(())
+    }
+
+    // Checks that every conditional has been closed, and returns the statements. Conditionals
+    // that are closed somewhere else are left as comments.
+    fn finish<'t>(mut self, tokens: TokenList<'t>) -> ParseResult<'t, Vec<Statement<'s>>> {
+        let mut has_error = false;
+        while let Some(conditional) = self.conditionals.last() {
+            if conditional.has_end_if {
+                self.dissolve();
+                continue;
+            }
+            if !has_error {
+                let error = self.error(ParseErrorType::ExpectedEndIf, tokens.end_index());
+                self.recover_error(error)?;
+                has_error = true;
+            }
+            self.close();
+        }
+        Ok((tokens, self.statements))
+    }
+}
+
+// Skips over tokens after an error, until the start of the next statement after the error token.
+// Delimited groups are skipped as a whole, so parsing does not resume in the middle of a group.
+fn error_statement<'s>(
+    mut tokens: TokenList<'s>,
+    error: ParseError,
+    mut is_end: impl FnMut(TokenList<'s>) -> bool,
+) -> (TokenList<'s>, Statement<'s>) {
+    let mut skipped = Vec::new();
+    let mut group_close_index = None;
+    while let Some((next_tokens, item)) = tokens.split_first() {
+        let index = tokens.start_index();
+        let is_in_group = group_close_index.is_some_and(|close_index| index <= close_index);
+        if !is_in_group {
+            let is_past_error = index > error.token_index && is_statement_boundary(tokens);
+            if !skipped.is_empty() && (is_past_error || is_end(tokens)) {
+                break;
+            }
+            group_close_index = item.close_index.filter(|&close_index| close_index > index);
+        }
+
+        skipped.push(&item.token);
+        tokens = next_tokens;
+    }
+
+    (
+        tokens,
+        Statement {
+            ty: StatementType::Error(ErrorStatement {
+                error,
+                tokens: skipped,
+            }),
+            semicolon: None,
+        },
+    )
+}
+
+// Returns true if a statement can end before the next token, either because the previous token
+// ended a line or a block, or because there are no more statements.
+fn is_statement_boundary(tokens: TokenList) -> bool {
+    if tokens.is_newline() || tokens.is_ended() {
+        return true;
+    }
+
+    // Statement can end if the last token was a `;` or `}`.
+    if let Some(last_item) = tokens.previous() {
+        if let TokenType::Terminal(TerminalToken::Semicolon | TerminalToken::CloseBrace) =
+            last_item.token.ty
+        {
+            return true;
+        }
+    }
+
+    // Statement can end if the next token is an empty statement.
+    tokens.empty().is_some()
+}
+
+fn end_of_statement_error(tokens: TokenList, next_tokens: TokenList) -> ParseError {
+    let error = next_tokens.error_before(ParseErrorType::ExpectedEndOfStatement);
+    let range = tokens.start_index()..error.token_index;
+    error.with_context(ContextType::Statement, range, TokenAffinity::Before)
 }
 
 fn inner_statement(tokens: TokenList) -> ParseResult<Statement> {
@@ -135,7 +480,7 @@ pub fn block_statement(tokens: TokenList) -> ParseResult<BlockStatement> {
             ContextType::BlockStatement,
             |tokens| tokens.terminal(TerminalToken::CloseBrace),
             |tokens, open, close| {
-                let (tokens, statements) = tokens.many_until_ended(statement)?;
+                let (tokens, statements) = statements(tokens)?;
                 Ok((
                     tokens,
                     BlockStatement {
@@ -6,6 +6,8 @@ pub struct TokenList<'s> {
     flavor: Flavor,
     tokens: &'s [TokenItem<'s>],
     index: usize,
+    end: usize,
+    recover: bool,
 }
 
 impl<'s> TokenList<'s> {
@@ -14,6 +16,15 @@ impl<'s> TokenList<'s> {
             flavor,
             tokens,
             index: 0,
+            end: tokens.len(),
+            recover: false,
+        }
+    }
+
+    pub fn recovering(self) -> Self {
+        TokenList {
+            recover: true,
+            ..self
         }
     }
 
@@ -21,6 +32,15 @@ impl<'s> TokenList<'s> {
         self.flavor
     }
 
+    pub fn is_recovering(self) -> bool {
+        self.recover
+    }
+
+    // All tokens in the input, including the ones before and after the list.
+    pub fn items(self) -> &'s [TokenItem<'s>] {
+        self.tokens
+    }
+
     pub fn previous(self) -> Option<&'s TokenItem<'s>> {
         if self.index > 0 {
             self.tokens.get(self.index - 1)
@@ -30,17 +50,30 @@ impl<'s> TokenList<'s> {
     }
 
     pub fn next(self) -> Option<&'s TokenItem<'s>> {
-        self.tokens.get(self.index)
+        if self.index < self.end {
+            self.tokens.get(self.index)
+        } else {
+            None
+        }
+    }
+
+    // The token after the end of the list, for example the `}` at the end of a block.
+    pub fn end_token(self) -> Option<&'s TokenItem<'s>> {
+        self.tokens.get(self.end)
     }
 
     pub fn is_ended(self) -> bool {
-        self.index == self.tokens.len()
+        self.index == self.end
     }
 
     pub fn start_index(self) -> usize {
         self.index
     }
 
+    pub fn end_index(self) -> usize {
+        self.end
+    }
+
     pub fn is_newline(self) -> bool {
         self.previous()
             .map(|item| item.token.new_line.is_some())
@@ -51,9 +84,8 @@ impl<'s> TokenList<'s> {
         self.next().map(|first| {
             (
                 TokenList {
-                    flavor: self.flavor,
-                    tokens: self.tokens,
                     index: self.index + 1,
+                    ..self
                 },
                 first,
             )
@@ -63,16 +95,8 @@ impl<'s> TokenList<'s> {
     pub fn split_at(self, index: usize) -> (TokenList<'s>, TokenList<'s>) {
         assert!(index >= self.index);
         (
-            TokenList {
-                flavor: self.flavor,
-                tokens: &self.tokens[..index],
-                index: self.index,
-            },
-            TokenList {
-                flavor: self.flavor,
-                tokens: self.tokens,
-                index,
-            },
+            TokenList { end: index, ..self },
+            TokenList { index, ..self },
         )
     }
 }
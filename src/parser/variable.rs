@@ -1,5 +1,5 @@
 use crate::ast::{Precedence, VarDefinition, VarInitializer};
-use crate::parser::expression::expression;
+use crate::parser::expression::required_expression;
 use crate::parser::identifier::identifier;
 use crate::parser::parse_result_ext::ParseResultExt;
 use crate::parser::token_list::TokenList;
@@ -17,6 +17,7 @@ pub fn var_initializer(tokens: TokenList) -> ParseResult<VarInitializer> {
     tokens
         .terminal(TerminalToken::Assign)
         .determines(|tokens, assign| {
-            expression(tokens, Precedence::Comma).map_val(|value| VarInitializer { assign, value })
+            required_expression(tokens, Precedence::Comma)
+                .map_val(|value| VarInitializer { assign, value })
         })
 }
@@ -0,0 +1,632 @@
+use crate::lexer::link_tokens;
+use crate::token::{Comment, Token};
+use crate::{LexerError, LexerErrorType, TokenItem};
+use std::ops::Range;
+
+/// A preprocessor directive.
+///
+/// Directives are stored in tokens as [`Comment::ScriptStyle`] comments. Use
+/// [`Directive::from_comment`] to parse one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Directive<'s> {
+    /// Starts a conditional block.
+    ///
+    /// Grammar: `#if` [Condition]
+    If(Condition<'s>),
+
+    /// Starts an alternative branch of a conditional block.
+    ///
+    /// Grammar: `#elseif` [Condition]
+    ElseIf(Condition<'s>),
+
+    /// Starts the final branch of a conditional block.
+    ///
+    /// Grammar: `#else`
+    Else,
+
+    /// Ends a conditional block.
+    ///
+    /// Grammar: `#endif`
+    EndIf,
+}
+
+/// A boolean condition in an `#if` or `#elseif` directive.
+///
+/// Conditions are made of defines like `SERVER`, `CLIENT`, `UI` and `DEV`, combined with `!`, `&&`,
+/// `||` and parentheses. `!` binds tightest, followed by `&&` then `||`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition<'s> {
+    /// A define, which is true if it is in the define set.
+    ///
+    /// Grammar: &lt;identifier>
+    Define(&'s str),
+
+    /// Grammar: `!` [Condition]
+    Not(Box<Condition<'s>>),
+
+    /// Grammar: [Condition] `&&` [Condition]
+    And(Box<Condition<'s>>, Box<Condition<'s>>),
+
+    /// Grammar: [Condition] `||` [Condition]
+    Or(Box<Condition<'s>>, Box<Condition<'s>>),
+}
+
+impl<'s> Directive<'s> {
+    /// Parses a directive from a comment.
+    ///
+    /// Returns `None` if the comment is not a directive, or if the directive is not formed
+    /// correctly.
+    ///
+    /// # Example
+    /// ```
+    /// use sqparse::{Condition, Directive};
+    /// use sqparse::token::Comment;
+    ///
+    /// let directive = Directive::from_comment(&Comment::ScriptStyle("if SERVER || CLIENT"));
+    /// assert_eq!(
+    ///     directive,
+    ///     Some(Directive::If(Condition::Or(
+    ///         Box::new(Condition::Define("SERVER")),
+    ///         Box::new(Condition::Define("CLIENT")),
+    ///     )))
+    /// );
+    ///
+    /// assert_eq!(Directive::from_comment(&Comment::ScriptStyle("endif")), Some(Directive::EndIf));
+    /// assert_eq!(Directive::from_comment(&Comment::SingleLine("endif")), None);
+    /// ```
+    pub fn from_comment(comment: &Comment<'s>) -> Option<Self> {
+        match *comment {
+            Comment::ScriptStyle(text) => parse_directive(text)?.ok(),
+            _ => None,
+        }
+    }
+}
+
+impl Condition<'_> {
+    /// Evaluates the condition with a set of defines.
+    ///
+    /// # Example
+    /// ```
+    /// use sqparse::{Condition, Directive};
+    /// use sqparse::token::Comment;
+    ///
+    /// let Some(Directive::If(condition)) =
+    ///     Directive::from_comment(&Comment::ScriptStyle("if (SERVER || CLIENT) && !DEV"))
+    /// else {
+    ///     unreachable!()
+    /// };
+    /// assert!(condition.evaluate(&["CLIENT"]));
+    /// assert!(!condition.evaluate(&["CLIENT", "DEV"]));
+    /// assert!(!condition.evaluate(&["UI"]));
+    /// ```
+    pub fn evaluate(&self, defines: &[&str]) -> bool {
+        match self {
+            Condition::Define(name) => defines.contains(name),
+            Condition::Not(value) => !value.evaluate(defines),
+            Condition::And(left, right) => left.evaluate(defines) && right.evaluate(defines),
+            Condition::Or(left, right) => left.evaluate(defines) || right.evaluate(defines),
+        }
+    }
+}
+
+impl std::fmt::Display for Condition<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Writes a nested condition, with parentheses if it binds looser than its parent.
+        let nested = |f: &mut std::fmt::Formatter<'_>, condition: &Condition, is_looser: bool| {
+            if is_looser {
+                write!(f, "({condition})")
+            } else {
+                write!(f, "{condition}")
+            }
+        };
+
+        match self {
+            Condition::Define(name) => write!(f, "{name}"),
+            Condition::Not(value) => {
+                write!(f, "!")?;
+                nested(
+                    f,
+                    value,
+                    !matches!(**value, Condition::Define(_) | Condition::Not(_)),
+                )
+            }
+            Condition::And(left, right) => {
+                nested(f, left, matches!(**left, Condition::Or(..)))?;
+                write!(f, " && ")?;
+                nested(f, right, matches!(**right, Condition::Or(..)))
+            }
+            Condition::Or(left, right) => write!(f, "{left} || {right}"),
+        }
+    }
+}
+
+/// Evaluates the preprocessor directives in a token list, returning only the active tokens.
+///
+/// Tokens in `#if`, `#elseif` and `#else` branches are kept if the branch's condition is the
+/// first one to be true with the provided defines, and removed otherwise. Directives in nested
+/// blocks are evaluated too. The returned tokens can be passed to [`parse`] to get the statements
+/// that are active with the defines, for example to check what a specific VM sees in a file.
+/// Directive comments are removed from the returned tokens, since they have been evaluated.
+///
+/// Returns an error if a directive is malformed or unmatched, or if removing inactive tokens
+/// leaves an opening delimiter unmatched.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, preprocess, tokenize};
+/// use sqparse::ast::Spanned;
+///
+/// let source = r#"
+/// #if SERVER
+/// void function OnDamage( entity victim ) {}
+/// #elseif CLIENT || UI
+/// void function OnDamage() {}
+/// #endif
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+///
+/// let server_tokens = preprocess(&tokens, &["SERVER"]).unwrap();
+/// let server_program = parse(&server_tokens, Flavor::SquirrelRespawn).unwrap();
+/// let server_span = server_program.statements[0].span().unwrap();
+/// assert_eq!(&source[server_span], "void function OnDamage( entity victim ) {}");
+///
+/// let client_tokens = preprocess(&tokens, &["CLIENT"]).unwrap();
+/// let client_program = parse(&client_tokens, Flavor::SquirrelRespawn).unwrap();
+/// let client_span = client_program.statements[0].span().unwrap();
+/// assert_eq!(&source[client_span], "void function OnDamage() {}");
+/// ```
+///
+/// [`parse`]: crate::parse
+pub fn preprocess<'s>(
+    tokens: &[TokenItem<'s>],
+    defines: &[&str],
+) -> Result<Vec<TokenItem<'s>>, LexerError<'s>> {
+    let mut blocks = Vec::<Block>::new();
+    let mut active_tokens = Vec::new();
+
+    for item in tokens {
+        for directive in &item.directives {
+            let range = directive.range.clone();
+            match &directive.directive {
+                Ok(directive) => apply_directive(&mut blocks, directive.clone(), range, defines)?,
+                Err(error_range) => {
+                    return Err(LexerError::new(
+                        LexerErrorType::InvalidDirective,
+                        error_range.clone(),
+                    ))
+                }
+            }
+        }
+
+        if blocks.last().is_none_or(|block| block.is_active) {
+            active_tokens.push(without_directives(item));
+        }
+    }
+
+    if let Some(block) = blocks.last() {
+        return Err(LexerError::new(
+            LexerErrorType::UnterminatedDirective,
+            block.range.clone(),
+        ));
+    }
+
+    link_tokens(active_tokens)
+}
+
+// Clones a token, removing any directive comments and their ranges. Lines that only had a
+// directive are removed too.
+fn without_directives<'s>(item: &TokenItem<'s>) -> (Token<'s>, Vec<Range<usize>>) {
+    let is_directive = |comment: &Comment| match *comment {
+        Comment::ScriptStyle(text) => parse_directive(text).is_some(),
+        _ => false,
+    };
+
+    let mut ranges = item.comment_ranges.iter();
+    let mut comment_ranges = Vec::new();
+    let mut retain = |comments: &mut Vec<Comment>| {
+        comments.retain(|comment| {
+            let range = ranges.next().cloned();
+            let keep = !is_directive(comment);
+            if keep {
+                comment_ranges.extend(range);
+            }
+            keep
+        })
+    };
+
+    let mut token = item.token.clone();
+    token.before_lines.retain_mut(|line| {
+        let had_comments = !line.comments.is_empty();
+        retain(&mut line.comments);
+        !had_comments || !line.comments.is_empty()
+    });
+    retain(&mut token.comments);
+
+    // Comments after the token are never directives.
+    comment_ranges.extend(ranges.cloned());
+    (token, comment_ranges)
+}
+
+// An `#if` block that is being evaluated.
+struct Block {
+    range: Range<usize>,
+    is_parent_active: bool,
+    is_active: bool,
+    has_active_branch: bool,
+    has_else: bool,
+}
+
+fn apply_directive<'s>(
+    blocks: &mut Vec<Block>,
+    directive: Directive,
+    range: Range<usize>,
+    defines: &[&str],
+) -> Result<(), LexerError<'s>> {
+    if let Directive::If(condition) = directive {
+        let is_parent_active = blocks.last().is_none_or(|block| block.is_active);
+        let is_active = is_parent_active && condition.evaluate(defines);
+        blocks.push(Block {
+            range,
+            is_parent_active,
+            is_active,
+            has_active_branch: is_active,
+            has_else: false,
+        });
+        return Ok(());
+    }
+
+    match (directive, blocks.last_mut()) {
+        (Directive::ElseIf(condition), Some(block)) if !block.has_else => {
+            block.is_active =
+                block.is_parent_active && !block.has_active_branch && condition.evaluate(defines);
+            block.has_active_branch |= block.is_active;
+        }
+        (Directive::Else, Some(block)) if !block.has_else => {
+            block.is_active = block.is_parent_active && !block.has_active_branch;
+            block.has_active_branch = true;
+            block.has_else = true;
+        }
+        (Directive::EndIf, Some(_)) => {
+            blocks.pop();
+        }
+        _ => return Err(LexerError::new(LexerErrorType::UnmatchedDirective, range)),
+    }
+    Ok(())
+}
+
+// A preprocessor directive in the comments before a token.
+//
+// Like delimiters are linked with `close_index`, directives are linked when tokenizing to the
+// `#if` they continue and to the next directive of the same conditional, so the parser can find
+// them without searching.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DirectiveItem<'s> {
+    // The directive, or the range of the error in the source if it is malformed.
+    pub directive: Result<Directive<'s>, Range<usize>>,
+
+    // The range of the directive in the source, including the `#`.
+    pub range: Range<usize>,
+
+    // The `#if` that an `#elseif`, `#else` or `#endif` continues.
+    pub if_: Option<DirectiveIndex>,
+
+    // The `#elseif`, `#else` or `#endif` that continues an `#if`, `#elseif` or `#else`.
+    pub next: Option<DirectiveIndex>,
+}
+
+// The position of a directive: the index of the token it is before, and its index in the token's
+// directives.
+pub(crate) type DirectiveIndex = (usize, usize);
+
+impl<'s> DirectiveItem<'s> {
+    pub fn new(directive: Result<Directive<'s>, Range<usize>>, range: Range<usize>) -> Self {
+        DirectiveItem {
+            directive,
+            range,
+            if_: None,
+            next: None,
+        }
+    }
+}
+
+// Links the directives in a list of tokens, matching them in the same way as `preprocess`. An
+// `#elseif` or `#else` after an `#else`, or a directive without an open `#if`, is not linked.
+pub(crate) fn link_directives(items: &mut [TokenItem]) {
+    // The `#if` and last directive of each open conditional, and whether it has had an `#else`.
+    let mut open = Vec::<(DirectiveIndex, DirectiveIndex, bool)>::new();
+    for token_index in 0..items.len() {
+        for directive_index in 0..items[token_index].directives.len() {
+            let index = (token_index, directive_index);
+            let Ok(directive) = &items[token_index].directives[directive_index].directive else {
+                continue;
+            };
+            if let Directive::If(_) = directive {
+                open.push((index, index, false));
+                continue;
+            }
+            let is_else = *directive == Directive::Else;
+            let is_end_if = *directive == Directive::EndIf;
+
+            // Only an `#endif` can continue an `#else`.
+            let Some((if_, last, has_else)) = open.last_mut() else {
+                continue;
+            };
+            if *has_else && !is_end_if {
+                continue;
+            }
+            items[last.0].directives[last.1].next = Some(index);
+            items[token_index].directives[directive_index].if_ = Some(*if_);
+            *last = index;
+            *has_else |= is_else;
+            if is_end_if {
+                open.pop();
+            }
+        }
+    }
+}
+
+// Parses a directive from the text of a script-style comment. Returns `None` if the comment is not
+// a directive, or the range of the error in the text if the directive is malformed.
+pub(crate) fn parse_directive(text: &str) -> Option<Result<Directive<'_>, Range<usize>>> {
+    let keyword_len = text
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+
+    // Allow a trailing comment after the directive.
+    let end = text.find("//").unwrap_or(text.len()).max(keyword_len);
+    let mut parser = ConditionParser {
+        text: &text[..end],
+        offset: keyword_len,
+    };
+
+    let directive = match &text[..keyword_len] {
+        "if" => parser.condition().map(Directive::If),
+        "elseif" => parser.condition().map(Directive::ElseIf),
+        "else" => Ok(Directive::Else),
+        "endif" => Ok(Directive::EndIf),
+        _ => return None,
+    };
+    Some(directive.and_then(|directive| parser.end().map(|_| directive)))
+}
+
+// Parses a whole string as a condition, like the `When` condition of a `scripts.rson` block.
+// Returns the range of the error in the text if the condition is malformed.
+pub(crate) fn parse_condition(text: &str) -> Result<Condition<'_>, Range<usize>> {
+    let mut parser = ConditionParser { text, offset: 0 };
+    let condition = parser.condition()?;
+    parser.end()?;
+    Ok(condition)
+}
+
+struct ConditionParser<'s> {
+    text: &'s str,
+    offset: usize,
+}
+
+impl<'s> ConditionParser<'s> {
+    fn condition(&mut self) -> Result<Condition<'s>, Range<usize>> {
+        let mut condition = self.and()?;
+        while self.symbol("||") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition<'s>, Range<usize>> {
+        let mut condition = self.not()?;
+        while self.symbol("&&") {
+            condition = Condition::And(Box::new(condition), Box::new(self.not()?));
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<Condition<'s>, Range<usize>> {
+        if self.symbol("!") {
+            return Ok(Condition::Not(Box::new(self.not()?)));
+        }
+
+        if self.symbol("(") {
+            let condition = self.condition()?;
+            return if self.symbol(")") {
+                Ok(condition)
+            } else {
+                Err(self.error())
+            };
+        }
+
+        let rest = self.rest();
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        if name_len == 0 || rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error());
+        }
+        self.offset += name_len;
+        Ok(Condition::Define(&rest[..name_len]))
+    }
+
+    fn end(&mut self) -> Result<(), Range<usize>> {
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    // Skips whitespace, and then the symbol if it is next.
+    fn symbol(&mut self, symbol: &str) -> bool {
+        let is_next = self.rest().starts_with(symbol);
+        if is_next {
+            self.offset += symbol.len();
+        }
+        is_next
+    }
+
+    // Skips whitespace and returns the rest of the text.
+    fn rest(&mut self) -> &'s str {
+        let text = &self.text[self.offset..];
+        let trimmed = text.trim_start();
+        self.offset += text.len() - trimmed.len();
+        trimmed.trim_end()
+    }
+
+    // Returns the range of the next character, or the end of the text.
+    fn error(&mut self) -> Range<usize> {
+        let char_len = self.rest().chars().next().map_or(0, char::len_utf8);
+        self.offset..self.offset + char_len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::token::Comment;
+    use crate::{parse, preprocess, tokenize, Condition, Directive, Flavor, LexerErrorType};
+
+    fn define(name: &str) -> Box<Condition<'_>> {
+        Box::new(Condition::Define(name))
+    }
+
+    fn active_identifiers(source: &str, defines: &[&str]) -> Vec<String> {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let active_tokens = preprocess(&tokens, defines).unwrap();
+        active_tokens
+            .iter()
+            .map(|item| source[item.token.range.clone()].to_string())
+            .collect()
+    }
+
+    fn error_type(source: &str) -> (LexerErrorType<'_>, &str) {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let error = preprocess(&tokens, &[]).unwrap_err();
+        (error.ty, &source[error.range])
+    }
+
+    #[test]
+    fn display_conditions() {
+        let display = |text| super::parse_condition(text).unwrap().to_string();
+        assert_eq!(display("SERVER"), "SERVER");
+        assert_eq!(
+            display("(SERVER||CLIENT) && !DEV"),
+            "(SERVER || CLIENT) && !DEV"
+        );
+        assert_eq!(display("SERVER || CLIENT && UI"), "SERVER || CLIENT && UI");
+        assert_eq!(display("!(A && B) || !!C"), "!(A && B) || !!C");
+    }
+
+    #[test]
+    fn parse_conditions() {
+        let parse = |text| match super::parse_directive(text) {
+            Some(Ok(Directive::If(condition))) => condition,
+            other => panic!("unexpected directive {other:?}"),
+        };
+
+        assert_eq!(parse("if SERVER"), Condition::Define("SERVER"));
+        assert_eq!(
+            parse("if !SERVER&&CLIENT || UI"),
+            Condition::Or(
+                Box::new(Condition::And(
+                    Box::new(Condition::Not(define("SERVER"))),
+                    define("CLIENT")
+                )),
+                define("UI")
+            )
+        );
+        assert_eq!(
+            parse("if !(SERVER || CLIENT) // not a VM"),
+            Condition::Not(Box::new(Condition::Or(define("SERVER"), define("CLIENT"))))
+        );
+    }
+
+    #[test]
+    fn parse_invalid_directives() {
+        assert_eq!(super::parse_directive("ifdef SERVER"), None);
+        assert_eq!(super::parse_directive(" if SERVER"), None);
+        assert_eq!(super::parse_directive("if"), Some(Err(2..2)));
+        assert_eq!(super::parse_directive("if SERVER &&"), Some(Err(12..12)));
+        assert_eq!(super::parse_directive("if (SERVER"), Some(Err(10..10)));
+        assert_eq!(
+            super::parse_directive("if SERVER CLIENT"),
+            Some(Err(10..11))
+        );
+        assert_eq!(super::parse_directive("endif SERVER"), Some(Err(6..7)));
+    }
+
+    #[test]
+    fn evaluate_branches() {
+        let source = "a\n#if SERVER\nb\n#elseif CLIENT\nc\n#elseif UI\nd\n#else\ne\n#endif\nf";
+
+        assert_eq!(active_identifiers(source, &["SERVER"]), ["a", "b", "f"]);
+        assert_eq!(
+            active_identifiers(source, &["CLIENT", "UI"]),
+            ["a", "c", "f"]
+        );
+        assert_eq!(active_identifiers(source, &["UI"]), ["a", "d", "f"]);
+        assert_eq!(active_identifiers(source, &[]), ["a", "e", "f"]);
+    }
+
+    #[test]
+    fn evaluate_nested() {
+        let source =
+            "#if SERVER\na\n#if DEV\nb\n#else\nc\n#endif\n#else\n#if DEV\nd\n#endif\n#endif";
+
+        assert_eq!(
+            active_identifiers(source, &["SERVER", "DEV"]),
+            ["a", "b", ""]
+        );
+        assert_eq!(active_identifiers(source, &["SERVER"]), ["a", "c", ""]);
+        assert_eq!(active_identifiers(source, &["DEV"]), ["d", ""]);
+        assert_eq!(active_identifiers(source, &[]), [""]);
+    }
+
+    #[test]
+    fn relinks_delimiters() {
+        let source = "a( 1,\n#if SERVER\n2 )\n#else\n3 )\n#endif\n";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        assert_eq!(tokens[1].close_index, Some(5));
+
+        let active_tokens = preprocess(&tokens, &[]).unwrap();
+        assert_eq!(active_tokens[1].close_index, Some(5));
+        assert_eq!(active_tokens[5].token.range, tokens[7].token.range);
+    }
+
+    #[test]
+    fn removes_directives() {
+        let source = "#if SERVER // server only\n/* note */\na()\n#endif\nb()";
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let active_tokens = preprocess(&tokens, &["SERVER"]).unwrap();
+
+        let before_lines = &active_tokens[0].token.before_lines;
+        assert_eq!(before_lines.len(), 1);
+        assert_eq!(before_lines[0].comments, [Comment::MultiLine(" note ")]);
+        assert!(active_tokens[3].token.before_lines.is_empty());
+        assert!(parse(&active_tokens, Flavor::SquirrelRespawn).is_ok());
+    }
+
+    #[test]
+    fn directive_errors() {
+        assert!(matches!(
+            error_type("#if SERVER\na"),
+            (LexerErrorType::UnterminatedDirective, "#if SERVER")
+        ));
+        assert!(matches!(
+            error_type("a\n#endif"),
+            (LexerErrorType::UnmatchedDirective, "#endif")
+        ));
+        assert!(matches!(
+            error_type("#if UI\n#else\n#else\n#endif"),
+            (LexerErrorType::UnmatchedDirective, "#else")
+        ));
+        assert!(matches!(
+            error_type("#if UI\n#else\n#elseif SERVER\n#endif"),
+            (LexerErrorType::UnmatchedDirective, "#elseif SERVER")
+        ));
+        assert!(matches!(
+            error_type("#if UI ||\n#endif"),
+            (LexerErrorType::InvalidDirective, "")
+        ));
+        assert!(matches!(
+            error_type("a(\n#if UI\n)\n#endif"),
+            (LexerErrorType::UnmatchedOpener { .. }, "(")
+        ));
+    }
+}
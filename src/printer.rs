@@ -0,0 +1,315 @@
+use crate::ast::{Program, Visit};
+use crate::lexer::literal_from_str;
+use crate::token::{Comment, LiteralBase, LiteralToken, StringToken, Token, TokenLine, TokenType};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Prints a syntax tree back into source code.
+///
+/// Every token in the tree is written along with the comments and newlines it owns. Horizontal
+/// whitespace is not stored in tokens, so it is recovered from the `source` string the tree was
+/// parsed from. As long as the tree has not been modified, the output is identical to `source`.
+///
+/// Tokens that do not come from `source` (for example, if the tree has been modified) are
+/// separated from the previous token by a single space, unless they have the range of a token in
+/// `source` (see [`TokenArena::replace`]), in which case the whitespace before that range is kept.
+///
+/// # Example
+/// ```
+/// use sqparse::{Flavor, parse, print_program, tokenize};
+///
+/// let source = r#"
+/// global function MyFunction
+///
+/// struct {
+///     int a // the value of a
+/// } file
+///
+/// string function MyFunction( List<number> values ) {
+///     values.push(1 + 2) /* push a value */
+/// }
+/// "#;
+/// let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+/// let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+///
+/// assert_eq!(print_program(&program, source), source);
+/// ```
+///
+/// [`TokenArena::replace`]: crate::token::TokenArena::replace
+pub fn print_program(program: &Program, source: &str) -> String {
+    let mut printer = Printer::new(source);
+    printer.visit_program(program);
+    printer.finish()
+}
+
+/// Returns the text of a token as it would appear in source code.
+///
+/// If the token was lexed from `source` the original text is returned, otherwise the text is
+/// generated from the token type.
+pub(crate) fn token_text<'a>(token: &Token<'a>, source: &'a str) -> Cow<'a, str> {
+    match token_source_text(token, source) {
+        Some(text) => Cow::Borrowed(text),
+        None => token_type_text(token.ty),
+    }
+}
+
+// Returns the text of a token in the source string, if the token was lexed from the source.
+fn token_source_text<'a>(token: &Token, source: &'a str) -> Option<&'a str> {
+    let text = source.get(token.range.clone())?;
+    let matches = match token.ty {
+        TokenType::Literal(literal @ (LiteralToken::Int(_, _) | LiteralToken::Float(_))) => {
+            literal_from_str(text) == Some(literal)
+        }
+        // Strings recovered by `tokenize_lossy` may be missing their closing delimiter.
+        ty @ TokenType::Literal(LiteralToken::Char(_) | LiteralToken::String(_)) => {
+            let expected = token_type_text(ty);
+            text == expected || text == &expected[..expected.len() - 1]
+        }
+        ty => text == token_type_text(ty),
+    };
+    matches.then_some(text)
+}
+
+// Generates the text of a token type.
+fn token_type_text<'s>(ty: TokenType<'s>) -> Cow<'s, str> {
+    match ty {
+        TokenType::Empty => Cow::Borrowed(""),
+        TokenType::Terminal(terminal) => Cow::Borrowed(terminal.as_str()),
+        TokenType::Identifier(identifier) | TokenType::Invalid(identifier) => {
+            Cow::Borrowed(identifier)
+        }
+        TokenType::Literal(LiteralToken::Int(val, LiteralBase::Decimal)) => {
+            Cow::Owned(val.to_string())
+        }
+        TokenType::Literal(LiteralToken::Int(val, LiteralBase::Octal)) => {
+            Cow::Owned(format!("0{val:o}"))
+        }
+        TokenType::Literal(LiteralToken::Int(val, LiteralBase::Hexadecimal)) => {
+            Cow::Owned(format!("0x{val:X}"))
+        }
+        TokenType::Literal(LiteralToken::Float(val)) => {
+            // Squirrel floats must contain a `.`, even if they have an exponent.
+            let text = format!("{val:?}");
+            match text.find('e') {
+                Some(exponent_index) if !text.contains('.') => Cow::Owned(format!(
+                    "{}.0{}",
+                    &text[..exponent_index],
+                    &text[exponent_index..]
+                )),
+                _ => Cow::Owned(text),
+            }
+        }
+        TokenType::Literal(LiteralToken::Char(val)) => Cow::Owned(format!("'{val}'")),
+        TokenType::Literal(LiteralToken::String(StringToken::Literal(val))) => {
+            Cow::Owned(format!("\"{val}\""))
+        }
+        TokenType::Literal(LiteralToken::String(StringToken::Verbatim(val))) => {
+            Cow::Owned(format!("@\"{val}\""))
+        }
+        TokenType::Literal(LiteralToken::String(StringToken::Asset(val))) => {
+            Cow::Owned(format!("$\"{val}\""))
+        }
+    }
+}
+
+/// Returns the text of a comment as it would appear in source code, including delimiters.
+pub(crate) fn comment_text(comment: &Comment, source: &str) -> String {
+    match *comment {
+        Comment::MultiLine(val) => {
+            // A multi-line comment is allowed to be unterminated at the end of the input.
+            let is_unterminated = str_offset(val, source)
+                .map(|offset| !source[offset + val.len()..].starts_with("*/"))
+                .unwrap_or(false);
+            if is_unterminated {
+                format!("/*{val}")
+            } else {
+                format!("/*{val}*/")
+            }
+        }
+        Comment::SingleLine(val) => format!("//{val}"),
+        Comment::ScriptStyle(val) => format!("#{val}"),
+    }
+}
+
+// Returns the start offset of a comment in the source string, including delimiters.
+fn comment_offset(comment: &Comment, source: &str) -> Option<usize> {
+    let (val, delimiter) = match *comment {
+        Comment::MultiLine(val) => (val, "/*"),
+        Comment::SingleLine(val) => (val, "//"),
+        Comment::ScriptStyle(val) => (val, "#"),
+    };
+    let offset = str_offset(val, source)?.checked_sub(delimiter.len())?;
+    source[offset..].starts_with(delimiter).then_some(offset)
+}
+
+// Returns the offset of `val` in `source`, if `val` is a slice of `source`.
+pub(crate) fn str_offset(val: &str, source: &str) -> Option<usize> {
+    let start = (val.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+    if start + val.len() <= source.len() {
+        Some(start)
+    } else {
+        None
+    }
+}
+
+// Returns the trailing horizontal whitespace in a string.
+fn trailing_whitespace(val: &str) -> &str {
+    match val.rfind(|c: char| c == '\n' || !c.is_whitespace()) {
+        Some(index) => {
+            let char_len = val[index..].chars().next().map_or(0, char::len_utf8);
+            &val[index + char_len..]
+        }
+        None => val,
+    }
+}
+
+struct Printer<'a> {
+    source: &'a str,
+    cursor: usize,
+    output: String,
+}
+
+impl<'a> Printer<'a> {
+    fn new(source: &'a str) -> Self {
+        Printer {
+            source,
+            cursor: 0,
+            output: String::with_capacity(source.len()),
+        }
+    }
+
+    fn finish(mut self) -> String {
+        let remaining = self.source.get(self.cursor..).unwrap_or("");
+        if remaining.chars().all(|c| c != '\n' && c.is_whitespace()) {
+            self.output.push_str(remaining);
+        }
+        self.output
+    }
+
+    fn token(&mut self, token: &Token) {
+        for line in &token.before_lines {
+            self.line(line);
+        }
+        for comment in &token.comments {
+            self.comment(comment);
+        }
+
+        match token_source_text(token, self.source) {
+            Some(text) => self.write_at(Some(token.range.clone()), text),
+            None => {
+                // Tokens that replace a source token keep the whitespace before it.
+                let range = Some(token.range.clone())
+                    .filter(|range| !range.is_empty() && self.source.get(range.clone()).is_some());
+                self.write_at(range, &token_type_text(token.ty));
+            }
+        }
+
+        if let Some(line) = &token.new_line {
+            self.line(line);
+        }
+    }
+
+    fn comment(&mut self, comment: &Comment) {
+        let text = comment_text(comment, self.source);
+        let range = comment_offset(comment, self.source).map(|offset| offset..offset + text.len());
+        self.write_at(range, &text);
+    }
+
+    fn line(&mut self, line: &TokenLine) {
+        for comment in &line.comments {
+            self.comment(comment);
+        }
+
+        // Find the newline in the source, to preserve any whitespace before it.
+        let rest = self.source.get(self.cursor..).unwrap_or("");
+        let newline_offset = rest
+            .find('\n')
+            .filter(|&index| rest[..index].chars().all(char::is_whitespace));
+        match newline_offset {
+            Some(index) => {
+                let newline = self.cursor + index;
+                self.write_at(Some(newline..newline + 1), "\n");
+            }
+            None => self.write_at(None, "\n"),
+        }
+    }
+
+    // Writes some text, preceded by whitespace from the source if the text has a known range in
+    // the source, or a separating space otherwise.
+    fn write_at(&mut self, range: Option<Range<usize>>, text: &str) {
+        match range {
+            Some(range) if range.start >= self.cursor => {
+                let whitespace = trailing_whitespace(&self.source[self.cursor..range.start]);
+                self.output.push_str(whitespace);
+                self.cursor = range.end;
+            }
+            _ => {
+                if text != "\n" && !text.is_empty() && !self.is_line_start() {
+                    self.output.push(' ');
+                }
+            }
+        }
+        self.output.push_str(text);
+    }
+
+    fn is_line_start(&self) -> bool {
+        self.output.is_empty() || self.output.ends_with('\n')
+    }
+}
+
+impl<'s> Visit<'s> for Printer<'_> {
+    fn visit_token(&mut self, token: &'s Token<'s>) {
+        self.token(token);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{parse, parse_recover, print_program, tokenize, tokenize_lossy, Flavor};
+
+    fn round_trip(source: &str) {
+        let tokens = tokenize(source, Flavor::SquirrelRespawn).unwrap();
+        let program = parse(&tokens, Flavor::SquirrelRespawn).unwrap();
+        assert_eq!(print_program(&program, source), source);
+    }
+
+    #[test]
+    fn round_trip_example_script() {
+        round_trip(include_str!("../examples/print_ast_script.nut"));
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        round_trip("");
+        round_trip("   ");
+        round_trip("\n\n  \n");
+    }
+
+    #[test]
+    fn round_trip_whitespace() {
+        round_trip("\tlocal  a =\t1  \r\n\r\n  a  <-  2\n   ");
+        round_trip("x = a<<b>>c>>>d\nfunction Foo ( a , b , ... ) { return a?b:c }");
+    }
+
+    #[test]
+    fn round_trip_comments() {
+        round_trip(
+            "// leading\n#if SERVER\n  /* a */ int a /* b */ = 1 // trailing\n#endif\n\n// end\n",
+        );
+        round_trip("int a = 1 /* unterminated");
+        round_trip("a(/* inline */b, c) /* x */ // y\n  // z");
+    }
+
+    #[test]
+    fn round_trip_literals() {
+        round_trip("x = [0x1f, 0777, 1.50, .5, 1e5, 1.0e-3, 'a', \"b\\\"c\", @\"d\"\"e\", $\"f\"]");
+    }
+
+    #[test]
+    fn round_trip_lossy() {
+        let source = "a = `x` + \"open\r\nb = ( 'c\nfunction F() { d }}";
+        let (tokens, _) = tokenize_lossy(source, Flavor::SquirrelRespawn);
+        let (program, _) = parse_recover(&tokens, Flavor::SquirrelRespawn);
+        assert_eq!(print_program(&program, source), source);
+    }
+}
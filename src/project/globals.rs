@@ -0,0 +1,548 @@
+use crate::analysis::Resolution;
+use crate::annotation::{line_column, Diagnostic, Label, Level};
+use crate::ast::{
+    walk_call_expression, CallExpression, ConditionalStatement, Expression, Identifier,
+    StatementType, Visit,
+};
+use crate::project::unit::active_statements;
+use crate::project::{CompilationUnit, Global, GlobalKind, Project, ProjectFile, Vm};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Type of [`GlobalError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GlobalErrorType<'s> {
+    /// A `global function` is declared, but the script does not define the function.
+    ///
+    /// # Example
+    /// ```text
+    /// global function Init
+    ///                 ^^^^ error
+    /// ```
+    UndefinedFunction { name: &'s str },
+
+    /// A name is declared global more than once in the same script.
+    ///
+    /// # Example
+    /// ```text
+    /// global function Init
+    /// global function Init
+    ///                 ^^^^ error
+    /// ```
+    DuplicateDeclaration { name: &'s str },
+
+    /// Two scripts that are loaded in the same VM declare the same global.
+    ///
+    /// # Example
+    /// ```text
+    /// // sh_a.nut
+    /// global function Init
+    ///
+    /// // sh_b.nut
+    /// global function Init
+    ///                 ^^^^ error
+    /// ```
+    DuplicateGlobal { name: &'s str },
+
+    /// A function is called that is not declared in the script, and is not a global declared by
+    /// the script or a script loaded before it.
+    ///
+    /// # Example
+    /// ```text
+    /// void function Init()
+    /// {
+    ///     InitWeapons()
+    ///     ^^^^^^^^^^^ error
+    /// }
+    /// ```
+    UnknownFunction { name: &'s str },
+}
+
+/// An error emitted while checking the globals of a [`CompilationUnit`].
+///
+/// Each error has a type with more information, the script and character range where the error
+/// occurred, and for duplicates, the earlier declaration of the global.
+#[derive(Debug, Clone)]
+pub struct GlobalError<'a> {
+    /// The type of error.
+    pub ty: GlobalErrorType<'a>,
+
+    /// The VM that the error occurred in.
+    pub vm: Vm,
+
+    /// The script with the error.
+    pub file: &'a ProjectFile<'a>,
+
+    /// The character range of where the error occurred.
+    pub range: Range<usize>,
+
+    /// The earlier declaration of the global, for duplicate declarations.
+    pub previous: Option<Global<'a>>,
+}
+
+/// Checks that the global functions in a compilation unit are declared and defined consistently,
+/// and that every function call refers to a function that is visible.
+///
+/// Declarations and calls in preprocessor branches that are not active in the VM are ignored.
+/// Native functions like `print` are not declared in scripts, so calls to names in `natives` are
+/// not reported.
+///
+/// # Example
+/// ```
+/// use sqparse::Flavor;
+/// use sqparse::project::{check_globals, GlobalErrorType, Project, ProjectSources, Vm};
+///
+/// let mut sources = ProjectSources::new();
+/// sources
+///     .add_file("sh_util.nut", "global function Util\nglobal function Missing\nvoid function Util() {}", None)
+///     .unwrap();
+/// sources
+///     .add_file("cl_main.nut", "void function Main() { Util(); print( 1 ); Later() }", Some("CLIENT"))
+///     .unwrap();
+///
+/// let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+/// let project = Project::new(&tokens).unwrap();
+/// let errors = check_globals(&project.unit(Vm::Client), &["print"]);
+///
+/// let errors: Vec<_> = errors.iter().map(|error| error.ty).collect();
+/// assert_eq!(
+///     errors,
+///     [
+///         GlobalErrorType::UndefinedFunction { name: "Missing" },
+///         GlobalErrorType::UnknownFunction { name: "Later" },
+///     ]
+/// );
+/// ```
+pub fn check_globals<'a>(unit: &CompilationUnit<'a>, natives: &[&str]) -> Vec<GlobalError<'a>> {
+    let mut errors = Vec::new();
+    let mut previous_globals: HashMap<&str, Global<'a>> = HashMap::new();
+
+    for &file in &unit.files {
+        let error = |ty, name: &Identifier, previous| GlobalError {
+            ty,
+            vm: unit.vm,
+            file,
+            range: name.token.range.clone(),
+            previous,
+        };
+
+        let mut statements = Vec::new();
+        active_statements(&file.program.statements, &unit.defines, &mut statements);
+        let defined: HashSet<_> = (statements.iter())
+            .filter_map(|statement| match &statement.ty {
+                StatementType::FunctionDefinition(function) if function.name.items.is_empty() => {
+                    Some(function.name.last_item.value)
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut file_globals: HashMap<&str, Global<'a>> = HashMap::new();
+        for global in unit.file_globals(file) {
+            let name = global.name.value;
+            let is_declaration = matches!(global.statement.ty, StatementType::Global(_));
+
+            if let Some(&first) = file_globals.get(name) {
+                // With `globalize_all_functions`, a function can also be declared with
+                // `global function`.
+                let first_is_declaration = matches!(first.statement.ty, StatementType::Global(_));
+                if is_declaration && first_is_declaration {
+                    let ty = GlobalErrorType::DuplicateDeclaration { name };
+                    errors.push(error(ty, global.name, Some(first)));
+                }
+                continue;
+            }
+            file_globals.insert(name, global);
+
+            if is_declaration && global.kind == GlobalKind::Function && !defined.contains(name) {
+                let ty = GlobalErrorType::UndefinedFunction { name };
+                errors.push(error(ty, global.name, None));
+            }
+            if let Some(&first) = previous_globals.get(name) {
+                let ty = GlobalErrorType::DuplicateGlobal { name };
+                errors.push(error(ty, global.name, Some(first)));
+            }
+        }
+        for (name, global) in file_globals {
+            previous_globals.entry(name).or_insert(global);
+        }
+
+        let resolution = Resolution::new(&file.program);
+        let mut checker = CallChecker {
+            resolution: &resolution,
+            defines: &unit.defines,
+            is_visible: |name: &str| previous_globals.contains_key(name) || natives.contains(&name),
+            unknown: Vec::new(),
+        };
+        for statement in statements {
+            checker.visit_statement(statement);
+        }
+        for name in checker.unknown {
+            let ty = GlobalErrorType::UnknownFunction { name: name.value };
+            errors.push(error(ty, name, None));
+        }
+    }
+    errors
+}
+
+/// Checks the globals of every VM in a project with [`check_globals`].
+///
+/// Scripts that are loaded in more than one VM are checked in each of them, but each error is only
+/// returned once, with the first VM it occurred in.
+pub fn check_project_globals<'a>(
+    project: &'a Project<'a>,
+    natives: &[&str],
+) -> Vec<GlobalError<'a>> {
+    let mut seen = HashSet::new();
+    let mut errors = Vec::new();
+    for unit in project.units() {
+        for error in check_globals(&unit, natives) {
+            let key = (
+                error.file.path(),
+                error.range.start,
+                error.range.end,
+                error.ty,
+            );
+            if seen.insert(key) {
+                errors.push(error);
+            }
+        }
+    }
+    errors
+}
+
+impl GlobalError<'_> {
+    /// Converts the error to a [`Diagnostic`] in the script with the error, with the error type's
+    /// [`code`].
+    ///
+    /// An earlier declaration in the same script is a secondary label. One in another script is a
+    /// note with that script's path and location.
+    ///
+    /// [`code`]: GlobalErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        let diagnostic = Diagnostic::new(Level::Error, self.ty.to_string(), label)
+            .with_code(self.ty.code())
+            .with_note(format!("in the {} VM", self.vm));
+
+        let Some(previous) = self.previous else {
+            return diagnostic;
+        };
+        let previous_range = previous.name.token.range.clone();
+        if previous.file.path() == self.file.path() {
+            return diagnostic.with_label(Label::new(previous_range, "first declared here"));
+        }
+        let (line, column) = line_column(&previous.file.source.source, previous_range.start);
+        diagnostic.with_note(format!(
+            "first declared at {}:{line}:{column}",
+            previous.file.path()
+        ))
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error using
+    /// [`Diagnostic::display`], using the script's path as the file name.
+    pub fn display(&self) -> impl std::fmt::Display + '_ {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source: &self.file.source.source,
+            file_name: self.file.path(),
+        }
+    }
+}
+
+impl std::fmt::Display for GlobalErrorType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlobalErrorType::UndefinedFunction { name } => {
+                write!(f, "global function `{name}` is declared but not defined")
+            }
+            GlobalErrorType::DuplicateDeclaration { name } => {
+                write!(f, "`{name}` is declared global more than once")
+            }
+            GlobalErrorType::DuplicateGlobal { name } => {
+                write!(f, "global `{name}` is declared by more than one script")
+            }
+            GlobalErrorType::UnknownFunction { name } => {
+                write!(f, "cannot find function `{name}`")
+            }
+        }
+    }
+}
+
+impl<'a> GlobalErrorType<'a> {
+    /// Returns a stable code for the error type, from `E0701` to `E0799`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(self) -> &'static str {
+        match self {
+            GlobalErrorType::UndefinedFunction { .. } => "E0701",
+            GlobalErrorType::DuplicateDeclaration { .. } => "E0702",
+            GlobalErrorType::DuplicateGlobal { .. } => "E0703",
+            GlobalErrorType::UnknownFunction { .. } => "E0704",
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(self) -> impl std::fmt::Display + 'a {
+        GlobalErrorInlineDisplay(self)
+    }
+}
+
+struct GlobalErrorInlineDisplay<'a>(GlobalErrorType<'a>);
+
+impl std::fmt::Display for GlobalErrorInlineDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            GlobalErrorType::UndefinedFunction { .. } => write!(f, "no function definition"),
+            GlobalErrorType::DuplicateDeclaration { .. }
+            | GlobalErrorType::DuplicateGlobal { .. } => {
+                write!(f, "declared again here")
+            }
+            GlobalErrorType::UnknownFunction { .. } => write!(f, "not found in this scope"),
+        }
+    }
+}
+
+// Finds calls to plain names that are not declared in the file or visible as globals.
+struct CallChecker<'r, 'a, F> {
+    resolution: &'r Resolution<'a>,
+    defines: &'r [&'a str],
+    is_visible: F,
+    unknown: Vec<&'a Identifier<'a>>,
+}
+
+impl<'a, F: Fn(&str) -> bool> Visit<'a> for CallChecker<'_, 'a, F> {
+    fn visit_conditional_statement(&mut self, node: &'a ConditionalStatement<'a>) {
+        for statement in node.active_statements(self.defines) {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_call_expression(&mut self, node: &'a CallExpression<'a>) {
+        let name = match &*node.function {
+            Expression::Var(var) => Some(&var.name),
+            Expression::RootVar(var) => Some(&var.name),
+            _ => None,
+        };
+        if let Some(name) = name {
+            if self.resolution.resolve(name).is_none() && !(self.is_visible)(name.value) {
+                self.unknown.push(name);
+            }
+        }
+        walk_call_expression(self, node);
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: &'a str,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, Some(self.file_name))
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_globals, check_project_globals, GlobalErrorType};
+    use crate::project::{Project, ProjectSources, Vm};
+    use crate::Flavor;
+
+    fn sources(files: &[(&str, &str, Option<&str>)]) -> ProjectSources {
+        let mut sources = ProjectSources::new();
+        for &(path, source, run_on) in files {
+            sources.add_file(path, source, run_on).unwrap();
+        }
+        sources
+    }
+
+    #[test]
+    fn declarations_and_definitions() {
+        let sources = sources(&[(
+            "sh_a.nut",
+            r#"
+            global function Defined
+            global function Undefined
+            global function Twice
+            global function Twice
+            global function ServerDefined
+
+            void function Defined() {}
+            void function Twice() {}
+            #if SERVER
+            void function ServerDefined() {}
+            #endif
+            "#,
+            None,
+        )]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let project = Project::new(&tokens).unwrap();
+
+        let errors = check_globals(&project.unit(Vm::Client), &[]);
+        let errors: Vec<_> = (errors.iter())
+            .map(|error| (error.ty, error.previous.map(|global| global.name.value)))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (
+                    GlobalErrorType::UndefinedFunction { name: "Undefined" },
+                    None
+                ),
+                (
+                    GlobalErrorType::DuplicateDeclaration { name: "Twice" },
+                    Some("Twice")
+                ),
+                (
+                    GlobalErrorType::UndefinedFunction {
+                        name: "ServerDefined"
+                    },
+                    None
+                ),
+            ]
+        );
+        assert_eq!(check_globals(&project.unit(Vm::Server), &[]).len(), 2);
+    }
+
+    #[test]
+    fn globalize_all_functions() {
+        let sources = sources(&[(
+            "sh_a.nut",
+            r#"
+            globalize_all_functions
+            global function A
+            void function A() {}
+            void function B() { A(); C() }
+            void function C() {}
+            "#,
+            None,
+        )]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let project = Project::new(&tokens).unwrap();
+        assert!(check_project_globals(&project, &[]).is_empty());
+    }
+
+    #[test]
+    fn duplicate_globals() {
+        let sources = sources(&[
+            (
+                "sv_a.nut",
+                "global function F\nvoid function F() {}",
+                Some("SERVER"),
+            ),
+            (
+                "cl_a.nut",
+                "global function F\nvoid function F() {}",
+                Some("CLIENT"),
+            ),
+            ("sh_b.nut", "global function F\nvoid function F() {}", None),
+            (
+                "ui_a.nut",
+                "globalize_all_functions\nvoid function F() {}",
+                Some("UI"),
+            ),
+        ]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let project = Project::new(&tokens).unwrap();
+
+        let errors = check_project_globals(&project, &[]);
+        let errors: Vec<_> = (errors.iter())
+            .map(|error| {
+                let previous = error.previous.unwrap().file.path();
+                (error.vm, error.file.path(), previous)
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (Vm::Server, "sh_b.nut", "sv_a.nut"),
+                (Vm::Ui, "ui_a.nut", "sh_b.nut"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_functions() {
+        let sources = sources(&[
+            (
+                "sh_a.nut",
+                "global function A\nvoid function A() { B() }",
+                None,
+            ),
+            (
+                "sh_b.nut",
+                r#"
+                global function B
+                void function B()
+                {
+                    local f = function() {}
+                    f()
+                    A()
+                    ::A()
+                    print( "" )
+                    Local()
+                    #if SERVER
+                    ServerOnly()
+                    #endif
+                    Missing().Method()
+                }
+                void function Local() {}
+                "#,
+                None,
+            ),
+        ]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let project = Project::new(&tokens).unwrap();
+
+        let names = |vm| -> Vec<_> {
+            (check_globals(&project.unit(vm), &["print"]).iter())
+                .map(|error| (error.file.path(), error.ty))
+                .collect()
+        };
+        assert_eq!(
+            names(Vm::Client),
+            [
+                ("sh_a.nut", GlobalErrorType::UnknownFunction { name: "B" }),
+                (
+                    "sh_b.nut",
+                    GlobalErrorType::UnknownFunction { name: "Missing" }
+                ),
+            ]
+        );
+        assert_eq!(names(Vm::Server).len(), 3);
+    }
+
+    #[test]
+    fn display() {
+        let sources = sources(&[
+            ("sh_a.nut", "global int x", None),
+            ("sh_b.nut", "\nglobal int x", None),
+        ]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let project = Project::new(&tokens).unwrap();
+
+        let errors = check_globals(&project.unit(Vm::Server), &[]);
+        yansi::Paint::disable();
+        assert_eq!(
+            errors[0].display().to_string(),
+            "error[E0703]: global `x` is declared by more than one script
+ --> sh_b.nut:2:12
+  |
+2 | global int x
+  |            ^ declared again here
+  |
+  = note: in the SERVER VM
+  = note: first declared at sh_a.nut:1:12"
+        );
+    }
+}
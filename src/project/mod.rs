@@ -0,0 +1,20 @@
+//! Projects made of multiple scripts, like a Northstar mod.
+//!
+//! Each script is parsed into its own [`Program`], but scripts are not compiled in isolation. A
+//! mod's `mod.json` or `scripts.rson` lists its scripts in load order, and which VMs load each one.
+//! Globals declared in a script are visible to the scripts that load after it in the same VM.
+//!
+//! [`ProjectSources`] loads the scripts, and [`Project`] parses them and groups them into a
+//! [`CompilationUnit`] for each [`Vm`], which can answer questions about what is visible where.
+//! [`check_globals`] uses this to find global functions that are declared inconsistently across
+//! scripts, and calls to functions that are not visible.
+//!
+//! [`Program`]: crate::ast::Program
+
+mod globals;
+mod sources;
+mod unit;
+
+pub use self::globals::*;
+pub use self::sources::*;
+pub use self::unit::*;
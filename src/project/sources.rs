@@ -0,0 +1,473 @@
+use crate::annotation::Diagnostic;
+use crate::preprocessor::parse_condition;
+use crate::rson::{self, RsonValue, ScriptsRson};
+use crate::{tokenize, Flavor, LexerError, ParseError, TokenItem};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A script file in [`ProjectSources`].
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// The path of the script, like `mp/_base_gametype_mp.gnut`. Scripts loaded from a mod are
+    /// relative to its `scripts/vscripts` directory.
+    pub path: String,
+
+    /// The contents of the script.
+    pub source: String,
+
+    /// The condition that decides which VMs load the script, like `SERVER || CLIENT`, or `None` if
+    /// it is loaded in every VM.
+    pub run_on: Option<String>,
+}
+
+/// The scripts in a project, in load order.
+///
+/// Sources are owned, so they can be loaded from disk. [`tokenize`](ProjectSources::tokenize)
+/// borrows them to tokenize each script, and the tokens can then be parsed into a [`Project`].
+///
+/// [`Project`]: crate::project::Project
+#[derive(Debug, Clone, Default)]
+pub struct ProjectSources {
+    files: Vec<SourceFile>,
+}
+
+/// Tokens of the scripts in [`ProjectSources`], returned by
+/// [`ProjectSources::tokenize`].
+#[derive(Debug, Clone)]
+pub struct ProjectTokens<'s> {
+    pub(crate) sources: &'s ProjectSources,
+    pub(crate) flavor: Flavor,
+    pub(crate) tokens: Vec<Vec<TokenItem<'s>>>,
+}
+
+/// An error emitted while loading [`ProjectSources`].
+#[derive(Debug)]
+pub enum ProjectError {
+    /// A file could not be read.
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+
+    /// A mod directory does not contain a `mod.json` or `scripts.rson` file.
+    MissingManifest { path: PathBuf },
+
+    /// A `mod.json` or `scripts.rson` file could not be read, or a `RunOn` condition is not formed
+    /// correctly.
+    InvalidManifest {
+        path: PathBuf,
+        message: String,
+
+        /// The character range of the error in the manifest.
+        range: Range<usize>,
+    },
+}
+
+/// Type of [`FileError`].
+#[derive(Debug, Clone)]
+pub enum FileErrorType<'s> {
+    /// The script could not be tokenized.
+    Lexer(LexerError<'s>),
+
+    /// The script could not be parsed.
+    Parser {
+        error: ParseError,
+        tokens: &'s [TokenItem<'s>],
+    },
+}
+
+/// An error in one of the scripts of a project.
+#[derive(Debug, Clone)]
+pub struct FileError<'s> {
+    /// The script with the error.
+    pub file: &'s SourceFile,
+
+    /// The type of error.
+    pub ty: FileErrorType<'s>,
+}
+
+impl ProjectSources {
+    /// Creates an empty set of sources.
+    pub fn new() -> Self {
+        ProjectSources::default()
+    }
+
+    /// Loads the scripts of a Northstar mod or a Respawn `scripts` directory.
+    ///
+    /// If the directory has a `mod.json` file, its `Scripts` entries are loaded from
+    /// `mod/scripts/vscripts`. Otherwise the scripts listed in a `scripts.rson` file are loaded,
+    /// looking for it in `mod/scripts/vscripts`, then `scripts/vscripts`, then the directory itself.
+    pub fn load_mod(dir: impl AsRef<Path>) -> Result<Self, ProjectError> {
+        let dir = dir.as_ref();
+        let mut sources = ProjectSources::new();
+
+        let mod_json = dir.join("mod.json");
+        if mod_json.is_file() {
+            let vscripts = dir.join("mod").join("scripts").join("vscripts");
+            let manifest = read(&mod_json)?;
+            let scripts = mod_json_scripts(&manifest)
+                .map_err(|(message, range)| invalid_manifest(&mod_json, message, range))?;
+            for (path, run_on) in scripts {
+                let source = read(&vscripts.join(&path))?;
+                sources.push(path, source, Some(run_on));
+            }
+            return Ok(sources);
+        }
+
+        let candidates = [
+            dir.join("mod").join("scripts").join("vscripts"),
+            dir.join("scripts").join("vscripts"),
+            dir.to_path_buf(),
+        ];
+        let vscripts = (candidates.into_iter())
+            .find(|vscripts| vscripts.join("scripts.rson").is_file())
+            .ok_or_else(|| ProjectError::MissingManifest {
+                path: dir.to_path_buf(),
+            })?;
+        let scripts_rson = vscripts.join("scripts.rson");
+        let manifest = read(&scripts_rson)?;
+        let invalid = |error: rson::RsonError| {
+            invalid_manifest(&scripts_rson, error.ty.to_string(), error.range)
+        };
+        let tokens = rson::tokenize(&manifest).map_err(invalid)?;
+        let document = rson::parse(&tokens).map_err(invalid)?;
+        let scripts = ScriptsRson::new(&document).map_err(invalid)?;
+        for block in &scripts.blocks {
+            let run_on = block.when.as_ref().map(|condition| condition.to_string());
+            for path in block.paths() {
+                let source = read(&vscripts.join(path))?;
+                sources.push(path, source, run_on.clone());
+            }
+        }
+        Ok(sources)
+    }
+
+    /// Reads a script from disk and adds it after the other scripts.
+    ///
+    /// The script's path is `path` as given.
+    pub fn add_path(
+        &mut self,
+        path: impl AsRef<Path>,
+        run_on: Option<&str>,
+    ) -> Result<(), ProjectError> {
+        let path = path.as_ref();
+        let source = read(path)?;
+        self.add_file(path.to_string_lossy(), source, run_on)
+    }
+
+    /// Adds a script after the other scripts.
+    ///
+    /// Returns an error if `run_on` is not a valid condition.
+    pub fn add_file(
+        &mut self,
+        path: impl Into<String>,
+        source: impl Into<String>,
+        run_on: Option<&str>,
+    ) -> Result<(), ProjectError> {
+        let path = path.into();
+        if let Some(Err(range)) = run_on.map(parse_condition) {
+            return Err(ProjectError::InvalidManifest {
+                path: PathBuf::from(path),
+                message: "invalid `RunOn` condition".to_string(),
+                range,
+            });
+        }
+        self.push(path, source.into(), run_on.map(str::to_string));
+        Ok(())
+    }
+
+    /// Returns the scripts, in load order.
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// Tokenizes every script.
+    ///
+    /// Returns the first error if a script could not be tokenized.
+    pub fn tokenize(&self, flavor: Flavor) -> Result<ProjectTokens<'_>, FileError<'_>> {
+        let tokens = (self.files.iter())
+            .map(|file| {
+                tokenize(&file.source, flavor).map_err(|error| FileError {
+                    file,
+                    ty: FileErrorType::Lexer(error),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ProjectTokens {
+            sources: self,
+            flavor,
+            tokens,
+        })
+    }
+
+    fn push(&mut self, path: impl Into<String>, source: String, run_on: Option<String>) {
+        self.files.push(SourceFile {
+            path: path.into(),
+            source,
+            run_on,
+        });
+    }
+}
+
+impl FileError<'_> {
+    /// Converts the error to a [`Diagnostic`], with the code of the lexer or parser error.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match &self.ty {
+            FileErrorType::Lexer(error) => error.to_diagnostic(),
+            FileErrorType::Parser { error, tokens } => error.to_diagnostic(tokens),
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
+    /// context using [`Diagnostic::display`], using the script's path as the file name.
+    pub fn display(&self) -> impl std::fmt::Display + '_ {
+        FileErrorDisplay {
+            diagnostic: self.to_diagnostic(),
+            file: self.file,
+        }
+    }
+}
+
+struct FileErrorDisplay<'a> {
+    diagnostic: Diagnostic,
+    file: &'a SourceFile,
+}
+
+impl std::fmt::Display for FileErrorDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic
+                .display(&self.file.source, Some(&self.file.path))
+        )
+    }
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::Io { path, error } => {
+                write!(f, "could not read `{}`: {error}", path.display())
+            }
+            ProjectError::MissingManifest { path } => write!(
+                f,
+                "no `mod.json` or `scripts.rson` found in `{}`",
+                path.display()
+            ),
+            ProjectError::InvalidManifest { path, message, .. } => {
+                write!(f, "{}: {message}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProjectError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<String, ProjectError> {
+    std::fs::read_to_string(path).map_err(|error| ProjectError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+fn invalid_manifest(path: &Path, message: String, range: Range<usize>) -> ProjectError {
+    ProjectError::InvalidManifest {
+        path: path.to_path_buf(),
+        message,
+        range,
+    }
+}
+
+// A message and range describing a problem in a manifest.
+type ManifestError = (String, Range<usize>);
+
+// Returns the path and `RunOn` condition of each script in a `mod.json` file.
+fn mod_json_scripts(manifest: &str) -> Result<Vec<(String, String)>, ManifestError> {
+    let tokens = rson::tokenize(manifest).map_err(|error| (error.ty.to_string(), error.range))?;
+    let value = rson::parse_value(&tokens).map_err(|error| (error.ty.to_string(), error.range))?;
+    let expected = |what: &str, value: &RsonValue| (format!("expected {what}"), value.range());
+
+    let object = value
+        .as_object()
+        .ok_or_else(|| expected("an object", &value))?;
+    let Some(scripts) = object.get("Scripts") else {
+        return Ok(Vec::new());
+    };
+    let scripts = scripts
+        .as_array()
+        .ok_or_else(|| expected("an array", scripts))?;
+
+    let mut paths = Vec::new();
+    for script in scripts.values() {
+        let entry = script
+            .as_object()
+            .ok_or_else(|| expected("an object", script))?;
+        let field = |name: &str| {
+            let value = entry
+                .get(name)
+                .ok_or_else(|| (format!("missing `{name}`"), script.range()))?;
+            value
+                .as_str()
+                .ok_or_else(|| expected("a string", value))
+                .map(|text| (text, value.range()))
+        };
+        let (path, _) = field("Path")?;
+        let (run_on, range) = field("RunOn")?;
+        if let Err(error) = parse_condition(run_on) {
+            // The condition is inside quotes.
+            let start = range.start + 1;
+            return Err((
+                "invalid `RunOn` condition".to_string(),
+                start + error.start..start + error.end,
+            ));
+        }
+        paths.push((path.to_string(), run_on.to_string()));
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ProjectError, ProjectSources};
+    use std::path::{Path, PathBuf};
+
+    // Creates a temporary directory containing files, which is removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let dir = std::env::temp_dir().join(format!("sqparse-{}-{name}", std::process::id()));
+            for (path, contents) in files {
+                let path = dir.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, contents).unwrap();
+            }
+            TempDir(dir)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn files(sources: &ProjectSources) -> Vec<(&str, Option<&str>)> {
+        (sources.files().iter())
+            .map(|file| (file.path.as_str(), file.run_on.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn load_mod_json() {
+        let dir = TempDir::new(
+            "mod-json",
+            &[
+                (
+                    "mod.json",
+                    r#"{
+                        "Name": "Example \"Mod\"",
+                        "Scripts": [
+                            { "Path": "sh_example.nut", "RunOn": "CLIENT || SERVER" },
+                            {
+                                "Path": "ui/menu.nut",
+                                "RunOn": "UI",
+                                "UICallback": { "Before": "InitMenu" }
+                            }
+                        ]
+                    }"#,
+                ),
+                ("mod/scripts/vscripts/sh_example.nut", "global function F"),
+                (
+                    "mod/scripts/vscripts/ui/menu.nut",
+                    "void function InitMenu() {}",
+                ),
+            ],
+        );
+        let sources = ProjectSources::load_mod(dir.path()).unwrap();
+        assert_eq!(
+            files(&sources),
+            [
+                ("sh_example.nut", Some("CLIENT || SERVER")),
+                ("ui/menu.nut", Some("UI")),
+            ]
+        );
+        assert_eq!(sources.files()[0].source, "global function F");
+    }
+
+    #[test]
+    fn load_scripts_rson() {
+        let dir = TempDir::new(
+            "scripts-rson",
+            &[
+                (
+                    "scripts/vscripts/scripts.rson",
+                    "When: \"SERVER\"\nScripts: [ sv.gnut ]\nWhen: \"CLIENT||UI\"\nScripts: [ cl.gnut ]",
+                ),
+                ("scripts/vscripts/sv.gnut", "a()"),
+                ("scripts/vscripts/cl.gnut", "b()"),
+            ],
+        );
+        let sources = ProjectSources::load_mod(dir.path()).unwrap();
+        assert_eq!(
+            files(&sources),
+            [
+                ("sv.gnut", Some("SERVER")),
+                ("cl.gnut", Some("CLIENT || UI"))
+            ]
+        );
+    }
+
+    #[test]
+    fn load_errors() {
+        let dir = TempDir::new("empty", &[("readme.txt", "")]);
+        let error = ProjectSources::load_mod(dir.path()).unwrap_err();
+        assert!(matches!(error, ProjectError::MissingManifest { .. }));
+
+        let dir = TempDir::new(
+            "missing-script",
+            &[(
+                "mod.json",
+                r#"{ "Scripts": [ { "Path": "a.nut", "RunOn": "SERVER" } ] }"#,
+            )],
+        );
+        let error = ProjectSources::load_mod(dir.path()).unwrap_err();
+        let ProjectError::Io { path, .. } = error else {
+            panic!("expected an io error");
+        };
+        assert!(path.ends_with("mod/scripts/vscripts/a.nut"));
+
+        let manifest = r#"{ "Scripts": [ { "Path": "a.nut", "RunOn": "SERVER &&& CLIENT" } ] }"#;
+        let dir = TempDir::new("bad-condition", &[("mod.json", manifest)]);
+        let error = ProjectSources::load_mod(dir.path()).unwrap_err();
+        let ProjectError::InvalidManifest { message, range, .. } = error else {
+            panic!("expected an invalid manifest error");
+        };
+        assert_eq!(message, "invalid `RunOn` condition");
+        assert_eq!(&manifest[range], "&");
+
+        let manifest = r#"{ "Scripts": [ { "RunOn": "SERVER" } ] }"#;
+        let dir = TempDir::new("missing-path", &[("mod.json", manifest)]);
+        let error = ProjectSources::load_mod(dir.path()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("{}: missing `Path`", dir.path().join("mod.json").display())
+        );
+
+        let mut sources = ProjectSources::new();
+        assert!(sources.add_file("a.nut", "", Some("SERVER ||")).is_err());
+        assert!(sources.files().is_empty());
+    }
+}
@@ -0,0 +1,482 @@
+use crate::analysis::{CallGraph, ConstEvaluator, TypeRegistry};
+use crate::ast::{Expression, GlobalDefinition, Identifier, Program, Statement, StatementType};
+use crate::preprocessor::parse_condition;
+use crate::project::{FileError, FileErrorType, ProjectTokens, SourceFile};
+use crate::{parse, Condition, TokenItem};
+
+/// A virtual machine that runs scripts.
+///
+/// Each VM compiles its own set of scripts, with a define of the same name set in the
+/// preprocessor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Vm {
+    Server,
+    Client,
+    Ui,
+}
+
+/// A parsed script in a [`Project`].
+#[derive(Debug, Clone)]
+pub struct ProjectFile<'s> {
+    /// The source of the script.
+    pub source: &'s SourceFile,
+
+    /// The tokens of the script.
+    pub tokens: &'s [TokenItem<'s>],
+
+    /// The parsed script.
+    pub program: Program<'s>,
+
+    /// The condition that decides which VMs load the script, or `None` if it is loaded in every VM.
+    pub run_on: Option<Condition<'s>>,
+}
+
+/// A set of parsed scripts that are loaded together, like the scripts of a mod.
+///
+/// Scripts are grouped into a [`CompilationUnit`] for each [`Vm`], which contains the scripts the
+/// VM loads in load order. Globals declared in a script are visible to the scripts loaded after it
+/// in the same VM.
+///
+/// Projects are created in stages, since syntax trees borrow their tokens and tokens borrow their
+/// source: the sources are loaded into a [`ProjectSources`], tokenized, and then parsed.
+///
+/// # Example
+/// ```
+/// use sqparse::Flavor;
+/// use sqparse::project::{Project, ProjectSources, Vm};
+///
+/// let mut sources = ProjectSources::new();
+/// sources
+///     .add_file("sh_util.nut", "global function Util\nvoid function Util() {}", None)
+///     .unwrap();
+/// sources
+///     .add_file("cl_foo.nut", "global function ClFoo\nvoid function ClFoo() { Util() }", Some("CLIENT"))
+///     .unwrap();
+/// sources
+///     .add_file("sv_foo.nut", "global function SvFoo\nvoid function SvFoo() {}", Some("SERVER"))
+///     .unwrap();
+///
+/// let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+/// let project = Project::new(&tokens).unwrap();
+///
+/// let client = project.unit(Vm::Client);
+/// let visible: Vec<_> = client
+///     .visible_globals("cl_foo.nut")
+///     .iter()
+///     .map(|global| global.name.value)
+///     .collect();
+/// assert_eq!(visible, ["Util", "ClFoo"]);
+/// ```
+///
+/// [`ProjectSources`]: crate::project::ProjectSources
+#[derive(Debug, Clone)]
+pub struct Project<'s> {
+    files: Vec<ProjectFile<'s>>,
+    defines: Vec<&'s str>,
+}
+
+/// The scripts that a [`Vm`] loads, in load order.
+#[derive(Debug, Clone)]
+pub struct CompilationUnit<'a> {
+    /// The VM that loads the scripts.
+    pub vm: Vm,
+
+    /// The defines that are set in the VM, used to decide which scripts load and which preprocessor
+    /// branches are active.
+    pub defines: Vec<&'a str>,
+
+    /// The scripts, in load order.
+    pub files: Vec<&'a ProjectFile<'a>>,
+}
+
+/// A kind of [`Global`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalKind {
+    /// A `global function` declaration, or a function definition in a script with
+    /// `globalize_all_functions`.
+    Function,
+
+    Var,
+    Const,
+    Enum,
+    Struct,
+    TypeDefinition,
+    Class,
+}
+
+/// A name that a script makes visible to other scripts.
+#[derive(Debug, Clone, Copy)]
+pub struct Global<'a> {
+    /// The name of the global.
+    pub name: &'a Identifier<'a>,
+
+    /// What kind of global the name is.
+    pub kind: GlobalKind,
+
+    /// The statement that declares the global.
+    pub statement: &'a Statement<'a>,
+
+    /// The script that declares the global.
+    pub file: &'a ProjectFile<'a>,
+}
+
+impl Vm {
+    /// Every VM.
+    pub const ALL: [Vm; 3] = [Vm::Server, Vm::Client, Vm::Ui];
+
+    /// Returns the preprocessor define that is set in the VM.
+    pub fn define(self) -> &'static str {
+        match self {
+            Vm::Server => "SERVER",
+            Vm::Client => "CLIENT",
+            Vm::Ui => "UI",
+        }
+    }
+}
+
+impl std::fmt::Display for Vm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.define())
+    }
+}
+
+impl<'s> ProjectFile<'s> {
+    /// Returns the path of the script.
+    pub fn path(&self) -> &'s str {
+        &self.source.path
+    }
+
+    /// Returns true if the script is loaded with a set of defines.
+    pub fn is_loaded(&self, defines: &[&str]) -> bool {
+        (self.run_on.as_ref()).is_none_or(|condition| condition.evaluate(defines))
+    }
+}
+
+impl<'s> Project<'s> {
+    /// Parses the tokens of every script in a project.
+    ///
+    /// Returns the first error if a script could not be parsed.
+    pub fn new(tokens: &'s ProjectTokens<'s>) -> Result<Self, FileError<'s>> {
+        let flavor = tokens.flavor;
+        let files = (tokens.sources.files().iter())
+            .zip(&tokens.tokens)
+            .map(|(source, tokens)| {
+                let program = parse(tokens, flavor).map_err(|error| FileError {
+                    file: source,
+                    ty: FileErrorType::Parser { error, tokens },
+                })?;
+                let run_on = (source.run_on.as_deref())
+                    .map(|run_on| parse_condition(run_on).expect("conditions are validated"));
+                Ok(ProjectFile {
+                    source,
+                    tokens,
+                    program,
+                    run_on,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Project {
+            files,
+            defines: Vec::new(),
+        })
+    }
+
+    /// Returns every script in the project, in load order.
+    pub fn files(&self) -> &[ProjectFile<'s>] {
+        &self.files
+    }
+
+    /// Returns the script with a path.
+    pub fn file(&self, path: &str) -> Option<&ProjectFile<'s>> {
+        self.files.iter().find(|file| file.path() == path)
+    }
+
+    /// Sets extra defines that are set in every VM, like `MP` or `DEV`.
+    pub fn set_defines(&mut self, defines: &[&'s str]) {
+        self.defines = defines.to_vec();
+    }
+
+    /// Returns the scripts that a VM loads.
+    pub fn unit(&self, vm: Vm) -> CompilationUnit<'_> {
+        let mut defines = vec![vm.define()];
+        defines.extend(&self.defines);
+        let files = (self.files.iter())
+            .filter(|file| file.is_loaded(&defines))
+            .collect();
+        CompilationUnit { vm, defines, files }
+    }
+
+    /// Returns the scripts that each VM loads.
+    pub fn units(&self) -> impl Iterator<Item = CompilationUnit<'_>> {
+        Vm::ALL.into_iter().map(|vm| self.unit(vm))
+    }
+}
+
+impl<'a> CompilationUnit<'a> {
+    /// Returns the script with a path, if it is loaded in the VM.
+    pub fn file(&self, path: &str) -> Option<&'a ProjectFile<'a>> {
+        self.files.iter().copied().find(|file| file.path() == path)
+    }
+
+    /// Returns the position of a script in the load order, if it is loaded in the VM.
+    pub fn load_index(&self, path: &str) -> Option<usize> {
+        self.files.iter().position(|file| file.path() == path)
+    }
+
+    /// Returns the globals declared by every script, in load order.
+    pub fn globals(&self) -> Vec<Global<'a>> {
+        (self.files.iter())
+            .flat_map(|file| self.file_globals(file))
+            .collect()
+    }
+
+    /// Returns the globals declared by a script.
+    ///
+    /// Only declarations in preprocessor branches that are active in the VM are included.
+    pub fn file_globals(&self, file: &'a ProjectFile<'a>) -> Vec<Global<'a>> {
+        let mut statements = Vec::new();
+        active_statements(&file.program.statements, &self.defines, &mut statements);
+        let globalize_all_functions = (statements.iter())
+            .any(|statement| matches!(statement.ty, StatementType::GlobalizeAllFunctions(_)));
+
+        let mut globals = Vec::new();
+        for statement in statements {
+            let mut push = |name, kind| {
+                globals.push(Global {
+                    name,
+                    kind,
+                    statement,
+                    file,
+                })
+            };
+            match &statement.ty {
+                StatementType::FunctionDefinition(function)
+                    if globalize_all_functions && function.name.items.is_empty() =>
+                {
+                    push(&function.name.last_item, GlobalKind::Function);
+                }
+                StatementType::Global(global) => match &global.definition {
+                    GlobalDefinition::Function { name, .. } => push(name, GlobalKind::Function),
+                    GlobalDefinition::UntypedVar { name, .. } => push(name, GlobalKind::Var),
+                    GlobalDefinition::TypedVar(definition) => {
+                        for definition in definition.definitions.iter() {
+                            push(&definition.name, GlobalKind::Var);
+                        }
+                    }
+                    GlobalDefinition::Const(definition) => {
+                        push(&definition.name, GlobalKind::Const)
+                    }
+                    GlobalDefinition::Enum(definition) => push(&definition.name, GlobalKind::Enum),
+                    GlobalDefinition::Struct(definition) => {
+                        push(&definition.name, GlobalKind::Struct)
+                    }
+                    GlobalDefinition::Type(definition) => {
+                        push(&definition.name, GlobalKind::TypeDefinition)
+                    }
+                    GlobalDefinition::Class(definition) => {
+                        if let Expression::Var(var) = &*definition.name {
+                            push(&var.name, GlobalKind::Class);
+                        }
+                    }
+                },
+                _ => {}
+            }
+        }
+        globals
+    }
+
+    /// Returns the globals that are visible to a script when it loads.
+    ///
+    /// These are the globals declared by the scripts loaded before it in the VM, and by the script
+    /// itself. Returns an empty list if the script is not loaded in the VM.
+    pub fn visible_globals(&self, path: &str) -> Vec<Global<'a>> {
+        let Some(index) = self.load_index(path) else {
+            return Vec::new();
+        };
+        (self.files[..=index].iter())
+            .flat_map(|file| self.file_globals(file))
+            .collect()
+    }
+
+    /// Returns a registry of the types declared in every script.
+    pub fn types(&self) -> TypeRegistry<'a> {
+        let mut types = TypeRegistry::default();
+        for file in &self.files {
+            types.add_program(&file.program);
+        }
+        types
+    }
+
+    /// Returns an evaluator for the constants and enums declared in every script.
+    pub fn consts(&self) -> ConstEvaluator<'a> {
+        let mut consts = ConstEvaluator::default();
+        for file in &self.files {
+            consts.add_program(&file.program);
+        }
+        consts
+    }
+
+    /// Returns the call graph of every script.
+    ///
+    /// The programs in the graph are identified by their index in the load order.
+    pub fn call_graph(&self) -> CallGraph<'a> {
+        CallGraph::from_programs(self.files.iter().map(|file| &file.program))
+    }
+}
+
+// Collects top-level statements, replacing preprocessor blocks with their active statements.
+pub(crate) fn active_statements<'a>(
+    statements: &'a [Statement<'a>],
+    defines: &[&str],
+    active: &mut Vec<&'a Statement<'a>>,
+) {
+    for statement in statements {
+        match &statement.ty {
+            StatementType::Conditional(conditional) => {
+                active_statements(conditional.active_statements(defines), defines, active)
+            }
+            _ => active.push(statement),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Global, GlobalKind, Project, Vm};
+    use crate::analysis::ConstValue;
+    use crate::project::{FileErrorType, ProjectSources};
+    use crate::Flavor;
+
+    fn sources(files: &[(&str, &str, Option<&str>)]) -> ProjectSources {
+        let mut sources = ProjectSources::new();
+        for &(path, source, run_on) in files {
+            sources.add_file(path, source, run_on).unwrap();
+        }
+        sources
+    }
+
+    #[test]
+    fn units_by_vm() {
+        let sources = sources(&[
+            ("sh.nut", "", Some("SERVER || CLIENT")),
+            ("sv.nut", "", Some("SERVER")),
+            ("cl.nut", "", Some("CLIENT")),
+            ("ui.nut", "", Some("UI")),
+            ("all.nut", "", None),
+            ("mp.nut", "", Some("SERVER && MP")),
+        ]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let mut project = Project::new(&tokens).unwrap();
+
+        fn paths<'a>(project: &'a Project<'a>, vm: Vm) -> Vec<&'a str> {
+            let unit = project.unit(vm);
+            unit.files.iter().map(|file| file.path()).collect()
+        }
+        assert_eq!(paths(&project, Vm::Server), ["sh.nut", "sv.nut", "all.nut"]);
+        assert_eq!(paths(&project, Vm::Client), ["sh.nut", "cl.nut", "all.nut"]);
+        assert_eq!(paths(&project, Vm::Ui), ["ui.nut", "all.nut"]);
+
+        project.set_defines(&["MP"]);
+        assert_eq!(
+            paths(&project, Vm::Server),
+            ["sh.nut", "sv.nut", "all.nut", "mp.nut"]
+        );
+        assert_eq!(project.unit(Vm::Server).load_index("all.nut"), Some(2));
+        assert_eq!(project.unit(Vm::Ui).load_index("sv.nut"), None);
+        assert_eq!(project.units().count(), 3);
+    }
+
+    #[test]
+    fn globals() {
+        let sources = sources(&[
+            (
+                "sh_a.nut",
+                r#"
+                global function A
+                global const int LIMIT = 1
+                global struct Data { int a }
+                #if SERVER
+                global function ServerOnly
+                #else
+                global function OtherVms
+                #endif
+                void function A() {}
+                "#,
+                None,
+            ),
+            (
+                "sh_b.nut",
+                r#"
+                globalize_all_functions
+                void function B() {}
+                void function Class::Method() {}
+                global enum Team { A }
+                global typedef Ids array<int>
+                global var x = 1
+                global int y, z
+                "#,
+                None,
+            ),
+        ]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let project = Project::new(&tokens).unwrap();
+
+        fn names<'a>(globals: Vec<Global<'a>>) -> Vec<(&'a str, GlobalKind, &'a str)> {
+            (globals.iter())
+                .map(|global| (global.name.value, global.kind, global.file.path()))
+                .collect()
+        }
+
+        let server = project.unit(Vm::Server);
+        assert_eq!(
+            names(server.visible_globals("sh_a.nut")),
+            [
+                ("A", GlobalKind::Function, "sh_a.nut"),
+                ("LIMIT", GlobalKind::Const, "sh_a.nut"),
+                ("Data", GlobalKind::Struct, "sh_a.nut"),
+                ("ServerOnly", GlobalKind::Function, "sh_a.nut"),
+            ]
+        );
+
+        let client = project.unit(Vm::Client);
+        let client_globals = names(client.globals());
+        assert_eq!(
+            client_globals[3],
+            ("OtherVms", GlobalKind::Function, "sh_a.nut")
+        );
+        assert_eq!(
+            client_globals[4..],
+            [
+                ("B", GlobalKind::Function, "sh_b.nut"),
+                ("Team", GlobalKind::Enum, "sh_b.nut"),
+                ("Ids", GlobalKind::TypeDefinition, "sh_b.nut"),
+                ("x", GlobalKind::Var, "sh_b.nut"),
+                ("y", GlobalKind::Var, "sh_b.nut"),
+                ("z", GlobalKind::Var, "sh_b.nut"),
+            ]
+        );
+        assert!(client.visible_globals("missing.nut").is_empty());
+
+        let types = client.types();
+        assert_eq!(types.resolve_name("Ids").to_string(), "array<int>");
+        assert_eq!(types.struct_properties("Data").unwrap().len(), 1);
+        let consts = client.consts();
+        assert_eq!(consts.const_value("LIMIT"), Some(Ok(ConstValue::Int(1))));
+        assert_eq!(consts.enum_value("Team", "A"), Some(Ok(ConstValue::Int(0))));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let sources = sources(&[("ok.nut", "a()", None), ("bad.nut", "a(", None)]);
+        let error = sources.tokenize(Flavor::SquirrelRespawn).unwrap_err();
+        assert_eq!(error.file.path, "bad.nut");
+
+        let sources = self::sources(&[("ok.nut", "a()", None), ("bad.nut", "a b c", None)]);
+        let tokens = sources.tokenize(Flavor::SquirrelRespawn).unwrap();
+        let error = Project::new(&tokens).unwrap_err();
+        assert_eq!(error.file.path, "bad.nut");
+        assert!(matches!(error.ty, FileErrorType::Parser { .. }));
+        yansi::Paint::disable();
+        assert!(error.display().to_string().contains("bad.nut"));
+    }
+}
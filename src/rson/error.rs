@@ -0,0 +1,216 @@
+use crate::annotation::{Diagnostic, Label, Level};
+use crate::rson::RsonTokenType;
+use std::ops::Range;
+
+/// Type of [`RsonError`].
+///
+/// Implements [`std::fmt::Display`] to write a useful error message.
+#[derive(Debug, Clone, Copy)]
+pub enum RsonErrorType<'s> {
+    /// A string is missing its closing `"` before the end of the line.
+    ///
+    /// # Example
+    /// ```text
+    /// When: "SERVER
+    ///       ^^^^^^^ error
+    /// ```
+    UnterminatedString,
+
+    /// Expected a key, but found something else.
+    ///
+    /// # Example
+    /// ```text
+    /// { [ a ]: b }
+    ///   ^ error
+    /// ```
+    ExpectedKey(RsonTokenType<'s>),
+
+    /// Expected a `:` after a key.
+    ///
+    /// # Example
+    /// ```text
+    /// When "SERVER"
+    ///      ^^^^^^^^ error
+    /// ```
+    ExpectedColon(RsonTokenType<'s>),
+
+    /// Expected a value, but found something else.
+    ///
+    /// # Example
+    /// ```text
+    /// Scripts: ]
+    ///          ^ error
+    /// ```
+    ExpectedValue(RsonTokenType<'s>),
+
+    /// A single value was parsed, but there is more input after it.
+    ///
+    /// # Example
+    /// ```text
+    /// { "Name": "Example" } }
+    ///                       ^ error
+    /// ```
+    ExpectedEnd(RsonTokenType<'s>),
+
+    /// An opening `{` or `[` was not matched by a closing delimiter.
+    ///
+    /// # Example
+    /// ```text
+    /// Scripts: [ a.nut
+    ///          ^ error
+    /// ```
+    UnmatchedOpener {
+        open: RsonTokenType<'s>,
+        close: RsonTokenType<'s>,
+    },
+
+    /// A value in a `scripts.rson` file was expected to be a string.
+    ///
+    /// # Example
+    /// ```text
+    /// When: [ SERVER ]
+    ///       ^^^^^^^^^^ error
+    /// ```
+    ExpectedString,
+
+    /// A value in a `scripts.rson` file was expected to be an array.
+    ///
+    /// # Example
+    /// ```text
+    /// Scripts: sv_main.gnut
+    ///          ^^^^^^^^^^^^ error
+    /// ```
+    ExpectedArray,
+
+    /// A `When` condition in a `scripts.rson` file is not formed correctly.
+    ///
+    /// # Example
+    /// ```text
+    /// When: "SERVER &&"
+    ///                 ^ error
+    /// ```
+    InvalidCondition,
+}
+
+/// An error emitted while reading an RSON file.
+///
+/// Each error has a type with more information, and a range indicating where in the source string
+/// the error occurred.
+#[derive(Debug, Clone)]
+pub struct RsonError<'s> {
+    /// The type of error.
+    pub ty: RsonErrorType<'s>,
+
+    /// The character range of where the error occurred.
+    pub range: Range<usize>,
+}
+
+impl<'s> RsonError<'s> {
+    /// Creates a new `RsonError`.
+    pub fn new(ty: RsonErrorType<'s>, range: Range<usize>) -> Self {
+        RsonError { ty, range }
+    }
+
+    /// Converts the error to a [`Diagnostic`], with the error type's [`code`].
+    ///
+    /// [`code`]: RsonErrorType::code
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let label = Label::new(self.range.clone(), self.ty.inline_display().to_string());
+        Diagnostic::new(Level::Error, self.ty.to_string(), label).with_code(self.ty.code())
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that pretty-prints the error with source
+    /// context using [`Diagnostic::display`].
+    pub fn display<'a>(
+        &'a self,
+        source: &'a str,
+        file_name: Option<&'a str>,
+    ) -> impl std::fmt::Display + 'a {
+        Display {
+            diagnostic: self.to_diagnostic(),
+            source,
+            file_name,
+        }
+    }
+}
+
+impl<'a> RsonErrorType<'a> {
+    /// Returns a stable code for the error type, from `E0801` to `E0899`.
+    ///
+    /// Codes are never reused for a different type of error.
+    pub fn code(self) -> &'static str {
+        match self {
+            RsonErrorType::UnterminatedString => "E0801",
+            RsonErrorType::ExpectedKey(_) => "E0802",
+            RsonErrorType::ExpectedColon(_) => "E0803",
+            RsonErrorType::ExpectedValue(_) => "E0804",
+            RsonErrorType::ExpectedEnd(_) => "E0805",
+            RsonErrorType::UnmatchedOpener { .. } => "E0806",
+            RsonErrorType::ExpectedString => "E0807",
+            RsonErrorType::ExpectedArray => "E0808",
+            RsonErrorType::InvalidCondition => "E0809",
+        }
+    }
+
+    /// Returns an implementation of [`std::fmt::Display`] that writes a short message to show
+    /// inline with the source code.
+    pub fn inline_display(self) -> impl std::fmt::Display + 'a {
+        RsonErrorInlineDisplay(self)
+    }
+}
+
+impl std::fmt::Display for RsonErrorType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RsonErrorType::UnterminatedString => write!(f, "strings cannot span multiple lines"),
+            RsonErrorType::ExpectedKey(found) => write!(f, "expected a key, found {found}"),
+            RsonErrorType::ExpectedColon(found) => write!(f, "expected `:`, found {found}"),
+            RsonErrorType::ExpectedValue(found) => write!(f, "expected a value, found {found}"),
+            RsonErrorType::ExpectedEnd(found) => {
+                write!(f, "expected end of input, found {found}")
+            }
+            RsonErrorType::UnmatchedOpener { open, .. } => {
+                write!(f, "unclosed delimiter {open}")
+            }
+            RsonErrorType::ExpectedString => write!(f, "expected a string"),
+            RsonErrorType::ExpectedArray => write!(f, "expected an array"),
+            RsonErrorType::InvalidCondition => write!(f, "invalid `When` condition"),
+        }
+    }
+}
+
+struct RsonErrorInlineDisplay<'a>(RsonErrorType<'a>);
+
+impl std::fmt::Display for RsonErrorInlineDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            RsonErrorType::UnterminatedString => write!(f, "help: add a `\"`"),
+            RsonErrorType::ExpectedKey(_) => write!(f, "not a key"),
+            RsonErrorType::ExpectedColon(_) => write!(f, "help: add a `:` before this"),
+            RsonErrorType::ExpectedValue(_) => write!(f, "not a value"),
+            RsonErrorType::ExpectedEnd(_) => write!(f, "unexpected input"),
+            RsonErrorType::UnmatchedOpener { close, .. } => {
+                write!(f, "does not have a matching {close}")
+            }
+            RsonErrorType::ExpectedString => write!(f, "not a string"),
+            RsonErrorType::ExpectedArray => write!(f, "not an array"),
+            RsonErrorType::InvalidCondition => write!(f, "not valid here"),
+        }
+    }
+}
+
+struct Display<'a> {
+    diagnostic: Diagnostic,
+    source: &'a str,
+    file_name: Option<&'a str>,
+}
+
+impl std::fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.diagnostic.display(self.source, self.file_name)
+        )
+    }
+}
@@ -0,0 +1,213 @@
+use crate::lexer::comment::try_comment;
+use crate::lexer::parse_str::ParseStr;
+use crate::rson::{RsonError, RsonErrorType, RsonToken, RsonTokenType};
+use crate::token::TokenLine;
+
+/// Parses an RSON input string into a list of tokens.
+///
+/// Comments are the same as in Squirrel, and are attached to tokens in the same way.
+///
+/// # Example
+/// ```
+/// use sqparse::rson::{tokenize, RsonTokenType};
+///
+/// let source = r#"
+/// When: "SERVER" // server only
+/// Scripts: [ sv_main.gnut ]
+/// "#;
+/// let tokens = tokenize(source).unwrap();
+///
+/// assert_eq!(tokens.len(), 8);
+/// assert_eq!(tokens[2].ty, RsonTokenType::String("SERVER"));
+/// assert_eq!(tokens[6].ty, RsonTokenType::Word("sv_main.gnut"));
+/// ```
+pub fn tokenize(source: &str) -> Result<Vec<RsonToken<'_>>, RsonError<'_>> {
+    let mut val = ParseStr::new(source);
+    let mut tokens = Vec::new();
+    let mut current_token: Option<RsonToken> = None;
+    let mut before_lines = Vec::new();
+    let mut current_line_comments = Vec::new();
+
+    loop {
+        // Note that trim_start does not trim newlines.
+        val = val.trim_start();
+        if val.is_ended() {
+            break;
+        }
+
+        if let Some(remaining) = val.strip_prefix("\n") {
+            val = remaining;
+            let line = TokenLine {
+                comments: std::mem::take(&mut current_line_comments),
+            };
+
+            // A newline ends the current token, otherwise the line is before the next token.
+            match current_token.take() {
+                Some(mut token) => {
+                    token.new_line = Some(line);
+                    tokens.push(token);
+                }
+                None => before_lines.push(line),
+            }
+        } else if let Ok(Some((comment, remaining))) = try_comment(val) {
+            val = remaining;
+            current_line_comments.push(comment);
+        } else {
+            let (ty, remaining) = token_ty(val)?;
+            let token = RsonToken {
+                ty,
+                range: val.start_offset()..remaining.start_offset(),
+                before_lines: std::mem::take(&mut before_lines),
+                comments: std::mem::take(&mut current_line_comments),
+                new_line: None,
+            };
+            val = remaining;
+            tokens.extend(current_token.replace(token));
+        }
+    }
+
+    tokens.extend(current_token);
+    if !current_line_comments.is_empty() || !before_lines.is_empty() {
+        tokens.push(RsonToken {
+            ty: RsonTokenType::Empty,
+            range: val.end_offset()..val.end_offset(),
+            before_lines,
+            comments: current_line_comments,
+            new_line: None,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn token_ty(val: ParseStr) -> Result<(RsonTokenType, ParseStr), RsonError> {
+    let symbol = match val.as_str().as_bytes()[0] {
+        b'{' => Some(RsonTokenType::OpenBrace),
+        b'}' => Some(RsonTokenType::CloseBrace),
+        b'[' => Some(RsonTokenType::OpenSquare),
+        b']' => Some(RsonTokenType::CloseSquare),
+        b':' => Some(RsonTokenType::Colon),
+        b',' => Some(RsonTokenType::Comma),
+        _ => None,
+    };
+    if let Some(symbol) = symbol {
+        return Ok((symbol, val.from(1)));
+    }
+
+    if let Some(contents) = val.strip_prefix("\"") {
+        // Skip over escaped characters, so `\"` does not end the string.
+        let mut chars = contents.as_str().char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((_, '\\')) => {
+                    chars.next();
+                }
+                Some((index, c @ ('"' | '\n'))) => break Some((index, c)),
+                Some(_) => {}
+                None => break None,
+            }
+        };
+        return match end {
+            Some((end, '"')) => {
+                let (value, remaining) = contents.split_at(end);
+                Ok((RsonTokenType::String(value), remaining.from(1)))
+            }
+            _ => {
+                let end_offset = contents.split_at(end.map(|(end, _)| end)).1.start_offset();
+                Err(RsonError::new(
+                    RsonErrorType::UnterminatedString,
+                    val.start_offset()..end_offset,
+                ))
+            }
+        };
+    }
+
+    let end = val.as_str().char_indices().find(|&(index, c)| {
+        c.is_whitespace()
+            || matches!(c, '{' | '}' | '[' | ']' | ':' | ',' | '"')
+            || matches!(try_comment(val.from(index)), Ok(Some(_)))
+    });
+    let (word, remaining) = val.split_at(end.map(|(index, _)| index));
+    Ok((RsonTokenType::Word(word), remaining))
+}
+
+#[cfg(test)]
+mod test {
+    use super::tokenize;
+    use crate::rson::{RsonErrorType, RsonTokenType};
+    use crate::token::{Comment, TokenLine};
+
+    #[test]
+    fn symbols_and_values() {
+        let tokens = tokenize("{ a: \"b c\", list: [x/y.nut,z] }").unwrap();
+        let types: Vec<_> = tokens.iter().map(|token| token.ty).collect();
+        assert_eq!(
+            types,
+            vec![
+                RsonTokenType::OpenBrace,
+                RsonTokenType::Word("a"),
+                RsonTokenType::Colon,
+                RsonTokenType::String("b c"),
+                RsonTokenType::Comma,
+                RsonTokenType::Word("list"),
+                RsonTokenType::Colon,
+                RsonTokenType::OpenSquare,
+                RsonTokenType::Word("x/y.nut"),
+                RsonTokenType::Comma,
+                RsonTokenType::Word("z"),
+                RsonTokenType::CloseSquare,
+                RsonTokenType::CloseBrace,
+            ]
+        );
+        assert_eq!(tokens[3].range, 5..10);
+    }
+
+    #[test]
+    fn comments() {
+        let tokens = tokenize("// header\n\n/* a */ a // after a\nb/*c*/\n// end").unwrap();
+        assert_eq!(tokens.len(), 3);
+
+        assert_eq!(
+            tokens[0].before_lines,
+            vec![
+                TokenLine {
+                    comments: vec![Comment::SingleLine(" header")]
+                },
+                TokenLine { comments: vec![] },
+            ]
+        );
+        assert_eq!(tokens[0].comments, vec![Comment::MultiLine(" a ")]);
+        assert_eq!(
+            tokens[0].new_line,
+            Some(TokenLine {
+                comments: vec![Comment::SingleLine(" after a")]
+            })
+        );
+
+        assert_eq!(tokens[1].ty, RsonTokenType::Word("b"));
+        assert_eq!(
+            tokens[1].new_line,
+            Some(TokenLine {
+                comments: vec![Comment::MultiLine("c")]
+            })
+        );
+
+        assert_eq!(tokens[2].ty, RsonTokenType::Empty);
+        assert_eq!(tokens[2].comments, vec![Comment::SingleLine(" end")]);
+    }
+
+    #[test]
+    fn escaped_quotes() {
+        let tokens = tokenize(r#"a: "say \"hi\"\\" b"#).unwrap();
+        assert_eq!(tokens[2].ty, RsonTokenType::String(r#"say \"hi\"\\"#));
+        assert_eq!(tokens[3].ty, RsonTokenType::Word("b"));
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let source = "When: \"SERVER\nScripts: []";
+        let error = tokenize(source).unwrap_err();
+        assert!(matches!(error.ty, RsonErrorType::UnterminatedString));
+        assert_eq!(&source[error.range], "\"SERVER");
+    }
+}
@@ -0,0 +1,48 @@
+//! Reader for Respawn's RSON files, like `scripts.rson`.
+//!
+//! RSON is a loose JSON-like format. Files are a list of `key: value` entries, where values are
+//! strings, `{ ... }` objects of more entries, or `[ ... ]` arrays. Strings can be quoted or
+//! unquoted, and commas between entries and values are optional.
+//!
+//! Like the Squirrel parser, reading is split into two stages: [`tokenize`] produces
+//! source-preserving [`RsonToken`]s that own their comments, and [`parse`] builds an
+//! [`RsonDocument`] tree that references the tokens. JSON files like a Northstar `mod.json` can be
+//! read with [`parse_value`].
+//!
+//! [`ScriptsRson`] is a typed view of the `When` and `Scripts` blocks in a `scripts.rson` file,
+//! which decide which scripts each VM loads.
+//!
+//! # Example
+//! ```
+//! use sqparse::rson::{parse, tokenize, ScriptsRson};
+//!
+//! let source = r#"
+//! // Scripts for the example mod.
+//! When: "SERVER && MP"
+//! Scripts:
+//! [
+//!     mp/sv_example.gnut
+//! ]
+//! "#;
+//! let tokens = tokenize(source).unwrap();
+//! let document = parse(&tokens).unwrap();
+//! let scripts = ScriptsRson::new(&document).unwrap();
+//!
+//! let mp_server: Vec<_> = scripts.scripts(&["SERVER", "MP"]).collect();
+//! assert_eq!(mp_server, ["mp/sv_example.gnut"]);
+//! assert_eq!(scripts.scripts(&["SERVER"]).count(), 0);
+//! ```
+
+mod error;
+mod lexer;
+mod parser;
+mod scripts;
+mod token;
+mod tree;
+
+pub use self::error::{RsonError, RsonErrorType};
+pub use self::lexer::tokenize;
+pub use self::parser::{parse, parse_value};
+pub use self::scripts::{ScriptsBlock, ScriptsRson};
+pub use self::token::{RsonToken, RsonTokenType};
+pub use self::tree::*;
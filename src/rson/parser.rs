@@ -0,0 +1,263 @@
+use crate::rson::{
+    RsonArray, RsonArrayItem, RsonDocument, RsonEntry, RsonError, RsonErrorType, RsonObject,
+    RsonToken, RsonTokenType, RsonValue,
+};
+
+/// Parses a list of RSON tokens into a document.
+///
+/// # Example
+/// ```
+/// use sqparse::rson::{parse, tokenize};
+///
+/// let source = r#"
+/// Name: "Example"
+/// Settings: { enabled: true, tags: [ a b, "c" ] }
+/// "#;
+/// let tokens = tokenize(source).unwrap();
+/// let document = parse(&tokens).unwrap();
+///
+/// assert_eq!(document.get("Name").unwrap().as_str(), Some("Example"));
+///
+/// let settings = document.get("Settings").unwrap().as_object().unwrap();
+/// let tags = settings.get("tags").unwrap().as_array().unwrap();
+/// let tags: Vec<_> = tags.values().map(|tag| tag.as_str().unwrap()).collect();
+/// assert_eq!(tags, ["a", "b", "c"]);
+/// ```
+pub fn parse<'s>(tokens: &'s [RsonToken<'s>]) -> Result<RsonDocument<'s>, RsonError<'s>> {
+    let mut parser = Parser { tokens, index: 0 };
+    let mut entries = Vec::new();
+    while parser.peek().is_some() {
+        entries.push(parser.entry()?);
+    }
+    let end = parser.next_if(|ty| ty == RsonTokenType::Empty);
+    Ok(RsonDocument { entries, end })
+}
+
+/// Parses a list of RSON tokens containing a single value, like a JSON file.
+///
+/// # Example
+/// ```
+/// use sqparse::rson::{parse_value, tokenize};
+///
+/// let source = r#"{ "Name": "Example", "LoadPriority": 1 }"#;
+/// let tokens = tokenize(source).unwrap();
+/// let value = parse_value(&tokens).unwrap();
+///
+/// let object = value.as_object().unwrap();
+/// assert_eq!(object.get("LoadPriority").unwrap().as_str(), Some("1"));
+/// ```
+pub fn parse_value<'s>(tokens: &'s [RsonToken<'s>]) -> Result<RsonValue<'s>, RsonError<'s>> {
+    let mut parser = Parser { tokens, index: 0 };
+    let value = parser.value()?;
+    match parser.peek() {
+        Some(token) => Err(RsonError::new(
+            RsonErrorType::ExpectedEnd(token.ty),
+            token.range.clone(),
+        )),
+        None => Ok(value),
+    }
+}
+
+struct Parser<'s> {
+    tokens: &'s [RsonToken<'s>],
+    index: usize,
+}
+
+impl<'s> Parser<'s> {
+    // Returns the next token, treating the empty token at the end of input as the end.
+    fn peek(&self) -> Option<&'s RsonToken<'s>> {
+        let tokens: &'s [RsonToken<'s>] = self.tokens;
+        tokens
+            .get(self.index)
+            .filter(|token| token.ty != RsonTokenType::Empty)
+    }
+
+    fn next_if(
+        &mut self,
+        is_match: impl Fn(RsonTokenType<'s>) -> bool,
+    ) -> Option<&'s RsonToken<'s>> {
+        let tokens: &'s [RsonToken<'s>] = self.tokens;
+        let token = tokens.get(self.index).filter(|token| is_match(token.ty))?;
+        self.index += 1;
+        Some(token)
+    }
+
+    // Returns an error with the range of the next token, or the end of the input.
+    fn error(&self, ty: impl FnOnce(RsonTokenType<'s>) -> RsonErrorType<'s>) -> RsonError<'s> {
+        match self.peek() {
+            Some(token) => RsonError::new(ty(token.ty), token.range.clone()),
+            None => {
+                let end = self.tokens.last().map_or(0, |token| token.range.end);
+                RsonError::new(ty(RsonTokenType::Empty), end..end)
+            }
+        }
+    }
+
+    fn entry(&mut self) -> Result<RsonEntry<'s>, RsonError<'s>> {
+        let key = self
+            .next_if(|ty| ty.value().is_some())
+            .ok_or_else(|| self.error(RsonErrorType::ExpectedKey))?;
+        let colon = self
+            .next_if(|ty| ty == RsonTokenType::Colon)
+            .ok_or_else(|| self.error(RsonErrorType::ExpectedColon))?;
+        let value = self.value()?;
+        let comma = self.next_if(|ty| ty == RsonTokenType::Comma);
+        Ok(RsonEntry {
+            key,
+            colon,
+            value,
+            comma,
+        })
+    }
+
+    fn value(&mut self) -> Result<RsonValue<'s>, RsonError<'s>> {
+        if let Some(token) = self.next_if(|ty| ty.value().is_some()) {
+            return Ok(RsonValue::String(token));
+        }
+
+        if let Some(open) = self.next_if(|ty| ty == RsonTokenType::OpenBrace) {
+            let mut entries = Vec::new();
+            let close = loop {
+                if let Some(close) = self.next_if(|ty| ty == RsonTokenType::CloseBrace) {
+                    break close;
+                }
+                if self.peek().is_none() {
+                    return Err(unmatched(open, RsonTokenType::CloseBrace));
+                }
+                entries.push(self.entry()?);
+            };
+            return Ok(RsonValue::Object(RsonObject {
+                open,
+                entries,
+                close,
+            }));
+        }
+
+        if let Some(open) = self.next_if(|ty| ty == RsonTokenType::OpenSquare) {
+            let mut items = Vec::new();
+            let close = loop {
+                if let Some(close) = self.next_if(|ty| ty == RsonTokenType::CloseSquare) {
+                    break close;
+                }
+                if self.peek().is_none() {
+                    return Err(unmatched(open, RsonTokenType::CloseSquare));
+                }
+                let value = self.value()?;
+                let comma = self.next_if(|ty| ty == RsonTokenType::Comma);
+                items.push(RsonArrayItem { value, comma });
+            };
+            return Ok(RsonValue::Array(RsonArray { open, items, close }));
+        }
+
+        Err(self.error(RsonErrorType::ExpectedValue))
+    }
+}
+
+fn unmatched<'s>(open: &'s RsonToken<'s>, close: RsonTokenType<'s>) -> RsonError<'s> {
+    let error = RsonErrorType::UnmatchedOpener {
+        open: open.ty,
+        close,
+    };
+    RsonError::new(error, open.range.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, parse_value};
+    use crate::rson::{tokenize, RsonErrorType, RsonValue};
+    use crate::token::Comment;
+
+    fn parse_error(source: &str) -> (String, &str) {
+        let tokens = tokenize(source).unwrap();
+        let error = parse(&tokens).unwrap_err();
+        (error.ty.to_string(), &source[error.range])
+    }
+
+    #[test]
+    fn nested_values() {
+        let source = "a: { b: [ 1, [ 2 ], { c: d } ], \"quoted key\": x }, e: f";
+        let tokens = tokenize(source).unwrap();
+        let document = parse(&tokens).unwrap();
+
+        assert_eq!(document.entries.len(), 2);
+        assert!(document.entries[0].comma.is_some());
+        assert!(document.end.is_none());
+
+        let a = document.get("a").unwrap().as_object().unwrap();
+        assert_eq!(a.get("quoted key").unwrap().as_str(), Some("x"));
+        let b = a.get("b").unwrap();
+        assert_eq!(&source[b.range()], "[ 1, [ 2 ], { c: d } ]");
+
+        let items: Vec<_> = b.as_array().unwrap().values().collect();
+        assert_eq!(items[0].as_str(), Some("1"));
+        assert!(matches!(items[1], RsonValue::Array(_)));
+        assert_eq!(
+            items[2].as_object().unwrap().get("c").unwrap().as_str(),
+            Some("d")
+        );
+        assert_eq!(document.get("e").unwrap().as_str(), Some("f"));
+        assert!(document.get("missing").is_none());
+    }
+
+    #[test]
+    fn preserves_comments() {
+        let source = "// header\nkey: value // trailing\n\n// footer\n";
+        let tokens = tokenize(source).unwrap();
+        let document = parse(&tokens).unwrap();
+
+        let entry = &document.entries[0];
+        assert_eq!(
+            entry.key.before_lines[0].comments,
+            vec![Comment::SingleLine(" header")]
+        );
+        let RsonValue::String(value) = &entry.value else {
+            panic!("expected a string");
+        };
+        assert_eq!(
+            value.new_line.as_ref().unwrap().comments,
+            vec![Comment::SingleLine(" trailing")]
+        );
+        let end = document.end.unwrap();
+        assert_eq!(
+            end.before_lines[1].comments,
+            vec![Comment::SingleLine(" footer")]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_error("a: b\n[ c ]"),
+            ("expected a key, found `[`".to_string(), "[")
+        );
+        assert_eq!(
+            parse_error("When \"SERVER\""),
+            ("expected `:`, found string".to_string(), "\"SERVER\"")
+        );
+        assert_eq!(
+            parse_error("a: ]"),
+            ("expected a value, found `]`".to_string(), "]")
+        );
+        assert_eq!(
+            parse_error("a:"),
+            ("expected a value, found end of input".to_string(), "")
+        );
+        assert_eq!(
+            parse_error("a: [ b c"),
+            ("unclosed delimiter `[`".to_string(), "[")
+        );
+        assert_eq!(
+            parse_error("a: { b: c"),
+            ("unclosed delimiter `{`".to_string(), "{")
+        );
+        assert!(matches!(
+            parse(&tokenize("a: { b }").unwrap()).unwrap_err().ty,
+            RsonErrorType::ExpectedColon(_)
+        ));
+
+        let tokens = tokenize("{ a: b } c").unwrap();
+        let error = parse_value(&tokens).unwrap_err();
+        assert_eq!(error.ty.to_string(), "expected end of input, found word");
+        assert_eq!(error.range, 9..10);
+    }
+}
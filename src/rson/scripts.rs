@@ -0,0 +1,191 @@
+use crate::preprocessor::parse_condition;
+use crate::rson::{RsonArray, RsonDocument, RsonError, RsonErrorType, RsonTokenType, RsonValue};
+use crate::Condition;
+
+/// A typed view of a `scripts.rson` file, which lists the scripts a mod loads.
+///
+/// Scripts are listed in `Scripts` arrays, each preceded by a `When` condition that decides which
+/// VMs load them. Conditions use the same syntax as `#if` directives, with defines like `SERVER`,
+/// `CLIENT`, `UI` and `MP`.
+///
+/// # Example
+/// ```
+/// use sqparse::rson::{parse, tokenize, ScriptsRson};
+///
+/// let source = r#"
+/// When: "SERVER"
+/// Scripts:
+/// [
+///     sv_main.gnut
+/// ]
+///
+/// When: "CLIENT || SERVER"
+/// Scripts:
+/// [
+///     sh_utility.gnut
+///     sh_constants.gnut
+/// ]
+/// "#;
+/// let tokens = tokenize(source).unwrap();
+/// let document = parse(&tokens).unwrap();
+/// let scripts = ScriptsRson::new(&document).unwrap();
+///
+/// assert_eq!(scripts.blocks.len(), 2);
+/// let server: Vec<_> = scripts.scripts(&["SERVER"]).collect();
+/// assert_eq!(server, ["sv_main.gnut", "sh_utility.gnut", "sh_constants.gnut"]);
+/// let client: Vec<_> = scripts.scripts(&["CLIENT"]).collect();
+/// assert_eq!(client, ["sh_utility.gnut", "sh_constants.gnut"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScriptsRson<'s> {
+    /// Blocks of scripts, in the order they are loaded.
+    pub blocks: Vec<ScriptsBlock<'s>>,
+}
+
+/// A `Scripts` array in a `scripts.rson` file, with the `When` condition before it.
+#[derive(Debug, Clone)]
+pub struct ScriptsBlock<'s> {
+    /// The `When` condition, or `None` if the scripts are loaded in every VM.
+    pub when: Option<Condition<'s>>,
+
+    /// The array of script paths.
+    pub scripts: &'s RsonArray<'s>,
+}
+
+impl<'s> ScriptsRson<'s> {
+    /// Reads the `When` and `Scripts` entries of a parsed `scripts.rson` file.
+    ///
+    /// Each `Scripts` array uses the condition of the last `When` entry before it. Other entries
+    /// are ignored.
+    ///
+    /// Returns an error if a `When` value is not a string containing a valid condition, or if a
+    /// `Scripts` value is not an array of strings.
+    pub fn new(document: &'s RsonDocument<'s>) -> Result<Self, RsonError<'s>> {
+        let mut blocks = Vec::new();
+        let mut when = None;
+        for entry in &document.entries {
+            match entry.key_str() {
+                "When" => when = Some(condition(&entry.value)?),
+                "Scripts" => {
+                    let scripts = entry.value.as_array().ok_or_else(|| {
+                        RsonError::new(RsonErrorType::ExpectedArray, entry.value.range())
+                    })?;
+                    if let Some(value) = scripts.values().find(|value| value.as_str().is_none()) {
+                        return Err(RsonError::new(RsonErrorType::ExpectedString, value.range()));
+                    }
+                    blocks.push(ScriptsBlock {
+                        when: when.take(),
+                        scripts,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(ScriptsRson { blocks })
+    }
+
+    /// Returns the paths of scripts loaded with a set of defines, in load order.
+    pub fn scripts<'a>(&'a self, defines: &'a [&str]) -> impl Iterator<Item = &'s str> + 'a {
+        (self.blocks.iter())
+            .filter(|block| block.is_loaded(defines))
+            .flat_map(|block| block.paths())
+    }
+}
+
+impl<'s> ScriptsBlock<'s> {
+    /// Returns true if the scripts are loaded with a set of defines.
+    pub fn is_loaded(&self, defines: &[&str]) -> bool {
+        (self.when.as_ref()).is_none_or(|condition| condition.evaluate(defines))
+    }
+
+    /// Returns the paths of the scripts in the block, relative to the mod's `scripts/vscripts`
+    /// directory.
+    pub fn paths(&self) -> impl Iterator<Item = &'s str> {
+        let scripts: &'s RsonArray<'s> = self.scripts;
+        scripts.values().filter_map(|value| value.as_str())
+    }
+}
+
+fn condition<'s>(value: &'s RsonValue<'s>) -> Result<Condition<'s>, RsonError<'s>> {
+    let RsonValue::String(token) = value else {
+        return Err(RsonError::new(RsonErrorType::ExpectedString, value.range()));
+    };
+    let (text, offset) = match token.ty {
+        RsonTokenType::String(text) => (text, token.range.start + 1),
+        RsonTokenType::Word(text) => (text, token.range.start),
+        _ => unreachable!("string values are string or word tokens"),
+    };
+    parse_condition(text).map_err(|range| {
+        let range = offset + range.start..offset + range.end;
+        RsonError::new(RsonErrorType::InvalidCondition, range)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::ScriptsRson;
+    use crate::rson::{parse, tokenize};
+    use crate::Condition;
+
+    fn error(source: &str) -> (String, &str) {
+        let tokens = tokenize(source).unwrap();
+        let document = parse(&tokens).unwrap();
+        let error = ScriptsRson::new(&document).unwrap_err();
+        (error.ty.to_string(), &source[error.range])
+    }
+
+    #[test]
+    fn blocks() {
+        let source = r#"
+        Scripts: [ always.nut ]
+        When: SERVER
+        When: "UI && !MP"
+        Scripts: [ "ui/menu.nut", ui/other.nut ]
+        Other: ignored
+        "#;
+        let tokens = tokenize(source).unwrap();
+        let document = parse(&tokens).unwrap();
+        let scripts = ScriptsRson::new(&document).unwrap();
+
+        assert_eq!(scripts.blocks.len(), 2);
+        assert_eq!(scripts.blocks[0].when, None);
+        assert_eq!(
+            scripts.blocks[1].when,
+            Some(Condition::And(
+                Box::new(Condition::Define("UI")),
+                Box::new(Condition::Not(Box::new(Condition::Define("MP")))),
+            ))
+        );
+        let paths: Vec<_> = scripts.blocks[1].paths().collect();
+        assert_eq!(paths, ["ui/menu.nut", "ui/other.nut"]);
+
+        let ui: Vec<_> = scripts.scripts(&["UI"]).collect();
+        assert_eq!(ui, ["always.nut", "ui/menu.nut", "ui/other.nut"]);
+        let ui_mp: Vec<_> = scripts.scripts(&["UI", "MP"]).collect();
+        assert_eq!(ui_mp, ["always.nut"]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("When: [ SERVER ]"),
+            ("expected a string".to_string(), "[ SERVER ]")
+        );
+        assert_eq!(
+            error("When: \"SERVER &&\""),
+            ("invalid `When` condition".to_string(), "")
+        );
+        assert_eq!(
+            error("When: \"SERVER ^ CLIENT\""),
+            ("invalid `When` condition".to_string(), "^")
+        );
+        assert_eq!(
+            error("Scripts: a.nut"),
+            ("expected an array".to_string(), "a.nut")
+        );
+        assert_eq!(
+            error("Scripts: [ a.nut, { b: c } ]"),
+            ("expected a string".to_string(), "{ b: c }")
+        );
+    }
+}
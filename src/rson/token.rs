@@ -0,0 +1,97 @@
+use crate::token::{Comment, TokenLine};
+use std::ops::Range;
+
+/// Type of [`RsonToken`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RsonTokenType<'s> {
+    /// An empty token at the end of the input, holding any trailing comments.
+    Empty,
+
+    /// `{`
+    OpenBrace,
+
+    /// `}`
+    CloseBrace,
+
+    /// `[`
+    OpenSquare,
+
+    /// `]`
+    CloseSquare,
+
+    /// `:`
+    Colon,
+
+    /// `,`
+    Comma,
+
+    /// A string in double quotes. The value does not include the quotes, and escape sequences like
+    /// `\"` are not processed.
+    ///
+    /// # Example
+    /// ```text
+    /// "SERVER && MP"
+    /// ```
+    String(&'s str),
+
+    /// An unquoted string, which runs until whitespace, a delimiter or a comment.
+    ///
+    /// # Example
+    /// ```text
+    /// mp/_base_gametype_mp.gnut
+    /// ```
+    Word(&'s str),
+}
+
+/// A token in an RSON file.
+///
+/// Like Squirrel [`Token`]s, RSON tokens are fully source-preserving and own the comments and
+/// newlines around them.
+///
+/// [`Token`]: crate::token::Token
+#[derive(Clone, Debug, PartialEq)]
+pub struct RsonToken<'s> {
+    /// The type of token.
+    pub ty: RsonTokenType<'s>,
+
+    /// The character range of the token in the source string.
+    pub range: Range<usize>,
+
+    /// Empty lines that appear before the token. The lines may contain comments.
+    pub before_lines: Vec<TokenLine<'s>>,
+
+    /// Comments that appear before the token on the same line.
+    pub comments: Vec<Comment<'s>>,
+
+    /// If this token ends a line, includes any comments between the token and newline.
+    pub new_line: Option<TokenLine<'s>>,
+}
+
+impl<'s> RsonTokenType<'s> {
+    /// Returns the string value of a [`String`] or [`Word`] token.
+    ///
+    /// [`String`]: RsonTokenType::String
+    /// [`Word`]: RsonTokenType::Word
+    pub fn value(self) -> Option<&'s str> {
+        match self {
+            RsonTokenType::String(value) | RsonTokenType::Word(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for RsonTokenType<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RsonTokenType::Empty => write!(f, "end of input"),
+            RsonTokenType::OpenBrace => write!(f, "`{{`"),
+            RsonTokenType::CloseBrace => write!(f, "`}}`"),
+            RsonTokenType::OpenSquare => write!(f, "`[`"),
+            RsonTokenType::CloseSquare => write!(f, "`]`"),
+            RsonTokenType::Colon => write!(f, "`:`"),
+            RsonTokenType::Comma => write!(f, "`,`"),
+            RsonTokenType::String(_) => write!(f, "string"),
+            RsonTokenType::Word(_) => write!(f, "word"),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::rson::RsonToken;
+use std::ops::Range;
+
+/// A parsed RSON file.
+///
+/// RSON files are a list of key-value entries, like the contents of an object without the braces.
+///
+/// Grammar: [RsonEntry]*
+#[derive(Debug, Clone)]
+pub struct RsonDocument<'s> {
+    pub entries: Vec<RsonEntry<'s>>,
+
+    /// The empty token at the end of the input, if the file ends with comments or empty lines.
+    pub end: Option<&'s RsonToken<'s>>,
+}
+
+/// A key-value entry in an [`RsonDocument`] or [`RsonObject`].
+///
+/// Grammar: (string | word) `:` [RsonValue] `,`?
+#[derive(Debug, Clone)]
+pub struct RsonEntry<'s> {
+    pub key: &'s RsonToken<'s>,
+    pub colon: &'s RsonToken<'s>,
+    pub value: RsonValue<'s>,
+    pub comma: Option<&'s RsonToken<'s>>,
+}
+
+/// A value in an RSON file.
+#[derive(Debug, Clone)]
+pub enum RsonValue<'s> {
+    /// A quoted or unquoted string.
+    ///
+    /// Grammar: string | word
+    String(&'s RsonToken<'s>),
+
+    Object(RsonObject<'s>),
+    Array(RsonArray<'s>),
+}
+
+/// An object of key-value entries.
+///
+/// Grammar: `{` [RsonEntry]* `}`
+#[derive(Debug, Clone)]
+pub struct RsonObject<'s> {
+    pub open: &'s RsonToken<'s>,
+    pub entries: Vec<RsonEntry<'s>>,
+    pub close: &'s RsonToken<'s>,
+}
+
+/// An array of values.
+///
+/// Grammar: `[` [RsonArrayItem]* `]`
+#[derive(Debug, Clone)]
+pub struct RsonArray<'s> {
+    pub open: &'s RsonToken<'s>,
+    pub items: Vec<RsonArrayItem<'s>>,
+    pub close: &'s RsonToken<'s>,
+}
+
+/// A value in an [`RsonArray`].
+///
+/// Grammar: [RsonValue] `,`?
+#[derive(Debug, Clone)]
+pub struct RsonArrayItem<'s> {
+    pub value: RsonValue<'s>,
+    pub comma: Option<&'s RsonToken<'s>>,
+}
+
+impl<'s> RsonDocument<'s> {
+    /// Returns the value of the first entry with a key.
+    pub fn get(&self, key: &str) -> Option<&RsonValue<'s>> {
+        find_entry(&self.entries, key)
+    }
+}
+
+impl<'s> RsonEntry<'s> {
+    /// Returns the key of the entry, without quotes.
+    pub fn key_str(&self) -> &'s str {
+        self.key.ty.value().unwrap_or_default()
+    }
+}
+
+impl<'s> RsonValue<'s> {
+    /// Returns the value if it is a string.
+    pub fn as_str(&self) -> Option<&'s str> {
+        match self {
+            RsonValue::String(token) => token.ty.value(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an object.
+    pub fn as_object(&self) -> Option<&RsonObject<'s>> {
+        match self {
+            RsonValue::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an array.
+    pub fn as_array(&self) -> Option<&RsonArray<'s>> {
+        match self {
+            RsonValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Returns the character range of the value in the source string.
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            RsonValue::String(token) => token.range.clone(),
+            RsonValue::Object(object) => object.open.range.start..object.close.range.end,
+            RsonValue::Array(array) => array.open.range.start..array.close.range.end,
+        }
+    }
+}
+
+impl<'s> RsonObject<'s> {
+    /// Returns the value of the first entry with a key.
+    pub fn get(&self, key: &str) -> Option<&RsonValue<'s>> {
+        find_entry(&self.entries, key)
+    }
+}
+
+impl<'s> RsonArray<'s> {
+    /// Returns an iterator over the values in the array.
+    pub fn values(&self) -> impl Iterator<Item = &RsonValue<'s>> {
+        self.items.iter().map(|item| &item.value)
+    }
+}
+
+fn find_entry<'a, 's>(entries: &'a [RsonEntry<'s>], key: &str) -> Option<&'a RsonValue<'s>> {
+    (entries.iter())
+        .find(|entry| entry.key_str() == key)
+        .map(|entry| &entry.value)
+}
@@ -8,10 +8,16 @@
 //! Tokens are also fully source-preserving. Each token maintains its range in the input string,
 //! as well as the comments and newlines after the token.
 //!
+//! Tokens created after lexing, for example to rewrite a syntax tree, can be owned by a
+//! [`TokenArena`].
+//!
 //! [`Identifier`]: TokenType::Identifier
 
 use crate::Flavor;
+use std::cell::RefCell;
+use std::marker::PhantomData;
 use std::ops::Range;
+use std::ptr::NonNull;
 
 macro_rules! _terminal_matches {
     ($ask_flavor:ident) => {
@@ -297,6 +303,15 @@ pub enum TokenType<'s> {
     /// ThereAre4
     /// ```
     Identifier(&'s str),
+
+    /// Input that was not recognized as a valid token.
+    ///
+    /// These are only emitted by [`tokenize_lossy`], which also returns a [`LexerError`] for each
+    /// one.
+    ///
+    /// [`tokenize_lossy`]: crate::tokenize_lossy
+    /// [`LexerError`]: crate::LexerError
+    Invalid(&'s str),
 }
 
 /// A comment.
@@ -328,11 +343,15 @@ pub enum Comment<'s> {
 
     /// A script-style single line comment, for e.g. preprocessor definitions.
     ///
+    /// Preprocessor directives can be parsed with [`Directive::from_comment`].
+    ///
     /// # Example
     /// ```text
     /// #ifdef CLIENT
     /// #endif
     /// ```
+    ///
+    /// [`Directive::from_comment`]: crate::Directive::from_comment
     ScriptStyle(&'s str),
 }
 
@@ -408,6 +427,110 @@ pub struct TokenLine<'s> {
     pub comments: Vec<Comment<'s>>,
 }
 
+/// Owns tokens that are created after lexing.
+///
+/// Nodes in a syntax tree borrow their tokens, usually from the list returned by [`tokenize`].
+/// When a tree is rewritten, for example with [`VisitMut`], tokens for new nodes can be allocated in
+/// an arena instead. Tokens in the arena are freed when the arena is dropped, so the arena must
+/// outlive any tree that references them.
+///
+/// # Example
+/// ```
+/// use sqparse::token::{TokenArena, TokenType};
+///
+/// let arena = TokenArena::new();
+/// let token = arena.token(TokenType::Identifier("MyFunction"));
+/// assert_eq!(token.ty, TokenType::Identifier("MyFunction"));
+/// assert_eq!(arena.len(), 1);
+/// ```
+///
+/// [`tokenize`]: crate::tokenize
+/// [`VisitMut`]: crate::ast::VisitMut
+pub struct TokenArena<'s> {
+    // Tokens are stored as untyped pointers so the arena is covariant over `'s`, like `Token`.
+    tokens: RefCell<Vec<NonNull<()>>>,
+    marker: PhantomData<Token<'s>>,
+}
+
+impl<'s> TokenArena<'s> {
+    /// Creates an empty arena.
+    pub fn new() -> Self {
+        TokenArena {
+            tokens: RefCell::new(Vec::new()),
+            marker: PhantomData,
+        }
+    }
+
+    /// Moves a token into the arena, returning a reference that lives as long as the arena.
+    pub fn alloc(&self, token: Token<'s>) -> &Token<'s> {
+        let token = NonNull::from(Box::leak(Box::new(token)));
+        self.tokens.borrow_mut().push(token.cast());
+
+        // SAFETY: The token is in its own heap allocation, which is only freed when the arena is
+        // dropped. The arena never creates mutable references to its tokens.
+        unsafe { token.as_ref() }
+    }
+
+    /// Creates a token with an empty range and no comments.
+    ///
+    /// The token does not come from the source, so it will be separated from the previous token
+    /// by a space when printed.
+    pub fn token(&self, ty: TokenType<'s>) -> &Token<'s> {
+        self.alloc(Token {
+            ty,
+            range: 0..0,
+            before_lines: Vec::new(),
+            comments: Vec::new(),
+            new_line: None,
+        })
+    }
+
+    /// Creates a token to replace `original` in a syntax tree.
+    ///
+    /// The new token has the range, comments and newlines of the original token, so the
+    /// formatting around the original token is kept when the tree is printed.
+    pub fn replace(&self, original: &Token<'s>, ty: TokenType<'s>) -> &Token<'s> {
+        self.alloc(Token {
+            ty,
+            ..original.clone()
+        })
+    }
+
+    /// Returns the number of tokens in the arena.
+    pub fn len(&self) -> usize {
+        self.tokens.borrow().len()
+    }
+
+    /// Returns `true` if the arena contains no tokens.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.borrow().is_empty()
+    }
+}
+
+impl Default for TokenArena<'_> {
+    fn default() -> Self {
+        TokenArena::new()
+    }
+}
+
+impl<'s> Drop for TokenArena<'s> {
+    fn drop(&mut self) {
+        for token in self.tokens.get_mut().drain(..) {
+            // SAFETY: Each pointer was created from a leaked `Box<Token<'s>>` in `alloc`, and is
+            // only freed here. References to the token cannot outlive the arena.
+            drop(unsafe { Box::from_raw(token.cast::<Token<'s>>().as_ptr()) });
+        }
+    }
+}
+
+impl std::fmt::Debug for TokenArena<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenArena")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
 impl std::fmt::Display for LiteralToken<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -426,6 +549,7 @@ impl std::fmt::Display for TokenType<'_> {
             TokenType::Terminal(terminal) => write!(f, "`{}`", terminal.as_str()),
             TokenType::Literal(literal) => write!(f, "{literal}"),
             TokenType::Identifier(text) => write!(f, "`{text}`"),
+            TokenType::Invalid(text) => write!(f, "`{text}`"),
         }
     }
 }